@@ -1,7 +1,17 @@
 //! Audio channel definitions and state.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::effects::ChannelEffects;
+use crate::error::{Error, Result};
+use crate::layout::ChannelLayout;
+use crate::mixer::{MixSend, MixType};
+
+/// Maximum length of a channel's internal name.
+pub const MAX_CHANNEL_NAME_LEN: usize = 32;
+
 /// Configuration for a virtual audio channel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelConfig {
@@ -17,6 +27,14 @@ pub struct ChannelConfig {
     pub sort_order: i32,
     /// Whether this is a system-defined channel
     pub is_system: bool,
+    /// Whether the channel gets its own recording source, fed from its
+    /// stream mix send
+    #[serde(default)]
+    pub direct_out: bool,
+    /// Speaker layout of the channel's sink; surround is downmixed to
+    /// stereo on the way into each mix
+    #[serde(default)]
+    pub layout: ChannelLayout,
 }
 
 impl ChannelConfig {
@@ -30,6 +48,23 @@ impl ChannelConfig {
             color: None,
             sort_order,
             is_system: true,
+            direct_out: false,
+            layout: ChannelLayout::Stereo,
+        }
+    }
+
+    /// Create a new user-defined channel configuration.
+    #[must_use]
+    pub fn user(name: &str, display_name: &str, sort_order: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            icon: None,
+            color: None,
+            sort_order,
+            is_system: false,
+            direct_out: false,
+            layout: ChannelLayout::Stereo,
         }
     }
 
@@ -39,16 +74,32 @@ impl ChannelConfig {
         format!("ut-ch-{}", self.name)
     }
 
-    /// Get the `PipeWire` node name for this channel's stream volume filter.
+    /// Get the `PipeWire` node name for the volume filter carrying this
+    /// channel into a mix (e.g. `ut-ch-music-stream-vol`).
     #[must_use]
-    pub fn stream_vol_node_name(&self) -> String {
-        format!("ut-ch-{}-stream-vol", self.name)
+    pub fn vol_node_name(&self, mix: &MixType) -> String {
+        mix.channel_vol_node_name(&self.name)
     }
 
-    /// Get the `PipeWire` node name for this channel's monitor volume filter.
+    /// Get the `PipeWire` node name for the input of this channel's effects
+    /// chain (e.g. `ut-fx-voice-in`).
     #[must_use]
-    pub fn monitor_vol_node_name(&self) -> String {
-        format!("ut-ch-{}-monitor-vol", self.name)
+    pub fn fx_input_node_name(&self) -> String {
+        format!("ut-fx-{}-in", self.name)
+    }
+
+    /// Get the `PipeWire` node name for the output of this channel's effects
+    /// chain (e.g. `ut-fx-voice-out`).
+    #[must_use]
+    pub fn fx_output_node_name(&self) -> String {
+        format!("ut-fx-{}-out", self.name)
+    }
+
+    /// Get the `PipeWire` node name for this channel's direct-out recording
+    /// source (e.g. `ut-direct-voice`).
+    #[must_use]
+    pub fn direct_node_name(&self) -> String {
+        format!("ut-direct-{}", self.name)
     }
 }
 
@@ -64,19 +115,53 @@ pub fn default_channels() -> Vec<ChannelConfig> {
     ]
 }
 
+/// Validate an internal channel name.
+///
+/// Names become part of `PipeWire` node names (`ut-ch-<name>`), so they are
+/// restricted to lowercase ASCII letters, digits and dashes, and must not end
+/// in `-vol`, which would clash with the volume filter nodes.
+///
+/// # Errors
+/// Returns `Error::InvalidChannelName` if the name is not usable.
+pub fn validate_channel_name(name: &str) -> Result<()> {
+    let valid_chars =
+        name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+    if name.is_empty()
+        || name.len() > MAX_CHANNEL_NAME_LEN
+        || !valid_chars
+        || name.starts_with('-')
+        || name.ends_with('-')
+        || name.ends_with("-vol")
+    {
+        return Err(Error::InvalidChannelName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Validate a channel color (`#rrggbb`).
+///
+/// # Errors
+/// Returns `Error::InvalidChannelColor` if the color is not a hex RGB code.
+pub fn validate_channel_color(color: &str) -> Result<()> {
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+
+    if valid { Ok(()) } else { Err(Error::InvalidChannelColor(color.to_string())) }
+}
+
 /// Runtime state for a channel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChannelState {
     /// Channel configuration
     pub config: ChannelConfig,
-    /// Volume level for stream mix (0.0 - 1.0)
-    pub stream_volume: f32,
-    /// Muted in stream mix
-    pub stream_muted: bool,
-    /// Volume level for monitor mix (0.0 - 1.0)
-    pub monitor_volume: f32,
-    /// Muted in monitor mix
-    pub monitor_muted: bool,
+    /// Level and mute per mix, keyed by mix name
+    pub sends: BTreeMap<MixType, MixSend>,
+    /// Effects chain between the channel sink and its volume filters
+    #[serde(default)]
+    pub effects: ChannelEffects,
     /// Current audio level (left channel, 0.0 - 1.0)
     pub level_left: f32,
     /// Current audio level (right channel, 0.0 - 1.0)
@@ -91,15 +176,36 @@ impl ChannelState {
     pub fn new(config: ChannelConfig) -> Self {
         Self {
             config,
-            stream_volume: 1.0,
-            stream_muted: false,
-            monitor_volume: 1.0,
-            monitor_muted: false,
+            sends: BTreeMap::new(),
+            effects: ChannelEffects::default(),
             level_left: 0.0,
             level_right: 0.0,
             node_id: None,
         }
     }
+
+    /// Level and mute of this channel in a mix.
+    ///
+    /// Mixes the channel has no stored send for get full level, unmuted.
+    #[must_use]
+    pub fn send(&self, mix: &MixType) -> MixSend {
+        self.sends.get(mix).copied().unwrap_or_default()
+    }
+
+    /// Set this channel's volume in a mix (0.0 - 1.0).
+    pub fn set_volume(&mut self, mix: &MixType, volume: f32) {
+        self.sends.entry(mix.clone()).or_default().volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Set this channel's mute state in a mix.
+    pub fn set_muted(&mut self, mix: &MixType, muted: bool) {
+        self.sends.entry(mix.clone()).or_default().muted = muted;
+    }
+
+    /// Set this channel's balance in a mix (-1.0 left to 1.0 right).
+    pub fn set_pan(&mut self, mix: &MixType, pan: f32) {
+        self.sends.entry(mix.clone()).or_default().pan = pan.clamp(-1.0, 1.0);
+    }
 }
 
 /// A channel with its full state.
@@ -110,3 +216,63 @@ pub struct Channel {
     /// Channel state
     pub state: ChannelState,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_channel_names() {
+        assert!(validate_channel_name("alerts").is_ok());
+        assert!(validate_channel_name("podcast-guest").is_ok());
+        assert!(validate_channel_name("guest2").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_channel_names() {
+        assert!(validate_channel_name("").is_err());
+        assert!(validate_channel_name("Podcast Guest").is_err());
+        assert!(validate_channel_name("-guest").is_err());
+        assert!(validate_channel_name("guest-").is_err());
+        assert!(validate_channel_name("music-stream-vol").is_err());
+        assert!(validate_channel_name("music-monitor-vol").is_err());
+        assert!(validate_channel_name(&"a".repeat(MAX_CHANNEL_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_channel_colors() {
+        assert!(validate_channel_color("#e94560").is_ok());
+        assert!(validate_channel_color("#E94560").is_ok());
+        assert!(validate_channel_color("e94560").is_err());
+        assert!(validate_channel_color("#e9456").is_err());
+        assert!(validate_channel_color("#zzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_user_channel_node_names() {
+        let config = ChannelConfig::user("alerts", "Alerts", 5);
+
+        assert!(!config.is_system);
+        assert_eq!(config.node_name(), "ut-ch-alerts");
+        assert_eq!(config.vol_node_name(&MixType::STREAM), "ut-ch-alerts-stream-vol");
+        assert_eq!(config.vol_node_name(&MixType::MONITOR), "ut-ch-alerts-monitor-vol");
+        assert_eq!(config.vol_node_name(&MixType::new("chat")), "ut-ch-alerts-chat-vol");
+        assert_eq!(config.fx_input_node_name(), "ut-fx-alerts-in");
+        assert_eq!(config.fx_output_node_name(), "ut-fx-alerts-out");
+        assert_eq!(config.direct_node_name(), "ut-direct-alerts");
+    }
+
+    #[test]
+    fn test_channel_sends() {
+        let mut state = ChannelState::new(ChannelConfig::user("alerts", "Alerts", 5));
+        let recording = MixType::new("recording");
+
+        assert_eq!(state.send(&recording), MixSend::default());
+
+        state.set_volume(&recording, 1.5);
+        state.set_muted(&MixType::MONITOR, true);
+        assert!((state.send(&recording).volume - 1.0).abs() < f32::EPSILON);
+        assert!(state.send(&MixType::MONITOR).muted);
+        assert!(!state.send(&MixType::STREAM).muted);
+    }
+}
@@ -3,33 +3,99 @@
 //! Commands are returned by IPC handlers to request state changes
 //! that the main daemon loop can process with mutable access.
 
+use std::time::Duration;
+
+use crate::ducking::DuckingRule;
+use crate::effects::ChannelEffects;
+use crate::layout::ChannelLayout;
 use crate::mixer::MixType;
+use crate::routing::{PatternType, RouteDirection, StreamSelector};
+use crate::solo::SoloMode;
 
 /// A command representing a state mutation request.
 #[derive(Debug, Clone)]
 pub enum Command {
     /// Set volume for a channel in a specific mix
     SetChannelVolume { channel: String, mix: MixType, volume: f32 },
-    /// Set mute state for a channel in a specific mix
-    SetChannelMute { channel: String, mix: MixType, muted: bool },
-    /// Set master volume for a mix
-    SetMasterVolume { mix: MixType, volume: f32 },
+    /// Set mute state for a channel in a specific mix, optionally fading
+    SetChannelMute { channel: String, mix: MixType, muted: bool, fade: Option<Duration> },
+    /// Set balance for a channel in a specific mix
+    SetChannelPan { channel: String, mix: MixType, pan: f32 },
+    /// Set master volume for a mix, optionally fading
+    SetMasterVolume { mix: MixType, volume: f32, fade: Option<Duration> },
     /// Set master mute for a mix
     SetMasterMute { mix: MixType, muted: bool },
-    /// Route an app to a channel
-    SetAppRoute { app_pattern: String, channel: String },
+    /// Create a user-defined channel
+    CreateChannel {
+        name: String,
+        display_name: String,
+        color: Option<String>,
+        icon: Option<String>,
+        layout: ChannelLayout,
+    },
+    /// Change a channel's display name
+    RenameChannel { name: String, display_name: String },
+    /// Change a channel's color
+    SetChannelColor { name: String, color: Option<String> },
+    /// Turn a channel's direct-out recording source on or off
+    SetChannelDirectOut { name: String, enabled: bool },
+    /// Change a channel's speaker layout
+    SetChannelLayout { name: String, layout: ChannelLayout },
+    /// Replace a channel's effects settings
+    SetChannelEffects { channel: String, effects: ChannelEffects },
+    /// Delete a user-defined channel
+    DeleteChannel { name: String },
+    /// Create a user-defined mix
+    CreateMix { name: String, display_name: String },
+    /// Delete a user-defined mix
+    DeleteMix { name: String },
+    /// Route an app to a channel, or a recording app to a mix or the mic,
+    /// or leave matching apps alone (`exclude`); with `stream`, only the
+    /// app's streams it selects
+    SetAppRoute {
+        app_pattern: String,
+        channel: String,
+        pattern_type: PatternType,
+        property: Option<String>,
+        direction: RouteDirection,
+        exclude: bool,
+        match_ancestors: bool,
+        stream: Option<StreamSelector>,
+    },
     /// Remove an app route
-    RemoveAppRoute { app_pattern: String },
+    RemoveAppRoute {
+        app_pattern: String,
+        property: Option<String>,
+        direction: RouteDirection,
+        stream: Option<StreamSelector>,
+    },
+    /// Move one playback stream to a channel until it closes, leaving the
+    /// app's other streams where they are
+    MoveStream { app_id: u32, channel: String },
+    /// Set the channel that apps no rule matches are sent to
+    SetFallbackChannel { channel: String },
     /// Save current state as a profile
     SaveProfile { name: String },
-    /// Load a saved profile
-    LoadProfile { name: String },
+    /// Load a saved profile, optionally fading to its levels
+    LoadProfile { name: String, fade: Option<Duration> },
     /// Delete a profile
     DeleteProfile { name: String },
     /// Set microphone gain
     SetMicGain { gain: f32 },
     /// Set microphone mute state
     SetMicMute { muted: bool },
+    /// Include or exclude the microphone from a mix
+    SetMicMixEnabled { mix: MixType, enabled: bool },
+    /// Set the microphone level in a mix
+    SetMicMixVolume { mix: MixType, volume: f32 },
+    /// Replace the ducking rules of a mix
+    SetDuckingRules { mix: MixType, rules: Vec<DuckingRule> },
+    /// Solo or release a channel in the monitor mix
+    SetChannelSolo { channel: String, soloed: bool },
+    /// Change how soloed channels are heard
+    SetSoloMode { mode: SoloMode },
+    /// Release every solo
+    ClearSolo,
     /// Set monitor mix output device
     SetMonitorOutput { device_name: String },
     /// Trigger reconciliation
@@ -0,0 +1,301 @@
+//! Automatic ducking of channels while a trigger signal is active.
+//!
+//! A ducking rule belongs to a mix: while its trigger (a channel or the mic)
+//! is above a threshold, the rule's target channels are lowered in that mix
+//! by a fixed depth. The reduction fades in over the attack time and back
+//! out over the release time. Where several rules duck the same channel in
+//! the same mix, the deepest reduction wins.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result, check_range};
+use crate::mixer::MixType;
+
+/// Signal that activates a ducking rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuckTrigger {
+    /// The Wave:3 microphone
+    Mic,
+    /// A channel, by name (metered pre-fader)
+    Channel(String),
+}
+
+/// One ducking rule within a mix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuckingRule {
+    /// Signal that activates the rule
+    pub trigger: DuckTrigger,
+    /// Channels lowered while the trigger is active
+    pub targets: Vec<String>,
+    /// Trigger peak level above which ducking starts (dBFS)
+    #[serde(default = "default_threshold_db")]
+    pub threshold_db: f32,
+    /// How far targets are lowered (dB)
+    #[serde(default = "default_depth_db")]
+    pub depth_db: f32,
+    /// Time to reach full depth (ms)
+    #[serde(default = "default_attack_ms")]
+    pub attack_ms: f32,
+    /// Time to recover once the trigger goes quiet (ms)
+    #[serde(default = "default_release_ms")]
+    pub release_ms: f32,
+    /// Whether the rule is in effect
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_threshold_db() -> f32 {
+    -40.0
+}
+
+fn default_depth_db() -> f32 {
+    12.0
+}
+
+fn default_attack_ms() -> f32 {
+    50.0
+}
+
+fn default_release_ms() -> f32 {
+    500.0
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl DuckingRule {
+    /// Check the rule's settings.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidDucking` naming the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        if self.targets.is_empty() {
+            return Err(Error::InvalidDucking("a rule needs at least one target".to_string()));
+        }
+        if let DuckTrigger::Channel(trigger) = &self.trigger
+            && self.targets.contains(trigger)
+        {
+            return Err(Error::InvalidDucking(format!("{trigger} cannot duck itself")));
+        }
+        let check =
+            |name: &str, value, min, max| check_range(Error::InvalidDucking, name, value, min, max);
+        check("threshold_db", self.threshold_db, -90.0, 0.0)?;
+        check("depth_db", self.depth_db, 0.0, 60.0)?;
+        check("attack_ms", self.attack_ms, 1.0, 5000.0)?;
+        check("release_ms", self.release_ms, 1.0, 10000.0)?;
+        Ok(())
+    }
+}
+
+/// Gain to apply to a channel's send into a mix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuckGain {
+    /// Channel name
+    pub channel: String,
+    /// Mix the send feeds
+    pub mix: MixType,
+    /// Linear gain (0.0 - 1.0) to multiply the send volume by
+    pub gain: f32,
+}
+
+/// Runs ducking rules against trigger levels.
+///
+/// Feed it the latest levels at a steady rate with [`Ducker::update`]; it
+/// keeps each rule's envelope and reports the send gains that changed.
+#[derive(Debug, Clone, Default)]
+pub struct Ducker {
+    /// Envelope per rule (0.0 = released, 1.0 = fully ducked), keyed by mix
+    /// and rule index
+    envelopes: BTreeMap<(MixType, usize), f32>,
+    /// Gains last reported, keyed by channel and mix
+    applied: BTreeMap<(String, MixType), f32>,
+}
+
+impl Ducker {
+    /// Advance every rule by `elapsed` and return the send gains that changed.
+    ///
+    /// `level` gives a trigger's current peak level (linear, 0.0 - 1.0), or
+    /// `None` if it is not being metered; unmetered triggers count as quiet.
+    /// Sends that are no longer ducked by any rule are reported once with a
+    /// gain of 1.0.
+    pub fn update(
+        &mut self,
+        rules: &BTreeMap<MixType, Vec<DuckingRule>>,
+        level: impl Fn(&DuckTrigger) -> Option<f32>,
+        elapsed: Duration,
+    ) -> Vec<DuckGain> {
+        let elapsed_ms = elapsed.as_secs_f32() * 1000.0;
+        let mut reductions: BTreeMap<(String, MixType), f32> = BTreeMap::new();
+
+        self.envelopes.retain(|(mix, index), _| {
+            rules.get(mix).and_then(|rules| rules.get(*index)).is_some_and(|rule| rule.enabled)
+        });
+
+        for (mix, mix_rules) in rules {
+            for (index, rule) in mix_rules.iter().enumerate().filter(|(_, rule)| rule.enabled) {
+                let active = level(&rule.trigger)
+                    .is_some_and(|peak| linear_to_db(peak) >= rule.threshold_db);
+                let envelope = self.envelopes.entry((mix.clone(), index)).or_default();
+                *envelope = if active {
+                    (*envelope + elapsed_ms / rule.attack_ms).min(1.0)
+                } else {
+                    (*envelope - elapsed_ms / rule.release_ms).max(0.0)
+                };
+
+                let reduction = *envelope * rule.depth_db;
+                for target in &rule.targets {
+                    let entry = reductions.entry((target.clone(), mix.clone())).or_default();
+                    *entry = entry.max(reduction);
+                }
+            }
+        }
+
+        let mut changes = Vec::new();
+        for ((channel, mix), reduction) in reductions {
+            let gain = db_to_linear(-reduction);
+            let last = self.applied.get(&(channel.clone(), mix.clone())).copied().unwrap_or(1.0);
+            if (gain - last).abs() > GAIN_EPSILON || (gain >= 1.0 && last < 1.0) {
+                changes.push(DuckGain { channel, mix, gain });
+            }
+        }
+        for (channel, mix) in self.applied.keys() {
+            let still_ducked = changes.iter().any(|c| c.channel == *channel && c.mix == *mix)
+                || rules.get(mix).is_some_and(|rules| {
+                    rules.iter().any(|r| r.enabled && r.targets.contains(channel))
+                });
+            if !still_ducked {
+                changes.push(DuckGain { channel: channel.clone(), mix: mix.clone(), gain: 1.0 });
+            }
+        }
+
+        for change in &changes {
+            let key = (change.channel.clone(), change.mix.clone());
+            if change.gain >= 1.0 {
+                self.applied.remove(&key);
+            } else {
+                self.applied.insert(key, change.gain);
+            }
+        }
+        changes
+    }
+
+    /// Current gain of a channel's send into a mix.
+    #[must_use]
+    pub fn gain(&self, channel: &str, mix: &MixType) -> f32 {
+        self.applied.get(&(channel.to_string(), mix.clone())).copied().unwrap_or(1.0)
+    }
+
+    /// Report every active gain again on the next update, e.g. after the
+    /// volume filters were recreated.
+    pub fn reapply(&mut self) {
+        self.applied.clear();
+    }
+
+    /// Drop the envelopes and gains of a deleted mix without reporting them,
+    /// so a mix later created under the same name starts at unity.
+    pub fn forget_mix(&mut self, mix: &MixType) {
+        self.envelopes.retain(|(m, _), _| m != mix);
+        self.applied.retain(|(_, m), _| m != mix);
+    }
+}
+
+/// Smallest gain change worth reporting.
+const GAIN_EPSILON: f32 = 0.001;
+
+fn linear_to_db(level: f32) -> f32 {
+    if level <= 0.0 { f32::NEG_INFINITY } else { 20.0 * level.log10() }
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voice_ducks_music() -> BTreeMap<MixType, Vec<DuckingRule>> {
+        let rule = DuckingRule {
+            trigger: DuckTrigger::Channel("voice".to_string()),
+            targets: vec!["music".to_string()],
+            threshold_db: -30.0,
+            depth_db: 20.0,
+            attack_ms: 100.0,
+            release_ms: 200.0,
+            enabled: true,
+        };
+        BTreeMap::from([(MixType::STREAM, vec![rule])])
+    }
+
+    #[test]
+    fn test_duck_attack_and_release() {
+        let rules = voice_ducks_music();
+        let mut ducker = Ducker::default();
+        let tick = Duration::from_millis(50);
+        let loud = |_: &DuckTrigger| Some(0.5);
+        let quiet = |_: &DuckTrigger| Some(0.001);
+
+        // Half way through the attack: 10 dB down
+        let changes = ducker.update(&rules, loud, tick);
+        assert_eq!(changes.len(), 1);
+        assert!((changes[0].gain - db_to_linear(-10.0)).abs() < 1e-4);
+
+        // Fully ducked: 20 dB down, then no further changes
+        ducker.update(&rules, loud, tick);
+        assert!((ducker.gain("music", &MixType::STREAM) - 0.1).abs() < 1e-4);
+        assert!(ducker.update(&rules, loud, tick).is_empty());
+
+        // Release takes four ticks and ends exactly at unity
+        for _ in 0..4 {
+            assert_eq!(ducker.update(&rules, quiet, tick).len(), 1);
+        }
+        assert!((ducker.gain("music", &MixType::STREAM) - 1.0).abs() < f32::EPSILON);
+        assert!(ducker.update(&rules, quiet, tick).is_empty());
+    }
+
+    #[test]
+    fn test_removed_rule_restores_gain() {
+        let rules = voice_ducks_music();
+        let mut ducker = Ducker::default();
+        ducker.update(&rules, |_| Some(1.0), Duration::from_millis(100));
+        assert!(ducker.gain("music", &MixType::STREAM) < 1.0);
+
+        let changes = ducker.update(&BTreeMap::new(), |_| None, Duration::from_millis(100));
+        assert_eq!(
+            changes,
+            vec![DuckGain { channel: "music".to_string(), mix: MixType::STREAM, gain: 1.0 }]
+        );
+    }
+
+    #[test]
+    fn test_forget_mix() {
+        let rules = voice_ducks_music();
+        let mut ducker = Ducker::default();
+        ducker.update(&rules, |_| Some(1.0), Duration::from_millis(100));
+
+        ducker.forget_mix(&MixType::STREAM);
+        assert!((ducker.gain("music", &MixType::STREAM) - 1.0).abs() < f32::EPSILON);
+        assert!(ducker.update(&BTreeMap::new(), |_| None, Duration::from_millis(100)).is_empty());
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let mut rules = voice_ducks_music();
+        let rule = &mut rules.get_mut(&MixType::STREAM).unwrap()[0];
+        assert!(rule.validate().is_ok());
+
+        rule.targets.push("voice".to_string());
+        assert!(rule.validate().is_err());
+
+        let json = r#"{"trigger": "mic", "targets": ["game"]}"#;
+        let rule: DuckingRule = serde_json::from_str(json).unwrap();
+        assert_eq!(rule.trigger, DuckTrigger::Mic);
+        assert!(rule.enabled);
+        assert!(rule.validate().is_ok());
+    }
+}
@@ -0,0 +1,258 @@
+//! Per-channel effects chain settings.
+//!
+//! A channel's effects run between its sink and its volume filters, in the
+//! order gate → EQ → compressor → limiter. Stages are individually enabled;
+//! a channel with no enabled stage has no effects chain at all.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result, check_range};
+
+/// Maximum number of parametric EQ bands per channel.
+pub const MAX_EQ_BANDS: usize = 8;
+
+/// Effects settings for one channel.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelEffects {
+    /// Noise gate
+    pub gate: Gate,
+    /// Parametric equalizer
+    pub eq: Equalizer,
+    /// Compressor
+    pub compressor: Compressor,
+    /// Limiter
+    pub limiter: Limiter,
+}
+
+impl ChannelEffects {
+    /// Whether any stage is enabled, i.e. the channel needs an effects chain.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.gate.enabled || self.eq.enabled || self.compressor.enabled || self.limiter.enabled
+    }
+
+    /// Check every parameter is within its supported range.
+    ///
+    /// Disabled stages are checked too, so their settings are still usable
+    /// when they are switched on.
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidEffects` naming the first offending parameter.
+    pub fn validate(&self) -> Result<()> {
+        let check =
+            |name: &str, value, min, max| check_range(Error::InvalidEffects, name, value, min, max);
+
+        let gate = &self.gate;
+        check("gate.threshold_db", gate.threshold_db, -70.0, 20.0)?;
+        check("gate.attack_ms", gate.attack_ms, 0.01, 1000.0)?;
+        check("gate.hold_ms", gate.hold_ms, 2.0, 2000.0)?;
+        check("gate.release_ms", gate.release_ms, 2.0, 4000.0)?;
+        check("gate.range_db", gate.range_db, -90.0, 0.0)?;
+
+        if self.eq.bands.len() > MAX_EQ_BANDS {
+            return Err(Error::InvalidEffects(format!(
+                "eq.bands: at most {MAX_EQ_BANDS} bands are supported"
+            )));
+        }
+        for band in &self.eq.bands {
+            check("eq.frequency", band.frequency, 20.0, 20000.0)?;
+            check("eq.gain_db", band.gain_db, -24.0, 24.0)?;
+            check("eq.q", band.q, 0.1, 10.0)?;
+        }
+
+        let comp = &self.compressor;
+        check("compressor.threshold_db", comp.threshold_db, -30.0, 0.0)?;
+        check("compressor.ratio", comp.ratio, 1.0, 20.0)?;
+        check("compressor.attack_ms", comp.attack_ms, 1.5, 400.0)?;
+        check("compressor.release_ms", comp.release_ms, 2.0, 800.0)?;
+        check("compressor.knee_db", comp.knee_db, 1.0, 10.0)?;
+        check("compressor.makeup_db", comp.makeup_db, 0.0, 24.0)?;
+
+        let limiter = &self.limiter;
+        check("limiter.input_gain_db", limiter.input_gain_db, -20.0, 20.0)?;
+        check("limiter.limit_db", limiter.limit_db, -20.0, 0.0)?;
+        check("limiter.release_ms", limiter.release_ms, 10.0, 2000.0)?;
+
+        Ok(())
+    }
+}
+
+/// Noise gate settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Gate {
+    /// Whether the gate is in the chain
+    pub enabled: bool,
+    /// Level below which the gate closes (dB)
+    pub threshold_db: f32,
+    /// Time to open (ms)
+    pub attack_ms: f32,
+    /// Time the gate stays open after the level drops (ms)
+    pub hold_ms: f32,
+    /// Time to close (ms)
+    pub release_ms: f32,
+    /// Attenuation while closed (dB)
+    pub range_db: f32,
+}
+
+impl Default for Gate {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -50.0,
+            attack_ms: 5.0,
+            hold_ms: 50.0,
+            release_ms: 200.0,
+            range_db: -90.0,
+        }
+    }
+}
+
+/// Parametric equalizer settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Equalizer {
+    /// Whether the EQ is in the chain
+    pub enabled: bool,
+    /// Bands, applied in order
+    pub bands: Vec<EqBand>,
+}
+
+/// A single EQ band.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EqBand {
+    /// Filter shape
+    pub kind: EqBandKind,
+    /// Center or corner frequency (Hz)
+    pub frequency: f32,
+    /// Boost or cut (dB); ignored by the pass filters
+    #[serde(default)]
+    pub gain_db: f32,
+    /// Bandwidth
+    #[serde(default = "default_q")]
+    pub q: f32,
+}
+
+fn default_q() -> f32 {
+    0.707
+}
+
+/// Shape of an EQ band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EqBandKind {
+    /// Bell around the frequency
+    Peaking,
+    /// Shelf below the frequency
+    LowShelf,
+    /// Shelf above the frequency
+    HighShelf,
+    /// Cut above the frequency
+    LowPass,
+    /// Cut below the frequency
+    HighPass,
+}
+
+/// Compressor settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Compressor {
+    /// Whether the compressor is in the chain
+    pub enabled: bool,
+    /// Level above which gain is reduced (dB)
+    pub threshold_db: f32,
+    /// Compression ratio (n:1)
+    pub ratio: f32,
+    /// Attack time (ms)
+    pub attack_ms: f32,
+    /// Release time (ms)
+    pub release_ms: f32,
+    /// Knee width (dB)
+    pub knee_db: f32,
+    /// Gain added after compression (dB)
+    pub makeup_db: f32,
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -18.0,
+            ratio: 4.0,
+            attack_ms: 10.0,
+            release_ms: 100.0,
+            knee_db: 3.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+/// Limiter settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limiter {
+    /// Whether the limiter is in the chain
+    pub enabled: bool,
+    /// Gain applied before limiting (dB)
+    pub input_gain_db: f32,
+    /// Output ceiling (dB)
+    pub limit_db: f32,
+    /// Release time (ms)
+    pub release_ms: f32,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self { enabled: false, input_gain_db: 0.0, limit_db: -1.0, release_ms: 50.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_effects_inactive() {
+        let mut effects = ChannelEffects::default();
+        assert!(!effects.is_active());
+        assert!(effects.validate().is_ok());
+
+        effects.limiter.enabled = true;
+        assert!(effects.is_active());
+    }
+
+    #[test]
+    fn test_effects_validation() {
+        let mut effects = ChannelEffects::default();
+        effects.compressor.ratio = 0.5;
+        assert!(effects.validate().is_err());
+
+        let mut effects = ChannelEffects::default();
+        effects.gate.threshold_db = f32::NAN;
+        assert!(effects.validate().is_err());
+
+        let band = EqBand { kind: EqBandKind::Peaking, frequency: 1000.0, gain_db: 3.0, q: 1.0 };
+        let mut effects = ChannelEffects::default();
+        effects.eq.bands = vec![band; MAX_EQ_BANDS];
+        assert!(effects.validate().is_ok());
+        effects.eq.bands.push(band);
+        assert!(effects.validate().is_err());
+    }
+
+    #[test]
+    fn test_partial_effects_json() {
+        let json = r#"{
+            "compressor": {"enabled": true, "ratio": 6.0},
+            "eq": {"enabled": true, "bands": [{"kind": "high_pass", "frequency": 80.0}]}
+        }"#;
+        let effects: ChannelEffects = serde_json::from_str(json).unwrap();
+
+        assert!(effects.compressor.enabled);
+        assert!((effects.compressor.ratio - 6.0).abs() < f32::EPSILON);
+        assert!((effects.compressor.threshold_db - -18.0).abs() < f32::EPSILON);
+        assert_eq!(effects.eq.bands[0].kind, EqBandKind::HighPass);
+        assert!(!effects.gate.enabled);
+        assert!(effects.validate().is_ok());
+    }
+}
@@ -14,9 +14,27 @@ pub enum Error {
     #[error("Invalid channel name: {0}")]
     InvalidChannelName(String),
 
-    #[error("Invalid volume value: {0} (must be 0.0-1.0)")]
+    #[error("Invalid channel color: {0} (must be #rrggbb)")]
+    InvalidChannelColor(String),
+
+    #[error("Mix not found: {0}")]
+    MixNotFound(String),
+
+    #[error("Invalid mix name: {0}")]
+    InvalidMixName(String),
+
+    #[error("Invalid effects settings: {0}")]
+    InvalidEffects(String),
+
+    #[error("Invalid ducking rule: {0}")]
+    InvalidDucking(String),
+
+    #[error("Invalid volume value: {0}")]
     InvalidVolume(f32),
 
+    #[error("Invalid fade duration: {0} ms")]
+    InvalidFade(u32),
+
     #[error("Route pattern error: {0}")]
     RoutePatternError(String),
 
@@ -29,3 +47,19 @@ pub enum Error {
 
 /// Result type alias for Undertone core operations.
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Check that a setting lies within `min..=max`, reporting it as `error`
+/// (e.g. [`Error::InvalidEffects`]) if it does not.
+pub(crate) fn check_range(
+    error: fn(String) -> Error,
+    name: &str,
+    value: f32,
+    min: f32,
+    max: f32,
+) -> Result<()> {
+    if (min..=max).contains(&value) {
+        Ok(())
+    } else {
+        Err(error(format!("{name} = {value} (must be {min} to {max})")))
+    }
+}
@@ -0,0 +1,245 @@
+//! Explaining routing decisions.
+//!
+//! A trace lists every rule for a direction in the order [`find_route`]
+//! tries them, what (if anything) each one matched, and the outcome. The
+//! winning rule is the one [`find_route`] picks, so a trace never disagrees
+//! with how the daemon actually routed the app.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use serde::{Deserialize, Serialize};
+
+use crate::process::Ancestor;
+use crate::routing::{RouteDirection, RouteRule, find_route, rules_by_priority};
+
+/// What part of an app a rule matched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "on", content = "value")]
+pub enum MatchedOn {
+    /// The app name
+    Name(String),
+    /// A node property
+    Property { key: String, value: String },
+    /// The binary name, tried once no rule matched the app itself
+    Binary(String),
+    /// A parent process, tried last
+    Ancestor(Ancestor),
+}
+
+/// One rule as evaluated for an app.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleEvaluation {
+    /// The rule
+    pub rule: RouteRule,
+    /// What the rule matched, if anything
+    pub matched: Option<MatchedOn>,
+    /// Why the rule can never match (an invalid pattern), if so
+    pub error: Option<String>,
+}
+
+/// Where an app ends up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RouteDecision {
+    /// A rule sends the app to `target` (a channel, or for recording apps
+    /// a mix or the mic)
+    Routed { target: String },
+    /// An exclusion rule leaves the app where the session manager put it
+    Excluded,
+    /// No rule matched, so the app plays into the fallback channel
+    Fallback { target: String },
+    /// No rule matched a recording app, so it is left alone
+    Unrouted,
+}
+
+/// How an app's route was decided.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteTrace {
+    /// Which of the app's streams was explained
+    pub direction: RouteDirection,
+    /// Every rule for the direction, in the order they are tried
+    pub rules: Vec<RuleEvaluation>,
+    /// Index into `rules` of the rule that decided, if one did
+    pub winner: Option<usize>,
+    /// The outcome
+    pub decision: RouteDecision,
+}
+
+/// Explain how an app would be routed in one direction, given the same
+/// inputs as [`find_route`] and the channel unmatched playback goes to.
+#[must_use]
+pub fn explain_route<S: BuildHasher>(
+    direction: RouteDirection,
+    app_name: &str,
+    binary_name: Option<&str>,
+    properties: &HashMap<String, String, S>,
+    stream_index: u32,
+    ancestors: &[Ancestor],
+    rules: &[RouteRule],
+    fallback: &str,
+) -> RouteTrace {
+    let sorted_rules = rules_by_priority(direction, rules);
+    let route =
+        find_route(direction, app_name, binary_name, properties, stream_index, ancestors, rules);
+    let winner = route.and_then(|m| sorted_rules.iter().position(|r| std::ptr::eq(*r, m.rule)));
+
+    let decision = match route {
+        Some(m) if m.rule.exclude => RouteDecision::Excluded,
+        Some(m) => RouteDecision::Routed { target: m.rule.channel.clone() },
+        None if direction == RouteDirection::Playback => {
+            RouteDecision::Fallback { target: fallback.to_string() }
+        }
+        None => RouteDecision::Unrouted,
+    };
+
+    let rules = sorted_rules
+        .into_iter()
+        .map(|rule| RuleEvaluation {
+            rule: rule.clone(),
+            matched: matched_on(rule, app_name, binary_name, properties, stream_index, ancestors),
+            error: rule.pattern_error(),
+        })
+        .collect();
+
+    RouteTrace { direction, rules, winner, decision }
+}
+
+/// What a rule matches on its own, trying the app the way [`find_route`]
+/// does: name or property, then binary name, then parent processes. Rules
+/// for other streams of the app match nothing.
+fn matched_on<S: BuildHasher>(
+    rule: &RouteRule,
+    app_name: &str,
+    binary_name: Option<&str>,
+    properties: &HashMap<String, String, S>,
+    stream_index: u32,
+    ancestors: &[Ancestor],
+) -> Option<MatchedOn> {
+    if !rule.applies_to_stream(properties, stream_index) {
+        return None;
+    }
+    if let Some(key) = &rule.property {
+        let value = properties.get(key).filter(|value| rule.matches(value))?;
+        return Some(MatchedOn::Property { key: key.clone(), value: value.clone() });
+    }
+    if rule.matches(app_name) {
+        return Some(MatchedOn::Name(app_name.to_string()));
+    }
+    if let Some(binary) = binary_name.filter(|binary| rule.matches(binary)) {
+        return Some(MatchedOn::Binary(binary.to_string()));
+    }
+    rule.matching_ancestor(ancestors).cloned().map(MatchedOn::Ancestor)
+}
+
+impl fmt::Display for MatchedOn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "name \"{name}\""),
+            Self::Property { key, value } => write!(f, "{key} = \"{value}\""),
+            Self::Binary(binary) => write!(f, "binary \"{binary}\""),
+            Self::Ancestor(ancestor) => {
+                write!(f, "parent process \"{}\" ({})", ancestor.name, ancestor.pid)
+            }
+        }
+    }
+}
+
+impl fmt::Display for RouteDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Routed { target } => write!(f, "routed to {target}"),
+            Self::Excluded => write!(f, "left alone by an exclusion rule"),
+            Self::Fallback { target } => write!(f, "no rule matched, sent to {target}"),
+            Self::Unrouted => write!(f, "no rule matched, left alone"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::{PatternType, default_routes};
+
+    #[test]
+    fn test_explain_lists_every_rule() {
+        let mut rules = default_routes();
+        rules.push(RouteRule::new("(bad".into(), PatternType::Regex, "music".into(), 10));
+        let none = HashMap::new();
+
+        let trace = explain_route(
+            RouteDirection::Playback,
+            "Firefox",
+            Some("firefox-bin"),
+            &none,
+            0,
+            &[],
+            &rules,
+            "system",
+        );
+        assert_eq!(trace.rules.len(), rules.len());
+        assert!(trace.rules.windows(2).all(|w| w[0].rule.priority >= w[1].rule.priority));
+
+        let winner = &trace.rules[trace.winner.unwrap()];
+        assert_eq!(winner.rule.pattern, "firefox");
+        assert_eq!(winner.matched, Some(MatchedOn::Name("Firefox".into())));
+        assert_eq!(trace.decision, RouteDecision::Routed { target: "browser".into() });
+
+        // The broken regex is reported rather than silently skipped
+        let bad = trace.rules.last().unwrap();
+        assert!(bad.matched.is_none() && bad.error.is_some());
+    }
+
+    #[test]
+    fn test_explain_winner_follows_find_route() {
+        let rules = vec![
+            RouteRule::new("game".into(), PatternType::Exact, "game".into(), 200),
+            RouteRule::new("Communication".into(), PatternType::Exact, "voice".into(), 50)
+                .with_property("media.role"),
+        ];
+        let properties = HashMap::from([("media.role".to_string(), "Communication".to_string())]);
+
+        // The binary matches the higher-priority rule, but the property rule
+        // matched the app itself and wins
+        let trace = explain_route(
+            RouteDirection::Playback,
+            "webrtc",
+            Some("game"),
+            &properties,
+            0,
+            &[],
+            &rules,
+            "system",
+        );
+        assert_eq!(trace.rules[0].matched, Some(MatchedOn::Binary("game".into())));
+        assert_eq!(trace.winner, Some(1));
+        assert_eq!(trace.decision, RouteDecision::Routed { target: "voice".into() });
+
+        let trace = explain_route(
+            RouteDirection::Capture,
+            "obs",
+            None,
+            &properties,
+            0,
+            &[],
+            &rules,
+            "system",
+        );
+        assert!(trace.rules.is_empty());
+        assert_eq!(trace.decision, RouteDecision::Unrouted);
+
+        let trace = explain_route(
+            RouteDirection::Playback,
+            "mpv",
+            None,
+            &HashMap::new(),
+            0,
+            &[],
+            &rules,
+            "music",
+        );
+        assert_eq!(trace.winner, None);
+        assert_eq!(trace.decision, RouteDecision::Fallback { target: "music".into() });
+    }
+}
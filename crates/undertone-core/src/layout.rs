@@ -0,0 +1,259 @@
+//! Speaker layouts for channels, and how surround is folded down to stereo.
+//!
+//! A surround channel keeps every speaker position as far as its volume
+//! filters. Each filter scales every position by its downmix coefficient,
+//! and the filter's ports are linked into the left side, the right side or
+//! both sides of the (always stereo) mix. The coefficients follow ITU-R
+//! BS.775: centre and surrounds at -3 dB, LFE dropped.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use serde::{Deserialize, Serialize};
+
+/// Speaker layout of a channel's sink.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChannelLayout {
+    /// Front left and right
+    #[default]
+    #[serde(rename = "stereo")]
+    Stereo,
+    /// Front pair, centre, LFE and rear pair
+    #[serde(rename = "5.1")]
+    Surround51,
+    /// 5.1 plus a side pair
+    #[serde(rename = "7.1")]
+    Surround71,
+}
+
+/// Side of the stereo mix a speaker position is folded into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownmixSide {
+    /// Left only
+    Left,
+    /// Right only
+    Right,
+    /// Both sides equally
+    Both,
+}
+
+/// How one speaker position reaches the stereo mix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DownmixTerm {
+    /// Speaker position (`PipeWire` channel name, e.g. `FC`)
+    pub position: &'static str,
+    /// Side(s) of the mix it feeds
+    pub side: DownmixSide,
+    /// Linear gain applied on the way
+    pub gain: f32,
+}
+
+const fn term(position: &'static str, side: DownmixSide, gain: f32) -> DownmixTerm {
+    DownmixTerm { position, side, gain }
+}
+
+const STEREO: [DownmixTerm; 2] =
+    [term("FL", DownmixSide::Left, 1.0), term("FR", DownmixSide::Right, 1.0)];
+
+const SURROUND_51: [DownmixTerm; 6] = [
+    term("FL", DownmixSide::Left, 1.0),
+    term("FR", DownmixSide::Right, 1.0),
+    term("FC", DownmixSide::Both, FRAC_1_SQRT_2),
+    term("LFE", DownmixSide::Both, 0.0),
+    term("RL", DownmixSide::Left, FRAC_1_SQRT_2),
+    term("RR", DownmixSide::Right, FRAC_1_SQRT_2),
+];
+
+const SURROUND_71: [DownmixTerm; 8] = [
+    term("FL", DownmixSide::Left, 1.0),
+    term("FR", DownmixSide::Right, 1.0),
+    term("FC", DownmixSide::Both, FRAC_1_SQRT_2),
+    term("LFE", DownmixSide::Both, 0.0),
+    term("RL", DownmixSide::Left, FRAC_1_SQRT_2),
+    term("RR", DownmixSide::Right, FRAC_1_SQRT_2),
+    term("SL", DownmixSide::Left, FRAC_1_SQRT_2),
+    term("SR", DownmixSide::Right, FRAC_1_SQRT_2),
+];
+
+/// A mono stream reaches both sides at full level.
+const MONO: DownmixTerm = term("MONO", DownmixSide::Both, 1.0);
+
+/// How a speaker position reaches a stereo input, whatever layout it came
+/// from; `None` for positions Undertone does not know.
+#[must_use]
+pub fn downmix_term(position: &str) -> Option<DownmixTerm> {
+    SURROUND_71.iter().chain([&MONO]).find(|t| t.position == position).copied()
+}
+
+/// Pair up two nodes' ports by speaker position, as (output index, input
+/// index) pairs.
+///
+/// Positions both nodes have are linked straight across. A lone output is
+/// treated as mono and a lone input takes everything, so mono sources reach
+/// both sides and stereo folds into a mono sink. Anything else is folded
+/// into the input's front pair following the downmix matrix; positions the
+/// matrix drops (LFE) or does not know are left unlinked.
+#[must_use]
+pub fn position_links(outputs: &[&str], inputs: &[&str]) -> Vec<(usize, usize)> {
+    let input = |position: &str| inputs.iter().position(|p| *p == position);
+    let mut links = Vec::new();
+
+    for (index, &position) in outputs.iter().enumerate() {
+        let position = if outputs.len() == 1 { MONO.position } else { position };
+        if let Some(input) = input(position) {
+            links.push((index, input));
+            continue;
+        }
+        let Some(term) = downmix_term(position).filter(|t| t.gain > 0.0) else {
+            continue;
+        };
+        if inputs.len() == 1 {
+            links.push((index, 0));
+            continue;
+        }
+        let sides: &[&str] = match term.side {
+            DownmixSide::Left => &["FL"],
+            DownmixSide::Right => &["FR"],
+            DownmixSide::Both => &["FL", "FR"],
+        };
+        links.extend(sides.iter().filter_map(|side| input(side)).map(|input| (index, input)));
+    }
+
+    links
+}
+
+impl ChannelLayout {
+    /// Name used in the database and over IPC (`stereo`, `5.1`, `7.1`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stereo => "stereo",
+            Self::Surround51 => "5.1",
+            Self::Surround71 => "7.1",
+        }
+    }
+
+    /// Look up a layout by the name `as_str` gives it.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Stereo, Self::Surround51, Self::Surround71].into_iter().find(|l| l.as_str() == name)
+    }
+
+    /// Whether the layout has more than the front pair.
+    #[must_use]
+    pub fn is_surround(self) -> bool {
+        self != Self::Stereo
+    }
+
+    /// Number of speaker positions.
+    #[must_use]
+    pub fn channels(self) -> u32 {
+        match self {
+            Self::Stereo => 2,
+            Self::Surround51 => 6,
+            Self::Surround71 => 8,
+        }
+    }
+
+    /// Speaker positions in port order, as `PipeWire`'s `audio.position`
+    /// expects them (e.g. `FL,FR,FC,LFE,RL,RR`).
+    #[must_use]
+    pub fn positions(self) -> String {
+        self.downmix().iter().map(|t| t.position).collect::<Vec<_>>().join(",")
+    }
+
+    /// Downmix matrix to stereo, one term per position in port order.
+    #[must_use]
+    pub fn downmix(self) -> &'static [DownmixTerm] {
+        match self {
+            Self::Stereo => &STEREO,
+            Self::Surround51 => &SURROUND_51,
+            Self::Surround71 => &SURROUND_71,
+        }
+    }
+
+    /// Per-position volumes for a send whose left and right volumes are
+    /// `volumes`, with the downmix coefficients applied.
+    ///
+    /// Positions feeding both sides take the average of the two, so the
+    /// centre stays put as the balance moves.
+    #[must_use]
+    pub fn position_volumes(self, volumes: [f32; 2]) -> Vec<f32> {
+        self.downmix()
+            .iter()
+            .map(|t| {
+                t.gain
+                    * match t.side {
+                        DownmixSide::Left => volumes[0],
+                        DownmixSide::Right => volumes[1],
+                        DownmixSide::Both => f32::midpoint(volumes[0], volumes[1]),
+                    }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_positions() {
+        assert_eq!(ChannelLayout::Stereo.positions(), "FL,FR");
+        assert_eq!(ChannelLayout::Surround51.positions(), "FL,FR,FC,LFE,RL,RR");
+        assert_eq!(ChannelLayout::Surround71.channels(), 8);
+
+        let layout: ChannelLayout = serde_json::from_str("\"7.1\"").unwrap();
+        assert_eq!(layout, ChannelLayout::Surround71);
+        assert_eq!(layout.as_str(), "7.1");
+        assert_eq!(ChannelLayout::from_name("5.1"), Some(ChannelLayout::Surround51));
+        assert_eq!(ChannelLayout::from_name("quad"), None);
+    }
+
+    #[test]
+    fn test_downmix_term_lookup() {
+        assert_eq!(downmix_term("SR").map(|t| t.side), Some(DownmixSide::Right));
+        assert_eq!(downmix_term("MONO").map(|t| t.side), Some(DownmixSide::Both));
+        assert!(downmix_term("TFL").is_none());
+    }
+
+    #[test]
+    fn test_position_links_mono_and_stereo() {
+        // Mono (however the lone port is named) reaches both sides
+        assert_eq!(position_links(&["MONO"], &["FL", "FR"]), vec![(0, 0), (0, 1)]);
+        assert_eq!(position_links(&["FL"], &["FL", "FR"]), vec![(0, 0), (0, 1)]);
+        assert_eq!(position_links(&["FL", "FR"], &["FL", "FR"]), vec![(0, 0), (1, 1)]);
+        // Stereo into a mono sink
+        assert_eq!(position_links(&["FL", "FR"], &["MONO"]), vec![(0, 0), (1, 0)]);
+        assert!(position_links(&["AUX0", "AUX1"], &["FL", "FR"]).is_empty());
+    }
+
+    #[test]
+    fn test_position_links_downmix() {
+        let outputs = ["FL", "FR", "FC", "LFE", "RL", "RR"];
+        let stereo = position_links(&outputs, &["FL", "FR"]);
+        assert_eq!(stereo, vec![(0, 0), (1, 1), (2, 0), (2, 1), (4, 0), (5, 1)]);
+
+        // Straight across when the input has every position
+        let surround = position_links(&outputs, &outputs);
+        assert_eq!(surround.len(), 6);
+        assert!(surround.iter().all(|(output, input)| output == input));
+    }
+
+    #[test]
+    fn test_stereo_volumes_pass_through() {
+        let volumes = ChannelLayout::Stereo.position_volumes([0.25, 0.5]);
+        assert_eq!(volumes.len(), 2);
+        assert!((volumes[0] - 0.25).abs() < f32::EPSILON);
+        assert!((volumes[1] - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_surround_downmix_volumes() {
+        let volumes = ChannelLayout::Surround51.position_volumes([1.0, 0.5]);
+        let expected = [1.0, 0.5, 0.75 * FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2, 0.5 * FRAC_1_SQRT_2];
+        assert_eq!(volumes.len(), expected.len());
+        for (volume, expected) in volumes.iter().zip(expected) {
+            assert!((volume - expected).abs() < 1e-6);
+        }
+    }
+}
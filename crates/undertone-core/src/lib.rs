@@ -5,16 +5,31 @@
 
 pub mod channel;
 pub mod command;
+pub mod ducking;
+pub mod effects;
 pub mod error;
+pub mod explain;
+pub mod layout;
+pub mod lint;
+pub mod meter;
 pub mod mixer;
+pub mod process;
 pub mod profile;
 pub mod routing;
+pub mod solo;
 pub mod state;
+pub mod volume;
 
 pub use channel::{Channel, ChannelConfig, ChannelState};
 pub use command::Command;
+pub use ducking::{Ducker, DuckingRule};
+pub use effects::ChannelEffects;
 pub use error::{Error, Result};
-pub use mixer::{MixType, MixerState};
+pub use layout::ChannelLayout;
+pub use meter::{LevelAccumulator, MeterLevels};
+pub use mixer::{MixConfig, MixSend, MixType, MixerState};
 pub use profile::{Profile, ProfileSummary};
 pub use routing::{AppRoute, RouteRule};
+pub use solo::{SoloMode, SoloState};
 pub use state::{DaemonEvent, DaemonState};
+pub use volume::{Fade, VolumeQueue, VolumeTaper, VolumeUpdate, VolumeValue, balance_volumes};
@@ -0,0 +1,215 @@
+//! Checking routing rules for mistakes.
+//!
+//! Three kinds of problem are reported: patterns that do not compile (such
+//! a rule silently never matches), rules whose target channel or mix does
+//! not exist, and rules that can never win because a rule tried before
+//! them matches everything they do. The shadowing check is conservative: it
+//! only flags rules it can prove are covered, so regex rules are only
+//! reported when an identical rule comes first.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::routing::{CAPTURE_MIC, PatternType, RouteDirection, RouteRule, rules_by_priority};
+
+/// What is wrong with a rule.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum RuleProblem {
+    /// The pattern is not a valid regex or glob, so the rule never matches
+    InvalidPattern { error: String },
+    /// The channel (or, for capture rules, the mix) the rule routes to
+    /// does not exist
+    MissingTarget,
+    /// A rule tried earlier matches every app this one does
+    Shadowed { by: String, priority: i32 },
+}
+
+/// A problem found with one rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleIssue {
+    /// The rule
+    pub rule: RouteRule,
+    /// What is wrong with it
+    pub problem: RuleProblem,
+}
+
+/// Check rules against the existing channels and mixes.
+///
+/// Issues are listed per direction, in the order the rules are tried.
+#[must_use]
+pub fn lint_rules(rules: &[RouteRule], channels: &[&str], mixes: &[&str]) -> Vec<RuleIssue> {
+    let mut issues = Vec::new();
+
+    for direction in [RouteDirection::Playback, RouteDirection::Capture] {
+        let sorted_rules = rules_by_priority(direction, rules);
+        for (index, rule) in sorted_rules.iter().enumerate() {
+            let mut report = |problem| issues.push(RuleIssue { rule: (*rule).clone(), problem });
+
+            if let Some(error) = rule.pattern_error() {
+                report(RuleProblem::InvalidPattern { error });
+            }
+
+            let targets = if direction == RouteDirection::Capture { mixes } else { channels };
+            let mic = direction == RouteDirection::Capture && rule.channel == CAPTURE_MIC;
+            if !rule.exclude && !mic && !targets.contains(&rule.channel.as_str()) {
+                report(RuleProblem::MissingTarget);
+            }
+
+            if let Some(earlier) = sorted_rules[..index].iter().find(|e| covers(e, rule)) {
+                report(RuleProblem::Shadowed {
+                    by: earlier.pattern.clone(),
+                    priority: earlier.priority,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+/// Whether `earlier` matches every app `later` could match, in every way
+/// `later` could match it.
+fn covers(earlier: &RouteRule, later: &RouteRule) -> bool {
+    if earlier.property != later.property
+        || earlier.stream != later.stream
+        || (later.match_ancestors && !earlier.match_ancestors)
+    {
+        return false;
+    }
+    if earlier.pattern_type == later.pattern_type && earlier.pattern == later.pattern {
+        return true;
+    }
+
+    let pattern = later.pattern.as_str();
+    let lower = later.pattern.to_lowercase();
+    let earlier_lower = earlier.pattern.to_lowercase();
+    match later.pattern_type {
+        // A literal name is covered by any rule matching it (in any case, if
+        // it ignores case)
+        PatternType::Exact => earlier.matches(pattern),
+        PatternType::ExactIgnoreCase => ignores_case(earlier) && earlier.matches(&lower),
+        PatternType::Prefix => match earlier.pattern_type {
+            PatternType::Prefix => pattern.starts_with(&earlier.pattern),
+            PatternType::PrefixIgnoreCase => lower.starts_with(&earlier_lower),
+            PatternType::Contains => lower.contains(&earlier_lower),
+            _ => false,
+        },
+        PatternType::PrefixIgnoreCase => match earlier.pattern_type {
+            PatternType::PrefixIgnoreCase => lower.starts_with(&earlier_lower),
+            PatternType::Contains => lower.contains(&earlier_lower),
+            _ => false,
+        },
+        PatternType::Contains => {
+            earlier.pattern_type == PatternType::Contains && lower.contains(&earlier_lower)
+        }
+        PatternType::Regex | PatternType::Glob => false,
+    }
+}
+
+fn ignores_case(rule: &RouteRule) -> bool {
+    matches!(
+        rule.pattern_type,
+        PatternType::ExactIgnoreCase
+            | PatternType::PrefixIgnoreCase
+            | PatternType::Contains
+            | PatternType::Glob
+    )
+}
+
+impl fmt::Display for RuleProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPattern { error } => write!(f, "invalid pattern, never matches: {error}"),
+            Self::MissingTarget => write!(f, "target does not exist"),
+            Self::Shadowed { by, priority } => {
+                write!(f, "never wins, \"{by}\" (priority {priority}) matches first")
+            }
+        }
+    }
+}
+
+impl fmt::Display for RuleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rule = &self.rule;
+        write!(f, "{} rule \"{}\"", rule.direction.as_str(), rule.pattern)?;
+        if !rule.exclude {
+            write!(f, " -> {}", rule.channel)?;
+        }
+        write!(f, ": {}", self.problem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::{StreamSelector, default_routes};
+
+    const CHANNELS: [&str; 5] = ["system", "voice", "music", "browser", "game"];
+    const MIXES: [&str; 2] = ["stream", "monitor"];
+
+    fn problems(rules: &[RouteRule]) -> Vec<(String, RuleProblem)> {
+        lint_rules(rules, &CHANNELS, &MIXES)
+            .into_iter()
+            .map(|i| (i.rule.pattern, i.problem))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_routes_are_clean() {
+        assert!(lint_rules(&default_routes(), &CHANNELS, &MIXES).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_pattern_and_missing_target() {
+        let rules = vec![
+            RouteRule::new("(bad".into(), PatternType::Regex, "music".into(), 100),
+            RouteRule::new("mpv".into(), PatternType::Exact, "movies".into(), 100),
+            RouteRule::new("obs".into(), PatternType::Exact, "mic".into(), 100)
+                .with_direction(RouteDirection::Capture),
+            RouteRule::new("rec".into(), PatternType::Exact, "music".into(), 100)
+                .with_direction(RouteDirection::Capture),
+            RouteRule::exclusion("pavucontrol".into(), PatternType::Exact, 100),
+        ];
+
+        let found = problems(&rules);
+        assert_eq!(found.len(), 3);
+        assert!(matches!(found[0].1, RuleProblem::InvalidPattern { .. }));
+        assert_eq!(found[1], ("mpv".into(), RuleProblem::MissingTarget));
+        // Capture rules target mixes, not channels
+        assert_eq!(found[2], ("rec".into(), RuleProblem::MissingTarget));
+    }
+
+    #[test]
+    fn test_shadowed_rules() {
+        let rules = vec![
+            RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "voice".into(), 100),
+            RouteRule::new("Discord-PTB".into(), PatternType::Prefix, "music".into(), 50),
+            RouteRule::new("discord".into(), PatternType::ExactIgnoreCase, "game".into(), 100),
+            RouteRule::new("Discord".into(), PatternType::Exact, "game".into(), 200),
+            // A different property, or a wider pattern, is not covered
+            RouteRule::new("discord".into(), PatternType::Exact, "voice".into(), 10)
+                .with_property("media.role"),
+            RouteRule::new("cord".into(), PatternType::Contains, "voice".into(), 10),
+            // Ancestor matching reaches apps the earlier rule cannot
+            RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "voice".into(), 10)
+                .with_ancestors(),
+            // Stream rules are tried before app-wide ones
+            RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "game".into(), 10)
+                .with_stream(StreamSelector::Index(1)),
+        ];
+
+        // Both lower rules are covered by the case-insensitive prefix rule
+        let shadowed = RuleProblem::Shadowed { by: "discord".into(), priority: 100 };
+        let found = problems(&rules);
+        assert_eq!(found, [("discord".into(), shadowed.clone()), ("Discord-PTB".into(), shadowed)]);
+
+        let issue = lint_rules(&rules, &CHANNELS, &MIXES).remove(1);
+        assert_eq!(
+            issue.to_string(),
+            "playback rule \"Discord-PTB\" -> music: never wins, \"discord\" (priority 100) \
+             matches first"
+        );
+    }
+}
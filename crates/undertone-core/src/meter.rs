@@ -0,0 +1,137 @@
+//! Audio level metering.
+
+use serde::{Deserialize, Serialize};
+
+/// Peak and RMS levels of a stereo signal over one metering period.
+///
+/// All values are linear amplitudes in the range 0.0 - 1.0. Mono signals
+/// report the same value on both sides.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MeterLevels {
+    /// Peak level, left
+    pub peak_left: f32,
+    /// Peak level, right
+    pub peak_right: f32,
+    /// RMS level, left
+    pub rms_left: f32,
+    /// RMS level, right
+    pub rms_right: f32,
+}
+
+/// Accumulates audio blocks between meter readings.
+///
+/// Audio arrives in small blocks from the audio thread; readings are taken at
+/// the (much lower) publish rate. Peaks are held and squares summed until the
+/// next `take`.
+#[derive(Debug, Clone, Default)]
+pub struct LevelAccumulator {
+    peak: [f32; 2],
+    sum_squares: [f64; 2],
+    frames: u64,
+}
+
+impl LevelAccumulator {
+    /// Add a block of interleaved `f32` samples with `channels` channels.
+    ///
+    /// The first channel is metered as left and the second as right; mono
+    /// input feeds both sides and any further channels are ignored.
+    pub fn add_interleaved(&mut self, samples: &[f32], channels: usize) {
+        if channels == 0 {
+            return;
+        }
+
+        for frame in samples.chunks_exact(channels) {
+            let left = frame[0];
+            let right = frame.get(1).copied().unwrap_or(left);
+
+            for (side, sample) in [left, right].into_iter().enumerate() {
+                self.peak[side] = self.peak[side].max(sample.abs());
+                self.sum_squares[side] += f64::from(sample) * f64::from(sample);
+            }
+            self.frames += 1;
+        }
+    }
+
+    /// Move everything accumulated in `other` into this accumulator, leaving
+    /// `other` empty.
+    pub fn merge(&mut self, other: &mut Self) {
+        for side in 0..2 {
+            self.peak[side] = self.peak[side].max(other.peak[side]);
+            self.sum_squares[side] += other.sum_squares[side];
+        }
+        self.frames += other.frames;
+        *other = Self::default();
+    }
+
+    /// Return the levels accumulated since the last call and start over.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn take(&mut self) -> MeterLevels {
+        let rms = |sum: f64| {
+            if self.frames == 0 { 0.0 } else { (sum / self.frames as f64).sqrt() as f32 }
+        };
+
+        let levels = MeterLevels {
+            peak_left: self.peak[0].min(1.0),
+            peak_right: self.peak[1].min(1.0),
+            rms_left: rms(self.sum_squares[0]).min(1.0),
+            rms_right: rms(self.sum_squares[1]).min(1.0),
+        };
+
+        *self = Self::default();
+        levels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stereo_peak_and_rms() {
+        let mut acc = LevelAccumulator::default();
+        acc.add_interleaved(&[0.5, -0.25, -0.5, 0.25], 2);
+
+        let levels = acc.take();
+        assert!((levels.peak_left - 0.5).abs() < f32::EPSILON);
+        assert!((levels.peak_right - 0.25).abs() < f32::EPSILON);
+        assert!((levels.rms_left - 0.5).abs() < 1e-6);
+        assert!((levels.rms_right - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mono_feeds_both_sides() {
+        let mut acc = LevelAccumulator::default();
+        acc.add_interleaved(&[0.1, -0.8, 0.3], 1);
+
+        let levels = acc.take();
+        assert!((levels.peak_left - 0.8).abs() < f32::EPSILON);
+        assert!((levels.peak_right - 0.8).abs() < f32::EPSILON);
+        assert!((levels.rms_left - levels.rms_right).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_take_resets() {
+        let mut acc = LevelAccumulator::default();
+        acc.add_interleaved(&[1.5, 1.5], 2);
+
+        // Clipped input is reported as full scale
+        assert!((acc.take().peak_left - 1.0).abs() < f32::EPSILON);
+        assert_eq!(acc.take(), MeterLevels::default());
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut acc = LevelAccumulator::default();
+        acc.add_interleaved(&[0.5, 0.5], 2);
+        let mut block = LevelAccumulator::default();
+        block.add_interleaved(&[-0.5, 0.25, 0.5, 0.25, 0.5, 0.25], 2);
+
+        acc.merge(&mut block);
+        assert_eq!(block.take(), MeterLevels::default());
+        let levels = acc.take();
+        assert!((levels.peak_left - 0.5).abs() < f32::EPSILON);
+        assert!((levels.rms_left - 0.5).abs() < 1e-6);
+        // Four frames of which the first was 0.5 and the rest 0.25
+        assert!((levels.rms_right - (0.4375f32 / 4.0).sqrt()).abs() < 1e-6);
+    }
+}
@@ -1,62 +1,451 @@
-//! Mixer state and mix types.
+//! Mixer state and mix buses.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-/// Type of audio mix.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
-#[serde(rename_all = "lowercase")]
-pub enum MixType {
+use crate::ducking::{DuckTrigger, DuckingRule};
+use crate::error::{Error, Result};
+
+/// Maximum length of a mix's internal name.
+pub const MAX_MIX_NAME_LEN: usize = 16;
+
+/// Identifies a mix bus by its internal name (e.g. `stream`, `monitor`).
+///
+/// Serialized as the bare name, so `"stream"` and `"monitor"` remain valid
+/// on the wire.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MixType(Cow<'static, str>);
+
+impl MixType {
     /// Stream mix - sent to streaming/recording applications
-    #[default]
-    Stream,
+    pub const STREAM: Self = Self(Cow::Borrowed("stream"));
     /// Monitor mix - sent to local headphone output
-    Monitor,
+    pub const MONITOR: Self = Self(Cow::Borrowed("monitor"));
+
+    /// Refer to a mix by name.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(Cow::Owned(name.into()))
+    }
+
+    /// The mix's internal name.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the node name prefix for this mix (e.g. `ut-stream`).
+    #[must_use]
+    pub fn node_prefix(&self) -> String {
+        format!("ut-{}", self.0)
+    }
+
+    /// Get the name of this mix's node (e.g. `ut-stream-mix`).
+    #[must_use]
+    pub fn mix_node_name(&self) -> String {
+        format!("{}-mix", self.node_prefix())
+    }
+
+    /// Get the name of the virtual source that carries this mix to capture
+    /// apps as a microphone (e.g. `ut-stream-source`).
+    #[must_use]
+    pub fn source_node_name(&self) -> String {
+        format!("{}-source", self.node_prefix())
+    }
+
+    /// Get the name of the volume filter carrying the mic into this mix
+    /// (e.g. `ut-mic-stream-vol`).
+    #[must_use]
+    pub fn mic_vol_node_name(&self) -> String {
+        format!("ut-mic-{}-vol", self.0)
+    }
+
+    /// Get the name of the volume filter carrying a channel into this mix
+    /// (e.g. `ut-ch-music-stream-vol`).
+    #[must_use]
+    pub fn channel_vol_node_name(&self, channel: &str) -> String {
+        format!("ut-ch-{channel}-{}-vol", self.0)
+    }
 }
 
-impl MixType {
-    /// Get the node name prefix for this mix type.
+impl Default for MixType {
+    fn default() -> Self {
+        Self::STREAM
+    }
+}
+
+impl fmt::Display for MixType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Configuration for a mix bus.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixConfig {
+    /// Internal name (e.g., "stream", "recording")
+    pub name: String,
+    /// Display name shown in UI
+    pub display_name: String,
+    /// Sort order in the UI
+    pub sort_order: i32,
+    /// Whether this is a system-defined mix
+    pub is_system: bool,
+}
+
+impl MixConfig {
+    /// Create a new system mix configuration.
+    #[must_use]
+    pub fn system(name: &str, display_name: &str, sort_order: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            sort_order,
+            is_system: true,
+        }
+    }
+
+    /// Create a new user-defined mix configuration.
     #[must_use]
-    pub fn node_prefix(&self) -> &'static str {
-        match self {
-            Self::Stream => "ut-stream",
-            Self::Monitor => "ut-monitor",
+    pub fn user(name: &str, display_name: &str, sort_order: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            sort_order,
+            is_system: false,
         }
     }
+
+    /// The identifier used to address this mix.
+    #[must_use]
+    pub fn id(&self) -> MixType {
+        MixType::new(self.name.clone())
+    }
+
+    /// Get the `PipeWire` node name for this mix.
+    #[must_use]
+    pub fn node_name(&self) -> String {
+        self.id().mix_node_name()
+    }
+}
+
+/// Default system mixes.
+#[must_use]
+pub fn default_mixes() -> Vec<MixConfig> {
+    vec![MixConfig::system("stream", "Stream", 0), MixConfig::system("monitor", "Monitor", 1)]
+}
+
+/// Validate an internal mix name.
+///
+/// Names become part of `PipeWire` node names (`ut-<name>-mix`,
+/// `ut-ch-<channel>-<name>-vol`), so they are restricted to lowercase ASCII
+/// letters and digits. Disallowing dashes keeps filter node names
+/// unambiguous. `mic` is taken by the mic sends, and `ch` and `direct` are
+/// channel node prefixes: a mix named `ch` would own `ut-ch-mix`, the sink of
+/// a channel named `mix`, and one named `direct` would own `ut-direct-mix`
+/// and `ut-direct-source`, the direct-outs of channels named `mix` and
+/// `source`.
+///
+/// # Errors
+/// Returns `Error::InvalidMixName` if the name is not usable.
+pub fn validate_mix_name(name: &str) -> Result<()> {
+    let valid_chars = name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+
+    if name.is_empty()
+        || name.len() > MAX_MIX_NAME_LEN
+        || !valid_chars
+        || matches!(name, "mic" | "ch" | "direct")
+    {
+        return Err(Error::InvalidMixName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Level, mute and balance of a signal sent into a mix.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MixSend {
+    /// Volume level (0.0 - 1.0)
+    pub volume: f32,
+    /// Whether the send is muted
+    pub muted: bool,
+    /// Balance from -1.0 (left only) through 0.0 (centered) to 1.0 (right only)
+    #[serde(default)]
+    pub pan: f32,
+}
+
+impl MixSend {
+    /// A centered send.
+    #[must_use]
+    pub const fn new(volume: f32, muted: bool) -> Self {
+        Self { volume, muted, pan: 0.0 }
+    }
+}
+
+impl Default for MixSend {
+    fn default() -> Self {
+        Self::new(1.0, false)
+    }
 }
 
 /// State of the overall mixer.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "StoredMixerState")]
 pub struct MixerState {
-    /// Master volume for stream mix
-    pub stream_master_volume: f32,
-    /// Master mute for stream mix
-    pub stream_master_muted: bool,
-    /// Master volume for monitor mix
-    pub monitor_master_volume: f32,
-    /// Master mute for monitor mix
-    pub monitor_master_muted: bool,
-    /// Whether microphone is included in stream mix
-    pub mic_to_stream: bool,
-    /// Microphone level in stream mix (0.0 - 1.0)
-    pub mic_stream_volume: f32,
-    /// Whether microphone is included in monitor mix (sidetone)
-    pub mic_to_monitor: bool,
-    /// Microphone level in monitor mix
-    pub mic_monitor_volume: f32,
-}
-
-impl Default for MixerState {
-    fn default() -> Self {
-        Self {
-            stream_master_volume: 1.0,
-            stream_master_muted: false,
-            monitor_master_volume: 1.0,
-            monitor_master_muted: false,
-            mic_to_stream: true,
-            mic_stream_volume: 1.0,
-            mic_to_monitor: false,
-            mic_monitor_volume: 0.0,
+    /// Master level per mix
+    pub masters: BTreeMap<MixType, MixSend>,
+    /// Microphone level per mix; a muted send leaves the mic out of that mix
+    pub mic_sends: BTreeMap<MixType, MixSend>,
+    /// Ducking rules per mix
+    pub ducking: BTreeMap<MixType, Vec<DuckingRule>>,
+}
+
+impl MixerState {
+    /// Master level of a mix.
+    #[must_use]
+    pub fn master(&self, mix: &MixType) -> MixSend {
+        self.masters.get(mix).copied().unwrap_or_default()
+    }
+
+    /// Set the master volume of a mix (0.0 - 1.0).
+    pub fn set_master_volume(&mut self, mix: &MixType, volume: f32) {
+        self.masters.entry(mix.clone()).or_default().volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// Set the master mute of a mix.
+    pub fn set_master_muted(&mut self, mix: &MixType, muted: bool) {
+        self.masters.entry(mix.clone()).or_default().muted = muted;
+    }
+
+    /// Microphone send into a mix.
+    ///
+    /// The mic is in every mix at full level by default, except the monitor
+    /// mix where sidetone starts off.
+    #[must_use]
+    pub fn mic_send(&self, mix: &MixType) -> MixSend {
+        self.mic_sends.get(mix).copied().unwrap_or_else(|| default_mic_send(mix))
+    }
+
+    /// Whether the microphone is sent to a mix.
+    #[must_use]
+    pub fn mic_enabled(&self, mix: &MixType) -> bool {
+        !self.mic_send(mix).muted
+    }
+
+    /// Microphone level in a mix.
+    #[must_use]
+    pub fn mic_volume(&self, mix: &MixType) -> f32 {
+        self.mic_send(mix).volume
+    }
+
+    /// Include or exclude the microphone from a mix.
+    pub fn set_mic_enabled(&mut self, mix: &MixType, enabled: bool) {
+        let send = self.mic_send(mix);
+        self.mic_sends.insert(mix.clone(), MixSend { muted: !enabled, ..send });
+    }
+
+    /// Set the microphone level in a mix (0.0 - 1.0).
+    pub fn set_mic_volume(&mut self, mix: &MixType, volume: f32) {
+        let send = self.mic_send(mix);
+        self.mic_sends.insert(mix.clone(), MixSend { volume: volume.clamp(0.0, 1.0), ..send });
+    }
+
+    /// Ducking rules of a mix.
+    #[must_use]
+    pub fn ducking_rules(&self, mix: &MixType) -> &[DuckingRule] {
+        self.ducking.get(mix).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replace the ducking rules of a mix.
+    pub fn set_ducking_rules(&mut self, mix: &MixType, rules: Vec<DuckingRule>) {
+        if rules.is_empty() {
+            self.ducking.remove(mix);
+        } else {
+            self.ducking.insert(mix.clone(), rules);
+        }
+    }
+
+    /// Drop everything stored for a mix.
+    pub fn remove_mix(&mut self, mix: &MixType) {
+        self.masters.remove(mix);
+        self.mic_sends.remove(mix);
+        self.ducking.remove(mix);
+    }
+
+    /// Drop a deleted channel from the ducking rules.
+    ///
+    /// Rules it triggered are removed, as are rules left without targets.
+    /// Returns the mixes whose rules changed.
+    pub fn remove_channel(&mut self, channel: &str) -> Vec<MixType> {
+        let mut changed = Vec::new();
+        for (mix, rules) in &mut self.ducking {
+            let before = rules.clone();
+            rules.retain(|r| r.trigger != DuckTrigger::Channel(channel.to_string()));
+            for rule in rules.iter_mut() {
+                rule.targets.retain(|t| t != channel);
+            }
+            rules.retain(|r| !r.targets.is_empty());
+            if *rules != before {
+                changed.push(mix.clone());
+            }
+        }
+        self.ducking.retain(|_, rules| !rules.is_empty());
+        changed
+    }
+}
+
+fn default_mic_send(mix: &MixType) -> MixSend {
+    if *mix == MixType::MONITOR { MixSend::new(0.0, true) } else { MixSend::default() }
+}
+
+/// Mixer state as stored in profiles, including the layout used before
+/// mixes were configurable.
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::struct_excessive_bools)]
+enum StoredMixerState {
+    Legacy {
+        stream_master_volume: f32,
+        stream_master_muted: bool,
+        monitor_master_volume: f32,
+        monitor_master_muted: bool,
+        mic_to_stream: bool,
+        mic_stream_volume: f32,
+        mic_to_monitor: bool,
+        mic_monitor_volume: f32,
+    },
+    Current {
+        #[serde(default)]
+        masters: BTreeMap<MixType, MixSend>,
+        #[serde(default)]
+        mic_sends: BTreeMap<MixType, MixSend>,
+        #[serde(default)]
+        ducking: BTreeMap<MixType, Vec<DuckingRule>>,
+    },
+}
+
+impl From<StoredMixerState> for MixerState {
+    fn from(stored: StoredMixerState) -> Self {
+        match stored {
+            StoredMixerState::Current { masters, mic_sends, ducking } => {
+                Self { masters, mic_sends, ducking }
+            }
+            StoredMixerState::Legacy {
+                stream_master_volume,
+                stream_master_muted,
+                monitor_master_volume,
+                monitor_master_muted,
+                mic_to_stream,
+                mic_stream_volume,
+                mic_to_monitor,
+                mic_monitor_volume,
+            } => Self {
+                masters: BTreeMap::from([
+                    (MixType::STREAM, MixSend::new(stream_master_volume, stream_master_muted)),
+                    (MixType::MONITOR, MixSend::new(monitor_master_volume, monitor_master_muted)),
+                ]),
+                mic_sends: BTreeMap::from([
+                    (MixType::STREAM, MixSend::new(mic_stream_volume, !mic_to_stream)),
+                    (MixType::MONITOR, MixSend::new(mic_monitor_volume, !mic_to_monitor)),
+                ]),
+                ducking: BTreeMap::new(),
+            },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mix_node_names() {
+        let recording = MixType::new("recording");
+
+        assert_eq!(MixType::STREAM.mix_node_name(), "ut-stream-mix");
+        assert_eq!(recording.mix_node_name(), "ut-recording-mix");
+        assert_eq!(MixType::STREAM.source_node_name(), "ut-stream-source");
+        assert_eq!(recording.mic_vol_node_name(), "ut-mic-recording-vol");
+        assert_eq!(serde_json::to_string(&MixType::MONITOR).unwrap(), r#""monitor""#);
+        assert_eq!(serde_json::from_str::<MixType>(r#""stream""#).unwrap(), MixType::STREAM);
+    }
+
+    #[test]
+    fn test_mix_names() {
+        assert!(validate_mix_name("recording").is_ok());
+        assert!(validate_mix_name("chat2").is_ok());
+        assert!(validate_mix_name("").is_err());
+        assert!(validate_mix_name("chat-mix").is_err());
+        assert!(validate_mix_name("Chat").is_err());
+        assert!(validate_mix_name("mic").is_err());
+        assert!(validate_mix_name("ch").is_err());
+        assert!(validate_mix_name("direct").is_err());
+    }
+
+    #[test]
+    fn test_mic_send_defaults() {
+        let mut mixer = MixerState::default();
+
+        assert!(mixer.mic_enabled(&MixType::STREAM));
+        assert!(!mixer.mic_enabled(&MixType::MONITOR));
+        assert!(mixer.mic_enabled(&MixType::new("chat")));
+
+        mixer.set_mic_volume(&MixType::MONITOR, 0.3);
+        assert!(!mixer.mic_enabled(&MixType::MONITOR));
+        mixer.set_mic_enabled(&MixType::MONITOR, true);
+        assert_eq!(mixer.mic_send(&MixType::MONITOR), MixSend::new(0.3, false));
+    }
+
+    #[test]
+    fn test_legacy_mixer_state() {
+        let json = r#"{
+            "stream_master_volume": 0.8, "stream_master_muted": false,
+            "monitor_master_volume": 0.5, "monitor_master_muted": true,
+            "mic_to_stream": true, "mic_stream_volume": 0.9,
+            "mic_to_monitor": false, "mic_monitor_volume": 0.2
+        }"#;
+        let mixer: MixerState = serde_json::from_str(json).unwrap();
+
+        assert_eq!(mixer.master(&MixType::MONITOR), MixSend::new(0.5, true));
+        assert_eq!(mixer.mic_send(&MixType::STREAM), MixSend::new(0.9, false));
+        assert!(!mixer.mic_enabled(&MixType::MONITOR));
+
+        let roundtrip: MixerState =
+            serde_json::from_str(&serde_json::to_string(&mixer).unwrap()).unwrap();
+        assert_eq!(roundtrip.masters, mixer.masters);
+    }
+
+    #[test]
+    fn test_remove_channel_from_ducking() {
+        let rule = |trigger: DuckTrigger, targets: &[&str]| DuckingRule {
+            trigger,
+            targets: targets.iter().map(ToString::to_string).collect(),
+            threshold_db: -40.0,
+            depth_db: 12.0,
+            attack_ms: 50.0,
+            release_ms: 500.0,
+            enabled: true,
+        };
+        let mut mixer = MixerState::default();
+        mixer.set_ducking_rules(
+            &MixType::STREAM,
+            vec![
+                rule(DuckTrigger::Mic, &["music", "game"]),
+                rule(DuckTrigger::Channel("voice".to_string()), &["music"]),
+            ],
+        );
+        mixer.set_ducking_rules(&MixType::MONITOR, vec![rule(DuckTrigger::Mic, &["game"])]);
+
+        assert_eq!(mixer.remove_channel("voice"), vec![MixType::STREAM]);
+        assert_eq!(mixer.ducking_rules(&MixType::STREAM).len(), 1);
+
+        assert_eq!(mixer.remove_channel("game"), vec![MixType::MONITOR, MixType::STREAM]);
+        assert_eq!(mixer.ducking_rules(&MixType::STREAM)[0].targets, vec!["music"]);
+        assert!(mixer.ducking_rules(&MixType::MONITOR).is_empty());
+        assert!(!mixer.ducking.contains_key(&MixType::MONITOR));
+    }
+}
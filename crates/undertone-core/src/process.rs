@@ -0,0 +1,91 @@
+//! Process ancestry, for routing apps by the launcher that started them.
+//!
+//! Games started through Steam, Proton, Lutris or Wine reach `PipeWire` as
+//! `wine64-preloader` or some `.exe`, but their parent processes still
+//! name the launcher. Ancestors are read from `/proc/<pid>/stat`, nearest
+//! first.
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+/// How many parents to follow before giving up.
+const MAX_DEPTH: usize = 32;
+
+/// A process an app was started from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ancestor {
+    /// Process ID
+    pub pid: u32,
+    /// Command name (`comm`, at most 15 characters)
+    pub name: String,
+}
+
+/// Parents of a process, nearest first, up to (not including) init.
+///
+/// Stops early at processes that have exited or cannot be read; returns an
+/// empty list where `/proc` is unavailable.
+#[must_use]
+pub fn ancestors(pid: u32) -> Vec<Ancestor> {
+    let mut ancestors = Vec::new();
+    let mut current = pid;
+
+    while ancestors.len() < MAX_DEPTH {
+        let Some((_, parent)) = read_stat(current) else {
+            break;
+        };
+        if parent <= 1 || parent == current {
+            break;
+        }
+        let Some((name, _)) = read_stat(parent) else {
+            break;
+        };
+        ancestors.push(Ancestor { pid: parent, name });
+        current = parent;
+    }
+
+    ancestors
+}
+
+fn read_stat(pid: u32) -> Option<(String, u32)> {
+    parse_stat(&fs::read_to_string(format!("/proc/{pid}/stat")).ok()?)
+}
+
+/// Command name and parent PID from the contents of `/proc/<pid>/stat`.
+///
+/// The name is in parentheses and may itself contain spaces and
+/// parentheses, so the fields after it are found from the last `)`.
+fn parse_stat(stat: &str) -> Option<(String, u32)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let mut fields = stat.get(close + 1..)?.split_whitespace();
+    let _state = fields.next()?;
+    let parent = fields.next()?.parse().ok()?;
+    Some((name, parent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = "4242 (wine64-preload) S 4200 4242 4100 0 -1 4194560 1234 0 0 0";
+        assert_eq!(parse_stat(stat), Some(("wine64-preload".to_string(), 4200)));
+
+        // Names may contain spaces and parentheses
+        let stat = "77 (Web Content (x)) R 12 77 77 0 -1";
+        assert_eq!(parse_stat(stat), Some(("Web Content (x)".to_string(), 12)));
+
+        assert_eq!(parse_stat("garbage"), None);
+    }
+
+    #[test]
+    fn test_own_ancestors() {
+        // The test runner was started by something, and init is never listed
+        let parents = ancestors(std::process::id());
+        assert!(parents.iter().all(|a| a.pid > 1));
+        assert!(ancestors(u32::MAX).is_empty());
+    }
+}
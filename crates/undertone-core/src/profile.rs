@@ -1,9 +1,12 @@
 //! Profile management for saving/loading mixer configurations.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::channel::ChannelState;
-use crate::mixer::MixerState;
+use crate::effects::ChannelEffects;
+use crate::mixer::{MixSend, MixType, MixerState};
 use crate::routing::RouteRule;
 
 /// Summary of a profile for listing.
@@ -39,24 +42,20 @@ pub struct Profile {
 pub struct ProfileChannel {
     /// Channel name
     pub name: String,
-    /// Stream mix volume
-    pub stream_volume: f32,
-    /// Stream mix muted
-    pub stream_muted: bool,
-    /// Monitor mix volume
-    pub monitor_volume: f32,
-    /// Monitor mix muted
-    pub monitor_muted: bool,
+    /// Level and mute per mix, keyed by mix name
+    pub sends: BTreeMap<MixType, MixSend>,
+    /// Effects settings; profiles saved before effects existed have none
+    /// and leave the channel's current effects alone
+    #[serde(default)]
+    pub effects: Option<ChannelEffects>,
 }
 
 impl From<&ChannelState> for ProfileChannel {
     fn from(state: &ChannelState) -> Self {
         Self {
             name: state.config.name.clone(),
-            stream_volume: state.stream_volume,
-            stream_muted: state.stream_muted,
-            monitor_volume: state.monitor_volume,
-            monitor_muted: state.monitor_muted,
+            sends: state.sends.clone(),
+            effects: Some(state.effects.clone()),
         }
     }
 }
@@ -1,36 +1,209 @@
 //! Application routing rules and matching.
 
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
 use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
+use crate::process::Ancestor;
+
+/// Channel that apps fall back to when no rule matches, unless another
+/// fallback channel is configured.
+pub const FALLBACK_CHANNEL: &str = "system";
+
+/// Capture-rule target standing for the Wave:3 microphone itself.
+pub const CAPTURE_MIC: &str = "mic";
+
+/// Node property holding a stream's title (e.g. a browser tab's).
+pub const MEDIA_NAME: &str = "media.name";
+
+/// Node property holding what a stream is for (e.g. `Communication`).
+pub const MEDIA_ROLE: &str = "media.role";
+
+/// Which of an app's streams a rule routes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteDirection {
+    /// What the app plays, into a channel
+    #[default]
+    Playback,
+    /// What the app records, fed from a mix or the mic
+    Capture,
+}
+
+impl RouteDirection {
+    /// Name used in the database and over IPC.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Playback => "playback",
+            Self::Capture => "capture",
+        }
+    }
+
+    /// Look up a direction by the name `as_str` gives it.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Playback, Self::Capture].into_iter().find(|d| d.as_str() == name)
+    }
+}
+
 /// Pattern type for matching applications.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PatternType {
     /// Exact match on application name
+    #[default]
     Exact,
     /// Prefix match (app name starts with pattern)
     Prefix,
     /// Regular expression match
     Regex,
+    /// Exact match, ignoring case
+    ExactIgnoreCase,
+    /// Prefix match, ignoring case
+    PrefixIgnoreCase,
+    /// App name contains the pattern, ignoring case
+    Contains,
+    /// Shell-style glob (`*` and `?`), ignoring case
+    Glob,
+}
+
+impl PatternType {
+    /// Every pattern type, in the order the UI offers them.
+    pub const ALL: [Self; 7] = [
+        Self::ExactIgnoreCase,
+        Self::PrefixIgnoreCase,
+        Self::Contains,
+        Self::Glob,
+        Self::Exact,
+        Self::Prefix,
+        Self::Regex,
+    ];
+
+    /// Name used in the database and over IPC (e.g. `prefix_ignore_case`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Prefix => "prefix",
+            Self::Regex => "regex",
+            Self::ExactIgnoreCase => "exact_ignore_case",
+            Self::PrefixIgnoreCase => "prefix_ignore_case",
+            Self::Contains => "contains",
+            Self::Glob => "glob",
+        }
+    }
+
+    /// Look up a pattern type by the name `as_str` gives it.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == name)
+    }
+}
+
+/// Which of an app's streams a rule applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "by", content = "value")]
+pub enum StreamSelector {
+    /// Streams whose `media.name` contains this text, ignoring case
+    Name(String),
+    /// Streams whose `media.role` is this, ignoring case
+    Role(String),
+    /// The app's Nth open playback stream, counting from 0 (see
+    /// [`free_stream_index`])
+    Index(u32),
+}
+
+impl StreamSelector {
+    /// Check if a stream is selected, given its node properties and its
+    /// index among the app's open streams.
+    #[must_use]
+    pub fn matches<S: BuildHasher>(
+        &self,
+        properties: &HashMap<String, String, S>,
+        stream_index: u32,
+    ) -> bool {
+        let property = |key| properties.get(key).map(|value: &String| value.to_lowercase());
+        match self {
+            Self::Name(text) => {
+                property(MEDIA_NAME).is_some_and(|name| name.contains(&text.to_lowercase()))
+            }
+            Self::Role(role) => property(MEDIA_ROLE).is_some_and(|r| r == role.to_lowercase()),
+            Self::Index(index) => *index == stream_index,
+        }
+    }
+
+    /// Whether the selector has no name or role to look for; an empty name
+    /// would select every stream.
+    #[must_use]
+    pub fn is_blank(&self) -> bool {
+        match self {
+            Self::Name(value) | Self::Role(value) => value.is_empty(),
+            Self::Index(_) => false,
+        }
+    }
+
+    /// Form stored in the database (`name:<text>`, `role:<role>` or
+    /// `index:<n>`).
+    #[must_use]
+    pub fn to_key(&self) -> String {
+        match self {
+            Self::Name(text) => format!("name:{text}"),
+            Self::Role(role) => format!("role:{role}"),
+            Self::Index(index) => format!("index:{index}"),
+        }
+    }
+
+    /// Parse the form `to_key` gives a selector; blank selectors are
+    /// rejected.
+    #[must_use]
+    pub fn from_key(key: &str) -> Option<Self> {
+        let (by, value) = key.split_once(':')?;
+        let selector = match by {
+            "name" => Self::Name(value.to_string()),
+            "role" => Self::Role(value.to_string()),
+            "index" => Self::Index(value.parse().ok()?),
+            _ => return None,
+        };
+        (!selector.is_blank()).then_some(selector)
+    }
 }
 
 /// A rule for routing applications to channels.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RouteRule {
-    /// Pattern to match against application name
+    /// Pattern to match against application name, or against `property`
     pub pattern: String,
     /// Type of pattern matching
     pub pattern_type: PatternType,
-    /// Target channel name
+    /// Target channel name; for capture rules, the mix the app records
+    /// (or [`CAPTURE_MIC`])
     pub channel: String,
     /// Priority (higher = matched first)
     pub priority: i32,
-    /// Cached compiled regex (if `pattern_type` is Regex)
+    /// `PipeWire` node property the pattern is matched against (e.g.
+    /// `media.role`); `None` matches the application and binary names
+    #[serde(default)]
+    pub property: Option<String>,
+    /// Which of the app's streams the rule routes
+    #[serde(default)]
+    pub direction: RouteDirection,
+    /// Leave matching streams alone ("never touch"); `channel` is unused
+    #[serde(default)]
+    pub exclude: bool,
+    /// Also match the processes the app was started from (e.g. `steam`
+    /// for games it launches)
+    #[serde(default)]
+    pub match_ancestors: bool,
+    /// Only route the app's streams this selects; `None` routes them all
+    #[serde(default)]
+    pub stream: Option<StreamSelector>,
+    /// Cached compiled regex (if `pattern_type` is Regex or Glob)
     #[serde(skip)]
-    compiled_regex: OnceLock<Option<regex::Regex>>,
+    compiled_regex: OnceLock<Result<regex::Regex, regex::Error>>,
 }
 
 impl Clone for RouteRule {
@@ -40,6 +213,11 @@ impl Clone for RouteRule {
             pattern_type: self.pattern_type,
             channel: self.channel.clone(),
             priority: self.priority,
+            property: self.property.clone(),
+            direction: self.direction,
+            exclude: self.exclude,
+            match_ancestors: self.match_ancestors,
+            stream: self.stream.clone(),
             // Don't clone the cache - it will be lazily recompiled
             compiled_regex: OnceLock::new(),
         }
@@ -50,28 +228,151 @@ impl RouteRule {
     /// Create a new route rule.
     #[must_use]
     pub fn new(pattern: String, pattern_type: PatternType, channel: String, priority: i32) -> Self {
-        Self { pattern, pattern_type, channel, priority, compiled_regex: OnceLock::new() }
+        Self {
+            pattern,
+            pattern_type,
+            channel,
+            priority,
+            property: None,
+            direction: RouteDirection::Playback,
+            exclude: false,
+            match_ancestors: false,
+            stream: None,
+            compiled_regex: OnceLock::new(),
+        }
+    }
+
+    /// Create a rule that leaves matching streams where the session manager
+    /// put them.
+    #[must_use]
+    pub fn exclusion(pattern: String, pattern_type: PatternType, priority: i32) -> Self {
+        Self { exclude: true, ..Self::new(pattern, pattern_type, String::new(), priority) }
+    }
+
+    /// Route the app's recording stream instead of its playback.
+    #[must_use]
+    pub fn with_direction(mut self, direction: RouteDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Also match the app's parent processes, so apps started by a
+    /// launcher inherit its route.
+    #[must_use]
+    pub fn with_ancestors(mut self) -> Self {
+        self.match_ancestors = true;
+        self
     }
 
-    /// Check if this rule matches an application name.
+    /// Only route the app's streams `selector` picks out.
+    #[must_use]
+    pub fn with_stream(mut self, selector: StreamSelector) -> Self {
+        self.stream = Some(selector);
+        self
+    }
+
+    /// Whether the rule applies to a stream of the app it matches: always,
+    /// unless it selects other streams.
+    #[must_use]
+    pub fn applies_to_stream<S: BuildHasher>(
+        &self,
+        properties: &HashMap<String, String, S>,
+        stream_index: u32,
+    ) -> bool {
+        self.stream.as_ref().is_none_or(|s| s.matches(properties, stream_index))
+    }
+
+    /// Match the pattern against a node property instead of the app name.
+    #[must_use]
+    pub fn with_property(mut self, key: impl Into<String>) -> Self {
+        self.property = Some(key.into());
+        self
+    }
+
+    /// Check if this rule matches an app, given the names it goes by and
+    /// its node properties.
+    ///
+    /// Property rules look only at their property; a node without it never
+    /// matches. Other rules match any of the names.
+    #[must_use]
+    pub fn matches_app<S: BuildHasher>(
+        &self,
+        names: &[&str],
+        properties: &HashMap<String, String, S>,
+    ) -> bool {
+        match &self.property {
+            Some(key) => properties.get(key).is_some_and(|value| self.matches(value)),
+            None => names.iter().any(|name| self.matches(name)),
+        }
+    }
+
+    /// The nearest of an app's parent processes this rule matches, for
+    /// rules that look at them. Property rules never do.
+    #[must_use]
+    pub fn matching_ancestor<'a>(&self, ancestors: &'a [Ancestor]) -> Option<&'a Ancestor> {
+        if !self.match_ancestors || self.property.is_some() {
+            return None;
+        }
+        ancestors.iter().find(|a| self.matches(&a.name))
+    }
+
+    /// Check if this rule's pattern matches a value (normally the
+    /// application name).
     #[must_use]
     pub fn matches(&self, app_name: &str) -> bool {
         match self.pattern_type {
             PatternType::Exact => app_name == self.pattern,
             PatternType::Prefix => app_name.starts_with(&self.pattern),
-            PatternType::Regex => {
-                let regex =
-                    self.compiled_regex.get_or_init(|| match regex::Regex::new(&self.pattern) {
-                        Ok(re) => Some(re),
-                        Err(e) => {
-                            warn!(pattern = %self.pattern, error = %e, "Invalid regex pattern");
-                            None
-                        }
-                    });
-                regex.as_ref().is_some_and(|re| re.is_match(app_name))
+            PatternType::ExactIgnoreCase => app_name.to_lowercase() == self.pattern.to_lowercase(),
+            PatternType::PrefixIgnoreCase => {
+                app_name.to_lowercase().starts_with(&self.pattern.to_lowercase())
+            }
+            PatternType::Contains => app_name.to_lowercase().contains(&self.pattern.to_lowercase()),
+            PatternType::Regex | PatternType::Glob => {
+                self.regex().is_ok_and(|re| re.is_match(app_name))
             }
         }
     }
+
+    /// Why the pattern can never match: the compile error of a regex or
+    /// glob rule whose pattern is invalid.
+    #[must_use]
+    pub fn pattern_error(&self) -> Option<String> {
+        match self.pattern_type {
+            PatternType::Regex | PatternType::Glob => self.regex().err().map(ToString::to_string),
+            _ => None,
+        }
+    }
+
+    fn regex(&self) -> Result<&regex::Regex, &regex::Error> {
+        self.compiled_regex
+            .get_or_init(|| {
+                let source = if self.pattern_type == PatternType::Glob {
+                    glob_to_regex(&self.pattern)
+                } else {
+                    self.pattern.clone()
+                };
+                regex::Regex::new(&source).inspect_err(|e| {
+                    warn!(pattern = %self.pattern, error = %e, "Invalid regex pattern");
+                })
+            })
+            .as_ref()
+    }
+}
+
+/// Translate a glob into an anchored, case-insensitive regex. `*` matches
+/// any run of characters and `?` any one; everything else is literal.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("(?i)^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
 }
 
 /// An active application route.
@@ -89,39 +390,159 @@ pub struct AppRoute {
     pub channel: String,
     /// Whether this is a saved/persistent route
     pub is_persistent: bool,
+    /// Parent process whose rule the app inherited, if any
+    #[serde(default)]
+    pub ancestor: Option<Ancestor>,
+    /// The stream's title (`media.name`)
+    #[serde(default)]
+    pub media_name: Option<String>,
+    /// Index of the stream among the app's open streams, counting from 0
+    /// (see [`free_stream_index`])
+    #[serde(default)]
+    pub stream_index: u32,
+    /// How this stream's route differs from the app's, if it does
+    #[serde(default)]
+    pub stream_route: Option<StreamRoute>,
+}
+
+/// Why a stream is not routed like the rest of its app.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamRoute {
+    /// A rule for some of the app's streams picked this one
+    Rule(StreamSelector),
+    /// It was moved by hand, until it closes
+    Manual,
+}
+
+/// Index for a stream an app just opened: the lowest one none of the
+/// app's other open streams holds.
+///
+/// Indexes are slots rather than a count, so a stream opened after another
+/// one closed takes the freed slot, and an app that closes and reopens its
+/// second stream gets index 1 back. Streams belong to the same app when
+/// their process IDs match, or by app name when either has none.
+#[must_use]
+pub fn free_stream_index(open: &[AppRoute], app_name: &str, pid: Option<u32>) -> u32 {
+    let taken: HashSet<u32> = open
+        .iter()
+        .filter(|app| match (app.pid, pid) {
+            (Some(theirs), Some(ours)) => theirs == ours,
+            _ => app.app_name == app_name,
+        })
+        .map(|app| app.stream_index)
+        .collect();
+    (0..=u32::MAX).find(|index| !taken.contains(index)).unwrap_or(u32::MAX)
+}
+
+/// The rule deciding an app's route.
+#[derive(Debug, Clone, Copy)]
+pub struct RouteMatch<'a> {
+    /// Winning rule
+    pub rule: &'a RouteRule,
+    /// Parent process the rule matched, when it did not match the app itself
+    pub ancestor: Option<&'a Ancestor>,
 }
 
 /// Find the matching route rule for an application.
 ///
-/// Returns the channel name if a matching rule is found, otherwise returns "system".
+/// Returns the channel name if a matching rule is found, otherwise returns
+/// [`FALLBACK_CHANNEL`].
 #[must_use]
 pub fn find_channel_for_app(
     app_name: &str,
     binary_name: Option<&str>,
     rules: &[RouteRule],
 ) -> String {
-    // Sort rules by priority (higher first)
-    let mut sorted_rules: Vec<_> = rules.iter().collect();
-    sorted_rules.sort_by(|a, b| b.priority.cmp(&a.priority));
-
-    // Try to match against app name
-    for rule in &sorted_rules {
-        if rule.matches(app_name) {
-            return rule.channel.clone();
-        }
+    find_channel_for_node(app_name, binary_name, &HashMap::new(), rules, FALLBACK_CHANNEL)
+}
+
+/// Find the matching route rule for an application, also trying rules
+/// that match its node properties.
+///
+/// Property rules take their turn by priority alongside rules matching the
+/// app name; the binary name is only tried once neither matched. Returns
+/// `fallback` if no rule matches, or if an exclusion rule wins; use
+/// [`find_route`] to tell the two apart.
+#[must_use]
+pub fn find_channel_for_node<S: BuildHasher>(
+    app_name: &str,
+    binary_name: Option<&str>,
+    properties: &HashMap<String, String, S>,
+    rules: &[RouteRule],
+    fallback: &str,
+) -> String {
+    find_route(RouteDirection::Playback, app_name, binary_name, properties, 0, &[], rules)
+        .filter(|m| !m.rule.exclude)
+        .map_or_else(|| fallback.to_string(), |m| m.rule.channel.clone())
+}
+
+/// Find the rule deciding a stream's route in one direction, also looking
+/// at the processes its app was started from (nearest first).
+///
+/// Rules for some of the app's streams are tried first, and only those
+/// selecting this stream (by its properties and `stream_index`) count; the
+/// app-wide rules are tried after them, so they stay the default. Within
+/// each group the app's own names and properties are tried first, then its
+/// binary name, each by rule priority. Only then do rules that opt in with
+/// `match_ancestors` get to match a parent process, again by priority, so
+/// a game with a rule of its own is not swept up by its launcher's.
+#[must_use]
+pub fn find_route<'a, S: BuildHasher>(
+    direction: RouteDirection,
+    app_name: &str,
+    binary_name: Option<&str>,
+    properties: &HashMap<String, String, S>,
+    stream_index: u32,
+    ancestors: &'a [Ancestor],
+    rules: &'a [RouteRule],
+) -> Option<RouteMatch<'a>> {
+    let (stream_rules, app_rules): (Vec<_>, Vec<_>) = rules_by_priority(direction, rules)
+        .into_iter()
+        .filter(|rule| rule.applies_to_stream(properties, stream_index))
+        .partition(|rule| rule.stream.is_some());
+
+    [stream_rules, app_rules]
+        .into_iter()
+        .find_map(|group| find_in_group(&group, app_name, binary_name, properties, ancestors))
+}
+
+fn find_in_group<'a, S: BuildHasher>(
+    sorted_rules: &[&'a RouteRule],
+    app_name: &str,
+    binary_name: Option<&str>,
+    properties: &HashMap<String, String, S>,
+    ancestors: &'a [Ancestor],
+) -> Option<RouteMatch<'a>> {
+    let own = |rule: &&'a RouteRule| RouteMatch { rule, ancestor: None };
+
+    // Try to match against app name and node properties
+    if let Some(rule) = sorted_rules.iter().find(|rule| rule.matches_app(&[app_name], properties)) {
+        return Some(own(rule));
     }
 
     // Try to match against binary name if provided
-    if let Some(binary) = binary_name {
-        for rule in &sorted_rules {
-            if rule.matches(binary) {
-                return rule.channel.clone();
-            }
-        }
+    if let Some(binary) = binary_name
+        && let Some(rule) =
+            sorted_rules.iter().find(|rule| rule.property.is_none() && rule.matches(binary))
+    {
+        return Some(own(rule));
     }
 
-    // Default to system channel
-    "system".to_string()
+    // Try the parent processes, for rules that look at them
+    sorted_rules.iter().find_map(|rule| {
+        let ancestor = rule.matching_ancestor(ancestors)?;
+        Some(RouteMatch { rule, ancestor: Some(ancestor) })
+    })
+}
+
+/// The rules for one direction in the order they are tried: rules for
+/// particular streams before app-wide ones, then highest priority first,
+/// ties in their original order.
+pub(crate) fn rules_by_priority(direction: RouteDirection, rules: &[RouteRule]) -> Vec<&RouteRule> {
+    let mut sorted_rules: Vec<_> = rules.iter().filter(|r| r.direction == direction).collect();
+    sorted_rules.sort_by_key(|rule| (rule.stream.is_none(), std::cmp::Reverse(rule.priority)));
+    sorted_rules
 }
 
 /// Default routing rules for common applications.
@@ -129,18 +550,19 @@ pub fn find_channel_for_app(
 pub fn default_routes() -> Vec<RouteRule> {
     vec![
         // Voice chat applications
-        RouteRule::new("discord".into(), PatternType::Prefix, "voice".into(), 100),
-        RouteRule::new("zoom".into(), PatternType::Prefix, "voice".into(), 100),
-        RouteRule::new("teams".into(), PatternType::Prefix, "voice".into(), 100),
+        RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "voice".into(), 100),
+        RouteRule::new("zoom".into(), PatternType::PrefixIgnoreCase, "voice".into(), 100),
+        RouteRule::new("teams".into(), PatternType::PrefixIgnoreCase, "voice".into(), 100),
         // Music applications
-        RouteRule::new("spotify".into(), PatternType::Exact, "music".into(), 100),
-        RouteRule::new("rhythmbox".into(), PatternType::Exact, "music".into(), 100),
+        RouteRule::new("spotify".into(), PatternType::ExactIgnoreCase, "music".into(), 100),
+        RouteRule::new("rhythmbox".into(), PatternType::ExactIgnoreCase, "music".into(), 100),
         // Browsers
-        RouteRule::new("firefox".into(), PatternType::Exact, "browser".into(), 50),
-        RouteRule::new("chromium".into(), PatternType::Prefix, "browser".into(), 50),
-        RouteRule::new("chrome".into(), PatternType::Prefix, "browser".into(), 50),
+        RouteRule::new("firefox".into(), PatternType::ExactIgnoreCase, "browser".into(), 50),
+        RouteRule::new("chromium".into(), PatternType::PrefixIgnoreCase, "browser".into(), 50),
+        RouteRule::new("chrome".into(), PatternType::PrefixIgnoreCase, "browser".into(), 50),
         // Games
-        RouteRule::new("steam".into(), PatternType::Exact, "game".into(), 100),
+        RouteRule::new("steam".into(), PatternType::ExactIgnoreCase, "game".into(), 100)
+            .with_ancestors(),
     ]
 }
 
@@ -199,6 +621,53 @@ mod tests {
         // Should return false without panicking
         assert!(!rule.matches("anything"));
         assert!(!rule.matches("(invalid"));
+        assert!(rule.pattern_error().is_some());
+
+        let valid = RouteRule::new(r"^ok$".into(), PatternType::Regex, "test".into(), 100);
+        assert_eq!(valid.pattern_error(), None);
+    }
+
+    #[test]
+    fn test_ignore_case_pattern_matches() {
+        let exact = RouteRule::new("Spotify".into(), PatternType::ExactIgnoreCase, "m".into(), 100);
+        assert!(exact.matches("spotify"));
+        assert!(exact.matches("SPOTIFY"));
+        assert!(!exact.matches("spotify-player"));
+
+        let prefix =
+            RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "v".into(), 100);
+        assert!(prefix.matches("Discord"));
+        assert!(prefix.matches("DiscordCanary"));
+        assert!(!prefix.matches("my-discord"));
+
+        let contains = RouteRule::new("obs".into(), PatternType::Contains, "v".into(), 100);
+        assert!(contains.matches("com.obsproject.Studio"));
+        assert!(contains.matches("OBS"));
+        assert!(!contains.matches("zoom"));
+    }
+
+    #[test]
+    fn test_glob_pattern_matches() {
+        let rule = RouteRule::new("*firefox*".into(), PatternType::Glob, "browser".into(), 100);
+        assert!(rule.matches("Firefox"));
+        assert!(rule.matches("org.mozilla.firefox"));
+        assert!(!rule.matches("chromium"));
+
+        // Regex syntax in a glob is literal
+        let rule = RouteRule::new("game?.exe".into(), PatternType::Glob, "game".into(), 100);
+        assert!(rule.matches("Game1.exe"));
+        assert!(!rule.matches("game1-exe"));
+        assert!(!rule.matches("game12.exe"));
+    }
+
+    #[test]
+    fn test_pattern_type_names() {
+        for pattern_type in PatternType::ALL {
+            assert_eq!(PatternType::from_name(pattern_type.as_str()), Some(pattern_type));
+            let json = serde_json::to_string(&pattern_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", pattern_type.as_str()));
+        }
+        assert_eq!(PatternType::from_name("fuzzy"), None);
     }
 
     #[test]
@@ -258,6 +727,173 @@ mod tests {
         assert_eq!(find_channel_for_app("spotify", Some("media-player"), &rules), "music");
     }
 
+    #[test]
+    fn test_property_rule_matches() {
+        let rules = vec![
+            RouteRule::new("Communication".into(), PatternType::Exact, "voice".into(), 50)
+                .with_property("media.role"),
+            RouteRule::new("firefox".into(), PatternType::Exact, "browser".into(), 100),
+        ];
+        let properties = HashMap::from([("media.role".to_string(), "Communication".to_string())]);
+
+        assert_eq!(
+            find_channel_for_node("webrtc", None, &properties, &rules, FALLBACK_CHANNEL),
+            "voice"
+        );
+        // Higher-priority name rules still win
+        assert_eq!(
+            find_channel_for_node("firefox", None, &properties, &rules, FALLBACK_CHANNEL),
+            "browser"
+        );
+        // Without the property, the rule is skipped, even for a matching name
+        assert_eq!(find_channel_for_app("Communication", None, &rules), "system");
+        assert!(!rules[0].matches_app(&["Communication"], &HashMap::new()));
+    }
+
+    #[test]
+    fn test_capture_rules() {
+        let rules = vec![
+            RouteRule::new("obs".into(), PatternType::PrefixIgnoreCase, "stream".into(), 100)
+                .with_direction(RouteDirection::Capture),
+            RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "mic".into(), 100)
+                .with_direction(RouteDirection::Capture),
+            RouteRule::new("discord".into(), PatternType::PrefixIgnoreCase, "voice".into(), 100),
+        ];
+        let none = HashMap::new();
+        let source = |name, binary| {
+            find_route(RouteDirection::Capture, name, binary, &none, 0, &[], &rules)
+                .map(|m| m.rule.channel.as_str())
+        };
+
+        assert_eq!(source("OBS", None), Some("stream"));
+        assert_eq!(source("x", Some("discord")), Some("mic"));
+        assert_eq!(source("zoom", None), None);
+
+        // Capture rules never route playback
+        assert_eq!(find_channel_for_app("Discord", None, &rules), "voice");
+        assert_eq!(find_channel_for_app("obs", None, &rules), "system");
+
+        assert_eq!(RouteDirection::from_name("capture"), Some(RouteDirection::Capture));
+        assert_eq!(RouteDirection::Capture.as_str(), "capture");
+    }
+
+    #[test]
+    fn test_exclusion_rules() {
+        let rules = vec![
+            RouteRule::exclusion("pavucontrol".into(), PatternType::ExactIgnoreCase, 200),
+            RouteRule::new("pavu".into(), PatternType::PrefixIgnoreCase, "music".into(), 100),
+            RouteRule::new("Peak".into(), PatternType::Contains, "voice".into(), 300),
+        ];
+        let none = HashMap::new();
+        let excluded = |name, binary| {
+            find_route(RouteDirection::Playback, name, binary, &none, 0, &[], &rules)
+                .is_some_and(|m| m.rule.exclude)
+        };
+
+        assert!(excluded("pavucontrol", None));
+        assert!(excluded("x", Some("Pavucontrol")));
+        // A higher-priority rule still wins over the exclusion
+        assert!(!excluded("pavucontrol peak", None));
+        assert_eq!(find_channel_for_node("pavucontrol peak", None, &none, &rules, "game"), "voice");
+
+        // Unmatched apps go to the configured fallback
+        assert!(!excluded("speech-dispatcher", None));
+        assert_eq!(find_channel_for_node("speech-dispatcher", None, &none, &rules, "game"), "game");
+    }
+
+    #[test]
+    fn test_ancestor_rules() {
+        let rules = default_routes();
+        let none = HashMap::new();
+        let ancestors = [
+            Ancestor { pid: 4200, name: "wine-preloader".into() },
+            Ancestor { pid: 4100, name: "reaper".into() },
+            Ancestor { pid: 4000, name: "steam".into() },
+        ];
+        let route = |name, ancestors| {
+            find_route(RouteDirection::Playback, name, None, &none, 0, ancestors, &rules)
+                .map(|m| (m.rule.channel.as_str(), m.ancestor.map(|a| a.pid)))
+        };
+
+        // The game inherits Steam's route, and the ancestor is reported
+        assert_eq!(route("game.exe", &ancestors), Some(("game", Some(4000))));
+        // Its own name still wins over the launcher
+        assert_eq!(route("Firefox", &ancestors), Some(("browser", None)));
+        assert_eq!(route("game.exe", &[]), None);
+
+        // Rules only look at ancestors when asked to
+        let discord = [Ancestor { pid: 10, name: "discord".into() }];
+        assert_eq!(route("game.exe", &discord), None);
+    }
+
+    #[test]
+    fn test_stream_rules() {
+        let rules = vec![
+            RouteRule::new("firefox".into(), PatternType::ExactIgnoreCase, "browser".into(), 100),
+            RouteRule::new("firefox".into(), PatternType::ExactIgnoreCase, "voice".into(), 10)
+                .with_stream(StreamSelector::Name("Meet".into())),
+            RouteRule::new("firefox".into(), PatternType::ExactIgnoreCase, "music".into(), 10)
+                .with_stream(StreamSelector::Index(2)),
+        ];
+        let stream = |title: &str| HashMap::from([(MEDIA_NAME.to_string(), title.to_string())]);
+        let route = |properties: &HashMap<String, String>, index| {
+            find_route(RouteDirection::Playback, "Firefox", None, properties, index, &[], &rules)
+                .map(|m| m.rule.channel.as_str())
+        };
+
+        // Stream rules win over the app's rule despite their lower priority
+        assert_eq!(route(&stream("Google Meet - standup"), 0), Some("voice"));
+        assert_eq!(route(&stream("YouTube"), 2), Some("music"));
+        // Other streams keep the app-wide route
+        assert_eq!(route(&stream("YouTube"), 0), Some("browser"));
+        assert_eq!(route(&HashMap::new(), 1), Some("browser"));
+
+        let role = StreamSelector::Role("communication".into());
+        let call = HashMap::from([(MEDIA_ROLE.to_string(), "Communication".to_string())]);
+        assert!(role.matches(&call, 0));
+        assert!(!role.matches(&stream("Communication"), 0));
+
+        for selector in [role, StreamSelector::Name("a:b".into()), StreamSelector::Index(3)] {
+            assert_eq!(StreamSelector::from_key(&selector.to_key()), Some(selector));
+        }
+        assert_eq!(StreamSelector::from_key("index:x"), None);
+        assert_eq!(StreamSelector::from_key("name:"), None);
+        assert_eq!(StreamSelector::from_key("role:"), None);
+        assert!(StreamSelector::Name(String::new()).is_blank());
+    }
+
+    #[test]
+    fn test_free_stream_index() {
+        let stream = |app_id, pid, stream_index| AppRoute {
+            app_id,
+            app_name: "Firefox".into(),
+            binary_name: None,
+            pid,
+            channel: "browser".into(),
+            is_persistent: false,
+            ancestor: None,
+            media_name: None,
+            stream_index,
+            stream_route: None,
+        };
+        let mut open = Vec::new();
+        for app_id in 0..3 {
+            let index = free_stream_index(&open, "Firefox", Some(100));
+            open.push(stream(app_id, Some(100), index));
+        }
+        assert_eq!(open.iter().map(|a| a.stream_index).collect::<Vec<_>>(), [0, 1, 2]);
+
+        // Closing the second stream and opening another gives its slot back
+        open.retain(|a| a.app_id != 1);
+        assert_eq!(free_stream_index(&open, "Firefox", Some(100)), 1);
+        open.push(stream(3, Some(100), 1));
+        assert_eq!(free_stream_index(&open, "Firefox", Some(100)), 3);
+
+        // Another process numbers its own streams
+        assert_eq!(free_stream_index(&open, "Firefox", Some(200)), 0);
+        assert_eq!(free_stream_index(&open, "Firefox", None), 3);
+    }
+
     #[test]
     fn test_empty_rules_returns_system() {
         assert_eq!(find_channel_for_app("any-app", None, &[]), "system");
@@ -271,6 +907,7 @@ mod tests {
         assert_eq!(find_channel_for_app("discord-canary", None, &routes), "voice");
         assert_eq!(find_channel_for_app("zoom", None, &routes), "voice");
         assert_eq!(find_channel_for_app("teams", None, &routes), "voice");
+        assert_eq!(find_channel_for_app("Discord", None, &routes), "voice");
     }
 
     #[test]
@@ -279,6 +916,7 @@ mod tests {
 
         assert_eq!(find_channel_for_app("spotify", None, &routes), "music");
         assert_eq!(find_channel_for_app("rhythmbox", None, &routes), "music");
+        assert_eq!(find_channel_for_app("Spotify", None, &routes), "music");
         // spotify-player doesn't match exact "spotify"
         assert_eq!(find_channel_for_app("spotify-player", None, &routes), "system");
     }
@@ -0,0 +1,122 @@
+//! Solo and pre-fader listen (PFL) in the monitor mix.
+//!
+//! Soloing only changes what is heard in the monitor mix; the stream mix is
+//! never touched. While any channel is soloed, every other channel's monitor
+//! send is muted. In PFL mode the soloed channels are also heard at full
+//! level and unmuted, regardless of their monitor fader.
+//!
+//! Solo is an overlay on the channels' sends rather than a change to them,
+//! so releasing it leaves every send exactly as the user set it.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mixer::MixType;
+
+/// How soloed channels are heard.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoloMode {
+    /// Soloed channels keep their monitor level and mute state
+    #[default]
+    Solo,
+    /// Soloed channels are heard at full level, bypassing their fader
+    Pfl,
+}
+
+/// Channels currently soloed, and how they are heard.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoloState {
+    /// Solo mode
+    pub mode: SoloMode,
+    /// Soloed channels, by name
+    pub channels: BTreeSet<String>,
+}
+
+impl SoloState {
+    /// Whether any channel is soloed.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.channels.is_empty()
+    }
+
+    /// Whether a channel is soloed.
+    #[must_use]
+    pub fn is_soloed(&self, channel: &str) -> bool {
+        self.channels.contains(channel)
+    }
+
+    /// Solo or release a channel. Returns whether anything changed.
+    pub fn set(&mut self, channel: &str, soloed: bool) -> bool {
+        if soloed {
+            self.channels.insert(channel.to_string())
+        } else {
+            self.channels.remove(channel)
+        }
+    }
+
+    /// Volume to apply to a channel's send into a mix whose fader is at
+    /// `volume`.
+    #[must_use]
+    pub fn send_volume(&self, channel: &str, mix: &MixType, volume: f32) -> f32 {
+        if self.overrides_fader(channel, mix) { 1.0 } else { volume }
+    }
+
+    /// Mute state to apply to a channel's send into a mix that the user
+    /// muted (or not).
+    #[must_use]
+    pub fn send_muted(&self, channel: &str, mix: &MixType, muted: bool) -> bool {
+        if *mix != MixType::MONITOR || !self.is_active() {
+            muted
+        } else if self.is_soloed(channel) {
+            muted && self.mode == SoloMode::Solo
+        } else {
+            true
+        }
+    }
+
+    fn overrides_fader(&self, channel: &str, mix: &MixType) -> bool {
+        self.mode == SoloMode::Pfl && *mix == MixType::MONITOR && self.is_soloed(channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solo_mutes_other_monitor_sends() {
+        let mut solo = SoloState::default();
+        assert!(!solo.send_muted("music", &MixType::MONITOR, false));
+
+        assert!(solo.set("voice", true));
+        assert!(!solo.set("voice", true));
+        assert!(solo.send_muted("music", &MixType::MONITOR, false));
+        assert!(!solo.send_muted("voice", &MixType::MONITOR, false));
+        // The soloed channel's own mute and level still apply
+        assert!(solo.send_muted("voice", &MixType::MONITOR, true));
+        assert!((solo.send_volume("voice", &MixType::MONITOR, 0.4) - 0.4).abs() < f32::EPSILON);
+
+        // The stream mix is never touched
+        assert!(!solo.send_muted("music", &MixType::STREAM, false));
+
+        // Releasing restores the user's mute state
+        assert!(solo.set("voice", false));
+        assert!(!solo.send_muted("music", &MixType::MONITOR, false));
+        assert!(solo.send_muted("music", &MixType::MONITOR, true));
+    }
+
+    #[test]
+    fn test_pfl_bypasses_fader_and_mute() {
+        let mut solo = SoloState { mode: SoloMode::Pfl, ..SoloState::default() };
+        solo.set("game", true);
+
+        assert!((solo.send_volume("game", &MixType::MONITOR, 0.2) - 1.0).abs() < f32::EPSILON);
+        assert!(!solo.send_muted("game", &MixType::MONITOR, true));
+        assert!((solo.send_volume("game", &MixType::STREAM, 0.2) - 0.2).abs() < f32::EPSILON);
+        assert!(solo.send_muted("game", &MixType::STREAM, true));
+        assert!(solo.send_muted("music", &MixType::MONITOR, false));
+    }
+}
@@ -4,9 +4,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::channel::ChannelState;
-use crate::mixer::MixerState;
+use crate::mixer::{MixConfig, MixerState, default_mixes};
 use crate::profile::ProfileSummary;
-use crate::routing::AppRoute;
+use crate::routing::{AppRoute, FALLBACK_CHANNEL, RouteRule};
+use crate::solo::SoloState;
+use crate::volume::VolumeTaper;
 
 /// Current state of the daemon.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -86,8 +88,25 @@ pub struct StateSnapshot {
     pub channels: Vec<ChannelState>,
     /// Active app routes
     pub app_routes: Vec<AppRoute>,
+    /// Routing rules
+    #[serde(default)]
+    pub routes: Vec<RouteRule>,
+    /// `PipeWire` node properties of the active apps, by app ID, for
+    /// explaining their routes
+    #[serde(skip)]
+    pub app_properties: HashMap<u32, HashMap<String, String>>,
+    /// Channel that apps no rule matches are sent to
+    pub fallback_channel: String,
+    /// Configured mixes
+    pub mixes: Vec<MixConfig>,
     /// Mixer state
     pub mixer: MixerState,
+    /// Curve turning volumes (fader positions) into gain
+    #[serde(default)]
+    pub volume_taper: VolumeTaper,
+    /// Channels soloed in the monitor mix
+    #[serde(default)]
+    pub solo: SoloState,
     /// Active profile name
     pub active_profile: String,
     /// Available profiles
@@ -110,7 +129,13 @@ impl Default for StateSnapshot {
             device_serial: None,
             channels: Vec::new(),
             app_routes: Vec::new(),
+            routes: Vec::new(),
+            app_properties: HashMap::new(),
+            fallback_channel: FALLBACK_CHANNEL.to_string(),
+            mixes: default_mixes(),
             mixer: MixerState::default(),
+            volume_taper: VolumeTaper::default(),
+            solo: SoloState::default(),
             active_profile: "Default".to_string(),
             profiles: vec![ProfileSummary {
                 name: "Default".to_string(),
@@ -0,0 +1,818 @@
+//! Volume curves, and coalescing and ramping of volume changes.
+//!
+//! Volumes throughout Undertone are fader positions from 0.0 to 1.0. A
+//! [`VolumeTaper`] turns a position into the linear gain written to
+//! `PipeWire`, so the same position can mean different levels depending on
+//! the configured curve.
+//!
+//! Changes can also be faded in over a set time (see [`Fade`]); the fade
+//! itself runs where the gain is written, on the `PipeWire` thread, which
+//! keeps track of it with [`NodeFades`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Quietest level that can be addressed in dB; anything lower is silence.
+pub const MIN_DB: f32 = -60.0;
+
+/// Curve mapping a fader position (0.0 - 1.0) to a linear gain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeTaper {
+    /// Gain equals position; most of the travel sits in the top few dB
+    #[default]
+    Linear,
+    /// Gain is the cube of position, as used by `PulseAudio` and `PipeWire`'s
+    /// own volume controls
+    Cubic,
+    /// Position is spread evenly in dB from [`MIN_DB`] (bottom) to 0 dB (top)
+    Db,
+}
+
+impl VolumeTaper {
+    /// Linear gain for a fader position.
+    #[must_use]
+    pub fn gain(self, position: f32) -> f32 {
+        let position = position.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => position,
+            Self::Cubic => position.powi(3),
+            Self::Db if position <= 0.0 => 0.0,
+            Self::Db => db_to_gain((1.0 - position) * MIN_DB),
+        }
+    }
+
+    /// Fader position for a linear gain; gains above unity give the top of
+    /// the range.
+    #[must_use]
+    pub fn position(self, gain: f32) -> f32 {
+        let gain = gain.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => gain,
+            Self::Cubic => gain.cbrt(),
+            Self::Db => 1.0 - (gain_to_db(gain) / MIN_DB).min(1.0),
+        }
+    }
+
+    /// Level of a fader position in dB (negative infinity for silence).
+    #[must_use]
+    pub fn position_to_db(self, position: f32) -> f32 {
+        gain_to_db(self.gain(position))
+    }
+
+    /// Fader position for a level in dB; levels at or below [`MIN_DB`] are
+    /// silence.
+    #[must_use]
+    pub fn db_to_position(self, db: f32) -> f32 {
+        if db <= MIN_DB { 0.0 } else { self.position(db_to_gain(db)) }
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+fn gain_to_db(gain: f32) -> f32 {
+    if gain <= 0.0 { f32::NEG_INFINITY } else { 20.0 * gain.log10() }
+}
+
+/// A requested volume: an absolute fader position or dB level, or a step
+/// from the current volume.
+///
+/// A bare number is a fader position, so `0.5`, `{"db": -12}`,
+/// `{"step": 0.05}` and `{"step_db": -3}` are all accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VolumeValue {
+    /// Fader position (0.0 - 1.0)
+    Position(f32),
+    /// Level in dB (0 dB is full volume)
+    Db { db: f32 },
+    /// Change in fader position
+    Step { step: f32 },
+    /// Change in level, in dB
+    StepDb { step_db: f32 },
+}
+
+impl VolumeValue {
+    /// Resolve to a fader position, given the current position and the
+    /// taper in use. Results are clamped to 0.0 - 1.0.
+    ///
+    /// Stepping up in dB from silence starts at [`MIN_DB`].
+    ///
+    /// # Errors
+    /// Returns `Error::InvalidVolume` if the value is not a number.
+    pub fn resolve(self, current: f32, taper: VolumeTaper) -> Result<f32> {
+        let position = match self {
+            Self::Position(position) => position,
+            Self::Db { db } => taper.db_to_position(db),
+            Self::Step { step } => current + step,
+            Self::StepDb { step_db } => {
+                let db = taper.position_to_db(current).max(MIN_DB);
+                taper.db_to_position(db + step_db)
+            }
+        };
+        if position.is_nan() {
+            return Err(Error::InvalidVolume(position));
+        }
+        Ok(position.clamp(0.0, 1.0))
+    }
+}
+
+impl From<f32> for VolumeValue {
+    fn from(position: f32) -> Self {
+        Self::Position(position)
+    }
+}
+
+/// Longest fade a request may ask for.
+pub const MAX_FADE_MS: u32 = 60_000;
+
+/// Turn a requested fade time into a duration; no time or zero means the
+/// change is applied at once.
+///
+/// # Errors
+/// Returns `Error::InvalidFade` if the time is above [`MAX_FADE_MS`].
+pub fn fade_duration(fade_ms: Option<u32>) -> Result<Option<Duration>> {
+    match fade_ms {
+        Some(ms) if ms > MAX_FADE_MS => Err(Error::InvalidFade(ms)),
+        Some(ms) if ms > 0 => Ok(Some(Duration::from_millis(u64::from(ms)))),
+        _ => Ok(None),
+    }
+}
+
+/// A timed transition between two sets of left and right gains.
+///
+/// Gains are interpolated on a cubic scale rather than a straight line, so
+/// a fade sounds even all the way down to silence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fade {
+    from: [f32; 2],
+    to: [f32; 2],
+    duration: Duration,
+}
+
+impl Fade {
+    /// Create a fade from `from` to `to` lasting `duration`.
+    #[must_use]
+    pub fn new(from: [f32; 2], to: [f32; 2], duration: Duration) -> Self {
+        Self { from, to, duration }
+    }
+
+    /// Gains the fade started from.
+    #[must_use]
+    pub fn from(&self) -> [f32; 2] {
+        self.from
+    }
+
+    /// Gains `elapsed` into the fade; the target once it is over.
+    #[must_use]
+    pub fn gains_at(&self, elapsed: Duration) -> [f32; 2] {
+        if self.is_done(elapsed) {
+            return self.to;
+        }
+        let t = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        let mut gains = [0.0; 2];
+        for (side, gain) in gains.iter_mut().enumerate() {
+            let (from, to) = (self.from[side].max(0.0).cbrt(), self.to[side].max(0.0).cbrt());
+            *gain = (from + (to - from) * t).powi(3);
+        }
+        gains
+    }
+
+    /// Whether the fade is over `elapsed` after it started.
+    #[must_use]
+    pub fn is_done(&self, elapsed: Duration) -> bool {
+        elapsed >= self.duration
+    }
+}
+
+/// Volume and mute state last written to a node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeLevel {
+    pub volumes: [f32; 2],
+    pub muted: bool,
+}
+
+impl Default for NodeLevel {
+    fn default() -> Self {
+        // Nodes are created at full volume, unmuted
+        Self { volumes: [1.0, 1.0], muted: false }
+    }
+}
+
+/// A single change written to a node by [`NodeFades`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LevelWrite {
+    Volume([f32; 2]),
+    Mute(bool),
+}
+
+/// A fade in progress on a node.
+#[derive(Debug, Clone, Copy)]
+struct RunningFade {
+    fade: Fade,
+    started: Instant,
+    /// Levels the node is left at when the fade ends or is cancelled
+    settle: [f32; 2],
+    /// Mute the node once the fade has reached silence
+    mute: bool,
+}
+
+/// Levels written to each node and the fades running on them.
+///
+/// Changes are handed to a `write` callback and only recorded once it
+/// succeeds, so the levels always match what the nodes were last set to.
+#[derive(Debug, Default)]
+pub struct NodeFades {
+    levels: HashMap<u32, NodeLevel>,
+    fades: HashMap<u32, RunningFade>,
+}
+
+impl NodeFades {
+    /// Create an empty set with no fades running.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Levels last written to a node.
+    #[must_use]
+    pub fn level(&self, node: u32) -> NodeLevel {
+        self.levels.get(&node).copied().unwrap_or_default()
+    }
+
+    /// Whether no fade is running.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.fades.is_empty()
+    }
+
+    /// Drop everything known about a node, e.g. once it is destroyed.
+    pub fn forget(&mut self, node: u32) {
+        self.levels.remove(&node);
+        self.fades.remove(&node);
+    }
+
+    /// Set a node's volume at once.
+    ///
+    /// A running fade to another volume is dropped. A fade out to mute keeps
+    /// going and leaves the node at `volumes` once muted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a failed write.
+    pub fn set_volume<E>(
+        &mut self,
+        node: u32,
+        volumes: [f32; 2],
+        mut write: impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        if let Some(running) = self.fades.get_mut(&node)
+            && running.mute
+        {
+            running.settle = volumes;
+            return Ok(());
+        }
+        self.fades.remove(&node);
+        self.write(node, LevelWrite::Volume(volumes), &mut write)
+    }
+
+    /// Set a node's mute state at once, leaving any running fade at the
+    /// levels it was heading for.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a failed write.
+    pub fn set_mute<E>(
+        &mut self,
+        node: u32,
+        muted: bool,
+        mut write: impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        if let Some(running) = self.fades.remove(&node) {
+            self.write(node, LevelWrite::Volume(running.settle), &mut write)?;
+        }
+        self.write(node, LevelWrite::Mute(muted), &mut write)
+    }
+
+    /// Start fading a node from its current volume to `volumes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a failed write.
+    pub fn fade_volume<E>(
+        &mut self,
+        node: u32,
+        volumes: [f32; 2],
+        duration: Duration,
+        now: Instant,
+        mut write: impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        // A new fade picks up from wherever the old one got to
+        self.fades.remove(&node);
+        let from = self.level(node).volumes;
+        self.start(node, Fade::new(from, volumes, duration), volumes, false, now, &mut write)
+    }
+
+    /// Start fading a node out to mute it, or unmute it and fade it in.
+    ///
+    /// `volumes` are the node's levels while audible: an unmuted node fades
+    /// in to them, a muted one is left at them so it is heard at the right
+    /// level once unmuted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a failed write.
+    pub fn fade_mute<E>(
+        &mut self,
+        node: u32,
+        muted: bool,
+        volumes: [f32; 2],
+        duration: Duration,
+        now: Instant,
+        mut write: impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        self.fades.remove(&node);
+        let level = self.level(node);
+
+        match (muted, level.muted) {
+            // Already silent: just keep the level for when it is unmuted
+            (true, true) => self.write(node, LevelWrite::Volume(volumes), &mut write),
+            (true, false) => {
+                let fade = Fade::new(level.volumes, [0.0; 2], duration);
+                self.start(node, fade, volumes, true, now, &mut write)
+            }
+            // Still audible, possibly part way through fading out
+            (false, false) => {
+                let fade = Fade::new(level.volumes, volumes, duration);
+                self.start(node, fade, volumes, false, now, &mut write)
+            }
+            (false, true) => {
+                self.write(node, LevelWrite::Volume([0.0; 2]), &mut write)?;
+                self.write(node, LevelWrite::Mute(false), &mut write)?;
+                let fade = Fade::new([0.0; 2], volumes, duration);
+                self.start(node, fade, volumes, false, now, &mut write)
+            }
+        }
+    }
+
+    /// Move every running fade on to `now`, finishing those that are over.
+    ///
+    /// A fade whose write fails is dropped; the failures are returned by
+    /// node.
+    pub fn step<E>(
+        &mut self,
+        now: Instant,
+        mut write: impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> Vec<(u32, E)> {
+        let nodes: Vec<u32> = self.fades.keys().copied().collect();
+        nodes
+            .into_iter()
+            .filter_map(|node| self.step_node(node, now, &mut write).err().map(|e| (node, e)))
+            .collect()
+    }
+
+    /// Register a fade on a node and write its first step; a zero-length
+    /// fade is applied at once.
+    fn start<E>(
+        &mut self,
+        node: u32,
+        fade: Fade,
+        settle: [f32; 2],
+        mute: bool,
+        now: Instant,
+        write: &mut impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        self.fades.insert(node, RunningFade { fade, started: now, settle, mute });
+        self.step_node(node, now, write)
+    }
+
+    fn step_node<E>(
+        &mut self,
+        node: u32,
+        now: Instant,
+        write: &mut impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        let Some(running) = self.fades.get(&node).copied() else {
+            return Ok(());
+        };
+        let elapsed = now.saturating_duration_since(running.started);
+        let done = running.fade.is_done(elapsed);
+
+        let result = self.write(node, LevelWrite::Volume(running.fade.gains_at(elapsed)), write);
+        let result = result.and_then(|()| {
+            if done && running.mute {
+                self.write(node, LevelWrite::Mute(true), write)?;
+                self.write(node, LevelWrite::Volume(running.settle), write)?;
+            }
+            Ok(())
+        });
+        if done || result.is_err() {
+            self.fades.remove(&node);
+        }
+        result
+    }
+
+    fn write<E>(
+        &mut self,
+        node: u32,
+        change: LevelWrite,
+        write: &mut impl FnMut(u32, LevelWrite) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        write(node, change)?;
+        let level = self.levels.entry(node).or_default();
+        match change {
+            LevelWrite::Volume(volumes) => level.volumes = volumes,
+            LevelWrite::Mute(muted) => level.muted = muted,
+        }
+        Ok(())
+    }
+}
+
+/// Split a volume into left and right levels for a balance setting.
+///
+/// `pan` runs from -1.0 (left only) through 0.0 (both sides at `volume`) to
+/// 1.0 (right only); the side being panned away from is lowered, the other
+/// stays at `volume`.
+#[must_use]
+pub fn balance_volumes(volume: f32, pan: f32) -> [f32; 2] {
+    let pan = pan.clamp(-1.0, 1.0);
+    [volume * (1.0 - pan.max(0.0)), volume * (1.0 + pan.min(0.0))]
+}
+
+/// A volume and/or mute change to apply to a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeUpdate {
+    /// Node name
+    pub node: String,
+    /// New volume (0.0 - 1.0), if it or the balance changed
+    pub volume: Option<f32>,
+    /// Balance to apply along with `volume` (see [`balance_volumes`])
+    pub pan: f32,
+    /// New mute state, if it changed
+    pub muted: Option<bool>,
+    /// Time to fade to the new volume or mute state over; `None` applies it
+    /// at once
+    pub fade: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct NodeLevels {
+    /// Volume last handed out by `tick`
+    current: Option<f32>,
+    /// Volume still to be reached
+    target: Option<f32>,
+    /// Balance last handed out by `tick`
+    pan: f32,
+    /// Balance still to be applied
+    pan_target: Option<f32>,
+    /// Mute state still to be applied
+    muted: Option<bool>,
+    /// Fade time for the changes still to be applied
+    fade: Option<Duration>,
+}
+
+/// Collects volume and mute changes between ticks, keeping only the latest
+/// value per node.
+///
+/// Slider drags produce far more changes than are worth applying; callers
+/// queue every change and apply what `tick` returns at a fixed rate. With a
+/// ramp step set, volume moves towards its target by at most that step per
+/// tick rather than jumping, which avoids zipper noise on large changes.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeQueue {
+    nodes: BTreeMap<String, NodeLevels>,
+    ramp_step: Option<f32>,
+}
+
+impl VolumeQueue {
+    /// Create a queue. `ramp_step` is the largest volume change per tick;
+    /// `None` applies every change in one step.
+    #[must_use]
+    pub fn new(ramp_step: Option<f32>) -> Self {
+        Self { nodes: BTreeMap::new(), ramp_step: ramp_step.filter(|step| *step > 0.0) }
+    }
+
+    /// Queue a volume change, replacing any change not yet applied.
+    ///
+    /// The change is applied without a fade unless one is queued after it.
+    pub fn set_volume(&mut self, node: &str, volume: f32) {
+        let levels = self.nodes.entry(node.to_string()).or_default();
+        levels.target = Some(volume.clamp(0.0, 1.0));
+        levels.fade = None;
+    }
+
+    /// Queue a balance change (-1.0 left to 1.0 right), replacing any change
+    /// not yet applied.
+    ///
+    /// Balance is applied in one step. A node whose volume was never queued
+    /// is assumed to be at full volume.
+    pub fn set_pan(&mut self, node: &str, pan: f32) {
+        self.nodes.entry(node.to_string()).or_default().pan_target = Some(pan.clamp(-1.0, 1.0));
+    }
+
+    /// Record a balance that was applied outside the queue, so later volume
+    /// changes keep it. Nothing is queued.
+    pub fn sync_pan(&mut self, node: &str, pan: f32) {
+        let levels = self.nodes.entry(node.to_string()).or_default();
+        levels.pan = pan.clamp(-1.0, 1.0);
+        levels.pan_target = None;
+    }
+
+    /// Queue a mute change, replacing any change not yet applied.
+    ///
+    /// The change is applied without a fade unless one is queued after it.
+    pub fn set_mute(&mut self, node: &str, muted: bool) {
+        let levels = self.nodes.entry(node.to_string()).or_default();
+        levels.muted = Some(muted);
+        levels.fade = None;
+    }
+
+    /// Fade a node's queued volume and mute changes in over `duration`
+    /// instead of applying them at once.
+    ///
+    /// A faded volume skips the ramp and is handed out in one update.
+    pub fn set_fade(&mut self, node: &str, duration: Duration) {
+        if let Some(levels) = self.nodes.get_mut(node)
+            && (levels.target.is_some() || levels.muted.is_some())
+        {
+            levels.fade = Some(duration);
+        }
+    }
+
+    /// Fade every queued volume and mute change in over `duration`.
+    pub fn fade_pending(&mut self, duration: Duration) {
+        for levels in self.nodes.values_mut() {
+            if levels.target.is_some() || levels.muted.is_some() {
+                levels.fade = Some(duration);
+            }
+        }
+    }
+
+    /// Whether nothing is waiting to be applied.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.nodes
+            .values()
+            .all(|n| n.target.is_none() && n.pan_target.is_none() && n.muted.is_none())
+    }
+
+    /// Take the changes to apply this tick, at most one per node.
+    pub fn tick(&mut self) -> Vec<VolumeUpdate> {
+        let mut updates = Vec::new();
+
+        for (node, levels) in &mut self.nodes {
+            let fade = levels.fade.take();
+            let ramp_step = if fade.is_some() { None } else { self.ramp_step };
+            let mut volume = levels.target.map(|target| {
+                let next = match (levels.current, ramp_step) {
+                    (Some(current), Some(step)) if (target - current).abs() > step => {
+                        current + step.copysign(target - current)
+                    }
+                    _ => target,
+                };
+                levels.current = Some(next);
+                if (next - target).abs() < f32::EPSILON {
+                    levels.target = None;
+                }
+                next
+            });
+            if let Some(pan) = levels.pan_target.take() {
+                levels.pan = pan;
+                volume = volume.or(Some(*levels.current.get_or_insert(1.0)));
+            }
+            let muted = levels.muted.take();
+            // A faded mute goes down from (or back up to) the current volume
+            if fade.is_some() && muted.is_some() {
+                volume = volume.or(Some(*levels.current.get_or_insert(1.0)));
+            }
+
+            if volume.is_some() || muted.is_some() {
+                updates.push(VolumeUpdate {
+                    node: node.clone(),
+                    volume,
+                    pan: levels.pan,
+                    muted,
+                    fade,
+                });
+            }
+        }
+
+        updates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latest_value_wins() {
+        let mut queue = VolumeQueue::new(None);
+        for volume in [0.1, 0.4, 0.7] {
+            queue.set_volume("ut-ch-music-stream-vol", volume);
+        }
+        queue.set_mute("ut-ch-music-stream-vol", true);
+        queue.set_mute("ut-ch-music-stream-vol", false);
+
+        let updates = queue.tick();
+        assert_eq!(
+            updates,
+            vec![VolumeUpdate {
+                node: "ut-ch-music-stream-vol".to_string(),
+                volume: Some(0.7),
+                pan: 0.0,
+                muted: Some(false),
+                fade: None,
+            }]
+        );
+        assert!(queue.is_idle());
+        assert!(queue.tick().is_empty());
+    }
+
+    #[test]
+    fn test_ramp_steps_towards_target() {
+        let mut queue = VolumeQueue::new(Some(0.25));
+        queue.set_volume("ut-stream-mix", 1.0);
+        // The first value for a node has nothing to ramp from
+        assert_eq!(queue.tick()[0].volume, Some(1.0));
+
+        queue.set_volume("ut-stream-mix", 0.4);
+        let steps: Vec<_> =
+            std::iter::from_fn(|| queue.tick().first().and_then(|u| u.volume)).take(10).collect();
+        assert_eq!(steps, vec![0.75, 0.5, 0.4]);
+        assert!(queue.is_idle());
+    }
+
+    #[test]
+    fn test_mute_does_not_wait_for_ramp() {
+        let mut queue = VolumeQueue::new(Some(0.1));
+        queue.set_volume("ut-monitor-mix", 0.0);
+        queue.tick();
+
+        queue.set_volume("ut-monitor-mix", 1.0);
+        queue.set_mute("ut-monitor-mix", true);
+        let update = &queue.tick()[0];
+        assert_eq!(update.muted, Some(true));
+        assert!(update.volume.is_some_and(|v| (v - 0.1).abs() < 1e-6));
+        assert!(!queue.is_idle());
+    }
+
+    #[test]
+    fn test_pan_reapplies_current_volume() {
+        let mut queue = VolumeQueue::new(None);
+        queue.set_volume("ut-ch-voice-stream-vol", 0.5);
+        queue.tick();
+
+        queue.set_pan("ut-ch-voice-stream-vol", -0.5);
+        let update = &queue.tick()[0];
+        assert_eq!(update.volume, Some(0.5));
+        assert!((update.pan - -0.5).abs() < f32::EPSILON);
+
+        // Later volume changes keep the balance
+        queue.set_volume("ut-ch-voice-stream-vol", 0.8);
+        assert!((queue.tick()[0].pan - -0.5).abs() < f32::EPSILON);
+
+        // A synced balance queues nothing but is used from then on
+        queue.sync_pan("ut-ch-voice-stream-vol", 0.25);
+        assert!(queue.is_idle());
+        queue.set_volume("ut-ch-voice-stream-vol", 0.6);
+        assert!((queue.tick()[0].pan - 0.25).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_fade_skips_ramp_and_carries_volume() {
+        let mut queue = VolumeQueue::new(Some(0.1));
+        queue.set_volume("ut-ch-game-monitor-vol", 0.8);
+        queue.tick();
+
+        let fade = Duration::from_millis(500);
+        queue.set_volume("ut-ch-game-monitor-vol", 0.2);
+        queue.set_fade("ut-ch-game-monitor-vol", fade);
+        let update = &queue.tick()[0];
+        assert_eq!((update.volume, update.fade), (Some(0.2), Some(fade)));
+        assert!(queue.is_idle());
+
+        // A faded mute hands out the volume to fade from
+        queue.set_mute("ut-ch-game-monitor-vol", true);
+        queue.fade_pending(fade);
+        let update = &queue.tick()[0];
+        assert_eq!((update.volume, update.muted), (Some(0.2), Some(true)));
+
+        // A later plain change drops the fade
+        queue.set_mute("ut-ch-game-monitor-vol", false);
+        queue.set_fade("ut-ch-game-monitor-vol", fade);
+        queue.set_mute("ut-ch-game-monitor-vol", true);
+        assert_eq!(queue.tick()[0].fade, None);
+    }
+
+    #[test]
+    fn test_fade_gains() {
+        let close = |a: [f32; 2], b: [f32; 2]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6);
+        let fade = Fade::new([1.0, 0.5], [0.0, 0.0], Duration::from_millis(100));
+        assert!(close(fade.gains_at(Duration::ZERO), [1.0, 0.5]));
+        let half = fade.gains_at(Duration::from_millis(50));
+        assert!((half[0] - 0.125).abs() < 1e-6);
+        assert!(half[1] < half[0]);
+        assert!(fade.is_done(Duration::from_millis(100)));
+        assert!(close(fade.gains_at(Duration::from_millis(150)), [0.0, 0.0]));
+
+        assert_eq!(fade_duration(None).unwrap(), None);
+        assert_eq!(fade_duration(Some(0)).unwrap(), None);
+        assert_eq!(fade_duration(Some(250)).unwrap(), Some(Duration::from_millis(250)));
+        assert!(fade_duration(Some(MAX_FADE_MS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_node_fades() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let fade = Duration::from_millis(100);
+        let mut written = Vec::new();
+        let mut fades = NodeFades::new();
+        let mut write = |node, change| {
+            written.push((node, change));
+            Ok::<(), ()>(())
+        };
+
+        // Fading out to mute ends muted, left at the audible level
+        fades.fade_mute(7, true, [0.8; 2], fade, start, &mut write).unwrap();
+        assert!(!fades.level(7).muted);
+        assert!(fades.step(at(50), &mut write).is_empty());
+        assert!(fades.level(7).volumes[0] < 1.0);
+
+        // A volume set part way keeps the fade and its final mute
+        fades.set_volume(7, [0.5; 2], &mut write).unwrap();
+        assert!(!fades.is_idle());
+        fades.step(at(150), &mut write);
+        assert!(fades.is_idle());
+        assert_eq!(fades.level(7), NodeLevel { volumes: [0.5; 2], muted: true });
+
+        // A volume set during a plain fade replaces it
+        fades.fade_volume(8, [0.2; 2], fade, start, &mut write).unwrap();
+        fades.set_volume(8, [0.6; 2], &mut write).unwrap();
+        assert!(fades.is_idle());
+        assert_eq!(fades.level(8), NodeLevel { volumes: [0.6; 2], muted: false });
+
+        // Unmuting fades in from silence
+        fades.fade_mute(7, false, [0.5; 2], fade, at(200), &mut write).unwrap();
+        assert_eq!(fades.level(7), NodeLevel { volumes: [0.0; 2], muted: false });
+        fades.step(at(300), &mut write);
+        assert_eq!(fades.level(7), NodeLevel { volumes: [0.5; 2], muted: false });
+        assert_eq!(written.last(), Some(&(7, LevelWrite::Volume([0.5; 2]))));
+
+        // Failed writes are not recorded and drop the fade
+        let mut fail = |_, _| Err("gone");
+        assert!(fades.fade_volume(7, [0.1; 2], fade, at(300), &mut fail).is_err());
+        assert!(fades.is_idle());
+        assert_eq!(fades.level(7), NodeLevel { volumes: [0.5; 2], muted: false });
+    }
+
+    #[test]
+    fn test_balance_volumes() {
+        let close = |a: [f32; 2], b: [f32; 2]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6);
+        assert!(close(balance_volumes(0.8, 0.0), [0.8, 0.8]));
+        assert!(close(balance_volumes(0.8, -1.0), [0.8, 0.0]));
+        assert!(close(balance_volumes(1.0, 0.25), [0.75, 1.0]));
+        assert!(close(balance_volumes(1.0, 3.0), [0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_tapers_round_trip() {
+        for taper in [VolumeTaper::Linear, VolumeTaper::Cubic, VolumeTaper::Db] {
+            assert!(taper.gain(0.0).abs() < f32::EPSILON);
+            assert!((taper.gain(1.0) - 1.0).abs() < 1e-6);
+            for position in [0.1, 0.5, 0.9] {
+                assert!((taper.position(taper.gain(position)) - position).abs() < 1e-4);
+            }
+        }
+
+        // Half way down the fader
+        assert!((VolumeTaper::Cubic.gain(0.5) - 0.125).abs() < 1e-6);
+        assert!((VolumeTaper::Db.position_to_db(0.5) - -30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_resolve_volume_values() {
+        let taper = VolumeTaper::Db;
+        let resolve = |json: &str, current: f32| {
+            serde_json::from_str::<VolumeValue>(json).unwrap().resolve(current, taper).unwrap()
+        };
+
+        assert!((resolve("0.25", 0.8) - 0.25).abs() < f32::EPSILON);
+        assert!((resolve(r#"{"db": -12}"#, 0.0) - 0.8).abs() < 1e-4);
+        assert!((resolve(r#"{"step": -0.1}"#, 0.8) - 0.7).abs() < 1e-6);
+        assert!((resolve(r#"{"step_db": 6}"#, 0.5) - 0.6).abs() < 1e-4);
+        // Clamped at both ends; stepping up from silence starts at MIN_DB
+        assert!((resolve(r#"{"db": 6}"#, 0.0) - 1.0).abs() < f32::EPSILON);
+        assert!(resolve(r#"{"step": -2}"#, 0.5).abs() < f32::EPSILON);
+        assert!((resolve(r#"{"step_db": 6}"#, 0.0) - 0.1).abs() < 1e-4);
+
+        assert!(VolumeValue::Position(f32::NAN).resolve(0.5, taper).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Feeding recording apps from a mix or the mic.
+
+use tracing::{debug, info, warn};
+
+use undertone_core::process::{Ancestor, ancestors};
+use undertone_core::routing::{RouteDirection, RouteRule, find_route};
+use undertone_pipewire::node::NodeInfo;
+use undertone_pipewire::{GraphManager, PipeWireRuntime};
+
+use crate::ports::{PORT_WAIT, wait_for_ports};
+
+/// Route a recording app that just appeared, if a capture rule matches it.
+///
+/// Apps no rule matches are left to the session manager. This waits for the
+/// app's input ports, so callers run it in a task of its own.
+pub async fn client_appeared(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    id: u32,
+    name: &str,
+    rules: &[RouteRule],
+) {
+    let node = graph.get_node(id);
+    let binary_name = node.as_ref().and_then(|n| n.binary_name.clone());
+    let parents = node.as_ref().map(|n| client_ancestors(n, rules)).unwrap_or_default();
+    let properties = node.map(|n| n.properties).unwrap_or_default();
+
+    let route = find_route(
+        RouteDirection::Capture,
+        name,
+        binary_name.as_deref(),
+        &properties,
+        0,
+        &parents,
+        rules,
+    );
+    let Some(winner) = route.filter(|m| !m.rule.exclude) else {
+        debug!(app_id = id, app_name = %name, "No capture rule, leaving recording app alone");
+        return;
+    };
+    let source = winner.rule.channel.clone();
+
+    wait_for_ports(PORT_WAIT, || !graph.get_input_ports(id).is_empty()).await;
+    route(runtime, id, name, &source).await;
+}
+
+/// Apply a capture rule to the recording apps already running, where it
+/// is the rule that wins among `rules` (which hold it).
+pub async fn apply_rule(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    rule: &RouteRule,
+    rules: &[RouteRule],
+) {
+    for client in graph.get_capture_clients() {
+        let parents = client_ancestors(&client, rules);
+        let winner = find_route(
+            RouteDirection::Capture,
+            client.application_name.as_deref().unwrap_or(&client.name),
+            client.binary_name.as_deref(),
+            &client.properties,
+            0,
+            &parents,
+            rules,
+        );
+        if winner.is_some_and(|m| std::ptr::eq(m.rule, rule)) {
+            route(runtime, client.id, &client.name, &rule.channel).await;
+        }
+    }
+}
+
+async fn route(runtime: &PipeWireRuntime, id: u32, name: &str, source: &str) {
+    match runtime.route_capture_to_source(id, source).await {
+        Ok(link_ids) => {
+            info!(
+                app_id = id,
+                app_name = %name,
+                source = %source,
+                links = link_ids.len(),
+                "Recording app routed"
+            );
+        }
+        Err(e) => {
+            warn!(app_id = id, app_name = %name, error = %e, "Failed to route recording app");
+        }
+    }
+}
+
+/// Parent processes of a client, read only if some rule looks at them.
+fn client_ancestors(client: &NodeInfo, rules: &[RouteRule]) -> Vec<Ancestor> {
+    match client.pid {
+        Some(pid) if rules.iter().any(|r| r.match_ancestors) => ancestors(pid),
+        _ => Vec::new(),
+    }
+}
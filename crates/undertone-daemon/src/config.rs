@@ -1,11 +1,13 @@
 //! Daemon configuration.
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use tracing::info;
+use undertone_core::volume::VolumeTaper;
 
 /// Daemon configuration.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -22,6 +24,15 @@ pub struct Config {
     /// Device settings
     #[serde(default)]
     pub device: DeviceConfig,
+    /// Level meter settings
+    #[serde(default)]
+    pub meters: MeterConfig,
+    /// Volume update settings
+    #[serde(default)]
+    pub volume: VolumeConfig,
+    /// Stream mix virtual source settings
+    #[serde(default)]
+    pub stream_source: StreamSourceConfig,
 }
 
 /// Daemon-specific settings.
@@ -109,6 +120,92 @@ fn default_pid() -> String {
     "0070".to_string()
 }
 
+/// Level meter settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeterConfig {
+    /// Enable level metering
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// How often `LevelsUpdated` events are published, in Hz
+    #[serde(default = "default_meter_rate")]
+    pub rate_hz: u32,
+}
+
+impl Default for MeterConfig {
+    fn default() -> Self {
+        Self { enabled: true, rate_hz: default_meter_rate() }
+    }
+}
+
+impl MeterConfig {
+    /// Interval between level publications, with the rate clamped to 1 - 60 Hz.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(1000 / u64::from(self.rate_hz.clamp(1, 60)))
+    }
+}
+
+fn default_meter_rate() -> u32 {
+    20
+}
+
+/// Volume update settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeConfig {
+    /// How often queued volume and mute changes are applied, in Hz
+    #[serde(default = "default_volume_rate")]
+    pub rate_hz: u32,
+    /// Time to ramp across the full volume range, in milliseconds (0 disables ramping)
+    #[serde(default)]
+    pub ramp_ms: u32,
+    /// Curve turning volumes (fader positions) into the gain applied
+    #[serde(default)]
+    pub taper: VolumeTaper,
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self { rate_hz: default_volume_rate(), ramp_ms: 0, taper: VolumeTaper::default() }
+    }
+}
+
+impl VolumeConfig {
+    /// Interval between volume updates, with the rate clamped to 10 - 200 Hz.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(1000 / u64::from(self.rate_hz.clamp(10, 200)))
+    }
+
+    /// Largest volume change per update, or `None` if ramping is disabled.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ramp_step(&self) -> Option<f32> {
+        (self.ramp_ms > 0)
+            .then(|| (self.interval().as_millis() as f32 / self.ramp_ms as f32).min(1.0))
+    }
+}
+
+fn default_volume_rate() -> u32 {
+    50
+}
+
+/// Stream mix virtual source settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamSourceConfig {
+    /// Offer the stream mix to apps as a microphone (`ut-stream-source`)
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Make it the default source when the daemon starts
+    #[serde(default)]
+    pub as_default: bool,
+}
+
+impl Default for StreamSourceConfig {
+    fn default() -> Self {
+        Self { enabled: true, as_default: false }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
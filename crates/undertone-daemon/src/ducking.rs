@@ -0,0 +1,43 @@
+//! Driving ducking rules from level meter readings.
+
+use std::time::Duration;
+
+use undertone_core::channel::ChannelState;
+use undertone_core::ducking::{DuckTrigger, Ducker};
+use undertone_core::meter::MeterLevels;
+use undertone_core::mixer::{MixConfig, MixerState};
+use undertone_ipc::LevelsData;
+
+use crate::volume::PendingVolumes;
+
+/// Run the ducking rules against the latest readings and queue the send
+/// volumes that changed.
+///
+/// Called once per meter period; `elapsed` is that period.
+pub fn update(
+    ducker: &mut Ducker,
+    mixer: &MixerState,
+    levels: &LevelsData,
+    channels: &[ChannelState],
+    mixes: &[MixConfig],
+    volumes: &mut PendingVolumes,
+    elapsed: Duration,
+) {
+    let peak = |levels: &MeterLevels| levels.peak_left.max(levels.peak_right);
+    let level = |trigger: &DuckTrigger| match trigger {
+        DuckTrigger::Mic => levels.mic.as_ref().map(peak),
+        DuckTrigger::Channel(name) => levels.channels.get(name).map(peak),
+    };
+
+    for change in ducker.update(&mixer.ducking, level, elapsed) {
+        // Sends of deleted channels and mixes have no node left to restore
+        let Some(ch) = channels.iter().find(|c| c.config.name == change.channel) else {
+            continue;
+        };
+        if !mixes.iter().any(|m| m.name == change.mix.as_str()) {
+            continue;
+        }
+        let volume = ch.send(&change.mix).volume;
+        volumes.duck(&change.channel, &change.mix, volume, change.gain);
+    }
+}
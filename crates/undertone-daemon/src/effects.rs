@@ -0,0 +1,104 @@
+//! Applying per-channel effects chains to the graph.
+
+use tracing::{info, warn};
+
+use undertone_core::channel::ChannelConfig;
+use undertone_core::effects::ChannelEffects;
+use undertone_core::mixer::MixConfig;
+use undertone_pipewire::{FilterChain, GraphManager, PipeWireRuntime};
+
+use crate::ports::{PORT_WAIT, wait_for_ports};
+
+/// Move a channel's effects chain from `old` to `new` settings.
+///
+/// Control changes are applied in place; enabling, disabling or reordering
+/// stages reloads the chain. A channel whose chain fails to load keeps
+/// feeding its volume filters directly. Chains are stereo, so surround
+/// channels never get one.
+pub async fn apply(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    channel: &ChannelConfig,
+    old: &ChannelEffects,
+    new: &ChannelEffects,
+    mixes: &[MixConfig],
+) {
+    if channel.layout.is_surround() {
+        if new.is_active() {
+            warn!(channel = %channel.name, "Effects need a stereo channel, not loading them");
+        }
+        return;
+    }
+
+    let old_chain = FilterChain::new(old);
+    let new_chain = FilterChain::new(new);
+
+    let new_chain = match (old_chain, new_chain) {
+        (None, None) => return,
+        (Some(_), None) => {
+            remove(runtime, graph, channel, mixes).await;
+            return;
+        }
+        (Some(old_chain), Some(new_chain)) if old_chain.same_layout(&new_chain) => {
+            if let Err(e) = runtime.set_effect_params(channel, &new_chain).await {
+                warn!(channel = %channel.name, error = %e, "Failed to update effects");
+            }
+            return;
+        }
+        (Some(_), Some(new_chain)) => {
+            remove(runtime, graph, channel, mixes).await;
+            new_chain
+        }
+        (None, Some(new_chain)) => new_chain,
+    };
+
+    load(runtime, graph, channel, &new_chain, mixes).await;
+}
+
+/// Load a chain and route the channel through it once its ports are up.
+async fn load(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    channel: &ChannelConfig,
+    chain: &FilterChain,
+    mixes: &[MixConfig],
+) {
+    if let Err(e) = runtime.load_effects(channel, chain).await {
+        warn!(channel = %channel.name, error = %e, "Failed to load effects chain");
+        return;
+    }
+
+    let names = [channel.fx_input_node_name(), channel.fx_output_node_name()];
+    wait_for_ports(PORT_WAIT, || names.iter().all(|name| graph.has_ports_for_node(name))).await;
+
+    match runtime.insert_channel_effects(channel, mixes).await {
+        Ok(created) => {
+            for (description, id) in created {
+                graph.record_created_link(description, id);
+            }
+            info!(channel = %channel.name, "Effects chain active");
+        }
+        Err(e) => {
+            warn!(channel = %channel.name, error = %e, "Failed to route channel through effects");
+        }
+    }
+}
+
+/// Unload a channel's chain and link the channel straight to its filters.
+async fn remove(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    channel: &ChannelConfig,
+    mixes: &[MixConfig],
+) {
+    match runtime.remove_channel_effects(channel, mixes).await {
+        Ok(created) => {
+            for (description, id) in created {
+                graph.record_created_link(description, id);
+            }
+        }
+        Err(e) => {
+            warn!(channel = %channel.name, error = %e, "Failed to remove effects chain");
+        }
+    }
+}
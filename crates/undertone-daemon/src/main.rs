@@ -3,6 +3,7 @@
 //! This is the main entry point for the Undertone daemon, which manages
 //! `PipeWire` audio routing, persistence, and Wave:3 hardware integration.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -11,23 +12,31 @@ use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
+mod capture;
 mod config;
+mod ducking;
+mod effects;
+mod meters;
+mod ports;
+mod reconcile;
 mod server;
 mod signals;
+mod volume;
 
-use undertone_core::channel::ChannelState;
+use undertone_core::channel::{ChannelConfig, ChannelState};
+use undertone_core::effects::ChannelEffects;
+use undertone_core::mixer::{MixConfig, MixType};
+use undertone_core::solo::SoloState;
 use undertone_core::state::{DaemonState, StateSnapshot};
 use undertone_db::Database;
 use undertone_hid::{Wave3Device, alsa_fallback::AlsaMicControl};
 use undertone_ipc::{
-    AppDiscoveredData, ChannelMuteChangedData, ChannelVolumeChangedData, DeviceConnectedData,
-    Event, EventType, IpcServer, socket_path,
+    AppDiscoveredData, AppRouteChangedData, ChannelDeletedData, ChannelEffectsChangedData,
+    DeviceConnectedData, DuckingChangedData, Event, EventType, FallbackChannelChangedData,
+    IpcServer, Method, MixDeletedData, socket_path,
 };
 use undertone_pipewire::{GraphEvent, GraphManager, PipeWireRuntime};
 
-/// Default channels to create
-const DEFAULT_CHANNELS: &[&str] = &["system", "voice", "music", "browser", "game"];
-
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
@@ -43,7 +52,7 @@ async fn main() -> Result<()> {
     info!(version = env!("CARGO_PKG_VERSION"), "Starting Undertone daemon");
 
     // Load configuration
-    let _config = config::load_config()?;
+    let daemon_config = config::load_config()?;
     info!("Configuration loaded");
 
     // Open database
@@ -77,9 +86,16 @@ async fn main() -> Result<()> {
     let mut channels: Vec<ChannelState> = db.load_channels().context("Failed to load channels")?;
     info!(count = channels.len(), "Loaded channels from database");
 
+    // Load mixes
+    let mut mixes: Vec<MixConfig> = db.load_mixes().context("Failed to load mixes")?;
+    info!(count = mixes.len(), "Loaded mixes from database");
+
     // Load routing rules
     let mut routes = db.load_routes().context("Failed to load routes")?;
     info!(count = routes.len(), "Loaded routing rules");
+    let mut fallback_channel =
+        db.load_fallback_channel().context("Failed to load fallback channel")?;
+    info!(channel = %fallback_channel, "Loaded fallback channel");
 
     // Track active app routes
     let mut active_apps: Vec<undertone_core::routing::AppRoute> = vec![];
@@ -91,6 +107,8 @@ async fn main() -> Result<()> {
     info!("Starting PipeWire runtime...");
     let (pw_runtime, mut graph_event_rx) =
         PipeWireRuntime::spawn(Arc::clone(&graph)).context("Failed to spawn PipeWire runtime")?;
+    // Shared with tasks that wait on the graph, such as routing new recording apps
+    let pw_runtime = Arc::new(pw_runtime);
 
     // Wait for PipeWire connection
     info!("Waiting for PipeWire connection...");
@@ -111,9 +129,10 @@ async fn main() -> Result<()> {
     // Give PipeWire a moment to enumerate existing nodes
     sleep(Duration::from_millis(500)).await;
 
-    // Create virtual channel sinks
+    // Create virtual channel sinks for every channel in the database
+    let channel_configs: Vec<ChannelConfig> = channels.iter().map(|c| c.config.clone()).collect();
     info!("Creating virtual channel sinks...");
-    match pw_runtime.create_channel_sinks(DEFAULT_CHANNELS) {
+    match pw_runtime.create_channel_sinks(&channel_configs).await {
         Ok(created) => {
             info!(count = created.len(), "Created channel sinks");
             for node in &created {
@@ -128,7 +147,7 @@ async fn main() -> Result<()> {
 
     // Create mix nodes
     info!("Creating mix nodes...");
-    match pw_runtime.create_mix_nodes() {
+    match pw_runtime.create_mix_nodes(&mixes).await {
         Ok(created) => {
             info!(count = created.len(), "Created mix nodes");
             for node in &created {
@@ -140,9 +159,31 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Offer the stream mix to capture apps as a microphone
+    let stream_source = mixes
+        .iter()
+        .find(|m| m.id() == MixType::STREAM)
+        .filter(|_| daemon_config.stream_source.enabled)
+        .cloned();
+    if let Some(mix) = &stream_source {
+        info!("Creating stream mix source...");
+        match pw_runtime.create_mix_source(mix).await {
+            Ok(node) => graph.record_created_node(node.name, node.id),
+            Err(e) => error!(error = %e, "Failed to create stream mix source"),
+        }
+    }
+
+    // Give channels that want one their own recording source
+    for ch in channel_configs.iter().filter(|c| c.direct_out) {
+        match pw_runtime.create_channel_direct_out(ch).await {
+            Ok(node) => graph.record_created_node(node.name, node.id),
+            Err(e) => error!(channel = %ch.name, error = %e, "Failed to create direct-out source"),
+        }
+    }
+
     // Create volume filter nodes for each channel
     info!("Creating volume filter nodes...");
-    match pw_runtime.create_channel_volume_filters(DEFAULT_CHANNELS) {
+    match pw_runtime.create_channel_volume_filters(&channel_configs, &mixes).await {
         Ok(created) => {
             info!(count = created.len(), "Created volume filter nodes");
             for (name, id) in &created {
@@ -154,6 +195,20 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Create the mic send filters for every mix
+    info!("Creating mic volume filter nodes...");
+    match pw_runtime.create_mic_volume_filters(&mixes).await {
+        Ok(created) => {
+            info!(count = created.len(), "Created mic volume filter nodes");
+            for (name, id) in &created {
+                graph.record_created_node(name.clone(), *id);
+            }
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create mic volume filter nodes");
+        }
+    }
+
     // Wait for nodes and ports to be fully registered in PipeWire before creating links.
     // The registry receives global events asynchronously, so we need to wait for our
     // newly created nodes to appear before we can look them up for linking.
@@ -162,7 +217,7 @@ async fn main() -> Result<()> {
 
     // Create links from channels through volume filters to mix nodes
     info!("Creating channel-to-mix links with volume filters...");
-    match pw_runtime.create_channel_to_mix_links_with_filters() {
+    match pw_runtime.create_channel_to_mix_links_with_filters(&channel_configs, &mixes).await {
         Ok(created) => {
             info!(count = created.len(), "Created channel-to-mix links with volume filters");
             for (description, id) in &created {
@@ -174,14 +229,81 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Feed the stream mix into its source
+    if let Some(mix) = &stream_source {
+        match pw_runtime.link_mix_source(mix).await {
+            Ok(created) => {
+                for (description, id) in created {
+                    graph.record_created_link(description, id);
+                }
+                if daemon_config.stream_source.as_default {
+                    let name = mix.id().source_node_name();
+                    if let Err(e) = pw_runtime.set_default_source(&name).await {
+                        warn!(error = %e, "Failed to make the stream mix source the default");
+                    }
+                }
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to link stream mix to its source");
+            }
+        }
+    }
+
+    // Feed direct-out sources from their channels' stream sends
+    for ch in channel_configs.iter().filter(|c| c.direct_out) {
+        match pw_runtime.link_channel_direct_out(ch).await {
+            Ok(created) => {
+                for (description, id) in created {
+                    graph.record_created_link(description, id);
+                }
+            }
+            Err(e) => {
+                error!(channel = %ch.name, error = %e, "Failed to link channel to its direct-out");
+            }
+        }
+    }
+
+    // Route channels with effects through their chains
+    for ch in channels.iter().filter(|c| c.effects.is_active()) {
+        info!(channel = %ch.config.name, "Loading effects chain...");
+        effects::apply(
+            &pw_runtime,
+            &graph,
+            &ch.config,
+            &ChannelEffects::default(),
+            &ch.effects,
+            &mixes,
+        )
+        .await;
+    }
+
+    // Link the mic into every mix if Wave:3 is connected
+    if graph.find_wave3_source().is_some() {
+        info!("Linking mic into mixes...");
+        match pw_runtime.link_mic_to_mixes(&mixes).await {
+            Ok(created) => {
+                info!(count = created.len(), "Mic linked into mixes");
+                for (description, id) in &created {
+                    graph.record_created_link(description.clone(), *id);
+                }
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to link mic into mixes");
+            }
+        }
+    } else {
+        info!("Wave:3 source not found - skipping mic links");
+    }
+
     // Link monitor-mix to headphones if Wave:3 is connected
     if let Some(wave3_sink) = graph.find_wave3_sink() {
         info!(sink_name = %wave3_sink.name, "Linking monitor-mix to Wave:3 headphones...");
-        match pw_runtime.link_monitor_to_headphones() {
-            Ok((left_id, right_id)) => {
+        match pw_runtime.link_monitor_to_headphones().await {
+            Ok(links) => {
                 info!("Monitor-mix linked to headphones");
-                graph.record_created_link("monitor-mix->wave3-sink:FL".to_string(), left_id);
-                graph.record_created_link("monitor-mix->wave3-sink:FR".to_string(), right_id);
+                for (position, id) in links {
+                    graph.record_created_link(format!("monitor-mix->wave3-sink:{position}"), id);
+                }
             }
             Err(e) => {
                 warn!(error = %e, "Failed to link monitor-mix to headphones (Wave:3 may not be connected)");
@@ -191,6 +313,9 @@ async fn main() -> Result<()> {
         info!("Wave:3 sink not found - skipping monitor-mix to headphones link");
     }
 
+    // Start level meters
+    meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+
     // Start IPC server
     let socket = socket_path();
     info!(?socket, "Starting IPC server");
@@ -212,9 +337,16 @@ async fn main() -> Result<()> {
     let mut state = DaemonState::Running;
     let mut active_profile = String::from("Default");
     let mut mixer = undertone_core::mixer::MixerState::default();
+    // Ducking envelopes, advanced on every meter reading
+    let mut ducker = undertone_core::ducking::Ducker::default();
     // Track current monitor output device (defaults to Wave:3 headphones)
     let mut monitor_output = String::from("wave3-sink");
 
+    // Volume and mute changes are coalesced and applied on this timer
+    let mut volumes = volume::PendingVolumes::new(&daemon_config.volume);
+    let mut volume_tick = tokio::time::interval(daemon_config.volume.interval());
+    volume_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
     // Load default profile on startup (apply channel states to PipeWire)
     if let Ok(Some(default_profile_name)) = db.get_default_profile()
         && let Ok(Some(profile)) = db.load_profile(&default_profile_name)
@@ -223,34 +355,43 @@ async fn main() -> Result<()> {
         active_profile = default_profile_name.clone();
         for profile_ch in &profile.channels {
             if let Some(ch) = channels.iter_mut().find(|c| c.config.name == profile_ch.name) {
-                ch.stream_volume = profile_ch.stream_volume;
-                ch.stream_muted = profile_ch.stream_muted;
-                ch.monitor_volume = profile_ch.monitor_volume;
-                ch.monitor_muted = profile_ch.monitor_muted;
-
-                // Apply to PipeWire filter nodes
-                use undertone_core::mixer::MixType;
-                for (mix, volume, muted) in [
-                    (MixType::Stream, ch.stream_volume, ch.stream_muted),
-                    (MixType::Monitor, ch.monitor_volume, ch.monitor_muted),
-                ] {
-                    let filter_name = match mix {
-                        MixType::Stream => format!("ut-ch-{}-stream-vol", ch.config.name),
-                        MixType::Monitor => format!("ut-ch-{}-monitor-vol", ch.config.name),
-                    };
-                    if let Some(node_id) = graph.get_created_node_id(&filter_name) {
-                        let _ = pw_runtime.set_node_volume(node_id, volume);
-                        let _ = pw_runtime.set_node_mute(node_id, muted);
+                // Applied to PipeWire filter nodes below; sends into mixes
+                // that no longer exist are ignored
+                for mix in mixes.iter().map(MixConfig::id) {
+                    if let Some(send) = profile_ch.sends.get(&mix).copied() {
+                        ch.sends.insert(mix, send);
                     }
                 }
+                if let Some(fx) = &profile_ch.effects {
+                    effects::apply(&pw_runtime, &graph, &ch.config, &ch.effects, fx, &mixes).await;
+                    ch.effects = fx.clone();
+                }
             }
         }
+        mixer = profile.mixer.clone();
         // Only replace routes if the profile has custom routes defined
         // Otherwise, keep the global routes from app_routes table
         if !profile.routes.is_empty() {
             routes = profile.routes;
         }
     }
+    // Report rules that can never do what they say
+    {
+        let channel_names: Vec<&str> = channels.iter().map(|c| c.config.name.as_str()).collect();
+        let mix_names: Vec<&str> = mixes.iter().map(|m| m.name.as_str()).collect();
+        for issue in undertone_core::lint::lint_rules(&routes, &channel_names, &mix_names) {
+            warn!(%issue, "Routing rule problem");
+        }
+    }
+    if !daemon_config.meters.enabled && !mixer.ducking.is_empty() {
+        warn!("Ducking rules have no effect while meters are disabled");
+    }
+    // Apply channel, master and mic send levels
+    volumes.restore(&pw_runtime, &graph, &channels, &mixes, &mixer);
+
+    // Level meter publish timer
+    let mut meter_tick = tokio::time::interval(daemon_config.meters.interval());
+    meter_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
     info!("Daemon running. Press Ctrl+C to exit.");
 
@@ -263,12 +404,41 @@ async fn main() -> Result<()> {
                     GraphEvent::Connected => {
                         info!("PipeWire reconnected");
                         state = DaemonState::Reconciling;
-                        // TODO: Trigger reconciliation
+
+                        // Give PipeWire a moment to enumerate existing nodes
+                        sleep(Duration::from_millis(500)).await;
+                        reconcile::run(&pw_runtime, &graph, &channels, &mixes, &mut active_apps, &monitor_output, daemon_config.stream_source.enabled).await;
+                        volumes.restore(&pw_runtime, &graph, &channels, &mixes, &mixer);
+                        ducker.reapply();
+                        meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+                        state = DaemonState::Running;
+                    }
+
+                    GraphEvent::SessionManagerRestarted => {
+                        info!("WirePlumber restarted, reconciling");
+                        state = DaemonState::Reconciling;
+
+                        // Let WirePlumber apply its own policy before we restore ours
+                        sleep(Duration::from_millis(1000)).await;
+                        reconcile::run(&pw_runtime, &graph, &channels, &mixes, &mut active_apps, &monitor_output, daemon_config.stream_source.enabled).await;
+                        volumes.restore(&pw_runtime, &graph, &channels, &mixes, &mixer);
+                        ducker.reapply();
+                        meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+                        state = DaemonState::Running;
                     }
 
                     GraphEvent::Disconnected => {
-                        warn!("PipeWire disconnected");
+                        warn!("PipeWire disconnected, runtime will reconnect");
                         state = DaemonState::Error("PipeWire disconnected".to_string());
+
+                        // App node IDs don't survive a PipeWire restart; apps are
+                        // rediscovered and routed again once we reconnect
+                        for app in active_apps.drain(..) {
+                            let _ = event_tx.send(Event {
+                                event: EventType::AppRemoved,
+                                data: serde_json::json!({ "app_id": app.app_id }),
+                            });
+                        }
                     }
 
                     GraphEvent::Wave3Detected { serial } => {
@@ -288,17 +458,37 @@ async fn main() -> Result<()> {
 
                         // Try to link monitor-mix to headphones now that Wave:3 is connected
                         if !graph.get_created_links().contains_key("monitor-mix->wave3-sink:FL") {
-                            match pw_runtime.link_monitor_to_headphones() {
-                                Ok((left_id, right_id)) => {
+                            match pw_runtime.link_monitor_to_headphones().await {
+                                Ok(links) => {
                                     info!("Monitor-mix linked to Wave:3 headphones");
-                                    graph.record_created_link("monitor-mix->wave3-sink:FL".to_string(), left_id);
-                                    graph.record_created_link("monitor-mix->wave3-sink:FR".to_string(), right_id);
+                                    for (position, id) in links {
+                                        graph.record_created_link(format!("monitor-mix->wave3-sink:{position}"), id);
+                                    }
                                 }
                                 Err(e) => {
                                     warn!(error = %e, "Failed to link monitor-mix to headphones");
                                 }
                             }
                         }
+
+                        // Link the mic into every mix once its ports have registered
+                        ports::wait_for_ports(ports::PORT_WAIT, || {
+                            graph.find_wave3_source().is_some_and(|mic| !graph.get_output_ports(mic.id).is_empty())
+                        }).await;
+                        match pw_runtime.link_mic_to_mixes(&mixes).await {
+                            Ok(created) => {
+                                info!(count = created.len(), "Mic linked into mixes");
+                                for (description, id) in &created {
+                                    graph.record_created_link(description.clone(), *id);
+                                }
+                            }
+                            Err(e) => {
+                                warn!(error = %e, "Failed to link mic into mixes");
+                            }
+                        }
+
+                        // Start metering the mic
+                        meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
                     }
 
                     GraphEvent::Wave3Removed => {
@@ -312,6 +502,9 @@ async fn main() -> Result<()> {
                             event: EventType::DeviceDisconnected,
                             data: serde_json::json!({}),
                         });
+
+                        // Stop metering the mic
+                        meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
                     }
 
                     GraphEvent::NodeAdded(node) => {
@@ -349,30 +542,58 @@ async fn main() -> Result<()> {
                     GraphEvent::ClientAppeared { id, name, pid } => {
                         info!(id, name = %name, pid = ?pid, "Audio client appeared");
 
-                        // Get the app's binary name from the graph if available
-                        let binary_name = graph.get_node(id).and_then(|n| n.binary_name.clone());
+                        // Get the app's binary name and properties from the graph if available
+                        let node = graph.get_node(id);
+                        let binary_name = node.as_ref().and_then(|n| n.binary_name.clone());
+                        let properties = node.map(|n| n.properties).unwrap_or_default();
+
+                        // Walk the process tree only if some rule looks at it
+                        let ancestors = match pid {
+                            Some(pid) if routes.iter().any(|r| r.match_ancestors) => undertone_core::process::ancestors(pid),
+                            _ => Vec::new(),
+                        };
 
-                        // Find the target channel based on routing rules
-                        let target_channel = undertone_core::routing::find_channel_for_app(
+                        // Give the stream the app's lowest free slot, for stream rules
+                        let stream_index = undertone_core::routing::free_stream_index(&active_apps, &name, pid);
+
+                        // Find the rule deciding the stream's route
+                        let route = undertone_core::routing::find_route(
+                            undertone_core::routing::RouteDirection::Playback,
                             &name,
                             binary_name.as_deref(),
+                            &properties,
+                            stream_index,
+                            &ancestors,
                             &routes,
                         );
 
+                        // Exclusion rules leave the stream where the session manager put it
+                        if route.is_some_and(|m| m.rule.exclude) {
+                            info!(app_id = id, app_name = %name, "App excluded from routing, leaving it alone");
+                            continue;
+                        }
+
+                        let target_channel = route.map_or_else(|| fallback_channel.clone(), |m| m.rule.channel.clone());
+                        let ancestor = route.and_then(|m| m.ancestor).cloned();
+                        let stream_route = route
+                            .and_then(|m| m.rule.stream.clone())
+                            .map(undertone_core::routing::StreamRoute::Rule);
+                        let media_name = properties.get(undertone_core::routing::MEDIA_NAME).cloned();
+
                         info!(
                             app_id = id,
                             app_name = %name,
                             channel = %target_channel,
+                            ancestor = ?ancestor.as_ref().map(|a| &a.name),
+                            stream = ?media_name,
                             "Routing new app to channel"
                         );
 
-                        // Check if this is a persistent (saved) route
-                        let is_persistent = routes.iter().any(|r| {
-                            r.matches(&name) || binary_name.as_ref().is_some_and(|b| r.matches(b))
-                        });
+                        // Apps a saved rule matched (directly or through a parent) are persistent
+                        let is_persistent = route.is_some();
 
                         // Route the app to the target channel
-                        match pw_runtime.route_app_to_channel(id, &target_channel) {
+                        match pw_runtime.route_app_to_channel(id, &target_channel).await {
                             Ok(link_ids) => {
                                 debug!(
                                     app_id = id,
@@ -397,6 +618,10 @@ async fn main() -> Result<()> {
                             pid,
                             channel: target_channel.clone(),
                             is_persistent,
+                            ancestor,
+                            media_name,
+                            stream_index,
+                            stream_route,
                         });
 
                         // Emit IPC event
@@ -413,6 +638,15 @@ async fn main() -> Result<()> {
                         });
                     }
 
+                    GraphEvent::CaptureClientAppeared { id, name, pid } => {
+                        info!(id, name = %name, pid = ?pid, "Recording client appeared");
+                        // Runs alongside the loop, as it waits for the app's ports
+                        let (runtime, graph, rules) = (Arc::clone(&pw_runtime), Arc::clone(&graph), routes.clone());
+                        tokio::spawn(async move {
+                            capture::client_appeared(&runtime, &graph, id, &name, &rules).await;
+                        });
+                    }
+
                     GraphEvent::ClientDisappeared { id } => {
                         debug!(id, "Audio client disappeared");
 
@@ -453,7 +687,21 @@ async fn main() -> Result<()> {
                     device_serial: device_serial.clone(),
                     channels: channels.clone(),
                     app_routes: active_apps.clone(),
+                    routes: routes.clone(),
+                    // Only explaining a route needs the apps' node properties
+                    app_properties: if matches!(request.method, Method::ExplainRoute { .. }) {
+                        active_apps
+                            .iter()
+                            .filter_map(|app| graph.get_node(app.app_id).map(|n| (app.app_id, n.properties)))
+                            .collect()
+                    } else {
+                        HashMap::new()
+                    },
+                    fallback_channel: fallback_channel.clone(),
+                    mixes: mixes.clone(),
                     mixer: mixer.clone(),
+                    volume_taper: daemon_config.volume.taper,
+                    solo: volumes.solo().clone(),
                     active_profile: active_profile.clone(),
                     profiles,
                     output_devices,
@@ -472,197 +720,686 @@ async fn main() -> Result<()> {
                 // Process command if one was returned
                 if let Some(cmd) = handle_result.command {
                     use undertone_core::Command;
-                    use undertone_core::mixer::MixType;
 
                     match cmd {
                         Command::SetChannelVolume { channel, mix, volume } => {
                             if let Some(ch) = channels.iter_mut().find(|c| c.config.name == channel) {
-                                match mix {
-                                    MixType::Stream => ch.stream_volume = volume,
-                                    MixType::Monitor => ch.monitor_volume = volume,
+                                if let Err(e) = volume::check_node(&pw_runtime, &graph, &ch.config.vol_node_name(&mix)) {
+                                    error!(channel = %channel, ?mix, error = %e, "Failed to set channel volume");
+                                    continue;
                                 }
-                                info!(channel = %channel, ?mix, volume, "Channel volume updated");
+                                ch.set_volume(&mix, volume);
+                                debug!(channel = %channel, ?mix, volume, "Channel volume updated");
 
-                                // Apply to PipeWire volume filter node
-                                let filter_name = match mix {
-                                    MixType::Stream => format!("ut-ch-{channel}-stream-vol"),
-                                    MixType::Monitor => format!("ut-ch-{channel}-monitor-vol"),
-                                };
-                                if let Some(node_id) = graph.get_created_node_id(&filter_name) {
-                                    if let Err(e) = pw_runtime.set_node_volume(node_id, volume) {
-                                        error!(error = %e, filter = %filter_name, "Failed to set volume on filter node");
-                                    } else {
-                                        debug!(filter = %filter_name, volume, "Volume applied to PipeWire");
-                                    }
-                                } else {
-                                    warn!(filter = %filter_name, "Volume filter node not found");
+                                // Applied to the volume filter node and broadcast on the next tick
+                                volumes.channel_volume(&channel, mix, volume);
+                            }
+                        }
+
+                        Command::SetChannelMute { channel, mix, muted, fade } => {
+                            if let Some(ch) = channels.iter_mut().find(|c| c.config.name == channel) {
+                                if let Err(e) = volume::check_node(&pw_runtime, &graph, &ch.config.vol_node_name(&mix)) {
+                                    error!(channel = %channel, ?mix, error = %e, "Failed to set channel mute");
+                                    continue;
                                 }
+                                ch.set_muted(&mix, muted);
+                                info!(channel = %channel, ?mix, muted, "Channel mute updated");
 
-                                // Emit event
-                                let _ = event_tx.send(Event {
-                                    event: EventType::ChannelVolumeChanged,
-                                    data: serde_json::to_value(ChannelVolumeChangedData {
-                                        channel: channel.clone(),
-                                        mix,
-                                        volume,
-                                    }).unwrap_or_default(),
-                                });
+                                // Applied to the volume filter node and broadcast on the next tick
+                                volumes.channel_mute(&channel, mix, muted, fade);
                             }
                         }
 
-                        Command::SetChannelMute { channel, mix, muted } => {
+                        Command::SetChannelPan { channel, mix, pan } => {
                             if let Some(ch) = channels.iter_mut().find(|c| c.config.name == channel) {
-                                match mix {
-                                    MixType::Stream => ch.stream_muted = muted,
-                                    MixType::Monitor => ch.monitor_muted = muted,
+                                if let Err(e) = volume::check_node(&pw_runtime, &graph, &ch.config.vol_node_name(&mix)) {
+                                    error!(channel = %channel, ?mix, error = %e, "Failed to set channel pan");
+                                    continue;
                                 }
-                                info!(channel = %channel, ?mix, muted, "Channel mute updated");
+                                ch.set_pan(&mix, pan);
+                                info!(channel = %channel, ?mix, pan, "Channel pan updated");
 
-                                // Apply to PipeWire volume filter node
-                                let filter_name = match mix {
-                                    MixType::Stream => format!("ut-ch-{channel}-stream-vol"),
-                                    MixType::Monitor => format!("ut-ch-{channel}-monitor-vol"),
-                                };
-                                if let Some(node_id) = graph.get_created_node_id(&filter_name) {
-                                    if let Err(e) = pw_runtime.set_node_mute(node_id, muted) {
-                                        error!(error = %e, filter = %filter_name, "Failed to set mute on filter node");
-                                    } else {
-                                        debug!(filter = %filter_name, muted, "Mute applied to PipeWire");
-                                    }
-                                } else {
-                                    warn!(filter = %filter_name, "Volume filter node not found");
+                                // Applied to the volume filter node and broadcast on the next tick
+                                let volume = ch.send(&mix).volume;
+                                volumes.channel_pan(&channel, mix, volume, pan);
+                            }
+                        }
+
+                        Command::SetChannelEffects { channel, effects } => {
+                            if let Some(ch) = channels.iter_mut().find(|c| c.config.name == channel) {
+                                effects::apply(&pw_runtime, &graph, &ch.config, &ch.effects, &effects, &mixes).await;
+                                ch.effects = effects.clone();
+                                info!(channel = %channel, active = effects.is_active(), "Channel effects updated");
+
+                                if let Err(e) = db.save_channel_effects(&channel, &effects) {
+                                    error!(channel = %channel, error = %e, "Failed to save channel effects");
                                 }
 
-                                // Emit event
                                 let _ = event_tx.send(Event {
-                                    event: EventType::ChannelMuteChanged,
-                                    data: serde_json::to_value(ChannelMuteChangedData {
-                                        channel: channel.clone(),
-                                        mix,
-                                        muted,
-                                    }).unwrap_or_default(),
+                                    event: EventType::ChannelEffectsChanged,
+                                    data: serde_json::to_value(ChannelEffectsChangedData { channel, effects })
+                                        .unwrap_or_default(),
                                 });
                             }
                         }
 
-                        Command::SetMasterVolume { mix, volume } => {
-                            // Update mixer state
-                            match mix {
-                                MixType::Stream => mixer.stream_master_volume = volume,
-                                MixType::Monitor => mixer.monitor_master_volume = volume,
-                            }
-                            info!(?mix, volume, "Master volume updated");
+                        Command::CreateChannel { name, display_name, color, icon, layout } => {
+                            let mut config = ChannelConfig::user(&name, &display_name, 0);
+                            config.color = color;
+                            config.icon = icon;
+                            config.layout = layout;
 
-                            // Apply to the mix node in PipeWire
-                            let mix_node_name = match mix {
-                                MixType::Stream => "ut-stream-mix",
-                                MixType::Monitor => "ut-monitor-mix",
+                            let config = match db.create_channel(&config) {
+                                Ok(config) => config,
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to save channel to database");
+                                    continue;
+                                }
                             };
-                            if let Some(node_id) = graph.get_created_node_id(mix_node_name) {
-                                if let Err(e) = pw_runtime.set_node_volume(node_id, volume) {
-                                    error!(error = %e, node = %mix_node_name, "Failed to set master volume");
-                                } else {
-                                    debug!(node = %mix_node_name, volume, "Master volume applied to PipeWire");
+
+                            // Create the channel sink and its volume filters
+                            match pw_runtime.create_channel(&config, &mixes).await {
+                                Ok(created) => {
+                                    for (node_name, id) in &created {
+                                        graph.record_created_node(node_name.clone(), *id);
+                                    }
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to create channel nodes");
+                                    // Don't keep a channel that has no nodes behind it
+                                    if let Err(e) = pw_runtime.destroy_channel(&config, &mixes).await {
+                                        warn!(channel = %name, error = %e, "Failed to clean up channel nodes");
+                                    }
+                                    if let Err(e) = db.delete_channel(&name) {
+                                        error!(channel = %name, error = %e, "Failed to remove channel from database");
+                                    }
+                                    continue;
                                 }
-                            } else {
-                                warn!(node = %mix_node_name, "Mix node not found for master volume");
                             }
+
+                            // Wait for the new nodes' ports to be registered before linking
+                            let node_names: Vec<String> = std::iter::once(config.node_name())
+                                .chain(mixes.iter().map(|mix| config.vol_node_name(&mix.id())))
+                                .collect();
+                            ports::wait_for_ports(ports::PORT_WAIT, || node_names.iter().all(|n| graph.has_ports_for_node(n))).await;
+
+                            match pw_runtime.link_channel_to_mixes(&config, &mixes).await {
+                                Ok(created) => {
+                                    for (description, id) in &created {
+                                        graph.record_created_link(description.clone(), *id);
+                                    }
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to link channel to mixes");
+                                }
+                            }
+
+                            info!(channel = %name, "Channel created");
+                            let ch = ChannelState::new(config.clone());
+                            // Silence the new channel in the monitor mix if others are soloed
+                            if volumes.solo().is_active() {
+                                volumes.channel(&name, &MixType::MONITOR, ch.send(&MixType::MONITOR));
+                            }
+                            channels.push(ch);
+                            meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+
+                            let _ = event_tx.send(Event {
+                                event: EventType::ChannelCreated,
+                                data: serde_json::to_value(&config).unwrap_or_default(),
+                            });
                         }
 
-                        Command::SetMasterMute { mix, muted } => {
-                            // Update mixer state
-                            match mix {
-                                MixType::Stream => mixer.stream_master_muted = muted,
-                                MixType::Monitor => mixer.monitor_master_muted = muted,
+                        Command::RenameChannel { name, display_name } => {
+                            match db.rename_channel(&name, &display_name) {
+                                Ok(true) => {
+                                    if let Some(ch) = channels.iter_mut().find(|c| c.config.name == name) {
+                                        ch.config.display_name = display_name;
+                                        info!(channel = %name, display_name = %ch.config.display_name, "Channel renamed");
+
+                                        let _ = event_tx.send(Event {
+                                            event: EventType::ChannelUpdated,
+                                            data: serde_json::to_value(&ch.config).unwrap_or_default(),
+                                        });
+                                    }
+                                }
+                                Ok(false) => {
+                                    warn!(channel = %name, "Cannot rename channel (not found)");
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to rename channel");
+                                }
                             }
-                            info!(?mix, muted, "Master mute updated");
+                        }
+
+                        Command::SetChannelColor { name, color } => {
+                            match db.set_channel_color(&name, color.as_deref()) {
+                                Ok(true) => {
+                                    if let Some(ch) = channels.iter_mut().find(|c| c.config.name == name) {
+                                        ch.config.color = color;
+                                        info!(channel = %name, color = ?ch.config.color, "Channel color updated");
+
+                                        let _ = event_tx.send(Event {
+                                            event: EventType::ChannelUpdated,
+                                            data: serde_json::to_value(&ch.config).unwrap_or_default(),
+                                        });
+                                    }
+                                }
+                                Ok(false) => {
+                                    warn!(channel = %name, "Cannot recolor channel (not found)");
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to set channel color");
+                                }
+                            }
+                        }
 
-                            // Apply to the mix node in PipeWire
-                            let mix_node_name = match mix {
-                                MixType::Stream => "ut-stream-mix",
-                                MixType::Monitor => "ut-monitor-mix",
+                        Command::SetChannelDirectOut { name, enabled } => {
+                            match db.set_channel_direct_out(&name, enabled) {
+                                Ok(true) => {
+                                    let Some(ch) = channels.iter_mut().find(|c| c.config.name == name) else {
+                                        continue;
+                                    };
+                                    let was_enabled = std::mem::replace(&mut ch.config.direct_out, enabled);
+                                    let config = ch.config.clone();
+
+                                    if enabled && !was_enabled {
+                                        match pw_runtime.create_channel_direct_out(&config).await {
+                                            Ok(node) => graph.record_created_node(node.name, node.id),
+                                            Err(e) => {
+                                                error!(channel = %name, error = %e, "Failed to create direct-out source");
+                                            }
+                                        }
+                                        let direct_name = config.direct_node_name();
+                                        ports::wait_for_ports(ports::PORT_WAIT, || graph.has_ports_for_node(&direct_name)).await;
+                                        match pw_runtime.link_channel_direct_out(&config).await {
+                                            Ok(created) => {
+                                                for (description, id) in created {
+                                                    graph.record_created_link(description, id);
+                                                }
+                                            }
+                                            Err(e) => {
+                                                error!(channel = %name, error = %e, "Failed to link channel to its direct-out");
+                                            }
+                                        }
+                                    } else if !enabled && was_enabled
+                                        && let Err(e) = pw_runtime.destroy_channel_direct_out(&config).await
+                                    {
+                                        error!(channel = %name, error = %e, "Failed to destroy direct-out source");
+                                    }
+                                    info!(channel = %name, enabled, "Channel direct-out updated");
+
+                                    let _ = event_tx.send(Event {
+                                        event: EventType::ChannelUpdated,
+                                        data: serde_json::to_value(&config).unwrap_or_default(),
+                                    });
+                                }
+                                Ok(false) => {
+                                    warn!(channel = %name, "Cannot set direct-out (channel not found)");
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to set channel direct-out");
+                                }
+                            }
+                        }
+
+                        Command::SetChannelLayout { name, layout } => {
+                            match db.set_channel_layout(&name, layout) {
+                                Ok(true) => {
+                                    let Some(ch) = channels.iter_mut().find(|c| c.config.name == name) else {
+                                        continue;
+                                    };
+                                    if ch.config.layout == layout {
+                                        continue;
+                                    }
+                                    ch.config.layout = layout;
+                                    let config = ch.config.clone();
+
+                                    // Tear the channel's nodes down and let reconciliation
+                                    // rebuild them with the new layout and re-link its apps
+                                    if let Err(e) = pw_runtime.destroy_channel(&config, &mixes).await {
+                                        error!(channel = %name, error = %e, "Failed to destroy channel nodes");
+                                    }
+                                    reconcile::run(&pw_runtime, &graph, &channels, &mixes, &mut active_apps, &monitor_output, daemon_config.stream_source.enabled).await;
+                                    volumes.restore(&pw_runtime, &graph, &channels, &mixes, &mixer);
+                                    ducker.reapply();
+                                    meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+                                    info!(channel = %name, layout = layout.as_str(), "Channel layout updated");
+
+                                    let _ = event_tx.send(Event {
+                                        event: EventType::ChannelUpdated,
+                                        data: serde_json::to_value(&config).unwrap_or_default(),
+                                    });
+                                }
+                                Ok(false) => {
+                                    warn!(channel = %name, "Cannot set layout (channel not found)");
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to set channel layout");
+                                }
+                            }
+                        }
+
+                        Command::DeleteChannel { name } => {
+                            use undertone_core::routing::{FALLBACK_CHANNEL, RouteDirection};
+
+                            match db.delete_channel(&name) {
+                                Ok(true) => {
+                                    // The database dropped the setting along with the channel
+                                    if fallback_channel == name {
+                                        fallback_channel = FALLBACK_CHANNEL.to_string();
+                                    }
+
+                                    // Move apps on the deleted channel to the fallback channel
+                                    // before its sink disappears
+                                    for app in active_apps.iter_mut().filter(|a| a.channel == name) {
+                                        if let Err(e) = pw_runtime.route_app_to_channel(app.app_id, &fallback_channel).await {
+                                            warn!(app_id = app.app_id, error = %e, "Failed to move app to fallback channel");
+                                        }
+                                        app.channel = fallback_channel.clone();
+                                        app.is_persistent = false;
+                                        app.stream_route = None;
+
+                                        let _ = event_tx.send(Event {
+                                            event: EventType::AppRouteChanged,
+                                            data: serde_json::to_value(AppRouteChangedData {
+                                                app_id: app.app_id,
+                                                name: app.app_name.clone(),
+                                                channel: app.channel.clone(),
+                                            }).unwrap_or_default(),
+                                        });
+                                    }
+
+                                    // Rules targeting the channel were removed from the database
+                                    routes.retain(|r| r.direction != RouteDirection::Playback || r.exclude || r.channel != name);
+
+                                    // Drop the channel from ducking rules
+                                    for mix in mixer.remove_channel(&name) {
+                                        let _ = event_tx.send(Event {
+                                            event: EventType::DuckingChanged,
+                                            data: serde_json::to_value(DuckingChangedData {
+                                                rules: mixer.ducking_rules(&mix).to_vec(),
+                                                mix,
+                                            }).unwrap_or_default(),
+                                        });
+                                    }
+
+                                    if let Some(ch) = channels.iter().find(|c| c.config.name == name)
+                                        && let Err(e) = pw_runtime.destroy_channel(&ch.config, &mixes).await
+                                    {
+                                        error!(channel = %name, error = %e, "Failed to destroy channel nodes");
+                                    }
+
+                                    channels.retain(|c| c.config.name != name);
+                                    volumes.forget_channel(&name, &mixes);
+                                    info!(channel = %name, "Channel deleted");
+
+                                    let mut solo = volumes.solo().clone();
+                                    solo.set(&name, false);
+                                    volumes.set_solo(solo, &channels);
+                                    meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+
+                                    let _ = event_tx.send(Event {
+                                        event: EventType::ChannelDeleted,
+                                        data: serde_json::to_value(ChannelDeletedData {
+                                            channel: name.clone(),
+                                            fallback: fallback_channel.clone(),
+                                        }).unwrap_or_default(),
+                                    });
+                                }
+                                Ok(false) => {
+                                    warn!(channel = %name, "Cannot delete channel (may be system or not found)");
+                                }
+                                Err(e) => {
+                                    error!(channel = %name, error = %e, "Failed to delete channel");
+                                }
+                            }
+                        }
+
+                        Command::CreateMix { name, display_name } => {
+                            let config = match db.create_mix(&MixConfig::user(&name, &display_name, 0)) {
+                                Ok(config) => config,
+                                Err(e) => {
+                                    error!(mix = %name, error = %e, "Failed to save mix to database");
+                                    continue;
+                                }
                             };
-                            if let Some(node_id) = graph.get_created_node_id(mix_node_name) {
-                                if let Err(e) = pw_runtime.set_node_mute(node_id, muted) {
-                                    error!(error = %e, node = %mix_node_name, "Failed to set master mute");
-                                } else {
-                                    debug!(node = %mix_node_name, muted, "Master mute applied to PipeWire");
+                            let new_mix = std::slice::from_ref(&config);
+                            let channel_configs: Vec<ChannelConfig> =
+                                channels.iter().map(|c| c.config.clone()).collect();
+
+                            // Create the mix node, a volume filter per channel and the mic filter
+                            match pw_runtime.create_mix(&config, &channel_configs).await {
+                                Ok(created) => {
+                                    for (node_name, id) in &created {
+                                        graph.record_created_node(node_name.clone(), *id);
+                                    }
                                 }
-                            } else {
-                                warn!(node = %mix_node_name, "Mix node not found for master mute");
+                                Err(e) => {
+                                    error!(mix = %name, error = %e, "Failed to create mix nodes");
+                                }
+                            }
+
+                            // Wait for the new nodes' ports to be registered before linking
+                            let mix = config.id();
+                            let mut node_names: Vec<String> =
+                                channel_configs.iter().map(|c| c.vol_node_name(&mix)).collect();
+                            node_names.push(mix.mix_node_name());
+                            node_names.push(mix.mic_vol_node_name());
+                            ports::wait_for_ports(ports::PORT_WAIT, || node_names.iter().all(|n| graph.has_ports_for_node(n))).await;
+
+                            for channel in &channel_configs {
+                                match pw_runtime.link_channel_to_mixes(channel, new_mix).await {
+                                    Ok(created) => {
+                                        for (description, id) in &created {
+                                            graph.record_created_link(description.clone(), *id);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!(channel = %channel.name, mix = %name, error = %e, "Failed to link channel to mix");
+                                    }
+                                }
+                            }
+                            if graph.find_wave3_source().is_some() {
+                                match pw_runtime.link_mic_to_mixes(new_mix).await {
+                                    Ok(created) => {
+                                        for (description, id) in &created {
+                                            graph.record_created_link(description.clone(), *id);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        warn!(mix = %name, error = %e, "Failed to link mic into mix");
+                                    }
+                                }
+                            }
+
+                            // Apply the master level and mic send on the next tick
+                            volumes.mixer(&mixer, new_mix);
+
+                            info!(mix = %name, "Mix created");
+                            mixes.push(config.clone());
+                            meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+
+                            let _ = event_tx.send(Event {
+                                event: EventType::MixCreated,
+                                data: serde_json::to_value(&config).unwrap_or_default(),
+                            });
+                        }
+
+                        Command::DeleteMix { name } => {
+                            let Some(config) = mixes.iter().find(|m| m.name == name).cloned() else {
+                                warn!(mix = %name, "Cannot delete mix (not found)");
+                                continue;
+                            };
+
+                            match db.delete_mix(&name) {
+                                Ok(true) => {
+                                    let channel_configs: Vec<ChannelConfig> =
+                                        channels.iter().map(|c| c.config.clone()).collect();
+                                    if let Err(e) = pw_runtime.destroy_mix(&config, &channel_configs).await {
+                                        error!(mix = %name, error = %e, "Failed to destroy mix nodes");
+                                    }
+
+                                    let mix = config.id();
+                                    mixes.retain(|m| m.name != name);
+                                    mixer.remove_mix(&mix);
+                                    ducker.forget_mix(&mix);
+                                    volumes.forget_mix(&mix, &channels);
+                                    for ch in &mut channels {
+                                        ch.sends.remove(&mix);
+                                    }
+                                    info!(mix = %name, "Mix deleted");
+                                    meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
+
+                                    let _ = event_tx.send(Event {
+                                        event: EventType::MixDeleted,
+                                        data: serde_json::to_value(MixDeletedData { mix }).unwrap_or_default(),
+                                    });
+                                }
+                                Ok(false) => {
+                                    warn!(mix = %name, "Cannot delete mix (may be system or not found)");
+                                }
+                                Err(e) => {
+                                    error!(mix = %name, error = %e, "Failed to delete mix");
+                                }
+                            }
+                        }
+
+                        Command::SetMasterVolume { mix, volume, fade } => {
+                            if let Err(e) = volume::check_node(&pw_runtime, &graph, &mix.mix_node_name()) {
+                                error!(?mix, error = %e, "Failed to set master volume");
+                                continue;
+                            }
+                            // Update mixer state
+                            mixer.set_master_volume(&mix, volume);
+                            debug!(?mix, volume, "Master volume updated");
+
+                            // Applied to the mix node on the next tick
+                            volumes.master_volume(mix, volume, fade);
+                        }
+
+                        Command::SetMasterMute { mix, muted } => {
+                            if let Err(e) = volume::check_node(&pw_runtime, &graph, &mix.mix_node_name()) {
+                                error!(?mix, error = %e, "Failed to set master mute");
+                                continue;
                             }
+                            // Update mixer state
+                            mixer.set_master_muted(&mix, muted);
+                            info!(?mix, muted, "Master mute updated");
+
+                            // Applied to the mix node on the next tick
+                            volumes.master_mute(mix, muted);
                         }
 
-                        Command::SetAppRoute { app_pattern, channel } => {
-                            use undertone_core::routing::{PatternType, RouteRule};
+                        Command::SetAppRoute { app_pattern, channel, pattern_type, property, direction, exclude, match_ancestors, stream } => {
+                            use undertone_core::routing::{RouteDirection, RouteRule, StreamRoute, find_route};
 
                             // Update in-memory routes
-                            routes.retain(|r| r.pattern != app_pattern);
-                            let rule = RouteRule::new(
+                            routes.retain(|r| r.pattern != app_pattern || r.property != property || r.direction != direction || r.stream != stream);
+                            let mut rule = RouteRule::new(
                                 app_pattern.clone(),
-                                PatternType::Exact,
+                                pattern_type,
                                 channel.clone(),
                                 100,
-                            );
+                            )
+                            .with_direction(direction);
+                            rule.property = property;
+                            rule.exclude = exclude;
+                            rule.match_ancestors = match_ancestors;
+                            rule.stream = stream;
                             routes.push(rule.clone());
-                            info!(app_pattern = %app_pattern, channel = %channel, "App route set");
+                            info!(app_pattern = %app_pattern, channel = %channel, ?direction, exclude, stream = ?rule.stream, "App route set");
 
                             // Save to database
                             if let Err(e) = db.save_route(&rule) {
                                 error!(error = %e, "Failed to save route to database");
                             }
 
-                            // Apply routing to matching active apps
+                            // Exclusion rules only affect streams that appear from now on
+                            if exclude {
+                                continue;
+                            }
+
+                            // Apps are only moved where the rule as stored wins over the others
+                            let rule = &routes[routes.len() - 1];
+
+                            // Capture rules feed matching recording apps from their source
+                            if direction == RouteDirection::Capture {
+                                capture::apply_rule(&pw_runtime, &graph, rule, &routes).await;
+                                continue;
+                            }
+
+                            // Move the active apps the new rule now wins for
                             let audio_clients = pw_runtime.get_audio_clients();
                             for client in audio_clients {
-                                // Check if this client matches the pattern
-                                let matches = client.application_name.as_ref().is_some_and(|name| {
-                                    rule.matches(name)
-                                }) || client.binary_name.as_ref().is_some_and(|name| {
-                                    rule.matches(name)
-                                }) || rule.matches(&client.name);
-
-                                if matches {
-                                    info!(
-                                        app_id = client.id,
-                                        app_name = %client.name,
-                                        channel = %channel,
-                                        "Re-routing matching app"
-                                    );
-                                    match pw_runtime.route_app_to_channel(client.id, &channel) {
-                                        Ok(link_ids) => {
-                                            debug!(
-                                                app_id = client.id,
-                                                links_created = link_ids.len(),
-                                                "App re-routed successfully"
-                                            );
-
-                                            // Update active_apps tracking
-                                            if let Some(app) = active_apps.iter_mut().find(|a| a.app_id == client.id) {
-                                                app.channel = channel.clone();
-                                                app.is_persistent = true;
-                                            }
-                                        }
-                                        Err(e) => {
-                                            warn!(
-                                                app_id = client.id,
-                                                error = %e,
-                                                "Failed to re-route app"
-                                            );
+                                // Streams moved by hand stay put until they close
+                                let tracked = active_apps.iter().find(|a| a.app_id == client.id);
+                                if tracked.is_some_and(|a| a.stream_route == Some(StreamRoute::Manual)) {
+                                    continue;
+                                }
+
+                                let ancestors = match client.pid {
+                                    Some(pid) if routes.iter().any(|r| r.match_ancestors) => undertone_core::process::ancestors(pid),
+                                    _ => Vec::new(),
+                                };
+                                let route = find_route(
+                                    RouteDirection::Playback,
+                                    client.application_name.as_deref().unwrap_or(&client.name),
+                                    client.binary_name.as_deref(),
+                                    &client.properties,
+                                    tracked.map_or(0, |a| a.stream_index),
+                                    &ancestors,
+                                    &routes,
+                                );
+                                let Some(route) = route.filter(|m| std::ptr::eq(m.rule, rule)) else {
+                                    continue;
+                                };
+                                let ancestor = route.ancestor.cloned();
+
+                                info!(
+                                    app_id = client.id,
+                                    app_name = %client.name,
+                                    channel = %channel,
+                                    "Re-routing matching app"
+                                );
+                                match pw_runtime.route_app_to_channel(client.id, &channel).await {
+                                    Ok(link_ids) => {
+                                        debug!(
+                                            app_id = client.id,
+                                            links_created = link_ids.len(),
+                                            "App re-routed successfully"
+                                        );
+
+                                        // Update active_apps tracking
+                                        if let Some(app) = active_apps.iter_mut().find(|a| a.app_id == client.id) {
+                                            app.channel = channel.clone();
+                                            app.is_persistent = true;
+                                            app.ancestor = ancestor;
+                                            app.stream_route = rule.stream.clone().map(StreamRoute::Rule);
                                         }
                                     }
+                                    Err(e) => {
+                                        warn!(
+                                            app_id = client.id,
+                                            error = %e,
+                                            "Failed to re-route app"
+                                        );
+                                    }
                                 }
                             }
                         }
 
-                        Command::RemoveAppRoute { app_pattern } => {
-                            routes.retain(|r| r.pattern != app_pattern);
-                            info!(app_pattern = %app_pattern, "App route removed");
+                        Command::RemoveAppRoute { app_pattern, property, direction, stream } => {
+                            use undertone_core::routing::{RouteDirection, StreamRoute, find_route};
+
+                            routes.retain(|r| r.pattern != app_pattern || r.property != property || r.direction != direction || r.stream != stream);
+                            info!(app_pattern = %app_pattern, ?property, ?direction, ?stream, "App route removed");
 
                             // Remove from database
-                            if let Err(e) = db.delete_route(&app_pattern) {
+                            if let Err(e) = db.delete_route(&app_pattern, property.as_deref(), direction, stream.as_ref()) {
                                 error!(error = %e, "Failed to remove route from database");
                             }
+
+                            // Streams the removed rule picked are routed by whichever rule wins for them now
+                            let Some(selector) = stream.filter(|_| direction == RouteDirection::Playback) else {
+                                continue;
+                            };
+                            let picked = Some(StreamRoute::Rule(selector));
+                            for app in active_apps.iter_mut().filter(|a| a.stream_route == picked) {
+                                let properties = graph.get_node(app.app_id).map(|n| n.properties).unwrap_or_default();
+                                let ancestors = match app.pid {
+                                    Some(pid) if routes.iter().any(|r| r.match_ancestors) => undertone_core::process::ancestors(pid),
+                                    _ => Vec::new(),
+                                };
+                                let route = find_route(
+                                    RouteDirection::Playback,
+                                    &app.app_name,
+                                    app.binary_name.as_deref(),
+                                    &properties,
+                                    app.stream_index,
+                                    &ancestors,
+                                    &routes,
+                                );
+
+                                app.stream_route = route.and_then(|m| m.rule.stream.clone()).map(StreamRoute::Rule);
+                                app.ancestor = route.and_then(|m| m.ancestor).cloned();
+                                // Exclusion rules leave the stream where it is
+                                if route.is_some_and(|m| m.rule.exclude) {
+                                    continue;
+                                }
+                                let channel = route.map_or_else(|| fallback_channel.clone(), |m| m.rule.channel.clone());
+                                if channel == app.channel {
+                                    continue;
+                                }
+
+                                match pw_runtime.route_app_to_channel(app.app_id, &channel).await {
+                                    Ok(_) => {
+                                        info!(app_id = app.app_id, app_name = %app.app_name, channel = %channel, "Stream re-routed after its rule was removed");
+                                        app.channel = channel;
+                                        app.is_persistent = route.is_some();
+
+                                        let _ = event_tx.send(Event {
+                                            event: EventType::AppRouteChanged,
+                                            data: serde_json::to_value(AppRouteChangedData {
+                                                app_id: app.app_id,
+                                                name: app.app_name.clone(),
+                                                channel: app.channel.clone(),
+                                            }).unwrap_or_default(),
+                                        });
+                                    }
+                                    Err(e) => {
+                                        warn!(app_id = app.app_id, error = %e, "Failed to re-route stream");
+                                    }
+                                }
+                            }
+                        }
+
+                        Command::MoveStream { app_id, channel } => {
+                            let Some(app) = active_apps.iter_mut().find(|a| a.app_id == app_id) else {
+                                warn!(app_id, "Cannot move stream (app has gone)");
+                                continue;
+                            };
+
+                            match pw_runtime.route_app_to_channel(app_id, &channel).await {
+                                Ok(_) => {
+                                    info!(app_id, app_name = %app.app_name, stream = ?app.media_name, channel = %channel, "Stream moved");
+                                    app.channel = channel;
+                                    app.stream_route = Some(undertone_core::routing::StreamRoute::Manual);
+
+                                    let _ = event_tx.send(Event {
+                                        event: EventType::AppRouteChanged,
+                                        data: serde_json::to_value(AppRouteChangedData {
+                                            app_id,
+                                            name: app.app_name.clone(),
+                                            channel: app.channel.clone(),
+                                        }).unwrap_or_default(),
+                                    });
+                                }
+                                Err(e) => {
+                                    warn!(app_id, error = %e, "Failed to move stream");
+                                }
+                            }
+                        }
+
+                        Command::SetFallbackChannel { channel } => {
+                            match db.save_fallback_channel(&channel) {
+                                Ok(true) => {
+                                    info!(channel = %channel, "Fallback channel set");
+                                    fallback_channel = channel;
+
+                                    let _ = event_tx.send(Event {
+                                        event: EventType::FallbackChannelChanged,
+                                        data: serde_json::to_value(FallbackChannelChangedData {
+                                            channel: fallback_channel.clone(),
+                                        }).unwrap_or_default(),
+                                    });
+                                }
+                                Ok(false) => {
+                                    warn!(channel = %channel, "Cannot set fallback channel (not found)");
+                                }
+                                Err(e) => {
+                                    error!(channel = %channel, error = %e, "Failed to save fallback channel");
+                                }
+                            }
                         }
 
                         Command::SaveProfile { name } => {
@@ -693,7 +1430,7 @@ async fn main() -> Result<()> {
                             }
                         }
 
-                        Command::LoadProfile { name } => {
+                        Command::LoadProfile { name, fade } => {
                             match db.load_profile(&name) {
                                 Ok(Some(profile)) => {
                                     info!(name = %name, "Loading profile");
@@ -703,25 +1440,30 @@ async fn main() -> Result<()> {
                                         if let Some(ch) = channels.iter_mut()
                                             .find(|c| c.config.name == profile_ch.name)
                                         {
-                                            ch.stream_volume = profile_ch.stream_volume;
-                                            ch.stream_muted = profile_ch.stream_muted;
-                                            ch.monitor_volume = profile_ch.monitor_volume;
-                                            ch.monitor_muted = profile_ch.monitor_muted;
-
-                                            // Apply to PipeWire filter nodes
-                                            use undertone_core::mixer::MixType;
-                                            for (mix, volume, muted) in [
-                                                (MixType::Stream, ch.stream_volume, ch.stream_muted),
-                                                (MixType::Monitor, ch.monitor_volume, ch.monitor_muted),
-                                            ] {
-                                                let filter_name = match mix {
-                                                    MixType::Stream => format!("ut-ch-{}-stream-vol", ch.config.name),
-                                                    MixType::Monitor => format!("ut-ch-{}-monitor-vol", ch.config.name),
+                                            // Apply to PipeWire filter nodes on the next tick;
+                                            // sends into mixes that no longer exist are ignored
+                                            for mix in mixes.iter().map(MixConfig::id) {
+                                                let Some(send) = profile_ch.sends.get(&mix).copied() else {
+                                                    continue;
                                                 };
-                                                if let Some(node_id) = graph.get_created_node_id(&filter_name) {
-                                                    let _ = pw_runtime.set_node_volume(node_id, volume);
-                                                    let _ = pw_runtime.set_node_mute(node_id, muted);
+                                                volumes.channel(&ch.config.name, &mix, send);
+                                                ch.sends.insert(mix, send);
+                                            }
+
+                                            if let Some(fx) = profile_ch.effects.as_ref().filter(|fx| **fx != ch.effects) {
+                                                effects::apply(&pw_runtime, &graph, &ch.config, &ch.effects, fx, &mixes).await;
+                                                ch.effects = fx.clone();
+                                                if let Err(e) = db.save_channel_effects(&ch.config.name, fx) {
+                                                    error!(channel = %ch.config.name, error = %e, "Failed to save channel effects");
                                                 }
+
+                                                let _ = event_tx.send(Event {
+                                                    event: EventType::ChannelEffectsChanged,
+                                                    data: serde_json::to_value(ChannelEffectsChangedData {
+                                                        channel: ch.config.name.clone(),
+                                                        effects: fx.clone(),
+                                                    }).unwrap_or_default(),
+                                                });
                                             }
                                         }
                                     }
@@ -734,19 +1476,10 @@ async fn main() -> Result<()> {
                                     // Apply mixer state (master volumes)
                                     mixer = profile.mixer.clone();
 
-                                    // Apply master volumes to PipeWire mix nodes
-                                    for (mix_type, volume, muted) in [
-                                        (MixType::Stream, mixer.stream_master_volume, mixer.stream_master_muted),
-                                        (MixType::Monitor, mixer.monitor_master_volume, mixer.monitor_master_muted),
-                                    ] {
-                                        let mix_node_name = match mix_type {
-                                            MixType::Stream => "ut-stream-mix",
-                                            MixType::Monitor => "ut-monitor-mix",
-                                        };
-                                        if let Some(node_id) = graph.get_created_node_id(mix_node_name) {
-                                            let _ = pw_runtime.set_node_volume(node_id, volume);
-                                            let _ = pw_runtime.set_node_mute(node_id, muted);
-                                        }
+                                    // Apply master volumes and mic sends on the next tick
+                                    volumes.mixer(&mixer, &mixes);
+                                    if let Some(fade) = fade {
+                                        volumes.fade_pending(fade);
                                     }
 
                                     // Update active profile name
@@ -807,28 +1540,73 @@ async fn main() -> Result<()> {
                             }
                         }
 
+                        Command::SetMicMixEnabled { mix, enabled } => {
+                            mixer.set_mic_enabled(&mix, enabled);
+                            volumes.mic(mix.clone(), mixer.mic_enabled(&mix), mixer.mic_volume(&mix));
+                        }
+
+                        Command::SetMicMixVolume { mix, volume } => {
+                            mixer.set_mic_volume(&mix, volume);
+                            volumes.mic(mix.clone(), mixer.mic_enabled(&mix), mixer.mic_volume(&mix));
+                        }
+
+                        Command::SetDuckingRules { mix, rules } => {
+                            mixer.set_ducking_rules(&mix, rules.clone());
+                            info!(?mix, count = rules.len(), "Ducking rules updated");
+                            if !daemon_config.meters.enabled && !rules.is_empty() {
+                                warn!(?mix, "Ducking rules have no effect while meters are disabled");
+                            }
+
+                            let _ = event_tx.send(Event {
+                                event: EventType::DuckingChanged,
+                                data: serde_json::to_value(DuckingChangedData { mix, rules }).unwrap_or_default(),
+                            });
+                        }
+
+                        Command::SetChannelSolo { channel, soloed } => {
+                            let mut solo = volumes.solo().clone();
+                            solo.set(&channel, soloed);
+                            info!(channel = %channel, soloed, "Channel solo updated");
+
+                            // Monitor sends are applied and SoloChanged broadcast on the next tick
+                            volumes.set_solo(solo, &channels);
+                        }
+
+                        Command::SetSoloMode { mode } => {
+                            let solo = SoloState { mode, ..volumes.solo().clone() };
+                            info!(?mode, "Solo mode updated");
+                            volumes.set_solo(solo, &channels);
+                        }
+
+                        Command::ClearSolo => {
+                            let solo = SoloState { mode: volumes.solo().mode, ..SoloState::default() };
+                            info!("Solo cleared");
+                            volumes.set_solo(solo, &channels);
+                        }
+
                         Command::SetMonitorOutput { device_name } => {
                             info!(device = %device_name, "Switching monitor output");
 
                             // Unlink from current output device
-                            if let Err(e) = pw_runtime.unlink_monitor_from_output(&monitor_output) {
+                            if let Err(e) = pw_runtime.unlink_monitor_from_output(&monitor_output).await {
                                 warn!(error = %e, current = %monitor_output, "Failed to unlink from current output");
                             }
 
                             // Link to new output device
-                            match pw_runtime.link_monitor_to_output(&device_name) {
-                                Ok((left_id, right_id)) => {
+                            match pw_runtime.link_monitor_to_output(&device_name).await {
+                                Ok(links) => {
                                     info!(device = %device_name, "Monitor output switched successfully");
                                     // Update tracked links
-                                    graph.record_created_link(format!("monitor-mix->{device_name}:FL"), left_id);
-                                    graph.record_created_link(format!("monitor-mix->{device_name}:FR"), right_id);
+                                    for (position, id) in links {
+                                        graph.record_created_link(format!("monitor-mix->{device_name}:{position}"), id);
+                                    }
                                     // Update current monitor output
                                     monitor_output = device_name;
                                 }
                                 Err(e) => {
                                     error!(error = %e, device = %device_name, "Failed to link to new output device");
                                     // Try to restore connection to previous device
-                                    if let Err(restore_err) = pw_runtime.link_monitor_to_output(&monitor_output) {
+                                    if let Err(restore_err) = pw_runtime.link_monitor_to_output(&monitor_output).await {
                                         error!(error = %restore_err, "Failed to restore previous output");
                                     }
                                 }
@@ -837,8 +1615,11 @@ async fn main() -> Result<()> {
 
                         Command::Reconcile => {
                             state = DaemonState::Reconciling;
-                            // TODO: Implement full reconciliation
                             info!("Reconciliation triggered");
+                            reconcile::run(&pw_runtime, &graph, &channels, &mixes, &mut active_apps, &monitor_output, daemon_config.stream_source.enabled).await;
+                            volumes.restore(&pw_runtime, &graph, &channels, &mixes, &mixer);
+                            ducker.reapply();
+                            meters::restart(&pw_runtime, &daemon_config.meters, &channels, &mixes).await;
                             state = DaemonState::Running;
                         }
 
@@ -850,6 +1631,31 @@ async fn main() -> Result<()> {
                 }
             }
 
+            // Apply coalesced volume and mute changes
+            _ = volume_tick.tick(), if !volumes.is_idle() => {
+                volumes.flush(&pw_runtime, &graph, &event_tx);
+            }
+
+            // Publish audio levels
+            _ = meter_tick.tick(), if daemon_config.meters.enabled => {
+                if let Some(levels) = meters::collect(&pw_runtime, &mut channels) {
+                    ducking::update(
+                        &mut ducker,
+                        &mixer,
+                        &levels,
+                        &channels,
+                        &mixes,
+                        &mut volumes,
+                        daemon_config.meters.interval(),
+                    );
+
+                    let _ = event_tx.send(Event {
+                        event: EventType::LevelsUpdated,
+                        data: serde_json::to_value(levels).unwrap_or_default(),
+                    });
+                }
+            }
+
             // Handle shutdown signal
             _ = shutdown_rx.recv() => {
                 info!("Shutdown signal received");
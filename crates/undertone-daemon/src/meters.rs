@@ -0,0 +1,61 @@
+//! Level metering: starting meter streams and publishing their readings.
+
+use tracing::{info, warn};
+
+use undertone_core::channel::{ChannelConfig, ChannelState};
+use undertone_core::mixer::MixConfig;
+use undertone_ipc::LevelsData;
+use undertone_pipewire::{MeterSource, PipeWireRuntime};
+
+use crate::config::MeterConfig;
+
+/// (Re)start meters for the current channels, mixes and mic.
+///
+/// Call whenever channels or mixes are added or removed, the mic comes or
+/// goes, or the graph has been rebuilt.
+pub async fn restart(
+    runtime: &PipeWireRuntime,
+    config: &MeterConfig,
+    channels: &[ChannelState],
+    mixes: &[MixConfig],
+) {
+    if !config.enabled {
+        return;
+    }
+
+    let configs: Vec<ChannelConfig> = channels.iter().map(|c| c.config.clone()).collect();
+    match runtime.start_metering(&configs, mixes).await {
+        Ok(count) => info!(count, "Level meters started"),
+        Err(e) => warn!(error = %e, "Failed to start level meters"),
+    }
+}
+
+/// Take the latest readings, store channel peaks in `channels` and build
+/// the `LevelsUpdated` payload.
+///
+/// Returns `None` if nothing is being metered.
+pub fn collect(runtime: &PipeWireRuntime, channels: &mut [ChannelState]) -> Option<LevelsData> {
+    let levels = runtime.take_levels();
+    if levels.is_empty() {
+        return None;
+    }
+
+    let mut data = LevelsData::default();
+    for (source, reading) in levels {
+        match source {
+            MeterSource::Channel(name) => {
+                if let Some(ch) = channels.iter_mut().find(|c| c.config.name == name) {
+                    ch.level_left = reading.peak_left;
+                    ch.level_right = reading.peak_right;
+                }
+                data.channels.insert(name, reading);
+            }
+            MeterSource::Mix(name) => {
+                data.mixes.insert(name, reading);
+            }
+            MeterSource::Mic => data.mic = Some(reading),
+        }
+    }
+
+    Some(data)
+}
@@ -0,0 +1,25 @@
+//! Waiting for new nodes to register their ports before linking them.
+
+use std::time::Duration;
+
+use tokio::time::{Instant, sleep};
+
+/// How long to wait for a few freshly created nodes.
+pub const PORT_WAIT: Duration = Duration::from_secs(2);
+/// How often the graph is checked while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Wait up to `timeout` until `ready` reports that the ports the caller
+/// needs are registered.
+///
+/// Returns `false` on timeout, leaving the caller to link whatever is there.
+pub async fn wait_for_ports(timeout: Duration, ready: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !ready() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+    true
+}
@@ -0,0 +1,168 @@
+//! Bringing the `PipeWire` graph back in line with daemon state.
+//!
+//! Used after `PipeWire` or `WirePlumber` restarts and on explicit
+//! `Reconcile` requests.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use undertone_core::channel::ChannelState;
+use undertone_core::mixer::{MixConfig, MixType, MixerState};
+use undertone_core::routing::AppRoute;
+use undertone_core::solo::SoloState;
+use undertone_core::volume::{VolumeTaper, balance_volumes};
+use undertone_pipewire::{FilterChain, GraphManager, PipeWireRuntime, ReconcileReport, Reconciler};
+
+use crate::ports;
+
+/// How long to wait for recreated nodes to register their ports.
+const PORT_WAIT: Duration = Duration::from_secs(3);
+
+/// Reconcile the graph, then re-apply app routes.
+///
+/// Recreated volume filters start at full volume; callers re-apply every
+/// level afterwards (see [`apply_volumes`]). `stream_source` says whether the
+/// stream mix should have its virtual source.
+///
+/// Nodes are created first; links are planned in a second pass once the new
+/// nodes' ports have appeared in the registry. Apps whose nodes are gone are
+/// dropped from `active_apps`.
+pub async fn run(
+    runtime: &PipeWireRuntime,
+    graph: &Arc<GraphManager>,
+    channels: &[ChannelState],
+    mixes: &[MixConfig],
+    active_apps: &mut Vec<AppRoute>,
+    monitor_output: &str,
+    stream_source: bool,
+) -> ReconcileReport {
+    let reconciler = Reconciler::new(Arc::clone(graph)).with_stream_source(stream_source);
+
+    // Pass 1: nodes
+    let plan = reconciler.reconcile(channels, mixes, monitor_output);
+    let node_actions: Vec<_> = plan.into_iter().filter(|a| a.is_node_action()).collect();
+    let mut report = reconciler.execute(runtime, &node_actions).await;
+
+    if report.changed_nodes() {
+        wait_for_ports(graph, channels, mixes, stream_source).await;
+    }
+
+    // Pass 2: links, planned against the graph as it is now
+    let plan = reconciler.reconcile(channels, mixes, monitor_output);
+    let link_actions: Vec<_> = plan.into_iter().filter(|a| !a.is_node_action()).collect();
+    report.merge(reconciler.execute(runtime, &link_actions).await);
+
+    reroute_apps(runtime, graph, active_apps).await;
+
+    for failure in &report.failures {
+        warn!(failure = %failure, "Reconciliation action failed");
+    }
+    info!(
+        nodes_created = report.nodes_created,
+        nodes_destroyed = report.nodes_destroyed,
+        links_created = report.links_created,
+        links_destroyed = report.links_destroyed,
+        failures = report.failures.len(),
+        "Reconciliation complete"
+    );
+
+    report
+}
+
+/// Wait until every channel, effects, filter, direct-out, mix and mix
+/// source node has its ports registered.
+async fn wait_for_ports(
+    graph: &GraphManager,
+    channels: &[ChannelState],
+    mixes: &[MixConfig],
+    stream_source: bool,
+) {
+    let mut names: Vec<String> = channels.iter().map(|c| c.config.node_name()).collect();
+    for ch in channels
+        .iter()
+        .filter(|c| !c.config.layout.is_surround() && FilterChain::new(&c.effects).is_some())
+    {
+        names.push(ch.config.fx_input_node_name());
+        names.push(ch.config.fx_output_node_name());
+    }
+    names.extend(
+        channels.iter().filter(|c| c.config.direct_out).map(|c| c.config.direct_node_name()),
+    );
+    for mix in mixes.iter().map(MixConfig::id) {
+        names.extend(channels.iter().map(|c| c.config.vol_node_name(&mix)));
+        names.push(mix.mix_node_name());
+        names.push(mix.mic_vol_node_name());
+        if stream_source && mix == MixType::STREAM {
+            names.push(mix.source_node_name());
+        }
+    }
+
+    let ready = || names.iter().all(|name| graph.has_ports_for_node(name));
+    if !ports::wait_for_ports(PORT_WAIT, ready).await {
+        warn!("Timed out waiting for node ports, linking what is available");
+    }
+}
+
+/// Push in-memory channel, master and mic send volumes/mutes (and channel
+/// balance) to the filter and mix nodes, as gains for `taper`, with any
+/// solo applied to the monitor sends.
+pub fn apply_volumes(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    channels: &[ChannelState],
+    mixes: &[MixConfig],
+    mixer: &MixerState,
+    taper: VolumeTaper,
+    solo: &SoloState,
+) {
+    let mut targets: Vec<(String, [f32; 2], bool)> = Vec::new();
+
+    for mix in mixes.iter().map(MixConfig::id) {
+        for ch in channels {
+            let send = ch.send(&mix);
+            let volume = solo.send_volume(&ch.config.name, &mix, send.volume);
+            let muted = solo.send_muted(&ch.config.name, &mix, send.muted);
+            let volumes = balance_volumes(taper.gain(volume), send.pan);
+            targets.push((ch.config.vol_node_name(&mix), volumes, muted));
+        }
+        let master = mixer.master(&mix);
+        targets.push((mix.mix_node_name(), [taper.gain(master.volume); 2], master.muted));
+        let mic_gain = taper.gain(mixer.mic_volume(&mix));
+        targets.push((mix.mic_vol_node_name(), [mic_gain; 2], !mixer.mic_enabled(&mix)));
+    }
+
+    for (node_name, volumes, muted) in targets {
+        let Some(node_id) = graph.get_created_node_id(&node_name) else {
+            warn!(node = %node_name, "Node not found when re-applying volume");
+            continue;
+        };
+        if let Err(e) = runtime.set_node_volumes(node_id, volumes) {
+            warn!(node = %node_name, error = %e, "Failed to re-apply volume");
+        }
+        if let Err(e) = runtime.set_node_mute(node_id, muted) {
+            warn!(node = %node_name, error = %e, "Failed to re-apply mute");
+        }
+    }
+}
+
+/// Route every still-present app back to its channel.
+async fn reroute_apps(
+    runtime: &PipeWireRuntime,
+    graph: &GraphManager,
+    active_apps: &mut Vec<AppRoute>,
+) {
+    active_apps.retain(|app| graph.get_node(app.app_id).is_some());
+
+    for app in active_apps.iter() {
+        match runtime.route_app_to_channel(app.app_id, &app.channel).await {
+            Ok(link_ids) => {
+                debug!(app_id = app.app_id, links = link_ids.len(), "App re-routed");
+            }
+            Err(e) => {
+                warn!(app_id = app.app_id, channel = %app.channel, error = %e, "Failed to re-route app");
+            }
+        }
+    }
+}
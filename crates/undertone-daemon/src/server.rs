@@ -1,10 +1,20 @@
 //! Request handling for the IPC server.
 
+use std::collections::HashMap;
+
 use serde_json::{Value, json};
 use tracing::{debug, info};
 
+use undertone_core::channel::{validate_channel_color, validate_channel_name};
 use undertone_core::command::Command;
+use undertone_core::ducking::DuckTrigger;
+use undertone_core::explain::explain_route;
+use undertone_core::lint::lint_rules;
+use undertone_core::mixer::{MixType, validate_mix_name};
+use undertone_core::process::ancestors;
+use undertone_core::routing::{CAPTURE_MIC, RouteDirection, StreamSelector};
 use undertone_core::state::StateSnapshot;
+use undertone_core::volume::fade_duration;
 use undertone_ipc::messages::{ErrorInfo, Method};
 
 /// Result of handling a request: response value and optional command.
@@ -29,6 +39,14 @@ impl HandleResult {
     fn channel_not_found(channel: &str) -> Self {
         Self::err(ErrorInfo::new(404, format!("Channel not found: {channel}")))
     }
+
+    fn mix_not_found(mix: &MixType) -> Self {
+        Self::err(ErrorInfo::new(404, format!("Mix not found: {mix}")))
+    }
+
+    fn invalid(error: &undertone_core::Error) -> Self {
+        Self::err(ErrorInfo::new(400, error.to_string()))
+    }
 }
 
 /// Check if a channel exists in the state.
@@ -36,6 +54,11 @@ fn channel_exists(state: &StateSnapshot, channel: &str) -> bool {
     state.channels.iter().any(|c| c.config.name == channel)
 }
 
+/// Check if a mix exists in the state.
+fn mix_exists(state: &StateSnapshot, mix: &MixType) -> bool {
+    state.mixes.iter().any(|m| m.name == mix.as_str())
+}
+
 /// Handle an IPC request and return a response value with optional command.
 pub fn handle_request(method: &Method, state: &StateSnapshot) -> HandleResult {
     match method {
@@ -81,64 +104,410 @@ pub fn handle_request(method: &Method, state: &StateSnapshot) -> HandleResult {
         })),
 
         Method::SetChannelVolume { channel, mix, volume } => {
-            if !channel_exists(state, channel) {
+            let Some(ch) = state.channels.iter().find(|c| &c.config.name == channel) else {
                 return HandleResult::channel_not_found(channel);
+            };
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
             }
-            let volume = volume.clamp(0.0, 1.0);
+            let volume = match volume.resolve(ch.send(mix).volume, state.volume_taper) {
+                Ok(volume) => volume,
+                Err(e) => return HandleResult::invalid(&e),
+            };
             debug!(?channel, ?mix, volume, "Setting channel volume");
             HandleResult::ok_with_command(
                 json!({"success": true, "volume": volume}),
-                Command::SetChannelVolume { channel: channel.clone(), mix: *mix, volume },
+                Command::SetChannelVolume { channel: channel.clone(), mix: mix.clone(), volume },
             )
         }
 
-        Method::SetChannelMute { channel, mix, muted } => {
+        Method::SetChannelMute { channel, mix, muted, fade_ms } => {
             if !channel_exists(state, channel) {
                 return HandleResult::channel_not_found(channel);
             }
-            debug!(?channel, ?mix, muted, "Setting channel mute");
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
+            let fade = match fade_duration(*fade_ms) {
+                Ok(fade) => fade,
+                Err(e) => return HandleResult::invalid(&e),
+            };
+            debug!(?channel, ?mix, muted, ?fade, "Setting channel mute");
             HandleResult::ok_with_command(
                 json!({"success": true, "muted": muted}),
-                Command::SetChannelMute { channel: channel.clone(), mix: *mix, muted: *muted },
+                Command::SetChannelMute {
+                    channel: channel.clone(),
+                    mix: mix.clone(),
+                    muted: *muted,
+                    fade,
+                },
+            )
+        }
+
+        Method::SetChannelPan { channel, mix, pan } => {
+            if !channel_exists(state, channel) {
+                return HandleResult::channel_not_found(channel);
+            }
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
+            let pan = pan.clamp(-1.0, 1.0);
+            debug!(?channel, ?mix, pan, "Setting channel pan");
+            HandleResult::ok_with_command(
+                json!({"success": true, "pan": pan}),
+                Command::SetChannelPan { channel: channel.clone(), mix: mix.clone(), pan },
+            )
+        }
+
+        Method::SetChannelEffects { channel, effects } => {
+            if !channel_exists(state, channel) {
+                return HandleResult::channel_not_found(channel);
+            }
+            if let Err(e) = effects.validate() {
+                return HandleResult::invalid(&e);
+            }
+            let surround = state
+                .channels
+                .iter()
+                .any(|c| &c.config.name == channel && c.config.layout.is_surround());
+            if effects.is_active() && surround {
+                return HandleResult::err(ErrorInfo::new(
+                    400,
+                    format!("Effects need a stereo channel: {channel}"),
+                ));
+            }
+            debug!(?channel, active = effects.is_active(), "Setting channel effects");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::SetChannelEffects { channel: channel.clone(), effects: effects.clone() },
+            )
+        }
+
+        Method::CreateChannel { name, display_name, color, icon, layout } => {
+            if let Err(e) = validate_channel_name(name) {
+                return HandleResult::invalid(&e);
+            }
+            if let Some(Err(e)) = color.as_deref().map(validate_channel_color) {
+                return HandleResult::invalid(&e);
+            }
+            if display_name.trim().is_empty() {
+                return HandleResult::err(ErrorInfo::new(400, "Display name must not be empty"));
+            }
+            if channel_exists(state, name) {
+                return HandleResult::err(ErrorInfo::new(
+                    409,
+                    format!("Channel already exists: {name}"),
+                ));
+            }
+            info!(?name, ?display_name, "Creating channel");
+            HandleResult::ok_with_command(
+                json!({"success": true, "name": name}),
+                Command::CreateChannel {
+                    name: name.clone(),
+                    display_name: display_name.trim().to_string(),
+                    color: color.clone(),
+                    icon: icon.clone(),
+                    layout: *layout,
+                },
+            )
+        }
+
+        Method::RenameChannel { name, display_name } => {
+            if !channel_exists(state, name) {
+                return HandleResult::channel_not_found(name);
+            }
+            if display_name.trim().is_empty() {
+                return HandleResult::err(ErrorInfo::new(400, "Display name must not be empty"));
+            }
+            info!(?name, ?display_name, "Renaming channel");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::RenameChannel {
+                    name: name.clone(),
+                    display_name: display_name.trim().to_string(),
+                },
+            )
+        }
+
+        Method::SetChannelColor { name, color } => {
+            if !channel_exists(state, name) {
+                return HandleResult::channel_not_found(name);
+            }
+            if let Some(Err(e)) = color.as_deref().map(validate_channel_color) {
+                return HandleResult::invalid(&e);
+            }
+            debug!(?name, ?color, "Setting channel color");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::SetChannelColor { name: name.clone(), color: color.clone() },
+            )
+        }
+
+        Method::SetChannelDirectOut { name, enabled } => {
+            if !channel_exists(state, name) {
+                return HandleResult::channel_not_found(name);
+            }
+            info!(?name, enabled, "Setting channel direct-out");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::SetChannelDirectOut { name: name.clone(), enabled: *enabled },
+            )
+        }
+
+        Method::SetChannelLayout { name, layout } => {
+            let Some(channel) = state.channels.iter().find(|c| &c.config.name == name) else {
+                return HandleResult::channel_not_found(name);
+            };
+            if layout.is_surround() && channel.effects.is_active() {
+                return HandleResult::err(ErrorInfo::new(
+                    400,
+                    format!("Turn off the effects on {name} before making it surround"),
+                ));
+            }
+            info!(?name, layout = layout.as_str(), "Setting channel layout");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::SetChannelLayout { name: name.clone(), layout: *layout },
+            )
+        }
+
+        Method::DeleteChannel { name } => {
+            let Some(channel) = state.channels.iter().find(|c| &c.config.name == name) else {
+                return HandleResult::channel_not_found(name);
+            };
+            if channel.config.is_system {
+                return HandleResult::err(ErrorInfo::new(
+                    403,
+                    format!("Cannot delete system channel: {name}"),
+                ));
+            }
+            info!(?name, "Deleting channel");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::DeleteChannel { name: name.clone() },
+            )
+        }
+
+        Method::GetMixes => {
+            HandleResult::ok(serde_json::to_value(&state.mixes).unwrap_or(json!([])))
+        }
+
+        Method::CreateMix { name, display_name } => {
+            if let Err(e) = validate_mix_name(name) {
+                return HandleResult::invalid(&e);
+            }
+            if display_name.trim().is_empty() {
+                return HandleResult::err(ErrorInfo::new(400, "Display name must not be empty"));
+            }
+            if mix_exists(state, &MixType::new(name.as_str())) {
+                return HandleResult::err(ErrorInfo::new(
+                    409,
+                    format!("Mix already exists: {name}"),
+                ));
+            }
+            info!(?name, ?display_name, "Creating mix");
+            HandleResult::ok_with_command(
+                json!({"success": true, "name": name}),
+                Command::CreateMix {
+                    name: name.clone(),
+                    display_name: display_name.trim().to_string(),
+                },
             )
         }
 
-        Method::SetMasterVolume { mix, volume } => {
-            let volume = volume.clamp(0.0, 1.0);
-            debug!(?mix, volume, "Setting master volume");
+        Method::DeleteMix { name } => {
+            let Some(mix) = state.mixes.iter().find(|m| &m.name == name) else {
+                return HandleResult::mix_not_found(&MixType::new(name.as_str()));
+            };
+            if mix.is_system {
+                return HandleResult::err(ErrorInfo::new(
+                    403,
+                    format!("Cannot delete system mix: {name}"),
+                ));
+            }
+            info!(?name, "Deleting mix");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::DeleteMix { name: name.clone() },
+            )
+        }
+
+        Method::SetMasterVolume { mix, volume, fade_ms } => {
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
+            let volume = match volume.resolve(state.mixer.master(mix).volume, state.volume_taper) {
+                Ok(volume) => volume,
+                Err(e) => return HandleResult::invalid(&e),
+            };
+            let fade = match fade_duration(*fade_ms) {
+                Ok(fade) => fade,
+                Err(e) => return HandleResult::invalid(&e),
+            };
+            debug!(?mix, volume, ?fade, "Setting master volume");
             HandleResult::ok_with_command(
                 json!({"success": true, "volume": volume}),
-                Command::SetMasterVolume { mix: *mix, volume },
+                Command::SetMasterVolume { mix: mix.clone(), volume, fade },
             )
         }
 
         Method::SetMasterMute { mix, muted } => {
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
             debug!(?mix, muted, "Setting master mute");
             HandleResult::ok_with_command(
                 json!({"success": true, "muted": muted}),
-                Command::SetMasterMute { mix: *mix, muted: *muted },
+                Command::SetMasterMute { mix: mix.clone(), muted: *muted },
             )
         }
 
-        Method::SetAppRoute { app_pattern, channel } => {
+        Method::SetAppRoute {
+            app_pattern,
+            channel,
+            pattern_type,
+            property,
+            direction,
+            exclude,
+            match_ancestors,
+            stream,
+        } => {
+            if stream.is_some() && *direction != RouteDirection::Playback {
+                return HandleResult::err(ErrorInfo::new(
+                    400,
+                    "Stream rules only apply to playback",
+                ));
+            }
+            if stream.as_ref().is_some_and(StreamSelector::is_blank) {
+                return HandleResult::err(ErrorInfo::new(
+                    400,
+                    "Stream selector needs a name or role to match",
+                ));
+            }
+            match direction {
+                _ if *exclude => {}
+                RouteDirection::Playback if !channel_exists(state, channel) => {
+                    return HandleResult::channel_not_found(channel);
+                }
+                RouteDirection::Capture
+                    if channel != CAPTURE_MIC && !mix_exists(state, &MixType::new(channel)) =>
+                {
+                    return HandleResult::mix_not_found(&MixType::new(channel));
+                }
+                _ => {}
+            }
+            info!(
+                ?app_pattern,
+                ?channel,
+                ?pattern_type,
+                ?property,
+                ?direction,
+                exclude,
+                match_ancestors,
+                ?stream,
+                "Setting app route"
+            );
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::SetAppRoute {
+                    app_pattern: app_pattern.clone(),
+                    channel: channel.clone(),
+                    pattern_type: *pattern_type,
+                    property: property.clone(),
+                    direction: *direction,
+                    exclude: *exclude,
+                    match_ancestors: *match_ancestors,
+                    stream: stream.clone(),
+                },
+            )
+        }
+
+        Method::RemoveAppRoute { app_pattern, property, direction, stream } => {
+            info!(?app_pattern, ?property, ?direction, ?stream, "Removing app route");
+            HandleResult::ok_with_command(
+                json!({"success": true}),
+                Command::RemoveAppRoute {
+                    app_pattern: app_pattern.clone(),
+                    property: property.clone(),
+                    direction: *direction,
+                    stream: stream.clone(),
+                },
+            )
+        }
+
+        Method::MoveStream { app_id, channel } => {
             if !channel_exists(state, channel) {
                 return HandleResult::channel_not_found(channel);
             }
-            info!(?app_pattern, ?channel, "Setting app route");
+            if !state.app_routes.iter().any(|a| a.app_id == *app_id) {
+                return HandleResult::err(ErrorInfo::new(404, format!("App not found: {app_id}")));
+            }
+            info!(app_id, ?channel, "Moving stream");
             HandleResult::ok_with_command(
                 json!({"success": true}),
-                Command::SetAppRoute { app_pattern: app_pattern.clone(), channel: channel.clone() },
+                Command::MoveStream { app_id: *app_id, channel: channel.clone() },
             )
         }
 
-        Method::RemoveAppRoute { app_pattern } => {
-            info!(?app_pattern, "Removing app route");
+        Method::SetFallbackChannel { channel } => {
+            if !channel_exists(state, channel) {
+                return HandleResult::channel_not_found(channel);
+            }
+            info!(?channel, "Setting fallback channel");
             HandleResult::ok_with_command(
                 json!({"success": true}),
-                Command::RemoveAppRoute { app_pattern: app_pattern.clone() },
+                Command::SetFallbackChannel { channel: channel.clone() },
             )
         }
 
+        Method::ExplainRoute {
+            app_id,
+            app_name,
+            binary_name,
+            properties,
+            stream_index,
+            direction,
+        } => {
+            let no_properties = HashMap::new();
+            let trace = if let Some(id) = app_id {
+                let Some(app) = state.app_routes.iter().find(|a| a.app_id == *id) else {
+                    return HandleResult::err(ErrorInfo::new(404, format!("App not found: {id}")));
+                };
+                explain_route(
+                    *direction,
+                    &app.app_name,
+                    app.binary_name.as_deref(),
+                    state.app_properties.get(id).unwrap_or(&no_properties),
+                    app.stream_index,
+                    &app.pid.map(ancestors).unwrap_or_default(),
+                    &state.routes,
+                    &state.fallback_channel,
+                )
+            } else if let Some(name) = app_name {
+                explain_route(
+                    *direction,
+                    name,
+                    binary_name.as_deref(),
+                    properties,
+                    *stream_index,
+                    &[],
+                    &state.routes,
+                    &state.fallback_channel,
+                )
+            } else {
+                return HandleResult::err(ErrorInfo::new(400, "Give an app_id or an app_name"));
+            };
+            HandleResult::ok(serde_json::to_value(trace).unwrap_or(json!({})))
+        }
+
+        Method::LintRoutes => {
+            let channels: Vec<&str> =
+                state.channels.iter().map(|c| c.config.name.as_str()).collect();
+            let mixes: Vec<&str> = state.mixes.iter().map(|m| m.name.as_str()).collect();
+            let issues = lint_rules(&state.routes, &channels, &mixes);
+            HandleResult::ok(serde_json::to_value(issues).unwrap_or(json!([])))
+        }
+
         Method::SaveProfile { name } => {
             info!(?name, "Saving profile");
             HandleResult::ok_with_command(
@@ -147,11 +516,15 @@ pub fn handle_request(method: &Method, state: &StateSnapshot) -> HandleResult {
             )
         }
 
-        Method::LoadProfile { name } => {
-            info!(?name, "Loading profile");
+        Method::LoadProfile { name, fade_ms } => {
+            let fade = match fade_duration(*fade_ms) {
+                Ok(fade) => fade,
+                Err(e) => return HandleResult::invalid(&e),
+            };
+            info!(?name, ?fade, "Loading profile");
             HandleResult::ok_with_command(
                 json!({"success": true}),
-                Command::LoadProfile { name: name.clone() },
+                Command::LoadProfile { name: name.clone(), fade },
             )
         }
 
@@ -180,6 +553,81 @@ pub fn handle_request(method: &Method, state: &StateSnapshot) -> HandleResult {
             )
         }
 
+        Method::SetMicMixEnabled { mix, enabled } => {
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
+            debug!(?mix, enabled, "Setting mic mix send");
+            HandleResult::ok_with_command(
+                json!({"success": true, "enabled": enabled}),
+                Command::SetMicMixEnabled { mix: mix.clone(), enabled: *enabled },
+            )
+        }
+
+        Method::SetMicMixVolume { mix, volume } => {
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
+            let volume = match volume.resolve(state.mixer.mic_volume(mix), state.volume_taper) {
+                Ok(volume) => volume,
+                Err(e) => return HandleResult::invalid(&e),
+            };
+            debug!(?mix, volume, "Setting mic mix volume");
+            HandleResult::ok_with_command(
+                json!({"success": true, "volume": volume}),
+                Command::SetMicMixVolume { mix: mix.clone(), volume },
+            )
+        }
+
+        Method::SetDuckingRules { mix, rules } => {
+            if !mix_exists(state, mix) {
+                return HandleResult::mix_not_found(mix);
+            }
+            for rule in rules {
+                if let Err(e) = rule.validate() {
+                    return HandleResult::invalid(&e);
+                }
+                let trigger = match &rule.trigger {
+                    DuckTrigger::Channel(channel) => Some(channel),
+                    DuckTrigger::Mic => None,
+                };
+                if let Some(channel) =
+                    trigger.into_iter().chain(&rule.targets).find(|c| !channel_exists(state, c))
+                {
+                    return HandleResult::channel_not_found(channel);
+                }
+            }
+            info!(?mix, count = rules.len(), "Setting ducking rules");
+            HandleResult::ok_with_command(
+                json!({"success": true, "count": rules.len()}),
+                Command::SetDuckingRules { mix: mix.clone(), rules: rules.clone() },
+            )
+        }
+
+        Method::SetChannelSolo { channel, soloed } => {
+            if !channel_exists(state, channel) {
+                return HandleResult::channel_not_found(channel);
+            }
+            debug!(?channel, soloed, "Setting channel solo");
+            HandleResult::ok_with_command(
+                json!({"success": true, "soloed": soloed}),
+                Command::SetChannelSolo { channel: channel.clone(), soloed: *soloed },
+            )
+        }
+
+        Method::SetSoloMode { mode } => {
+            debug!(?mode, "Setting solo mode");
+            HandleResult::ok_with_command(
+                json!({"success": true, "mode": mode}),
+                Command::SetSoloMode { mode: *mode },
+            )
+        }
+
+        Method::ClearSolo => {
+            debug!("Clearing solo");
+            HandleResult::ok_with_command(json!({"success": true}), Command::ClearSolo)
+        }
+
         Method::GetOutputDevices => {
             debug!("Getting output devices");
             HandleResult::ok(json!({
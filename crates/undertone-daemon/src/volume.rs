@@ -0,0 +1,359 @@
+//! Coalescing volume and mute changes into one update per node per tick.
+//!
+//! Slider drags send many `SetChannelVolume` requests per second. Rather than
+//! applying and broadcasting each one, changes are queued here and flushed on
+//! the volume tick: each node gets its latest value and each channel/mix pair
+//! gets one event.
+//!
+//! Every volume written to `PipeWire` goes through here: queued volumes are
+//! fader positions, turned into gain by the configured taper and multiplied
+//! by the node's ducking gain when flushed. Events carry the volume the user
+//! set.
+//!
+//! Solo is applied here as well: while channels are soloed, monitor sends are
+//! queued with the solo overlay instead of the user's level and mute.
+//!
+//! Changes queued with a fade are handed to the `PipeWire` thread as a fade
+//! rather than a single write; any later change to the node cancels it.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use undertone_core::channel::ChannelState;
+use undertone_core::mixer::{MixConfig, MixSend, MixType, MixerState};
+use undertone_core::solo::SoloState;
+use undertone_core::volume::{VolumeQueue, VolumeTaper, balance_volumes};
+use undertone_ipc::{
+    ChannelMuteChangedData, ChannelPanChangedData, ChannelVolumeChangedData, Event, EventType,
+    MicMixChangedData, SoloChangedData,
+};
+use undertone_pipewire::{GraphManager, PipeWireRuntime, PwError, PwResult};
+
+use crate::config::VolumeConfig;
+use crate::reconcile;
+
+/// Volume and mute changes waiting for the next tick.
+pub struct PendingVolumes {
+    queue: VolumeQueue,
+    taper: VolumeTaper,
+    volume_events: HashMap<(String, MixType), f32>,
+    mute_events: HashMap<(String, MixType), bool>,
+    pan_events: HashMap<(String, MixType), f32>,
+    mic_events: HashMap<MixType, (bool, f32)>,
+    /// Ducking gain per node; nodes not listed are at unity
+    duck_gains: HashMap<String, f32>,
+    solo: SoloState,
+    /// Whether a `SoloChanged` event is due
+    solo_event: bool,
+}
+
+impl PendingVolumes {
+    /// Create an empty set of pending changes.
+    pub fn new(config: &VolumeConfig) -> Self {
+        Self {
+            queue: VolumeQueue::new(config.ramp_step()),
+            taper: config.taper,
+            volume_events: HashMap::new(),
+            mute_events: HashMap::new(),
+            pan_events: HashMap::new(),
+            mic_events: HashMap::new(),
+            duck_gains: HashMap::new(),
+            solo: SoloState::default(),
+            solo_event: false,
+        }
+    }
+
+    /// Queue a channel volume change and its `ChannelVolumeChanged` event.
+    pub fn channel_volume(&mut self, channel: &str, mix: MixType, volume: f32) {
+        self.send_volume(channel, &mix, volume);
+        self.volume_events.insert((channel.to_string(), mix), volume);
+    }
+
+    /// Queue a new ducking gain for a channel's send into a mix whose volume
+    /// is `volume`.
+    pub fn duck(&mut self, channel: &str, mix: &MixType, volume: f32, gain: f32) {
+        let node = mix.channel_vol_node_name(channel);
+        if gain >= 1.0 {
+            self.duck_gains.remove(&node);
+        } else {
+            self.duck_gains.insert(node, gain);
+        }
+        self.send_volume(channel, mix, volume);
+    }
+
+    /// Forget the ducking gains of a deleted channel's sends.
+    pub fn forget_channel(&mut self, channel: &str, mixes: &[MixConfig]) {
+        for mix in mixes.iter().map(MixConfig::id) {
+            self.duck_gains.remove(&mix.channel_vol_node_name(channel));
+        }
+    }
+
+    /// Forget the ducking gains of a deleted mix's channel sends.
+    pub fn forget_mix(&mut self, mix: &MixType, channels: &[ChannelState]) {
+        for ch in channels {
+            self.duck_gains.remove(&ch.config.vol_node_name(mix));
+        }
+    }
+
+    /// Queue a channel mute change and its `ChannelMuteChanged` event,
+    /// fading the send out or in over `fade` if given.
+    pub fn channel_mute(
+        &mut self,
+        channel: &str,
+        mix: MixType,
+        muted: bool,
+        fade: Option<Duration>,
+    ) {
+        self.send_mute(channel, &mix, muted);
+        if let Some(fade) = fade {
+            self.queue.set_fade(&mix.channel_vol_node_name(channel), fade);
+        }
+        self.mute_events.insert((channel.to_string(), mix), muted);
+    }
+
+    /// Queue a channel balance change and its `ChannelPanChanged` event.
+    ///
+    /// `volume` is the send's current volume, which is reapplied split
+    /// across the two sides.
+    pub fn channel_pan(&mut self, channel: &str, mix: MixType, volume: f32, pan: f32) {
+        self.send_volume(channel, &mix, volume);
+        self.queue.set_pan(&mix.channel_vol_node_name(channel), pan);
+        self.pan_events.insert((channel.to_string(), mix), pan);
+    }
+
+    /// Queue a channel's whole send into a mix without emitting events.
+    pub fn channel(&mut self, channel: &str, mix: &MixType, send: MixSend) {
+        self.send_volume(channel, mix, send.volume);
+        self.send_mute(channel, mix, send.muted);
+        self.queue.set_pan(&mix.channel_vol_node_name(channel), send.pan);
+    }
+
+    /// Queue a channel send's volume through the solo overlay.
+    fn send_volume(&mut self, channel: &str, mix: &MixType, volume: f32) {
+        let volume = self.solo.send_volume(channel, mix, volume);
+        self.set_volume(&mix.channel_vol_node_name(channel), volume);
+    }
+
+    /// Queue a channel send's mute state through the solo overlay.
+    fn send_mute(&mut self, channel: &str, mix: &MixType, muted: bool) {
+        let muted = self.solo.send_muted(channel, mix, muted);
+        self.queue.set_mute(&mix.channel_vol_node_name(channel), muted);
+    }
+
+    /// Channels currently soloed.
+    pub fn solo(&self) -> &SoloState {
+        &self.solo
+    }
+
+    /// Replace the solo state, queue every channel's monitor send with it and
+    /// queue a `SoloChanged` event. Does nothing if the state is unchanged.
+    pub fn set_solo(&mut self, solo: SoloState, channels: &[ChannelState]) {
+        if solo == self.solo {
+            return;
+        }
+        self.solo = solo;
+        self.solo_event = true;
+        for ch in channels {
+            self.channel(&ch.config.name, &MixType::MONITOR, ch.send(&MixType::MONITOR));
+        }
+    }
+
+    /// Record the balance of every channel send in the given mixes, as
+    /// applied directly when the graph was (re)built.
+    fn sync_pans(&mut self, channels: &[ChannelState], mixes: &[MixConfig]) {
+        for mix in mixes.iter().map(MixConfig::id) {
+            for ch in channels {
+                self.queue.sync_pan(&ch.config.vol_node_name(&mix), ch.send(&mix).pan);
+            }
+        }
+    }
+
+    /// Push every channel, master and mic send level straight to the graph,
+    /// e.g. after it was (re)built, and record the balances as applied.
+    pub fn restore(
+        &mut self,
+        runtime: &PipeWireRuntime,
+        graph: &GraphManager,
+        channels: &[ChannelState],
+        mixes: &[MixConfig],
+        mixer: &MixerState,
+    ) {
+        reconcile::apply_volumes(runtime, graph, channels, mixes, mixer, self.taper, &self.solo);
+        self.sync_pans(channels, mixes);
+    }
+
+    /// Queue a volume and mute change for a node without emitting events.
+    pub fn node(&mut self, node_name: &str, volume: f32, muted: bool) {
+        self.set_volume(node_name, volume);
+        self.queue.set_mute(node_name, muted);
+    }
+
+    /// Queue a volume for a node.
+    fn set_volume(&mut self, node_name: &str, volume: f32) {
+        self.queue.set_volume(node_name, volume);
+    }
+
+    /// Queue a master volume change, fading to it over `fade` if given.
+    pub fn master_volume(&mut self, mix: MixType, volume: f32, fade: Option<Duration>) {
+        let node = mix.mix_node_name();
+        self.set_volume(&node, volume);
+        if let Some(fade) = fade {
+            self.queue.set_fade(&node, fade);
+        }
+    }
+
+    /// Queue a master mute change.
+    pub fn master_mute(&mut self, mix: MixType, muted: bool) {
+        self.queue.set_mute(&mix.mix_node_name(), muted);
+    }
+
+    /// Queue the mic send into a mix and its `MicMixChanged` event.
+    ///
+    /// A disabled send is muted rather than unlinked, so it can be turned
+    /// back on without touching the graph.
+    pub fn mic(&mut self, mix: MixType, enabled: bool, volume: f32) {
+        self.node(&mix.mic_vol_node_name(), volume, !enabled);
+        self.mic_events.insert(mix, (enabled, volume));
+    }
+
+    /// Queue every master and mic send level in `mixer` for the given mixes.
+    pub fn mixer(&mut self, mixer: &MixerState, mixes: &[MixConfig]) {
+        for mix in mixes.iter().map(MixConfig::id) {
+            let master = mixer.master(&mix);
+            self.node(&mix.mix_node_name(), master.volume, master.muted);
+            self.mic(mix.clone(), mixer.mic_enabled(&mix), mixer.mic_volume(&mix));
+        }
+    }
+
+    /// Fade every change queued so far in over `duration`, e.g. the levels
+    /// of a profile being loaded.
+    pub fn fade_pending(&mut self, duration: Duration) {
+        self.queue.fade_pending(duration);
+    }
+
+    /// Whether there is nothing to flush.
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_idle()
+            && self.volume_events.is_empty()
+            && self.mute_events.is_empty()
+            && self.pan_events.is_empty()
+            && self.mic_events.is_empty()
+            && !self.solo_event
+    }
+
+    /// Apply this tick's changes to `PipeWire` and broadcast the events.
+    pub fn flush(
+        &mut self,
+        runtime: &PipeWireRuntime,
+        graph: &GraphManager,
+        event_tx: &broadcast::Sender<Event>,
+    ) {
+        // Nodes PipeWire refused; their changes are not announced
+        let mut failed = HashSet::new();
+        for update in self.queue.tick() {
+            let Some(node_id) = graph.get_created_node_id(&update.node) else {
+                warn!(node = %update.node, "Volume node not found");
+                continue;
+            };
+
+            let gains = update.volume.map(|volume| {
+                let duck = self.duck_gains.get(&update.node).copied().unwrap_or(1.0);
+                balance_volumes(self.taper.gain(volume) * duck, update.pan)
+            });
+
+            if let (Some(fade), Some(gains)) = (update.fade, gains) {
+                let result = match update.muted {
+                    Some(muted) => runtime.fade_node_mute(node_id, muted, gains, fade),
+                    None => runtime.fade_node_volumes(node_id, gains, fade),
+                };
+                if let Err(e) = result {
+                    warn!(node = %update.node, error = %e, "Failed to start fade");
+                    failed.insert(update.node);
+                } else {
+                    debug!(node = %update.node, ?gains, muted = ?update.muted, ?fade, "Fade started in PipeWire");
+                }
+                continue;
+            }
+
+            if let Some(gains) = gains {
+                if let Err(e) = runtime.set_node_volumes(node_id, gains) {
+                    warn!(node = %update.node, error = %e, "Failed to set volume");
+                    failed.insert(update.node.clone());
+                } else {
+                    debug!(node = %update.node, volume = ?update.volume, ?gains, "Volume applied to PipeWire");
+                }
+            }
+            if let Some(muted) = update.muted {
+                if let Err(e) = runtime.set_node_mute(node_id, muted) {
+                    warn!(node = %update.node, error = %e, "Failed to set mute");
+                    failed.insert(update.node.clone());
+                } else {
+                    debug!(node = %update.node, muted, "Mute applied to PipeWire");
+                }
+            }
+        }
+
+        for ((channel, mix), volume) in self.volume_events.drain() {
+            if failed.contains(&mix.channel_vol_node_name(&channel)) {
+                continue;
+            }
+            let _ = event_tx.send(Event {
+                event: EventType::ChannelVolumeChanged,
+                data: serde_json::to_value(ChannelVolumeChangedData { channel, mix, volume })
+                    .unwrap_or_default(),
+            });
+        }
+        for ((channel, mix), muted) in self.mute_events.drain() {
+            if failed.contains(&mix.channel_vol_node_name(&channel)) {
+                continue;
+            }
+            let _ = event_tx.send(Event {
+                event: EventType::ChannelMuteChanged,
+                data: serde_json::to_value(ChannelMuteChangedData { channel, mix, muted })
+                    .unwrap_or_default(),
+            });
+        }
+        for ((channel, mix), pan) in self.pan_events.drain() {
+            if failed.contains(&mix.channel_vol_node_name(&channel)) {
+                continue;
+            }
+            let _ = event_tx.send(Event {
+                event: EventType::ChannelPanChanged,
+                data: serde_json::to_value(ChannelPanChangedData { channel, mix, pan })
+                    .unwrap_or_default(),
+            });
+        }
+        for (mix, (enabled, volume)) in self.mic_events.drain() {
+            let _ = event_tx.send(Event {
+                event: EventType::MicMixChanged,
+                data: serde_json::to_value(MicMixChangedData { mix, enabled, volume })
+                    .unwrap_or_default(),
+            });
+        }
+        if std::mem::take(&mut self.solo_event) {
+            let _ = event_tx.send(Event {
+                event: EventType::SoloChanged,
+                data: serde_json::to_value(SoloChangedData {
+                    mode: self.solo.mode,
+                    channels: self.solo.channels.iter().cloned().collect(),
+                })
+                .unwrap_or_default(),
+            });
+        }
+    }
+}
+
+/// Check that a node's volume and mute can be set now, so a command that
+/// would fail is refused before it changes any state.
+pub fn check_node(runtime: &PipeWireRuntime, graph: &GraphManager, node: &str) -> PwResult<()> {
+    if !runtime.is_connected() {
+        return Err(PwError::Disconnected);
+    }
+    if graph.get_created_node_id(node).is_none() {
+        return Err(PwError::NodeNotFound(node.to_string()));
+    }
+    Ok(())
+}
@@ -7,13 +7,302 @@ use crate::error::{DbError, DbResult};
 use crate::schema::{DEFAULT_DATA, SCHEMA_V1};
 
 /// Current schema version.
-const CURRENT_VERSION: i32 = 2;
+const CURRENT_VERSION: i32 = 14;
 
 /// Migration v2: Add `mixer_state` column to profiles.
 const SCHEMA_V2: &str = r"
 ALTER TABLE profiles ADD COLUMN mixer_state TEXT;
 ";
 
+/// Migration v3: Mixes become data.
+///
+/// Adds the `mixes` table with the built-in stream and monitor mixes and
+/// moves per-mix channel levels from fixed columns into send tables.
+const SCHEMA_V3: &str = r"
+CREATE TABLE IF NOT EXISTS mixes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    display_name TEXT NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT OR IGNORE INTO mixes (name, display_name, sort_order, is_system) VALUES
+    ('stream', 'Stream', 0, TRUE),
+    ('monitor', 'Monitor', 1, TRUE);
+
+-- Channel levels per mix (current state)
+CREATE TABLE IF NOT EXISTS channel_sends (
+    channel_id INTEGER NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    mix_id INTEGER NOT NULL REFERENCES mixes(id) ON DELETE CASCADE,
+    volume REAL NOT NULL DEFAULT 1.0,
+    muted BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (channel_id, mix_id)
+);
+
+INSERT INTO channel_sends (channel_id, mix_id, volume, muted)
+    SELECT cs.channel_id, m.id, cs.stream_volume, cs.stream_muted
+    FROM channel_state cs, mixes m WHERE m.name = 'stream';
+INSERT INTO channel_sends (channel_id, mix_id, volume, muted)
+    SELECT cs.channel_id, m.id, cs.monitor_volume, cs.monitor_muted
+    FROM channel_state cs, mixes m WHERE m.name = 'monitor';
+
+-- Profile channel levels per mix
+CREATE TABLE IF NOT EXISTS profile_channel_sends (
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    channel_id INTEGER NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    mix_id INTEGER NOT NULL REFERENCES mixes(id) ON DELETE CASCADE,
+    volume REAL NOT NULL,
+    muted BOOLEAN NOT NULL,
+    PRIMARY KEY (profile_id, channel_id, mix_id)
+);
+
+INSERT INTO profile_channel_sends (profile_id, channel_id, mix_id, volume, muted)
+    SELECT pc.profile_id, pc.channel_id, m.id, pc.stream_volume, pc.stream_muted
+    FROM profile_channels pc, mixes m WHERE m.name = 'stream';
+INSERT INTO profile_channel_sends (profile_id, channel_id, mix_id, volume, muted)
+    SELECT pc.profile_id, pc.channel_id, m.id, pc.monitor_volume, pc.monitor_muted
+    FROM profile_channels pc, mixes m WHERE m.name = 'monitor';
+
+DROP TABLE channel_state;
+DROP TABLE profile_channels;
+";
+
+/// Migration v4: Per-channel effects settings, stored as JSON.
+const SCHEMA_V4: &str = r"
+CREATE TABLE IF NOT EXISTS channel_effects (
+    channel_id INTEGER PRIMARY KEY REFERENCES channels(id) ON DELETE CASCADE,
+    effects TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS profile_channel_effects (
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    channel_id INTEGER NOT NULL REFERENCES channels(id) ON DELETE CASCADE,
+    effects TEXT NOT NULL,
+    PRIMARY KEY (profile_id, channel_id)
+);
+";
+
+/// Migration v5: Per-send balance.
+const SCHEMA_V5: &str = r"
+ALTER TABLE channel_sends ADD COLUMN pan REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE profile_channel_sends ADD COLUMN pan REAL NOT NULL DEFAULT 0.0;
+";
+
+/// Migration v6: Per-channel direct-out recording sources.
+const SCHEMA_V6: &str = r"
+ALTER TABLE channels ADD COLUMN direct_out BOOLEAN NOT NULL DEFAULT FALSE;
+";
+
+/// Migration v7: Per-channel speaker layout.
+const SCHEMA_V7: &str = r"
+ALTER TABLE channels ADD COLUMN layout TEXT NOT NULL DEFAULT 'stereo';
+";
+
+/// Migration v8: Seeded routing rules ignore case.
+///
+/// Packagers disagree on capitalisation ("Discord" vs "discord"), so the
+/// rules seeded by `DEFAULT_DATA` switch to their case-insensitive types.
+/// Rules the user added are left alone.
+const SCHEMA_V8: &str = r"
+UPDATE app_routes SET pattern_type = 'prefix_ignore_case'
+    WHERE pattern_type = 'prefix' AND pattern IN ('discord', 'zoom', 'teams', 'chromium', 'chrome');
+UPDATE app_routes SET pattern_type = 'exact_ignore_case'
+    WHERE pattern_type = 'exact' AND pattern IN ('spotify', 'rhythmbox', 'firefox', 'steam');
+
+UPDATE profile_routes SET pattern_type = 'prefix_ignore_case'
+    WHERE pattern_type = 'prefix' AND pattern IN ('discord', 'zoom', 'teams', 'chromium', 'chrome');
+UPDATE profile_routes SET pattern_type = 'exact_ignore_case'
+    WHERE pattern_type = 'exact' AND pattern IN ('spotify', 'rhythmbox', 'firefox', 'steam');
+";
+
+/// Migration v9: Routing rules can match a node property.
+const SCHEMA_V9: &str = r"
+ALTER TABLE app_routes ADD COLUMN property TEXT;
+ALTER TABLE profile_routes ADD COLUMN property TEXT;
+";
+
+/// Migration v10: Routing rules for recording streams.
+///
+/// Capture rules name a mix or the mic rather than a channel, so the route
+/// tables are rebuilt with a nullable `channel_id`, a `source` for capture
+/// targets and a `direction`. An app can have one rule per direction.
+const SCHEMA_V10: &str = r"
+CREATE TABLE app_routes_v10 (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    pattern_type TEXT NOT NULL DEFAULT 'exact',
+    channel_id INTEGER REFERENCES channels(id),
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    property TEXT,
+    direction TEXT NOT NULL DEFAULT 'playback',
+    source TEXT,
+    UNIQUE (pattern, direction)
+);
+
+INSERT INTO app_routes_v10 (id, pattern, pattern_type, channel_id, priority, created_at, property)
+    SELECT id, pattern, pattern_type, channel_id, priority, created_at, property FROM app_routes;
+DROP TABLE app_routes;
+ALTER TABLE app_routes_v10 RENAME TO app_routes;
+CREATE INDEX IF NOT EXISTS idx_app_routes_pattern ON app_routes(pattern);
+
+CREATE TABLE profile_routes_v10 (
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    pattern TEXT NOT NULL,
+    pattern_type TEXT NOT NULL,
+    channel_id INTEGER REFERENCES channels(id),
+    priority INTEGER NOT NULL,
+    property TEXT,
+    direction TEXT NOT NULL DEFAULT 'playback',
+    source TEXT,
+    PRIMARY KEY (profile_id, pattern, direction)
+);
+
+INSERT INTO profile_routes_v10 (profile_id, pattern, pattern_type, channel_id, priority, property)
+    SELECT profile_id, pattern, pattern_type, channel_id, priority, property FROM profile_routes;
+DROP TABLE profile_routes;
+ALTER TABLE profile_routes_v10 RENAME TO profile_routes;
+";
+
+/// Migration v11: Exclusion rules and daemon-wide settings.
+///
+/// Exclusion rules leave matching streams alone, so they have neither a
+/// channel nor a source. Settings hold values such as the fallback channel
+/// by key.
+const SCHEMA_V11: &str = r"
+ALTER TABLE app_routes ADD COLUMN exclude BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE profile_routes ADD COLUMN exclude BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Migration v12: Rules can match an app's parent processes.
+///
+/// The seeded Steam rule opts in, so games Steam launches through Proton
+/// or Wine land on Game.
+const SCHEMA_V12: &str = r"
+ALTER TABLE app_routes ADD COLUMN match_ancestors BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE profile_routes ADD COLUMN match_ancestors BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE app_routes SET match_ancestors = TRUE
+    WHERE pattern = 'steam' AND direction = 'playback';
+";
+
+/// Migration v13: Rules for individual streams of an app.
+///
+/// An app can have a rule for some of its streams besides its app-wide
+/// rule, so the route tables are rebuilt with a `stream` selector key
+/// (empty for app-wide rules) in their unique keys.
+const SCHEMA_V13: &str = r"
+CREATE TABLE app_routes_v13 (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    pattern_type TEXT NOT NULL DEFAULT 'exact',
+    channel_id INTEGER REFERENCES channels(id),
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    property TEXT,
+    direction TEXT NOT NULL DEFAULT 'playback',
+    source TEXT,
+    exclude BOOLEAN NOT NULL DEFAULT FALSE,
+    match_ancestors BOOLEAN NOT NULL DEFAULT FALSE,
+    stream TEXT NOT NULL DEFAULT '',
+    UNIQUE (pattern, direction, stream)
+);
+
+INSERT INTO app_routes_v13 (id, pattern, pattern_type, channel_id, priority, created_at,
+                            property, direction, source, exclude, match_ancestors)
+    SELECT id, pattern, pattern_type, channel_id, priority, created_at,
+           property, direction, source, exclude, match_ancestors FROM app_routes;
+DROP TABLE app_routes;
+ALTER TABLE app_routes_v13 RENAME TO app_routes;
+CREATE INDEX IF NOT EXISTS idx_app_routes_pattern ON app_routes(pattern);
+
+CREATE TABLE profile_routes_v13 (
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    pattern TEXT NOT NULL,
+    pattern_type TEXT NOT NULL,
+    channel_id INTEGER REFERENCES channels(id),
+    priority INTEGER NOT NULL,
+    property TEXT,
+    direction TEXT NOT NULL DEFAULT 'playback',
+    source TEXT,
+    exclude BOOLEAN NOT NULL DEFAULT FALSE,
+    match_ancestors BOOLEAN NOT NULL DEFAULT FALSE,
+    stream TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY (profile_id, pattern, direction, stream)
+);
+
+INSERT INTO profile_routes_v13 (profile_id, pattern, pattern_type, channel_id, priority,
+                                property, direction, source, exclude, match_ancestors)
+    SELECT profile_id, pattern, pattern_type, channel_id, priority,
+           property, direction, source, exclude, match_ancestors FROM profile_routes;
+DROP TABLE profile_routes;
+ALTER TABLE profile_routes_v13 RENAME TO profile_routes;
+";
+
+/// Migration v14: Property rules are keyed by their property.
+///
+/// A rule matching a node property no longer replaces a name rule with the
+/// same pattern, so `property` joins the unique keys, stored empty (rather
+/// than NULL) for rules matching the app name as `stream` is.
+const SCHEMA_V14: &str = r"
+CREATE TABLE app_routes_v14 (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    pattern_type TEXT NOT NULL DEFAULT 'exact',
+    channel_id INTEGER REFERENCES channels(id),
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    property TEXT NOT NULL DEFAULT '',
+    direction TEXT NOT NULL DEFAULT 'playback',
+    source TEXT,
+    exclude BOOLEAN NOT NULL DEFAULT FALSE,
+    match_ancestors BOOLEAN NOT NULL DEFAULT FALSE,
+    stream TEXT NOT NULL DEFAULT '',
+    UNIQUE (pattern, property, direction, stream)
+);
+
+INSERT INTO app_routes_v14 (id, pattern, pattern_type, channel_id, priority, created_at,
+                            property, direction, source, exclude, match_ancestors, stream)
+    SELECT id, pattern, pattern_type, channel_id, priority, created_at,
+           COALESCE(property, ''), direction, source, exclude, match_ancestors, stream
+    FROM app_routes;
+DROP TABLE app_routes;
+ALTER TABLE app_routes_v14 RENAME TO app_routes;
+CREATE INDEX IF NOT EXISTS idx_app_routes_pattern ON app_routes(pattern);
+
+CREATE TABLE profile_routes_v14 (
+    profile_id INTEGER NOT NULL REFERENCES profiles(id) ON DELETE CASCADE,
+    pattern TEXT NOT NULL,
+    pattern_type TEXT NOT NULL,
+    channel_id INTEGER REFERENCES channels(id),
+    priority INTEGER NOT NULL,
+    property TEXT NOT NULL DEFAULT '',
+    direction TEXT NOT NULL DEFAULT 'playback',
+    source TEXT,
+    exclude BOOLEAN NOT NULL DEFAULT FALSE,
+    match_ancestors BOOLEAN NOT NULL DEFAULT FALSE,
+    stream TEXT NOT NULL DEFAULT '',
+    PRIMARY KEY (profile_id, pattern, property, direction, stream)
+);
+
+INSERT INTO profile_routes_v14 (profile_id, pattern, pattern_type, channel_id, priority,
+                                property, direction, source, exclude, match_ancestors, stream)
+    SELECT profile_id, pattern, pattern_type, channel_id, priority,
+           COALESCE(property, ''), direction, source, exclude, match_ancestors, stream
+    FROM profile_routes;
+DROP TABLE profile_routes;
+ALTER TABLE profile_routes_v14 RENAME TO profile_routes;
+";
+
 /// Run all pending migrations.
 pub fn run(conn: &mut Connection) -> DbResult<()> {
     let current = get_version(conn)?;
@@ -66,6 +355,54 @@ fn apply_migration(conn: &Connection, version: i32) -> DbResult<()> {
             conn.execute_batch(SCHEMA_V2)?;
             conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
         }
+        3 => {
+            conn.execute_batch(SCHEMA_V3)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        4 => {
+            conn.execute_batch(SCHEMA_V4)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        5 => {
+            conn.execute_batch(SCHEMA_V5)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        6 => {
+            conn.execute_batch(SCHEMA_V6)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        7 => {
+            conn.execute_batch(SCHEMA_V7)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        8 => {
+            conn.execute_batch(SCHEMA_V8)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        9 => {
+            conn.execute_batch(SCHEMA_V9)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        10 => {
+            conn.execute_batch(SCHEMA_V10)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        11 => {
+            conn.execute_batch(SCHEMA_V11)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        12 => {
+            conn.execute_batch(SCHEMA_V12)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        13 => {
+            conn.execute_batch(SCHEMA_V13)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
+        14 => {
+            conn.execute_batch(SCHEMA_V14)?;
+            conn.execute("INSERT INTO schema_version (version) VALUES (?)", [version])?;
+        }
         _ => {
             return Err(DbError::MigrationFailed(format!("Unknown migration version: {version}")));
         }
@@ -98,5 +435,125 @@ mod tests {
             conn.query_row("SELECT mixer_state FROM profiles WHERE name = 'Default'", [], |row| {
                 row.get(0)
             });
+
+        // Verify built-in mixes and per-mix channel sends exist (v3 migration)
+        let mixes: i32 =
+            conn.query_row("SELECT COUNT(*) FROM mixes", [], |row| row.get(0)).unwrap();
+        assert_eq!(mixes, 2);
+        let sends: i32 =
+            conn.query_row("SELECT COUNT(*) FROM channel_sends", [], |row| row.get(0)).unwrap();
+        assert_eq!(sends, 10);
+
+        // Verify effects tables exist (v4 migration)
+        let effects: i32 =
+            conn.query_row("SELECT COUNT(*) FROM channel_effects", [], |row| row.get(0)).unwrap();
+        assert_eq!(effects, 0);
+
+        // Verify sends are centered (v5 migration)
+        let off_center: i32 = conn
+            .query_row("SELECT COUNT(*) FROM channel_sends WHERE pan != 0.0", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(off_center, 0);
+
+        // Verify direct-outs start off (v6 migration)
+        let direct: i32 = conn
+            .query_row("SELECT COUNT(*) FROM channels WHERE direct_out", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(direct, 0);
+
+        // Verify channels start out stereo (v7 migration)
+        let surround: i32 = conn
+            .query_row("SELECT COUNT(*) FROM channels WHERE layout != 'stereo'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(surround, 0);
+
+        // Verify seeded routes ignore case (v8 migration)
+        let case_sensitive: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM app_routes WHERE pattern_type NOT LIKE '%_ignore_case'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(case_sensitive, 0);
+
+        // Verify seeded routes match app names (v9 migration)
+        let property_rules: i32 = conn
+            .query_row("SELECT COUNT(*) FROM app_routes WHERE property != ''", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(property_rules, 0);
+
+        // Verify existing routes kept, as playback rules (v10 migration)
+        let playback: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM app_routes WHERE direction = 'playback' AND source IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(playback, 9);
+
+        // Verify only the Steam rule follows parent processes (v12 migration)
+        let ancestor_rules: String = conn
+            .query_row("SELECT pattern FROM app_routes WHERE match_ancestors", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(ancestor_rules, "steam");
+
+        // Verify existing routes kept, as app-wide rules (v13 migration)
+        let app_wide: i32 = conn
+            .query_row("SELECT COUNT(*) FROM app_routes WHERE stream = ''", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(app_wide, 9);
+
+        // Verify name rules keep an empty property key (v14 migration)
+        let name_rules: i32 = conn
+            .query_row("SELECT COUNT(*) FROM app_routes WHERE property = ''", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name_rules, 9);
+    }
+
+    #[test]
+    fn test_v3_migrates_channel_levels() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+
+        for version in 1..=2 {
+            apply_migration(&conn, version).unwrap();
+        }
+        conn.execute(
+            r"UPDATE channel_state SET stream_volume = 0.25, monitor_muted = TRUE
+              WHERE channel_id = (SELECT id FROM channels WHERE name = 'music')",
+            [],
+        )
+        .unwrap();
+
+        run(&mut conn).expect("Migrations failed");
+
+        let (volume, muted): (f64, bool) = conn
+            .query_row(
+                r"SELECT s.volume, s.muted FROM channel_sends s
+                  JOIN channels c ON s.channel_id = c.id
+                  JOIN mixes m ON s.mix_id = m.id
+                  WHERE c.name = 'music' AND m.name = 'stream'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!((volume - 0.25).abs() < 0.01);
+        assert!(!muted);
+
+        let monitor_muted: bool = conn
+            .query_row(
+                r"SELECT s.muted FROM channel_sends s
+                  JOIN channels c ON s.channel_id = c.id
+                  JOIN mixes m ON s.mix_id = m.id
+                  WHERE c.name = 'music' AND m.name = 'monitor'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(monitor_muted);
     }
 }
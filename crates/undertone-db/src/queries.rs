@@ -1,92 +1,337 @@
 //! Database query functions.
 
-use rusqlite::params;
+use std::collections::BTreeMap;
+
+use rusqlite::{OptionalExtension, params};
 use undertone_core::{
     channel::{ChannelConfig, ChannelState},
-    mixer::MixerState,
+    effects::ChannelEffects,
+    layout::ChannelLayout,
+    mixer::{MixConfig, MixSend, MixType, MixerState},
     profile::{Profile, ProfileChannel, ProfileSummary},
-    routing::{PatternType, RouteRule},
+    routing::{FALLBACK_CHANNEL, PatternType, RouteDirection, RouteRule, StreamSelector},
 };
 
 use crate::{Database, DbResult};
 
+/// Settings key holding the name of the fallback channel.
+const FALLBACK_CHANNEL_KEY: &str = "fallback_channel";
+
 impl Database {
     /// Load all channels with their current state.
     pub fn load_channels(&self) -> DbResult<Vec<ChannelState>> {
         let mut stmt = self.conn.prepare(
             r"SELECT c.id, c.name, c.display_name, c.icon, c.color, c.sort_order, c.is_system,
-                     cs.stream_volume, cs.stream_muted, cs.monitor_volume, cs.monitor_muted
+                     c.direct_out, c.layout
               FROM channels c
-              LEFT JOIN channel_state cs ON c.id = cs.channel_id
               ORDER BY c.sort_order",
         )?;
 
-        let channels = stmt
+        let mut channels = stmt
             .query_map([], |row| {
-                Ok(ChannelState {
-                    config: ChannelConfig {
-                        name: row.get(1)?,
-                        display_name: row.get(2)?,
-                        icon: row.get(3)?,
-                        color: row.get(4)?,
-                        sort_order: row.get(5)?,
-                        is_system: row.get(6)?,
-                    },
-                    stream_volume: row.get::<_, Option<f64>>(7)?.unwrap_or(1.0) as f32,
-                    stream_muted: row.get::<_, Option<bool>>(8)?.unwrap_or(false),
-                    monitor_volume: row.get::<_, Option<f64>>(9)?.unwrap_or(1.0) as f32,
-                    monitor_muted: row.get::<_, Option<bool>>(10)?.unwrap_or(false),
-                    level_left: 0.0,
-                    level_right: 0.0,
-                    node_id: None,
-                })
+                Ok(ChannelState::new(ChannelConfig {
+                    name: row.get(1)?,
+                    display_name: row.get(2)?,
+                    icon: row.get(3)?,
+                    color: row.get(4)?,
+                    sort_order: row.get(5)?,
+                    is_system: row.get(6)?,
+                    direct_out: row.get(7)?,
+                    layout: ChannelLayout::from_name(&row.get::<_, String>(8)?).unwrap_or_default(),
+                }))
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
+        // Fill in per-mix levels; mixes without a row keep the default send
+        let mut stmt = self.conn.prepare(
+            r"SELECT c.name, m.name, s.volume, s.muted, s.pan
+              FROM channel_sends s
+              JOIN channels c ON s.channel_id = c.id
+              JOIN mixes m ON s.mix_id = m.id",
+        )?;
+
+        let sends = stmt
+            .query_map([], |row| {
+                let send = MixSend {
+                    volume: row.get::<_, f64>(2)? as f32,
+                    muted: row.get(3)?,
+                    pan: row.get::<_, f64>(4)? as f32,
+                };
+                Ok((row.get::<_, String>(0)?, MixType::new(row.get::<_, String>(1)?), send))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (channel, mix, send) in sends {
+            if let Some(state) = channels.iter_mut().find(|c| c.config.name == channel) {
+                state.sends.insert(mix, send);
+            }
+        }
+
+        // Fill in effects; channels without a row (or with unreadable JSON)
+        // keep the default, which has every stage off
+        let mut stmt = self.conn.prepare(
+            r"SELECT c.name, e.effects
+              FROM channel_effects e
+              JOIN channels c ON e.channel_id = c.id",
+        )?;
+
+        let effects = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (channel, json) in effects {
+            if let Some(state) = channels.iter_mut().find(|c| c.config.name == channel)
+                && let Ok(effects) = serde_json::from_str(&json)
+            {
+                state.effects = effects;
+            }
+        }
+
         Ok(channels)
     }
 
+    /// Save a channel's effects settings.
+    pub fn save_channel_effects(
+        &self,
+        channel_name: &str,
+        effects: &ChannelEffects,
+    ) -> DbResult<()> {
+        let json = serde_json::to_string(effects).map_err(|e| {
+            crate::error::DbError::Serialization(format!("Failed to serialize effects: {e}"))
+        })?;
+
+        self.conn.execute(
+            r"INSERT INTO channel_effects (channel_id, effects)
+              SELECT id, ? FROM channels WHERE name = ?
+              ON CONFLICT(channel_id) DO UPDATE SET
+                effects = excluded.effects,
+                updated_at = datetime('now')",
+            params![json, channel_name],
+        )?;
+        Ok(())
+    }
+
     /// Save channel state.
+    ///
+    /// Sends into mixes that no longer exist are ignored.
     pub fn save_channel_state(&self, channel_name: &str, state: &ChannelState) -> DbResult<()> {
+        for (mix, send) in &state.sends {
+            self.conn.execute(
+                r"INSERT INTO channel_sends (channel_id, mix_id, volume, muted, pan)
+                  SELECT c.id, m.id, ?, ?, ? FROM channels c, mixes m
+                  WHERE c.name = ? AND m.name = ?
+                  ON CONFLICT(channel_id, mix_id) DO UPDATE SET
+                    volume = excluded.volume,
+                    muted = excluded.muted,
+                    pan = excluded.pan,
+                    updated_at = datetime('now')",
+                params![
+                    f64::from(send.volume),
+                    send.muted,
+                    f64::from(send.pan),
+                    channel_name,
+                    mix.as_str()
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Load all mixes.
+    pub fn load_mixes(&self) -> DbResult<Vec<MixConfig>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, display_name, sort_order, is_system FROM mixes ORDER BY sort_order",
+        )?;
+
+        let mixes = stmt
+            .query_map([], |row| {
+                Ok(MixConfig {
+                    name: row.get(0)?,
+                    display_name: row.get(1)?,
+                    sort_order: row.get(2)?,
+                    is_system: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(mixes)
+    }
+
+    /// Create a user-defined mix.
+    ///
+    /// The mix is appended after all existing mixes. Channels have no sends
+    /// stored for it until their levels in it are changed.
+    pub fn create_mix(&self, config: &MixConfig) -> DbResult<MixConfig> {
+        let sort_order: i32 = self.conn.query_row(
+            "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM mixes",
+            [],
+            |row| row.get(0),
+        )?;
+
         self.conn.execute(
-            r"UPDATE channel_state SET
-                stream_volume = ?,
-                stream_muted = ?,
-                monitor_volume = ?,
-                monitor_muted = ?,
-                updated_at = datetime('now')
-              WHERE channel_id = (SELECT id FROM channels WHERE name = ?)",
+            r"INSERT INTO mixes (name, display_name, sort_order, is_system)
+              VALUES (?, ?, ?, FALSE)",
+            params![config.name, config.display_name, sort_order],
+        )?;
+
+        Ok(MixConfig { sort_order, is_system: false, ..config.clone() })
+    }
+
+    /// Delete a user-defined mix along with every channel and profile send
+    /// into it.
+    ///
+    /// System mixes cannot be deleted; returns `false` if the mix is a system
+    /// mix or does not exist.
+    pub fn delete_mix(&self, name: &str) -> DbResult<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM mixes WHERE name = ? AND is_system = FALSE", params![name])?;
+        Ok(deleted > 0)
+    }
+
+    /// Create a user-defined channel with default state.
+    ///
+    /// The channel is appended after all existing channels.
+    pub fn create_channel(&self, config: &ChannelConfig) -> DbResult<ChannelConfig> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let sort_order: i32 =
+            tx.query_row("SELECT COALESCE(MAX(sort_order) + 1, 0) FROM channels", [], |row| {
+                row.get(0)
+            })?;
+
+        tx.execute(
+            r"INSERT INTO channels
+                (name, display_name, icon, color, sort_order, is_system, direct_out, layout)
+              VALUES (?, ?, ?, ?, ?, FALSE, ?, ?)",
             params![
-                f64::from(state.stream_volume),
-                state.stream_muted,
-                f64::from(state.monitor_volume),
-                state.monitor_muted,
-                channel_name,
+                config.name,
+                config.display_name,
+                config.icon,
+                config.color,
+                sort_order,
+                config.direct_out,
+                config.layout.as_str()
             ],
         )?;
-        Ok(())
+
+        tx.execute(
+            r"INSERT INTO channel_sends (channel_id, mix_id)
+              SELECT ?, id FROM mixes",
+            params![tx.last_insert_rowid()],
+        )?;
+
+        tx.commit()?;
+
+        Ok(ChannelConfig { sort_order, is_system: false, ..config.clone() })
+    }
+
+    /// Change a channel's display name.
+    ///
+    /// Returns `false` if the channel does not exist.
+    pub fn rename_channel(&self, name: &str, display_name: &str) -> DbResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE channels SET display_name = ? WHERE name = ?",
+            params![display_name, name],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Change a channel's color.
+    ///
+    /// Returns `false` if the channel does not exist.
+    pub fn set_channel_color(&self, name: &str, color: Option<&str>) -> DbResult<bool> {
+        let updated = self
+            .conn
+            .execute("UPDATE channels SET color = ? WHERE name = ?", params![color, name])?;
+        Ok(updated > 0)
+    }
+
+    /// Turn a channel's direct-out recording source on or off.
+    ///
+    /// Returns `false` if the channel does not exist.
+    pub fn set_channel_direct_out(&self, name: &str, enabled: bool) -> DbResult<bool> {
+        let updated = self
+            .conn
+            .execute("UPDATE channels SET direct_out = ? WHERE name = ?", params![enabled, name])?;
+        Ok(updated > 0)
+    }
+
+    /// Change a channel's speaker layout.
+    ///
+    /// Returns `false` if the channel does not exist.
+    pub fn set_channel_layout(&self, name: &str, layout: ChannelLayout) -> DbResult<bool> {
+        let updated = self.conn.execute(
+            "UPDATE channels SET layout = ? WHERE name = ?",
+            params![layout.as_str(), name],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Delete a user-defined channel.
+    ///
+    /// Routing rules and profile entries that reference the channel are
+    /// removed with it. System channels cannot be deleted; returns `false`
+    /// if the channel is a system channel or does not exist.
+    pub fn delete_channel(&self, name: &str) -> DbResult<bool> {
+        let channel_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM channels WHERE name = ? AND is_system = FALSE",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        let Some(channel_id) = channel_id else {
+            return Ok(false);
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM app_routes WHERE channel_id = ?", params![channel_id])?;
+        tx.execute("DELETE FROM profile_channel_sends WHERE channel_id = ?", params![channel_id])?;
+        tx.execute(
+            "DELETE FROM profile_channel_effects WHERE channel_id = ?",
+            params![channel_id],
+        )?;
+        tx.execute("DELETE FROM profile_routes WHERE channel_id = ?", params![channel_id])?;
+        tx.execute(
+            "DELETE FROM settings WHERE key = ? AND value = ?",
+            params![FALLBACK_CHANNEL_KEY, name],
+        )?;
+        tx.execute("DELETE FROM channels WHERE id = ?", params![channel_id])?;
+        tx.commit()?;
+
+        Ok(true)
     }
 
     /// Load all routing rules.
     pub fn load_routes(&self) -> DbResult<Vec<RouteRule>> {
         let mut stmt = self.conn.prepare(
-            r"SELECT ar.pattern, ar.pattern_type, c.name, ar.priority
+            r"SELECT ar.pattern, ar.pattern_type, COALESCE(c.name, ar.source, ''), ar.priority,
+                     ar.property, ar.direction, ar.exclude, ar.match_ancestors, ar.stream
               FROM app_routes ar
-              JOIN channels c ON ar.channel_id = c.id
+              LEFT JOIN channels c ON ar.channel_id = c.id
+              WHERE c.id IS NOT NULL OR ar.source IS NOT NULL OR ar.exclude
               ORDER BY ar.priority DESC",
         )?;
 
         let routes = stmt
             .query_map([], |row| {
                 let pattern_type_str: String = row.get(1)?;
-                let pattern_type = match pattern_type_str.as_str() {
-                    "exact" => PatternType::Exact,
-                    "prefix" => PatternType::Prefix,
-                    "regex" => PatternType::Regex,
-                    _ => PatternType::Exact,
-                };
-
-                Ok(RouteRule::new(row.get(0)?, pattern_type, row.get(2)?, row.get(3)?))
+                let pattern_type =
+                    PatternType::from_name(&pattern_type_str).unwrap_or(PatternType::Exact);
+
+                let mut rule = RouteRule::new(row.get(0)?, pattern_type, row.get(2)?, row.get(3)?);
+                rule.property = property_from_key(row.get(4)?);
+                let direction: String = row.get(5)?;
+                rule.direction =
+                    RouteDirection::from_name(&direction).unwrap_or(RouteDirection::Playback);
+                rule.exclude = row.get(6)?;
+                rule.match_ancestors = row.get(7)?;
+                let stream: String = row.get(8)?;
+                rule.stream = StreamSelector::from_key(&stream);
+                Ok(rule)
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -94,31 +339,96 @@ impl Database {
     }
 
     /// Add or update a routing rule.
+    ///
+    /// Playback rules reference their channel; capture rules keep the mix
+    /// (or mic) they record from by name.
     pub fn save_route(&self, rule: &RouteRule) -> DbResult<()> {
-        let pattern_type = match rule.pattern_type {
-            PatternType::Exact => "exact",
-            PatternType::Prefix => "prefix",
-            PatternType::Regex => "regex",
-        };
+        let pattern_type = rule.pattern_type.as_str();
+        let (channel, source) = route_target(rule);
 
         self.conn.execute(
-            r"INSERT INTO app_routes (pattern, pattern_type, channel_id, priority)
-              VALUES (?, ?, (SELECT id FROM channels WHERE name = ?), ?)
-              ON CONFLICT(pattern) DO UPDATE SET
+            r"INSERT INTO app_routes
+              (pattern, pattern_type, channel_id, priority, property, direction, source, exclude,
+               match_ancestors, stream)
+              VALUES (?, ?, (SELECT id FROM channels WHERE name = ?), ?, ?, ?, ?, ?, ?, ?)
+              ON CONFLICT(pattern, property, direction, stream) DO UPDATE SET
                 pattern_type = excluded.pattern_type,
                 channel_id = excluded.channel_id,
-                priority = excluded.priority",
-            params![rule.pattern, pattern_type, rule.channel, rule.priority],
+                priority = excluded.priority,
+                source = excluded.source,
+                exclude = excluded.exclude,
+                match_ancestors = excluded.match_ancestors",
+            params![
+                rule.pattern,
+                pattern_type,
+                channel,
+                rule.priority,
+                property_key(rule.property.as_deref()),
+                rule.direction.as_str(),
+                source,
+                rule.exclude,
+                rule.match_ancestors,
+                stream_key(rule.stream.as_ref())
+            ],
         )?;
         Ok(())
     }
 
-    /// Delete a routing rule.
-    pub fn delete_route(&self, pattern: &str) -> DbResult<()> {
-        self.conn.execute("DELETE FROM app_routes WHERE pattern = ?", params![pattern])?;
+    /// Delete a routing rule, or with `stream`, the app's rule for the
+    /// streams it selects. With `property`, the rule matching that node
+    /// property is deleted rather than the one matching the app name.
+    pub fn delete_route(
+        &self,
+        pattern: &str,
+        property: Option<&str>,
+        direction: RouteDirection,
+        stream: Option<&StreamSelector>,
+    ) -> DbResult<()> {
+        self.conn.execute(
+            r"DELETE FROM app_routes
+              WHERE pattern = ? AND property = ? AND direction = ? AND stream = ?",
+            params![pattern, property_key(property), direction.as_str(), stream_key(stream)],
+        )?;
         Ok(())
     }
 
+    /// Load the channel that apps no rule matches are sent to.
+    ///
+    /// Returns [`FALLBACK_CHANNEL`] until another one is chosen.
+    pub fn load_fallback_channel(&self) -> DbResult<String> {
+        let channel: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?",
+                params![FALLBACK_CHANNEL_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(channel.unwrap_or_else(|| FALLBACK_CHANNEL.to_string()))
+    }
+
+    /// Choose the channel that apps no rule matches are sent to.
+    ///
+    /// Returns false if no channel has that name. The setting is cleared
+    /// again when the channel is deleted.
+    pub fn save_fallback_channel(&self, channel: &str) -> DbResult<bool> {
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM channels WHERE name = ?)",
+            params![channel],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Ok(false);
+        }
+
+        self.conn.execute(
+            r"INSERT INTO settings (key, value) VALUES (?, ?)
+              ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![FALLBACK_CHANNEL_KEY, channel],
+        )?;
+        Ok(true)
+    }
+
     /// Log an event to the database.
     pub fn log_event(
         &self,
@@ -180,64 +490,88 @@ impl Database {
         )?;
 
         // Clear existing channel states for this profile
-        self.conn
-            .execute("DELETE FROM profile_channels WHERE profile_id = ?", params![profile_id])?;
+        self.conn.execute(
+            "DELETE FROM profile_channel_sends WHERE profile_id = ?",
+            params![profile_id],
+        )?;
 
-        // Insert channel states
+        // Insert channel sends; unknown channels and mixes are skipped
         for channel in &profile.channels {
-            // Get channel ID
-            let channel_id: Option<i64> = self
-                .conn
-                .query_row("SELECT id FROM channels WHERE name = ?", params![channel.name], |row| {
-                    row.get(0)
-                })
-                .ok();
-
-            if let Some(ch_id) = channel_id {
+            for (mix, send) in &channel.sends {
                 self.conn.execute(
-                    r"INSERT INTO profile_channels
-                      (profile_id, channel_id, stream_volume, stream_muted, monitor_volume, monitor_muted)
-                      VALUES (?, ?, ?, ?, ?, ?)",
+                    r"INSERT INTO profile_channel_sends
+                      (profile_id, channel_id, mix_id, volume, muted, pan)
+                      SELECT ?, c.id, m.id, ?, ?, ? FROM channels c, mixes m
+                      WHERE c.name = ? AND m.name = ?",
                     params![
                         profile_id,
-                        ch_id,
-                        f64::from(channel.stream_volume),
-                        channel.stream_muted,
-                        f64::from(channel.monitor_volume),
-                        channel.monitor_muted,
+                        f64::from(send.volume),
+                        send.muted,
+                        f64::from(send.pan),
+                        channel.name,
+                        mix.as_str(),
                     ],
                 )?;
             }
         }
 
+        // Replace channel effects; channels without effects settings are left out
+        self.conn.execute(
+            "DELETE FROM profile_channel_effects WHERE profile_id = ?",
+            params![profile_id],
+        )?;
+
+        for channel in &profile.channels {
+            let Some(effects) = &channel.effects else {
+                continue;
+            };
+            let json = serde_json::to_string(effects).map_err(|e| {
+                crate::error::DbError::Serialization(format!("Failed to serialize effects: {e}"))
+            })?;
+
+            self.conn.execute(
+                r"INSERT INTO profile_channel_effects (profile_id, channel_id, effects)
+                  SELECT ?, id, ? FROM channels WHERE name = ?",
+                params![profile_id, json, channel.name],
+            )?;
+        }
+
         // Clear existing routes for this profile
         self.conn
             .execute("DELETE FROM profile_routes WHERE profile_id = ?", params![profile_id])?;
 
         // Insert routes
         for route in &profile.routes {
-            let pattern_type = match route.pattern_type {
-                PatternType::Exact => "exact",
-                PatternType::Prefix => "prefix",
-                PatternType::Regex => "regex",
-            };
+            let pattern_type = route.pattern_type.as_str();
+            let (channel, source) = route_target(route);
 
             // Get channel ID
             let channel_id: Option<i64> = self
                 .conn
-                .query_row(
-                    "SELECT id FROM channels WHERE name = ?",
-                    params![route.channel],
-                    |row| row.get(0),
-                )
+                .query_row("SELECT id FROM channels WHERE name = ?", params![channel], |row| {
+                    row.get(0)
+                })
                 .ok();
 
-            if let Some(ch_id) = channel_id {
+            if channel_id.is_some() || source.is_some() || route.exclude {
                 self.conn.execute(
                     r"INSERT INTO profile_routes
-                      (profile_id, pattern, pattern_type, channel_id, priority)
-                      VALUES (?, ?, ?, ?, ?)",
-                    params![profile_id, route.pattern, pattern_type, ch_id, route.priority,],
+                      (profile_id, pattern, pattern_type, channel_id, priority, property,
+                       direction, source, exclude, match_ancestors, stream)
+                      VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        profile_id,
+                        route.pattern,
+                        pattern_type,
+                        channel_id,
+                        route.priority,
+                        property_key(route.property.as_deref()),
+                        route.direction.as_str(),
+                        source,
+                        route.exclude,
+                        route.match_ancestors,
+                        stream_key(route.stream.as_ref())
+                    ],
                 )?;
             }
         }
@@ -263,46 +597,96 @@ impl Database {
         let mixer: MixerState =
             mixer_json.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default();
 
-        // Load channel states
+        // Load channel sends, grouped by channel
         let mut stmt = self.conn.prepare(
-            r"SELECT c.name, pc.stream_volume, pc.stream_muted, pc.monitor_volume, pc.monitor_muted
-              FROM profile_channels pc
-              JOIN channels c ON pc.channel_id = c.id
-              WHERE pc.profile_id = ?",
+            r"SELECT c.name, m.name, ps.volume, ps.muted, ps.pan
+              FROM profile_channel_sends ps
+              JOIN channels c ON ps.channel_id = c.id
+              JOIN mixes m ON ps.mix_id = m.id
+              WHERE ps.profile_id = ?
+              ORDER BY c.sort_order, m.sort_order",
         )?;
 
-        let channels: Vec<ProfileChannel> = stmt
+        let sends = stmt
             .query_map(params![profile_id], |row| {
-                Ok(ProfileChannel {
-                    name: row.get(0)?,
-                    stream_volume: row.get::<_, f64>(1)? as f32,
-                    stream_muted: row.get(2)?,
-                    monitor_volume: row.get::<_, f64>(3)? as f32,
-                    monitor_muted: row.get(4)?,
-                })
+                let send = MixSend {
+                    volume: row.get::<_, f64>(2)? as f32,
+                    muted: row.get(3)?,
+                    pan: row.get::<_, f64>(4)? as f32,
+                };
+                Ok((row.get::<_, String>(0)?, MixType::new(row.get::<_, String>(1)?), send))
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
+        let mut channels: Vec<ProfileChannel> = Vec::new();
+        for (name, mix, send) in sends {
+            match channels.last_mut() {
+                Some(channel) if channel.name == name => {
+                    channel.sends.insert(mix, send);
+                }
+                _ => channels.push(ProfileChannel {
+                    name,
+                    sends: [(mix, send)].into(),
+                    effects: None,
+                }),
+            }
+        }
+
+        // Load channel effects
+        let mut stmt = self.conn.prepare(
+            r"SELECT c.name, pe.effects
+              FROM profile_channel_effects pe
+              JOIN channels c ON pe.channel_id = c.id
+              WHERE pe.profile_id = ?
+              ORDER BY c.sort_order",
+        )?;
+
+        let effects = stmt
+            .query_map(params![profile_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (name, json) in effects {
+            let Ok(effects) = serde_json::from_str(&json) else {
+                continue;
+            };
+            match channels.iter_mut().find(|c| c.name == name) {
+                Some(channel) => channel.effects = Some(effects),
+                None => channels.push(ProfileChannel {
+                    name,
+                    sends: BTreeMap::new(),
+                    effects: Some(effects),
+                }),
+            }
+        }
+
         // Load routes
         let mut stmt = self.conn.prepare(
-            r"SELECT pr.pattern, pr.pattern_type, c.name, pr.priority
+            r"SELECT pr.pattern, pr.pattern_type, COALESCE(c.name, pr.source, ''), pr.priority,
+                     pr.property, pr.direction, pr.exclude, pr.match_ancestors, pr.stream
               FROM profile_routes pr
-              JOIN channels c ON pr.channel_id = c.id
-              WHERE pr.profile_id = ?
+              LEFT JOIN channels c ON pr.channel_id = c.id
+              WHERE pr.profile_id = ? AND (c.id IS NOT NULL OR pr.source IS NOT NULL OR pr.exclude)
               ORDER BY pr.priority DESC",
         )?;
 
         let routes: Vec<RouteRule> = stmt
             .query_map(params![profile_id], |row| {
                 let pattern_type_str: String = row.get(1)?;
-                let pattern_type = match pattern_type_str.as_str() {
-                    "exact" => PatternType::Exact,
-                    "prefix" => PatternType::Prefix,
-                    "regex" => PatternType::Regex,
-                    _ => PatternType::Exact,
-                };
-
-                Ok(RouteRule::new(row.get(0)?, pattern_type, row.get(2)?, row.get(3)?))
+                let pattern_type =
+                    PatternType::from_name(&pattern_type_str).unwrap_or(PatternType::Exact);
+
+                let mut rule = RouteRule::new(row.get(0)?, pattern_type, row.get(2)?, row.get(3)?);
+                rule.property = property_from_key(row.get(4)?);
+                let direction: String = row.get(5)?;
+                rule.direction =
+                    RouteDirection::from_name(&direction).unwrap_or(RouteDirection::Playback);
+                rule.exclude = row.get(6)?;
+                rule.match_ancestors = row.get(7)?;
+                let stream: String = row.get(8)?;
+                rule.stream = StreamSelector::from_key(&stream);
+                Ok(rule)
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -343,6 +727,33 @@ impl Database {
     }
 }
 
+/// Channel a rule feeds (playback) or source it records from (capture);
+/// exclusion rules have neither.
+fn route_target(rule: &RouteRule) -> (Option<&str>, Option<&str>) {
+    if rule.exclude {
+        return (None, None);
+    }
+    match rule.direction {
+        RouteDirection::Playback => (Some(rule.channel.as_str()), None),
+        RouteDirection::Capture => (None, Some(rule.channel.as_str())),
+    }
+}
+
+/// Stored form of a rule's property; empty for rules matching the app name.
+fn property_key(property: Option<&str>) -> &str {
+    property.unwrap_or_default()
+}
+
+/// Read back the form `property_key` stores.
+fn property_from_key(key: String) -> Option<String> {
+    (!key.is_empty()).then_some(key)
+}
+
+/// Stored form of a rule's stream selector; empty for app-wide rules.
+fn stream_key(stream: Option<&StreamSelector>) -> String {
+    stream.map(StreamSelector::to_key).unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -380,10 +791,11 @@ mod tests {
 
         // Modify and save
         let mut modified = music_channel.clone();
-        modified.stream_volume = 0.5;
-        modified.stream_muted = true;
-        modified.monitor_volume = 0.75;
-        modified.monitor_muted = false;
+        modified.set_volume(&MixType::STREAM, 0.5);
+        modified.set_muted(&MixType::STREAM, true);
+        modified.set_volume(&MixType::MONITOR, 0.75);
+        modified.set_muted(&MixType::MONITOR, false);
+        modified.set_pan(&MixType::MONITOR, -0.25);
 
         db.save_channel_state("music", &modified).expect("Failed to save channel state");
 
@@ -391,10 +803,14 @@ mod tests {
         let channels = db.load_channels().expect("Failed to reload channels");
         let music_channel = channels.iter().find(|c| c.config.name == "music").unwrap();
 
-        assert!((music_channel.stream_volume - 0.5).abs() < 0.01);
-        assert!(music_channel.stream_muted);
-        assert!((music_channel.monitor_volume - 0.75).abs() < 0.01);
-        assert!(!music_channel.monitor_muted);
+        let stream = music_channel.send(&MixType::STREAM);
+        let monitor = music_channel.send(&MixType::MONITOR);
+        assert!((stream.volume - 0.5).abs() < 0.01);
+        assert!(stream.muted);
+        assert!((monitor.volume - 0.75).abs() < 0.01);
+        assert!(!monitor.muted);
+        assert!((monitor.pan - -0.25).abs() < 0.01);
+        assert!(stream.pan.abs() < f32::EPSILON);
     }
 
     #[test]
@@ -426,7 +842,8 @@ mod tests {
         assert_eq!(my_route.unwrap().priority, 200);
 
         // Delete the route
-        db.delete_route("my-app").expect("Failed to delete route");
+        db.delete_route("my-app", None, RouteDirection::Playback, None)
+            .expect("Failed to delete route");
 
         // Verify it's gone
         let routes = db.load_routes().expect("Failed to load routes");
@@ -478,10 +895,12 @@ mod tests {
             is_default: false,
             channels: vec![ProfileChannel {
                 name: "music".into(),
-                stream_volume: 0.8,
-                stream_muted: false,
-                monitor_volume: 0.6,
-                monitor_muted: true,
+                sends: [
+                    (MixType::STREAM, MixSend { pan: 0.5, ..MixSend::new(0.8, false) }),
+                    (MixType::MONITOR, MixSend::new(0.6, true)),
+                ]
+                .into(),
+                effects: None,
             }],
             routes: vec![RouteRule::new(
                 "custom-app".into(),
@@ -503,7 +922,34 @@ mod tests {
         assert_eq!(loaded.description, Some("A test profile".into()));
         assert!(!loaded.is_default);
         assert_eq!(loaded.channels.len(), 1);
+        assert_eq!(loaded.channels[0].sends[&MixType::MONITOR], MixSend::new(0.6, true));
+        assert!((loaded.channels[0].sends[&MixType::STREAM].pan - 0.5).abs() < 0.01);
         assert_eq!(loaded.routes.len(), 1);
+        assert!(loaded.channels[0].effects.is_none());
+    }
+
+    #[test]
+    fn test_save_and_load_effects() {
+        let db = test_db();
+
+        let mut effects = ChannelEffects::default();
+        effects.compressor.enabled = true;
+        effects.compressor.ratio = 6.0;
+        db.save_channel_effects("voice", &effects).expect("Failed to save effects");
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        let voice = channels.iter().find(|c| c.config.name == "voice").unwrap();
+        assert_eq!(voice.effects, effects);
+        let music = channels.iter().find(|c| c.config.name == "music").unwrap();
+        assert!(!music.effects.is_active());
+
+        let mut profile = Profile::new("with-effects");
+        profile.channels = vec![ProfileChannel::from(voice)];
+        db.save_profile(&profile).expect("Failed to save profile");
+
+        let loaded = db.load_profile("with-effects").expect("Failed to load profile").unwrap();
+        let voice = loaded.channels.iter().find(|c| c.name == "voice").unwrap();
+        assert_eq!(voice.effects.as_ref(), Some(&effects));
     }
 
     #[test]
@@ -600,4 +1046,312 @@ mod tests {
         assert_eq!(prefix_loaded.pattern_type, PatternType::Prefix);
         assert_eq!(regex_loaded.pattern_type, PatternType::Regex);
     }
+
+    #[test]
+    fn test_route_ignore_case_and_glob_types_persist() {
+        let db = test_db();
+
+        for (index, pattern_type) in PatternType::ALL.into_iter().enumerate() {
+            let rule = RouteRule::new(format!("app-{index}"), pattern_type, "music".into(), 10);
+            db.save_route(&rule).expect("Failed to save route");
+        }
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        for (index, pattern_type) in PatternType::ALL.into_iter().enumerate() {
+            let pattern = format!("app-{index}");
+            let loaded = routes.iter().find(|r| r.pattern == pattern).unwrap();
+            assert_eq!(loaded.pattern_type, pattern_type);
+        }
+
+        // Seeded rules match regardless of packaging
+        let discord = routes.iter().find(|r| r.pattern == "discord").unwrap();
+        assert!(discord.matches("Discord"));
+    }
+
+    #[test]
+    fn test_property_route_persists() {
+        let db = test_db();
+
+        let rule = RouteRule::new("Communication".into(), PatternType::Exact, "voice".into(), 80)
+            .with_property("media.role");
+        db.save_route(&rule).expect("Failed to save route");
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        let loaded = routes.iter().find(|r| r.pattern == "Communication").unwrap();
+        assert_eq!(loaded.property.as_deref(), Some("media.role"));
+        assert_eq!(loaded.channel, "voice");
+
+        // A name rule with the same pattern is a rule of its own
+        db.save_route(&RouteRule::new(
+            "Communication".into(),
+            PatternType::Exact,
+            "system".into(),
+            80,
+        ))
+        .expect("Failed to save route");
+        let routes = db.load_routes().expect("Failed to load routes");
+        let both: Vec<_> = routes.iter().filter(|r| r.pattern == "Communication").collect();
+        assert_eq!(both.len(), 2);
+        assert!(both.iter().any(|r| r.property.is_none() && r.channel == "system"));
+
+        // Deleting one leaves the other
+        db.delete_route("Communication", Some("media.role"), RouteDirection::Playback, None)
+            .expect("Failed to delete route");
+        let routes = db.load_routes().expect("Failed to load routes");
+        let loaded = routes.iter().find(|r| r.pattern == "Communication").unwrap();
+        assert!(loaded.property.is_none());
+
+        let discord = routes.iter().find(|r| r.pattern == "discord").unwrap();
+        assert!(discord.property.is_none());
+    }
+
+    #[test]
+    fn test_capture_route_persists() {
+        let db = test_db();
+
+        // A capture rule sits alongside the playback rule for the same app
+        let rule = RouteRule::new("discord".into(), PatternType::Exact, "stream".into(), 100)
+            .with_direction(RouteDirection::Capture);
+        db.save_route(&rule).expect("Failed to save capture route");
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        let discord: Vec<_> = routes.iter().filter(|r| r.pattern == "discord").collect();
+        assert_eq!(discord.len(), 2);
+        let capture = discord.iter().find(|r| r.direction == RouteDirection::Capture).unwrap();
+        assert_eq!(capture.channel, "stream");
+
+        db.delete_route("discord", None, RouteDirection::Capture, None)
+            .expect("Failed to delete route");
+        let routes = db.load_routes().expect("Failed to load routes");
+        let discord = routes.iter().find(|r| r.pattern == "discord").unwrap();
+        assert_eq!(discord.direction, RouteDirection::Playback);
+        assert_eq!(discord.channel, "voice");
+    }
+
+    #[test]
+    fn test_stream_route_persists() {
+        let db = test_db();
+
+        // A stream rule sits alongside the app-wide rule for the same app
+        let rule = RouteRule::new("discord".into(), PatternType::Exact, "music".into(), 100)
+            .with_stream(StreamSelector::Name("Stage".into()));
+        db.save_route(&rule).expect("Failed to save stream route");
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        let discord: Vec<_> = routes.iter().filter(|r| r.pattern == "discord").collect();
+        assert_eq!(discord.len(), 2);
+        let stream = discord.iter().find(|r| r.stream.is_some()).unwrap();
+        assert_eq!(stream.stream, Some(StreamSelector::Name("Stage".into())));
+        assert_eq!(stream.channel, "music");
+
+        db.delete_route("discord", None, RouteDirection::Playback, rule.stream.as_ref())
+            .expect("Failed to delete route");
+        let routes = db.load_routes().expect("Failed to load routes");
+        let discord = routes.iter().find(|r| r.pattern == "discord").unwrap();
+        assert!(discord.stream.is_none());
+        assert_eq!(discord.channel, "voice");
+    }
+
+    #[test]
+    fn test_exclusion_route_persists() {
+        let db = test_db();
+
+        let rule = RouteRule::exclusion("pavucontrol".into(), PatternType::ExactIgnoreCase, 300);
+        db.save_route(&rule).expect("Failed to save exclusion");
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        let loaded = routes.iter().find(|r| r.pattern == "pavucontrol").unwrap();
+        assert!(loaded.exclude);
+        assert_eq!(loaded.priority, 300);
+        assert!(routes.iter().filter(|r| r.pattern != "pavucontrol").all(|r| !r.exclude));
+    }
+
+    #[test]
+    fn test_ancestor_route_persists() {
+        let db = test_db();
+
+        let rule = RouteRule::new("lutris".into(), PatternType::Exact, "game".into(), 100)
+            .with_ancestors();
+        db.save_route(&rule).expect("Failed to save route");
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        assert!(routes.iter().find(|r| r.pattern == "lutris").unwrap().match_ancestors);
+        assert!(routes.iter().find(|r| r.pattern == "steam").unwrap().match_ancestors);
+        assert!(!routes.iter().find(|r| r.pattern == "discord").unwrap().match_ancestors);
+    }
+
+    #[test]
+    fn test_fallback_channel() {
+        let db = test_db();
+        assert_eq!(db.load_fallback_channel().unwrap(), "system");
+
+        assert!(!db.save_fallback_channel("nope").unwrap());
+        db.create_channel(&ChannelConfig::user("alerts", "Alerts", 0)).unwrap();
+        assert!(db.save_fallback_channel("alerts").unwrap());
+        assert_eq!(db.load_fallback_channel().unwrap(), "alerts");
+
+        // Deleting the channel restores the default
+        assert!(db.delete_channel("alerts").unwrap());
+        assert_eq!(db.load_fallback_channel().unwrap(), "system");
+    }
+
+    #[test]
+    fn test_create_channel() {
+        let db = test_db();
+
+        let mut config = ChannelConfig::user("alerts", "Alerts", 0);
+        config.color = Some("#ff8800".into());
+        let created = db.create_channel(&config).expect("Failed to create channel");
+
+        // Appended after the default channels
+        assert_eq!(created.sort_order, 5);
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        assert_eq!(channels.len(), 6);
+
+        let alerts = channels.iter().find(|c| c.config.name == "alerts").unwrap();
+        assert_eq!(alerts.config.display_name, "Alerts");
+        assert_eq!(alerts.config.color.as_deref(), Some("#ff8800"));
+        assert!(!alerts.config.is_system);
+        assert!((alerts.send(&MixType::STREAM).volume - 1.0).abs() < 0.01);
+
+        // Names are unique
+        assert!(db.create_channel(&config).is_err());
+    }
+
+    #[test]
+    fn test_rename_and_recolor_channel() {
+        let db = test_db();
+
+        assert!(db.rename_channel("music", "Tunes").expect("Failed to rename"));
+        assert!(db.set_channel_color("music", Some("#123456")).expect("Failed to recolor"));
+        assert!(!db.rename_channel("nonexistent", "Nope").expect("Failed to rename"));
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        let music = channels.iter().find(|c| c.config.name == "music").unwrap();
+        assert_eq!(music.config.display_name, "Tunes");
+        assert_eq!(music.config.color.as_deref(), Some("#123456"));
+    }
+
+    #[test]
+    fn test_channel_direct_out() {
+        let db = test_db();
+
+        assert!(db.set_channel_direct_out("voice", true).expect("Failed to set direct-out"));
+        assert!(!db.set_channel_direct_out("nonexistent", true).expect("Failed to set direct-out"));
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        let direct: Vec<_> = channels
+            .iter()
+            .filter(|c| c.config.direct_out)
+            .map(|c| c.config.name.as_str())
+            .collect();
+        assert_eq!(direct, ["voice"]);
+    }
+
+    #[test]
+    fn test_channel_layout() {
+        let db = test_db();
+
+        assert!(
+            db.set_channel_layout("game", ChannelLayout::Surround71).expect("Failed to set layout")
+        );
+        let surround = ChannelConfig {
+            layout: ChannelLayout::Surround51,
+            ..ChannelConfig::user("movies", "Movies", 0)
+        };
+        db.create_channel(&surround).expect("Failed to create channel");
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        let layout = |name: &str| {
+            channels.iter().find(|c| c.config.name == name).map(|c| c.config.layout).unwrap()
+        };
+        assert_eq!(layout("game"), ChannelLayout::Surround71);
+        assert_eq!(layout("movies"), ChannelLayout::Surround51);
+        assert_eq!(layout("music"), ChannelLayout::Stereo);
+    }
+
+    #[test]
+    fn test_delete_channel() {
+        let db = test_db();
+
+        db.create_channel(&ChannelConfig::user("alerts", "Alerts", 0))
+            .expect("Failed to create channel");
+        db.save_route(&RouteRule::new("notify".into(), PatternType::Exact, "alerts".into(), 100))
+            .expect("Failed to save route");
+
+        let profile = Profile {
+            name: "with-alerts".into(),
+            description: None,
+            is_default: false,
+            channels: vec![ProfileChannel {
+                name: "alerts".into(),
+                sends: [(MixType::STREAM, MixSend::new(0.5, false))].into(),
+                effects: Some(ChannelEffects::default()),
+            }],
+            routes: vec![RouteRule::new("notify".into(), PatternType::Exact, "alerts".into(), 100)],
+            mixer: MixerState::default(),
+        };
+        db.save_profile(&profile).expect("Failed to save profile");
+
+        assert!(db.delete_channel("alerts").expect("Failed to delete channel"));
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        assert!(!channels.iter().any(|c| c.config.name == "alerts"));
+
+        let routes = db.load_routes().expect("Failed to load routes");
+        assert!(!routes.iter().any(|r| r.pattern == "notify"));
+
+        let loaded = db.load_profile("with-alerts").expect("Failed to load profile").unwrap();
+        assert!(loaded.channels.is_empty());
+        assert!(loaded.routes.is_empty());
+    }
+
+    #[test]
+    fn test_cannot_delete_system_channel() {
+        let db = test_db();
+
+        assert!(!db.delete_channel("system").expect("Failed to query channel"));
+        assert!(!db.delete_channel("music").expect("Failed to query channel"));
+        assert!(!db.delete_channel("nonexistent").expect("Failed to query channel"));
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        assert_eq!(channels.len(), 5);
+    }
+
+    #[test]
+    fn test_create_and_delete_mix() {
+        let db = test_db();
+
+        let created = db
+            .create_mix(&MixConfig::user("recording", "Recording", 0))
+            .expect("Failed to create mix");
+        assert_eq!(created.sort_order, 2);
+        assert!(db.create_mix(&MixConfig::user("recording", "Again", 0)).is_err());
+
+        let recording = created.id();
+        let mut music = db
+            .load_channels()
+            .expect("Failed to load channels")
+            .into_iter()
+            .find(|c| c.config.name == "music")
+            .unwrap();
+        music.set_volume(&recording, 0.3);
+        db.save_channel_state("music", &music).expect("Failed to save channel state");
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        let music = channels.iter().find(|c| c.config.name == "music").unwrap();
+        assert!((music.send(&recording).volume - 0.3).abs() < 0.01);
+
+        assert!(!db.delete_mix("stream").expect("Failed to query mix"));
+        assert!(db.delete_mix("recording").expect("Failed to delete mix"));
+
+        let names: Vec<_> =
+            db.load_mixes().expect("Failed to load mixes").into_iter().map(|m| m.name).collect();
+        assert_eq!(names, ["stream", "monitor"]);
+
+        let channels = db.load_channels().expect("Failed to load channels");
+        let music = channels.iter().find(|c| c.config.name == "music").unwrap();
+        assert!(!music.sends.contains_key(&recording));
+    }
 }
@@ -1,8 +1,14 @@
 //! IPC event types (server to client).
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use undertone_core::ducking::DuckingRule;
+use undertone_core::effects::ChannelEffects;
+use undertone_core::meter::MeterLevels;
 use undertone_core::mixer::MixType;
+use undertone_core::solo::SoloMode;
 
 /// Event sent from daemon to subscribed clients.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,10 +29,26 @@ pub enum EventType {
     ChannelVolumeChanged,
     /// Channel mute state changed
     ChannelMuteChanged,
+    /// Channel balance changed
+    ChannelPanChanged,
     /// Audio levels updated (for VU meters)
     LevelsUpdated,
+    /// Channel created
+    ChannelCreated,
+    /// Channel display name or color changed
+    ChannelUpdated,
+    /// Channel effects settings changed
+    ChannelEffectsChanged,
+    /// Channel deleted
+    ChannelDeleted,
+    /// Mix created
+    MixCreated,
+    /// Mix deleted
+    MixDeleted,
     /// App routing changed
     AppRouteChanged,
+    /// Fallback channel for unmatched apps changed
+    FallbackChannelChanged,
     /// New audio app discovered
     AppDiscovered,
     /// Audio app removed
@@ -37,6 +59,12 @@ pub enum EventType {
     DeviceDisconnected,
     /// Microphone mute state changed
     MicMuteChanged,
+    /// Microphone send to a mix changed
+    MicMixChanged,
+    /// Ducking rules of a mix changed
+    DuckingChanged,
+    /// Soloed channels or solo mode changed
+    SoloChanged,
     /// Profile changed
     ProfileChanged,
     /// Error occurred
@@ -59,13 +87,70 @@ pub struct ChannelMuteChangedData {
     pub muted: bool,
 }
 
-/// Audio levels update data.
+/// Channel balance changed event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelPanChangedData {
+    pub channel: String,
+    pub mix: MixType,
+    pub pan: f32,
+}
+
+/// Channel effects changed event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelEffectsChangedData {
+    pub channel: String,
+    pub effects: ChannelEffects,
+}
+
+/// Microphone mix send changed event data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicMixChangedData {
+    pub mix: MixType,
+    pub enabled: bool,
+    pub volume: f32,
+}
+
+/// Ducking rules changed event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckingChangedData {
+    pub mix: MixType,
+    pub rules: Vec<DuckingRule>,
+}
+
+/// Solo changed event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoloChangedData {
+    pub mode: SoloMode,
+    /// Soloed channels; empty when nothing is soloed
+    pub channels: Vec<String>,
+}
+
+/// Channel deleted event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelDeletedData {
+    pub channel: String,
+    /// Channel that the deleted channel's apps were moved to
+    pub fallback: String,
+}
+
+/// Mix deleted event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixDeletedData {
+    pub mix: MixType,
+}
+
+/// Audio levels update data.
+///
+/// Levels are linear peak and RMS values (0.0 - 1.0) measured since the
+/// previous update.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LevelsData {
-    /// Per-channel levels: (`channel_name`, left, right)
-    pub channels: Vec<(String, f32, f32)>,
-    /// Master output levels
-    pub master: (f32, f32),
+    /// Per-channel levels (pre-fader), keyed by channel name
+    pub channels: BTreeMap<String, MeterLevels>,
+    /// Per-mix levels, keyed by mix name (e.g. `stream`, `monitor`)
+    pub mixes: BTreeMap<String, MeterLevels>,
+    /// Microphone levels, if the mic is being metered
+    pub mic: Option<MeterLevels>,
 }
 
 /// App discovered event data.
@@ -78,6 +163,20 @@ pub struct AppDiscoveredData {
     pub channel: String,
 }
 
+/// App route changed event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppRouteChangedData {
+    pub app_id: u32,
+    pub name: String,
+    pub channel: String,
+}
+
+/// Fallback channel changed event data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackChannelChangedData {
+    pub channel: String,
+}
+
 /// Device connected event data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceConnectedData {
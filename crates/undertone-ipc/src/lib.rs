@@ -12,8 +12,10 @@ pub mod server;
 pub use client::IpcClient;
 pub use error::{IpcError, IpcResult};
 pub use events::{
-    AppDiscoveredData, ChannelMuteChangedData, ChannelVolumeChangedData, DeviceConnectedData,
-    ErrorData, Event, EventType, LevelsData,
+    AppDiscoveredData, AppRouteChangedData, ChannelDeletedData, ChannelEffectsChangedData,
+    ChannelMuteChangedData, ChannelPanChangedData, ChannelVolumeChangedData, DeviceConnectedData,
+    DuckingChangedData, ErrorData, Event, EventType, FallbackChannelChangedData, LevelsData,
+    MicMixChangedData, MixDeletedData, SoloChangedData,
 };
 pub use messages::{Method, Request, Response};
 pub use server::IpcServer;
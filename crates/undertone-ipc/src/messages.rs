@@ -1,8 +1,16 @@
 //! IPC message types.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use undertone_core::ducking::DuckingRule;
+use undertone_core::effects::ChannelEffects;
+use undertone_core::layout::ChannelLayout;
 use undertone_core::mixer::MixType;
+use undertone_core::routing::{PatternType, RouteDirection, StreamSelector};
+use undertone_core::solo::SoloMode;
+use undertone_core::volume::VolumeValue;
 
 /// Request envelope sent from client to daemon.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,28 +70,143 @@ pub enum Method {
     GetDiagnostics,
 
     // Channel control
-    /// Set volume for a channel in a specific mix
-    SetChannelVolume { channel: String, mix: MixType, volume: f32 },
-    /// Set mute state for a channel in a specific mix
-    SetChannelMute { channel: String, mix: MixType, muted: bool },
+    /// Set volume for a channel in a specific mix (fader position, dB or step)
+    SetChannelVolume { channel: String, mix: MixType, volume: VolumeValue },
+    /// Set mute state for a channel in a specific mix, optionally fading
+    /// out or in over `fade_ms`
+    SetChannelMute {
+        channel: String,
+        mix: MixType,
+        muted: bool,
+        #[serde(default)]
+        fade_ms: Option<u32>,
+    },
+    /// Set balance for a channel in a specific mix (-1.0 left to 1.0 right)
+    SetChannelPan { channel: String, mix: MixType, pan: f32 },
+    /// Replace a channel's effects settings (EQ, compressor, gate, limiter)
+    SetChannelEffects { channel: String, effects: ChannelEffects },
+
+    // Channel management
+    /// Create a user-defined channel
+    CreateChannel {
+        name: String,
+        display_name: String,
+        #[serde(default)]
+        color: Option<String>,
+        #[serde(default)]
+        icon: Option<String>,
+        /// Speaker layout of the channel's sink
+        #[serde(default)]
+        layout: ChannelLayout,
+    },
+    /// Change a channel's display name
+    RenameChannel { name: String, display_name: String },
+    /// Change a channel's color (`None` clears it)
+    SetChannelColor { name: String, color: Option<String> },
+    /// Turn a channel's direct-out recording source (`ut-direct-<name>`) on
+    /// or off
+    SetChannelDirectOut { name: String, enabled: bool },
+    /// Change a channel's speaker layout; its nodes are rebuilt and its apps
+    /// re-linked
+    SetChannelLayout { name: String, layout: ChannelLayout },
+    /// Delete a user-defined channel, moving its apps to the fallback channel
+    DeleteChannel { name: String },
+
+    // Mix management
+    /// Get all mixes
+    GetMixes,
+    /// Create a user-defined mix; every channel is sent into it at full level
+    CreateMix { name: String, display_name: String },
+    /// Delete a user-defined mix
+    DeleteMix { name: String },
 
     // Master volume control
-    /// Set master volume for a mix (0.0 - 1.0)
-    SetMasterVolume { mix: MixType, volume: f32 },
+    /// Set master volume for a mix (fader position, dB or step), optionally
+    /// fading over `fade_ms`
+    SetMasterVolume {
+        mix: MixType,
+        volume: VolumeValue,
+        #[serde(default)]
+        fade_ms: Option<u32>,
+    },
     /// Set master mute state for a mix
     SetMasterMute { mix: MixType, muted: bool },
 
     // App routing
     /// Route an app to a channel
-    SetAppRoute { app_pattern: String, channel: String },
+    SetAppRoute {
+        app_pattern: String,
+        /// Target of the rule; unused by exclusion rules
+        #[serde(default)]
+        channel: String,
+        /// How `app_pattern` is matched
+        #[serde(default)]
+        pattern_type: PatternType,
+        /// Node property to match instead of the app name (e.g. `media.role`)
+        #[serde(default)]
+        property: Option<String>,
+        /// Playback rules send the app to `channel`; capture rules record
+        /// from the mix named by `channel`, or from the mic
+        #[serde(default)]
+        direction: RouteDirection,
+        /// Leave matching streams alone instead of routing them
+        #[serde(default)]
+        exclude: bool,
+        /// Also match the processes the app was started from
+        #[serde(default)]
+        match_ancestors: bool,
+        /// Only route the app's streams this selects (by `media.name`,
+        /// `media.role` or index); other streams keep the app's route
+        #[serde(default)]
+        stream: Option<StreamSelector>,
+    },
     /// Remove an app route
-    RemoveAppRoute { app_pattern: String },
+    RemoveAppRoute {
+        app_pattern: String,
+        /// Property the rule matches, for a property rule
+        #[serde(default)]
+        property: Option<String>,
+        #[serde(default)]
+        direction: RouteDirection,
+        #[serde(default)]
+        stream: Option<StreamSelector>,
+    },
+    /// Move one playback stream (an `app_id` from `GetApps`) to a channel
+    /// until it closes, leaving the app's other streams where they are
+    MoveStream { app_id: u32, channel: String },
+    /// Choose the channel that apps no rule matches are sent to
+    SetFallbackChannel { channel: String },
+    /// Explain how an app is routed: every rule for `direction` in the
+    /// order they are tried, what each matched, and the outcome. Give a
+    /// running app's `app_id` (as listed by `GetApps`), or the names and
+    /// properties to try
+    ExplainRoute {
+        #[serde(default)]
+        app_id: Option<u32>,
+        #[serde(default)]
+        app_name: Option<String>,
+        #[serde(default)]
+        binary_name: Option<String>,
+        #[serde(default)]
+        properties: HashMap<String, String>,
+        #[serde(default)]
+        stream_index: u32,
+        #[serde(default)]
+        direction: RouteDirection,
+    },
+    /// Check the routing rules for invalid patterns, missing targets and
+    /// rules that can never win
+    LintRoutes,
 
     // Profile management
     /// Save current state as a profile
     SaveProfile { name: String },
-    /// Load a saved profile
-    LoadProfile { name: String },
+    /// Load a saved profile, optionally fading to its levels over `fade_ms`
+    LoadProfile {
+        name: String,
+        #[serde(default)]
+        fade_ms: Option<u32>,
+    },
     /// Delete a profile
     DeleteProfile { name: String },
 
@@ -92,6 +215,23 @@ pub enum Method {
     SetMicGain { gain: f32 },
     /// Set microphone mute state
     SetMicMute { muted: bool },
+    /// Include or exclude the microphone from a mix (stream voice or monitor sidetone)
+    SetMicMixEnabled { mix: MixType, enabled: bool },
+    /// Set the microphone level in a mix (fader position, dB or step)
+    SetMicMixVolume { mix: MixType, volume: VolumeValue },
+
+    // Ducking
+    /// Replace the ducking rules of a mix (an empty list removes them)
+    SetDuckingRules { mix: MixType, rules: Vec<DuckingRule> },
+
+    // Solo
+    /// Solo or release a channel in the monitor mix
+    SetChannelSolo { channel: String, soloed: bool },
+    /// Choose whether soloed channels keep their level (`solo`) or are heard
+    /// at full level (`pfl`)
+    SetSoloMode { mode: SoloMode },
+    /// Release every solo
+    ClearSolo,
 
     // Output device control
     /// Get available audio output devices
@@ -160,20 +300,40 @@ mod tests {
             id: 3,
             method: Method::SetChannelVolume {
                 channel: "voice".into(),
-                mix: MixType::Stream,
-                volume: 0.75,
+                mix: MixType::STREAM,
+                volume: VolumeValue::Position(0.75),
             },
         };
         let json = serde_json::to_string(&request).unwrap();
 
         assert!(json.contains(r#""type":"SetChannelVolume""#));
         assert!(json.contains(r#""channel":"voice""#));
+        assert!(json.contains(r#""volume":0.75"#));
 
         let parsed = roundtrip_request(&request);
         if let Method::SetChannelVolume { channel, mix, volume } = parsed.method {
             assert_eq!(channel, "voice");
-            assert!(matches!(mix, MixType::Stream));
-            assert!((volume - 0.75).abs() < 0.01);
+            assert_eq!(mix, MixType::STREAM);
+            assert_eq!(volume, VolumeValue::Position(0.75));
+        } else {
+            panic!("Expected SetChannelVolume method");
+        }
+    }
+
+    #[test]
+    fn test_request_volume_in_db_and_steps() {
+        let json = r#"{"id":6,"method":{"type":"SetMasterVolume","params":{"mix":"stream","volume":{"db":-6.0}}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetMasterVolume { volume, .. } = parsed.method {
+            assert_eq!(volume, VolumeValue::Db { db: -6.0 });
+        } else {
+            panic!("Expected SetMasterVolume method");
+        }
+
+        let json = r#"{"id":7,"method":{"type":"SetChannelVolume","params":{"channel":"music","mix":"monitor","volume":{"step_db":-3.0}}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetChannelVolume { volume, .. } = parsed.method {
+            assert_eq!(volume, VolumeValue::StepDb { step_db: -3.0 });
         } else {
             panic!("Expected SetChannelVolume method");
         }
@@ -185,35 +345,342 @@ mod tests {
             id: 4,
             method: Method::SetChannelMute {
                 channel: "music".into(),
-                mix: MixType::Monitor,
+                mix: MixType::MONITOR,
                 muted: true,
+                fade_ms: None,
             },
         };
 
         let parsed = roundtrip_request(&request);
-        if let Method::SetChannelMute { channel, mix, muted } = parsed.method {
+        if let Method::SetChannelMute { channel, mix, muted, fade_ms } = parsed.method {
             assert_eq!(channel, "music");
-            assert!(matches!(mix, MixType::Monitor));
+            assert_eq!(mix, MixType::MONITOR);
             assert!(muted);
+            assert_eq!(fade_ms, None);
         } else {
             panic!("Expected SetChannelMute method");
         }
     }
 
+    #[test]
+    fn test_request_fade() {
+        let json = r#"{"id":8,"method":{"type":"LoadProfile","params":{"name":"Streaming","fade_ms":1500}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::LoadProfile { name, fade_ms } = parsed.method {
+            assert_eq!(name, "Streaming");
+            assert_eq!(fade_ms, Some(1500));
+        } else {
+            panic!("Expected LoadProfile method");
+        }
+
+        let json = r#"{"id":9,"method":{"type":"SetChannelMute","params":{"channel":"music","mix":"stream","muted":true,"fade_ms":300}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(parsed.method, Method::SetChannelMute { fade_ms: Some(300), .. }));
+    }
+
+    #[test]
+    fn test_request_set_channel_pan() {
+        let json = r#"{"id":5,"method":{"type":"SetChannelPan","params":{"channel":"voice","mix":"stream","pan":-0.3}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetChannelPan { channel, mix, pan } = parsed.method {
+            assert_eq!(channel, "voice");
+            assert_eq!(mix, MixType::STREAM);
+            assert!((pan - -0.3).abs() < f32::EPSILON);
+        } else {
+            panic!("Expected SetChannelPan method");
+        }
+    }
+
+    #[test]
+    fn test_request_set_mic_mix() {
+        let request = Request {
+            id: 12,
+            method: Method::SetMicMixVolume { mix: MixType::MONITOR, volume: 0.3.into() },
+        };
+
+        let parsed = roundtrip_request(&request);
+        if let Method::SetMicMixVolume { mix, volume } = parsed.method {
+            assert_eq!(mix, MixType::MONITOR);
+            assert_eq!(volume, VolumeValue::Position(0.3));
+        } else {
+            panic!("Expected SetMicMixVolume method");
+        }
+
+        let json = r#"{"id":13,"method":{"type":"SetMicMixEnabled","params":{"mix":"stream","enabled":false}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetMicMixEnabled { mix, enabled } = parsed.method {
+            assert_eq!(mix, MixType::STREAM);
+            assert!(!enabled);
+        } else {
+            panic!("Expected SetMicMixEnabled method");
+        }
+    }
+
+    #[test]
+    fn test_request_set_ducking_rules() {
+        use undertone_core::ducking::DuckTrigger;
+
+        let json = r#"{"id":14,"method":{"type":"SetDuckingRules","params":{"mix":"stream","rules":[
+            {"trigger":{"channel":"voice"},"targets":["music","game"],"depth_db":15.0},
+            {"trigger":"mic","targets":["music"]}
+        ]}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetDuckingRules { mix, rules } = parsed.method {
+            assert_eq!(mix, MixType::STREAM);
+            assert_eq!(rules.len(), 2);
+            assert_eq!(rules[0].trigger, DuckTrigger::Channel("voice".to_string()));
+            assert!((rules[0].depth_db - 15.0).abs() < f32::EPSILON);
+            assert_eq!(rules[1].trigger, DuckTrigger::Mic);
+        } else {
+            panic!("Expected SetDuckingRules method");
+        }
+    }
+
     #[test]
     fn test_request_set_app_route() {
         let request = Request {
             id: 5,
-            method: Method::SetAppRoute { app_pattern: "spotify".into(), channel: "music".into() },
+            method: Method::SetAppRoute {
+                app_pattern: "spotify".into(),
+                channel: "music".into(),
+                pattern_type: PatternType::ExactIgnoreCase,
+                property: Some("application.id".into()),
+                direction: RouteDirection::Capture,
+                exclude: false,
+                match_ancestors: true,
+                stream: None,
+            },
         };
 
         let parsed = roundtrip_request(&request);
-        if let Method::SetAppRoute { app_pattern, channel } = parsed.method {
+        if let Method::SetAppRoute {
+            app_pattern,
+            channel,
+            pattern_type,
+            property,
+            direction,
+            match_ancestors,
+            ..
+        } = parsed.method
+        {
+            assert!(match_ancestors);
             assert_eq!(app_pattern, "spotify");
             assert_eq!(channel, "music");
+            assert_eq!(pattern_type, PatternType::ExactIgnoreCase);
+            assert_eq!(property.as_deref(), Some("application.id"));
+            assert_eq!(direction, RouteDirection::Capture);
+        } else {
+            panic!("Expected SetAppRoute method");
+        }
+
+        // Older clients send no pattern type and get an exact match
+        let json = r#"{"id":6,"method":{"type":"SetAppRoute","params":{"app_pattern":"obs","channel":"voice"}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            parsed.method,
+            Method::SetAppRoute {
+                pattern_type: PatternType::Exact,
+                property: None,
+                direction: RouteDirection::Playback,
+                exclude: false,
+                match_ancestors: false,
+                stream: None,
+                ..
+            }
+        ));
+
+        // Stream rules pick out some of the app's streams
+        let json = r#"{"id":8,"method":{"type":"SetAppRoute","params":{"app_pattern":"firefox","channel":"voice","stream":{"by":"name","value":"Meet"}}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetAppRoute { stream, .. } = parsed.method {
+            assert_eq!(stream, Some(StreamSelector::Name("Meet".into())));
         } else {
             panic!("Expected SetAppRoute method");
         }
+
+        let request =
+            Request { id: 9, method: Method::MoveStream { app_id: 42, channel: "music".into() } };
+        let parsed = roundtrip_request(&request);
+        assert!(
+            matches!(parsed.method, Method::MoveStream { app_id: 42, ref channel } if channel == "music")
+        );
+
+        // Exclusion rules need no channel
+        let json = r#"{"id":7,"method":{"type":"SetAppRoute","params":{"app_pattern":"pavucontrol","exclude":true}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            parsed.method,
+            Method::SetAppRoute { ref channel, exclude: true, .. } if channel.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_request_create_channel() {
+        let request = Request {
+            id: 9,
+            method: Method::CreateChannel {
+                name: "alerts".into(),
+                display_name: "Alerts".into(),
+                color: Some("#ff8800".into()),
+                icon: None,
+                layout: ChannelLayout::Surround51,
+            },
+        };
+        let json = serde_json::to_string(&request).unwrap();
+
+        assert!(json.contains(r#""type":"CreateChannel""#));
+
+        let parsed = roundtrip_request(&request);
+        if let Method::CreateChannel { name, display_name, color, icon, layout } = parsed.method {
+            assert_eq!(name, "alerts");
+            assert_eq!(display_name, "Alerts");
+            assert_eq!(color.as_deref(), Some("#ff8800"));
+            assert!(icon.is_none());
+            assert_eq!(layout, ChannelLayout::Surround51);
+        } else {
+            panic!("Expected CreateChannel method");
+        }
+    }
+
+    #[test]
+    fn test_create_channel_optional_fields() {
+        let json = r#"{"id":10,"method":{"type":"CreateChannel","params":{"name":"alerts","display_name":"Alerts"}}}"#;
+        let request: Request = serde_json::from_str(json).expect("Failed to parse request JSON");
+
+        if let Method::CreateChannel { color, icon, layout, .. } = request.method {
+            assert!(color.is_none());
+            assert!(icon.is_none());
+            assert_eq!(layout, ChannelLayout::Stereo);
+        } else {
+            panic!("Expected CreateChannel method");
+        }
+    }
+
+    #[test]
+    fn test_request_set_channel_direct_out() {
+        let json = r#"{"id":12,"method":{"type":"SetChannelDirectOut","params":{"name":"voice","enabled":true}}}"#;
+        let request: Request = serde_json::from_str(json).expect("Failed to parse request JSON");
+
+        if let Method::SetChannelDirectOut { name, enabled } = roundtrip_request(&request).method {
+            assert_eq!(name, "voice");
+            assert!(enabled);
+        } else {
+            panic!("Expected SetChannelDirectOut method");
+        }
+    }
+
+    #[test]
+    fn test_request_set_channel_layout() {
+        let json = r#"{"id":13,"method":{"type":"SetChannelLayout","params":{"name":"game","layout":"7.1"}}}"#;
+        let request: Request = serde_json::from_str(json).expect("Failed to parse request JSON");
+
+        if let Method::SetChannelLayout { name, layout } = roundtrip_request(&request).method {
+            assert_eq!(name, "game");
+            assert_eq!(layout, ChannelLayout::Surround71);
+        } else {
+            panic!("Expected SetChannelLayout method");
+        }
+    }
+
+    #[test]
+    fn test_request_delete_channel() {
+        let request = Request { id: 11, method: Method::DeleteChannel { name: "alerts".into() } };
+
+        let parsed = roundtrip_request(&request);
+        if let Method::DeleteChannel { name } = parsed.method {
+            assert_eq!(name, "alerts");
+        } else {
+            panic!("Expected DeleteChannel method");
+        }
+    }
+
+    #[test]
+    fn test_request_mix_by_name() {
+        let request = Request {
+            id: 14,
+            method: Method::CreateMix {
+                name: "recording".into(),
+                display_name: "Recording".into(),
+            },
+        };
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.contains(r#""type":"CreateMix""#));
+
+        let parsed = roundtrip_request(&request);
+        if let Method::CreateMix { name, display_name } = parsed.method {
+            assert_eq!(name, "recording");
+            assert_eq!(display_name, "Recording");
+        } else {
+            panic!("Expected CreateMix method");
+        }
+
+        // User-defined mixes are addressed by name like the built-in ones
+        let json = r#"{"id":15,"method":{"type":"SetChannelVolume","params":{"channel":"music","mix":"recording","volume":0.4}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetChannelVolume { mix, .. } = parsed.method {
+            assert_eq!(mix, MixType::new("recording"));
+        } else {
+            panic!("Expected SetChannelVolume method");
+        }
+    }
+
+    #[test]
+    fn test_request_solo() {
+        let json = r#"{"id":17,"method":{"type":"SetChannelSolo","params":{"channel":"voice","soloed":true}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::SetChannelSolo { channel, soloed } = parsed.method {
+            assert_eq!(channel, "voice");
+            assert!(soloed);
+        } else {
+            panic!("Expected SetChannelSolo method");
+        }
+
+        let json = r#"{"id":18,"method":{"type":"SetSoloMode","params":{"mode":"pfl"}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        assert!(matches!(parsed.method, Method::SetSoloMode { mode: SoloMode::Pfl }));
+
+        let parsed = roundtrip_request(&Request { id: 19, method: Method::ClearSolo });
+        assert!(matches!(parsed.method, Method::ClearSolo));
+    }
+
+    #[test]
+    fn test_request_set_channel_effects() {
+        let json = r#"{"id":16,"method":{"type":"SetChannelEffects","params":{"channel":"voice","effects":{"compressor":{"enabled":true,"threshold_db":-20.0}}}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+
+        if let Method::SetChannelEffects { channel, effects } = parsed.method {
+            assert_eq!(channel, "voice");
+            assert!(effects.compressor.enabled);
+            assert!((effects.compressor.threshold_db - -20.0).abs() < f32::EPSILON);
+            assert!(!effects.limiter.enabled);
+        } else {
+            panic!("Expected SetChannelEffects method");
+        }
+    }
+
+    #[test]
+    fn test_request_explain_route() {
+        let json = r#"{"id":20,"method":{"type":"ExplainRoute","params":{"app_id":42}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::ExplainRoute { app_id, app_name, properties, direction, .. } = parsed.method
+        {
+            assert_eq!(app_id, Some(42));
+            assert_eq!(app_name, None);
+            assert!(properties.is_empty());
+            assert_eq!(direction, RouteDirection::Playback);
+        } else {
+            panic!("Expected ExplainRoute method");
+        }
+
+        let json = r#"{"id":21,"method":{"type":"ExplainRoute","params":{"app_name":"webrtc","properties":{"media.role":"Communication"},"direction":"capture"}}}"#;
+        let parsed: Request = serde_json::from_str(json).unwrap();
+        if let Method::ExplainRoute { app_name, properties, direction, .. } = parsed.method {
+            assert_eq!(app_name.as_deref(), Some("webrtc"));
+            assert_eq!(properties["media.role"], "Communication");
+            assert_eq!(direction, RouteDirection::Capture);
+        } else {
+            panic!("Expected ExplainRoute method");
+        }
     }
 
     #[test]
@@ -306,6 +773,7 @@ mod tests {
             Method::GetDeviceStatus,
             Method::GetDiagnostics,
             Method::GetOutputDevices,
+            Method::LintRoutes,
             Method::Shutdown,
             Method::Reconcile,
         ];
@@ -322,7 +790,11 @@ mod tests {
         // Test Stream mix type (serializes as lowercase "stream")
         let stream_request = Request {
             id: 1,
-            method: Method::SetMasterVolume { mix: MixType::Stream, volume: 1.0 },
+            method: Method::SetMasterVolume {
+                mix: MixType::STREAM,
+                volume: 1.0.into(),
+                fade_ms: None,
+            },
         };
         let json = serde_json::to_string(&stream_request).unwrap();
         assert!(json.contains(r#""mix":"stream""#));
@@ -330,7 +802,7 @@ mod tests {
         // Test Monitor mix type (serializes as lowercase "monitor")
         let monitor_request = Request {
             id: 2,
-            method: Method::SetMasterMute { mix: MixType::Monitor, muted: false },
+            method: Method::SetMasterMute { mix: MixType::MONITOR, muted: false },
         };
         let json = serde_json::to_string(&monitor_request).unwrap();
         assert!(json.contains(r#""mix":"monitor""#));
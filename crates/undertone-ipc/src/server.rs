@@ -190,8 +190,10 @@ impl IpcServer {
                 // Forward events to client (only if not at EOF)
                 Ok(event) = event_rx.recv(), if !client_eof => {
                     let clients = clients.read().await;
+                    // Level updates are high-rate, so only clients that ask for them get them
                     if let Some(handle) = clients.get(&client_id)
-                        && (handle.subscriptions.contains(&event.event) || handle.subscriptions.is_empty()) {
+                        && (handle.subscriptions.contains(&event.event)
+                            || (handle.subscriptions.is_empty() && event.event != EventType::LevelsUpdated)) {
                             let json = match serde_json::to_string(&event) {
                                 Ok(s) => s + "\n",
                                 Err(e) => {
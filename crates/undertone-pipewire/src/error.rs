@@ -8,6 +8,9 @@ pub enum PwError {
     #[error("PipeWire connection failed: {0}")]
     ConnectionFailed(String),
 
+    #[error("PipeWire is disconnected")]
+    Disconnected,
+
     #[error("Node creation failed: {0}")]
     NodeCreationFailed(String),
 
@@ -29,8 +32,14 @@ pub enum PwError {
     #[error("Property not found: {0}")]
     PropertyNotFound(String),
 
+    #[error("Timed out waiting for PipeWire")]
+    Timeout,
+
     #[error("Volume control failed: {0}")]
     VolumeControlFailed(String),
+
+    #[error("Metadata update failed: {0}")]
+    MetadataFailed(String),
 }
 
 /// Result type for `PipeWire` operations.
@@ -3,14 +3,23 @@
 //! This module provides functions to create virtual audio nodes that Undertone
 //! uses for channel mixing and routing.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc as std_mpsc;
+use std::time::Duration;
 
+use parking_lot::Mutex;
 use pipewire::core::Core;
 use pipewire::properties::properties;
 use pipewire::proxy::ProxyT;
+use tokio::sync::oneshot;
 use tracing::{debug, info};
 
+use undertone_core::layout::ChannelLayout;
+
 use crate::error::{PwError, PwResult};
+use crate::meter::MeterTarget;
 use crate::node::VirtualSinkProps;
 
 /// SPA property keys for volume control
@@ -29,6 +38,8 @@ pub mod spa_props {
     pub const SPA_PROP_SOFT_MUTE: u32 = 65551;
     /// Soft volumes array (software-based volume)
     pub const SPA_PROP_SOFT_VOLUMES: u32 = 65552;
+    /// Named control values (used by filter-chain nodes)
+    pub const SPA_PROP_PARAMS: u32 = 524289;
 }
 
 /// Result of node creation
@@ -49,8 +60,15 @@ pub enum FactoryRequest {
         name: String,
         /// Human-readable description
         description: String,
-        /// Number of channels (2 for stereo)
-        channels: u32,
+        /// Speaker layout; surround filters also apply the downmix gains
+        layout: ChannelLayout,
+    },
+    /// Load a filter-chain module into our own context
+    LoadFilterChain {
+        /// Name of the chain's input node (e.g., "ut-fx-voice-in")
+        name: String,
+        /// Module arguments (SPA JSON)
+        args: String,
     },
     /// Create a link between ports
     CreateLink { output_node: u32, output_port: String, input_node: u32, input_port: String },
@@ -58,8 +76,8 @@ pub enum FactoryRequest {
     SetNodeVolume {
         /// Node ID to set volume on
         node_id: u32,
-        /// Volume level (0.0 - 1.0)
-        volume: f32,
+        /// Left and right volume levels (0.0 - 1.0)
+        volumes: [f32; 2],
     },
     /// Set mute state on a node
     SetNodeMute {
@@ -68,12 +86,46 @@ pub enum FactoryRequest {
         /// Mute state
         muted: bool,
     },
-    /// Destroy a node by ID
+    /// Fade a node's volume, cancelling any fade already running on it
+    FadeNodeVolume {
+        /// Node ID to fade
+        node_id: u32,
+        /// Left and right volume levels to end at (0.0 - 1.0)
+        volumes: [f32; 2],
+        /// How long the fade takes
+        duration: Duration,
+    },
+    /// Fade a node out and mute it, or unmute it and fade it in, cancelling
+    /// any fade already running on it
+    FadeNodeMute {
+        /// Node ID to fade
+        node_id: u32,
+        /// Mute state to end in
+        muted: bool,
+        /// Left and right volume levels of the node while audible
+        volumes: [f32; 2],
+        /// How long the fade takes
+        duration: Duration,
+    },
+    /// Set named control values on a node we did not create
+    SetNodeParams {
+        /// Registry ID of the node
+        node_id: u32,
+        /// Control values, keyed as `node:control`
+        params: Vec<(String, f32)>,
+    },
+    /// Destroy a node we created, by proxy ID
     DestroyNode(u32),
+    /// Destroy a node we did not create, by registry ID
+    DestroyExternalNode(u32),
     /// Destroy a link by ID
     DestroyLink(u32),
     /// Destroy all links between two nodes
     DestroyLinksBetweenNodes { output_node: u32, input_node: u32 },
+    /// Replace the set of metered nodes
+    SetMeters(Vec<MeterTarget>),
+    /// Make a source the configured default input, by node name
+    SetDefaultSource(String),
     /// Shutdown the factory
     Shutdown,
 }
@@ -83,22 +135,102 @@ pub enum FactoryRequest {
 pub enum FactoryResponse {
     /// Node was created
     NodeCreated(CreatedNode),
+    /// Filter-chain module was loaded
+    FilterChainLoaded,
     /// Link was created
     LinkCreated { id: u32 },
     /// Volume was set on a node
     VolumeSet { node_id: u32 },
     /// Mute was set on a node
     MuteSet { node_id: u32 },
+    /// Control values were set on a node
+    ParamsSet { node_id: u32 },
     /// Node was destroyed
     NodeDestroyed { id: u32 },
     /// Link was destroyed
     LinkDestroyed { id: u32 },
     /// Multiple links were destroyed
     LinksDestroyed { count: usize },
+    /// Meter streams were (re)created
+    MetersSet { count: usize },
+    /// The default source was set
+    DefaultSourceSet,
     /// Operation failed
     Error(String),
 }
 
+/// Identifies a factory request so its response reaches the right caller.
+pub type RequestId = u64;
+
+/// A factory request tagged with the ID its response is delivered under.
+#[derive(Debug)]
+pub struct TaggedRequest {
+    pub id: RequestId,
+    pub request: FactoryRequest,
+}
+
+/// Callers waiting on factory responses, keyed by request ID.
+///
+/// Shared between the runtime handle, which registers requests, and the
+/// `PipeWire` thread, which completes them. Any number of requests can be in
+/// flight at once.
+#[derive(Debug, Default)]
+pub(crate) struct PendingRequests {
+    next_id: AtomicU64,
+    waiting: Mutex<HashMap<RequestId, oneshot::Sender<FactoryResponse>>>,
+}
+
+impl PendingRequests {
+    /// Allocate a request ID and start waiting for its response.
+    pub(crate) fn register(self: &Arc<Self>) -> PendingResponse {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.waiting.lock().insert(id, tx);
+        PendingResponse { id, rx, pending: Arc::clone(self) }
+    }
+
+    /// Deliver the response to a request.
+    ///
+    /// Responses nobody is waiting for any more are dropped.
+    pub(crate) fn complete(&self, id: RequestId, response: FactoryResponse) {
+        if let Some(tx) = self.waiting.lock().remove(&id) {
+            let _ = tx.send(response);
+        }
+    }
+
+    /// Stop waiting on every request; their callers get an error.
+    pub(crate) fn abandon_all(&self) {
+        self.waiting.lock().clear();
+    }
+}
+
+/// The response to a request that is in flight.
+///
+/// Dropping this abandons the wait, not the request: the `PipeWire` thread
+/// still carries it out and discards the response.
+pub(crate) struct PendingResponse {
+    pub(crate) id: RequestId,
+    rx: oneshot::Receiver<FactoryResponse>,
+    pending: Arc<PendingRequests>,
+}
+
+impl PendingResponse {
+    /// Wait up to `timeout` for the response.
+    pub(crate) async fn wait(mut self, timeout: Duration) -> PwResult<FactoryResponse> {
+        match tokio::time::timeout(timeout, &mut self.rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(PwError::MainLoopError("PipeWire runtime stopped".to_string())),
+            Err(_) => Err(PwError::Timeout),
+        }
+    }
+}
+
+impl Drop for PendingResponse {
+    fn drop(&mut self) {
+        self.pending.waiting.lock().remove(&self.id);
+    }
+}
+
 /// Factory thread for creating `PipeWire` objects.
 ///
 /// This runs in the same thread as the monitor since `PipeWire` objects
@@ -133,18 +265,9 @@ impl NodeFactory {
                         let _ = self.response_tx.send(FactoryResponse::Error(e.to_string()));
                     }
                 },
-                FactoryRequest::CreateVolumeFilter { name, description, channels } => {
+                FactoryRequest::CreateVolumeFilter { name, description, layout } => {
                     // Volume filters are handled the same as sinks in this context
-                    let props = VirtualSinkProps {
-                        name,
-                        description,
-                        channels,
-                        positions: if channels == 1 {
-                            "MONO".to_string()
-                        } else {
-                            "FL,FR".to_string()
-                        },
-                    };
+                    let props = VirtualSinkProps::stereo(&name, &description).with_layout(layout);
                     match self.create_sink(core, &props) {
                         Ok(node) => {
                             let _ = self.response_tx.send(FactoryResponse::NodeCreated(node));
@@ -154,6 +277,12 @@ impl NodeFactory {
                         }
                     }
                 }
+                FactoryRequest::LoadFilterChain { .. } => {
+                    // Loading modules needs the runtime's context, not available in legacy path
+                    let _ = self.response_tx.send(FactoryResponse::Error(
+                        "Filter chains are not supported by the legacy factory".to_string(),
+                    ));
+                }
                 FactoryRequest::CreateLink { output_node, output_port, input_node, input_port } => {
                     match self.create_link(core, output_node, &output_port, input_node, &input_port)
                     {
@@ -169,11 +298,22 @@ impl NodeFactory {
                     // Volume control requires node proxy access, not implemented in this legacy path
                     let _ = self.response_tx.send(FactoryResponse::VolumeSet { node_id });
                 }
+                FactoryRequest::FadeNodeVolume { node_id, .. } => {
+                    // Fades need the runtime's timer, not available in legacy path
+                    let _ = self.response_tx.send(FactoryResponse::VolumeSet { node_id });
+                }
+                FactoryRequest::FadeNodeMute { node_id, .. } => {
+                    let _ = self.response_tx.send(FactoryResponse::MuteSet { node_id });
+                }
                 FactoryRequest::SetNodeMute { node_id, .. } => {
                     // Mute control requires node proxy access, not implemented in this legacy path
                     let _ = self.response_tx.send(FactoryResponse::MuteSet { node_id });
                 }
-                FactoryRequest::DestroyNode(id) => {
+                FactoryRequest::SetNodeParams { node_id, .. } => {
+                    // Params require a bound node proxy, not implemented in this legacy path
+                    let _ = self.response_tx.send(FactoryResponse::ParamsSet { node_id });
+                }
+                FactoryRequest::DestroyNode(id) | FactoryRequest::DestroyExternalNode(id) => {
                     // Node destruction is handled by proxy.destroy()
                     // For now, we just acknowledge
                     let _ = self.response_tx.send(FactoryResponse::NodeDestroyed { id });
@@ -185,6 +325,16 @@ impl NodeFactory {
                     // Not implemented in legacy path
                     let _ = self.response_tx.send(FactoryResponse::LinksDestroyed { count: 0 });
                 }
+                FactoryRequest::SetMeters(_) => {
+                    // Metering needs the runtime's level meter, not available in legacy path
+                    let _ = self.response_tx.send(FactoryResponse::MetersSet { count: 0 });
+                }
+                FactoryRequest::SetDefaultSource(_) => {
+                    // Needs the registry's default metadata, not available in legacy path
+                    let _ = self.response_tx.send(FactoryResponse::Error(
+                        "Default source is not supported by the legacy factory".to_string(),
+                    ));
+                }
                 FactoryRequest::Shutdown => {
                     info!("Factory received shutdown request");
                     break;
@@ -201,7 +351,7 @@ impl NodeFactory {
             "factory.name" => "support.null-audio-sink",
             "node.name" => props.name.as_str(),
             "node.description" => props.description.as_str(),
-            "media.class" => "Audio/Sink",
+            "media.class" => props.media_class.as_str(),
             "audio.channels" => props.channels.to_string().as_str(),
             "audio.position" => props.positions.as_str(),
             "undertone.managed" => "true",
@@ -0,0 +1,253 @@
+//! Effects chains built on `libpipewire-module-filter-chain`.
+//!
+//! A channel's effects become a filter-chain graph: EQ bands use the
+//! built-in biquads, while the gate, compressor and limiter use the SWH
+//! LADSPA plugins (`swh-plugins` must be installed). Mono plugins run once
+//! per side; the graph is always stereo.
+//!
+//! The module creates two nodes per channel: `ut-fx-<name>-in`, which takes
+//! the channel's audio, and `ut-fx-<name>-out`, which feeds its volume
+//! filters.
+
+use undertone_core::channel::ChannelConfig;
+use undertone_core::effects::{ChannelEffects, EqBandKind};
+
+/// Where a filter-chain node's implementation comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Plugin {
+    /// A filter-chain builtin, by label
+    Builtin(&'static str),
+    /// A LADSPA plugin: (library, label)
+    Ladspa(&'static str, &'static str),
+}
+
+/// Port layout of a plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ports {
+    /// One input and one output; the stage runs once per side
+    Mono { input: &'static str, output: &'static str },
+    /// Left and right inputs and outputs on a single node
+    Stereo { inputs: [&'static str; 2], outputs: [&'static str; 2] },
+}
+
+/// One step of the chain.
+#[derive(Debug, Clone, PartialEq)]
+struct Stage {
+    name: String,
+    plugin: Plugin,
+    ports: Ports,
+    controls: Vec<(&'static str, f32)>,
+}
+
+impl Stage {
+    /// Names of the filter-chain nodes this stage becomes.
+    fn node_names(&self) -> Vec<String> {
+        match self.ports {
+            Ports::Mono { .. } => vec![format!("{}_l", self.name), format!("{}_r", self.name)],
+            Ports::Stereo { .. } => vec![self.name.clone()],
+        }
+    }
+
+    /// Input port for a side (0 = left, 1 = right), as `node:port`.
+    fn input(&self, side: usize) -> String {
+        match self.ports {
+            Ports::Mono { input, .. } => format!("{}:{input}", self.node_names()[side]),
+            Ports::Stereo { inputs, .. } => format!("{}:{}", self.name, inputs[side]),
+        }
+    }
+
+    /// Output port for a side (0 = left, 1 = right), as `node:port`.
+    fn output(&self, side: usize) -> String {
+        match self.ports {
+            Ports::Mono { output, .. } => format!("{}:{output}", self.node_names()[side]),
+            Ports::Stereo { outputs, .. } => format!("{}:{}", self.name, outputs[side]),
+        }
+    }
+}
+
+/// A channel's effects as a filter-chain graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterChain {
+    stages: Vec<Stage>,
+}
+
+impl FilterChain {
+    /// Build the chain for a channel's effects.
+    ///
+    /// Returns `None` if no stage is enabled; the channel then feeds its
+    /// volume filters directly.
+    #[must_use]
+    pub fn new(effects: &ChannelEffects) -> Option<Self> {
+        let mut stages = Vec::new();
+
+        let gate = &effects.gate;
+        if gate.enabled {
+            stages.push(Stage {
+                name: "gate".to_string(),
+                plugin: Plugin::Ladspa("gate_1410", "gate"),
+                ports: Ports::Mono { input: "Input", output: "Output" },
+                controls: vec![
+                    ("Threshold (dB)", gate.threshold_db),
+                    ("Attack (ms)", gate.attack_ms),
+                    ("Hold (ms)", gate.hold_ms),
+                    ("Decay (ms)", gate.release_ms),
+                    ("Range (dB)", gate.range_db),
+                ],
+            });
+        }
+
+        if effects.eq.enabled {
+            for (i, band) in effects.eq.bands.iter().enumerate() {
+                let label = match band.kind {
+                    EqBandKind::Peaking => "bq_peaking",
+                    EqBandKind::LowShelf => "bq_lowshelf",
+                    EqBandKind::HighShelf => "bq_highshelf",
+                    EqBandKind::LowPass => "bq_lowpass",
+                    EqBandKind::HighPass => "bq_highpass",
+                };
+                stages.push(Stage {
+                    name: format!("eq{i}"),
+                    plugin: Plugin::Builtin(label),
+                    ports: Ports::Mono { input: "In", output: "Out" },
+                    controls: vec![("Freq", band.frequency), ("Q", band.q), ("Gain", band.gain_db)],
+                });
+            }
+        }
+
+        let comp = &effects.compressor;
+        if comp.enabled {
+            stages.push(Stage {
+                name: "comp".to_string(),
+                plugin: Plugin::Ladspa("sc4_1882", "sc4"),
+                ports: Ports::Stereo {
+                    inputs: ["Left input", "Right input"],
+                    outputs: ["Left output", "Right output"],
+                },
+                controls: vec![
+                    ("Attack time (ms)", comp.attack_ms),
+                    ("Release time (ms)", comp.release_ms),
+                    ("Threshold level (dB)", comp.threshold_db),
+                    ("Ratio (1:n)", comp.ratio),
+                    ("Knee radius (dB)", comp.knee_db),
+                    ("Makeup gain (dB)", comp.makeup_db),
+                ],
+            });
+        }
+
+        let limiter = &effects.limiter;
+        if limiter.enabled {
+            stages.push(Stage {
+                name: "limiter".to_string(),
+                plugin: Plugin::Ladspa("fast_lookahead_limiter_1913", "fastLookaheadLimiter"),
+                ports: Ports::Stereo {
+                    inputs: ["Input 1", "Input 2"],
+                    outputs: ["Output 1", "Output 2"],
+                },
+                controls: vec![
+                    ("Input gain (dB)", limiter.input_gain_db),
+                    ("Limit (dB)", limiter.limit_db),
+                    ("Release time (s)", limiter.release_ms / 1000.0),
+                ],
+            });
+        }
+
+        (!stages.is_empty()).then_some(Self { stages })
+    }
+
+    /// Whether two chains have the same nodes and links, differing at most
+    /// in control values.
+    ///
+    /// Chains with the same layout can be updated in place with
+    /// [`FilterChain::params`]; anything else needs the chain reloaded.
+    #[must_use]
+    pub fn same_layout(&self, other: &Self) -> bool {
+        self.stages.len() == other.stages.len()
+            && self
+                .stages
+                .iter()
+                .zip(&other.stages)
+                .all(|(a, b)| a.name == b.name && a.plugin == b.plugin)
+    }
+
+    /// Every control value, keyed as `node:control` the way the filter-chain
+    /// `params` property expects.
+    #[must_use]
+    pub fn params(&self) -> Vec<(String, f32)> {
+        self.stages
+            .iter()
+            .flat_map(|stage| {
+                stage.node_names().into_iter().flat_map(|node| {
+                    stage
+                        .controls
+                        .iter()
+                        .map(move |(control, value)| (format!("{node}:{control}"), *value))
+                })
+            })
+            .collect()
+    }
+
+    /// Arguments for loading `libpipewire-module-filter-chain` for a channel.
+    #[must_use]
+    pub fn module_args(&self, channel: &ChannelConfig) -> String {
+        let mut nodes = Vec::new();
+        for stage in &self.stages {
+            let (kind, plugin, label) = match stage.plugin {
+                Plugin::Builtin(label) => ("builtin", None, label),
+                Plugin::Ladspa(plugin, label) => ("ladspa", Some(plugin), label),
+            };
+            let control = stage
+                .controls
+                .iter()
+                .map(|(name, value)| format!("{} = {value}", quote(name)))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            for node in stage.node_names() {
+                let plugin = plugin.map(|p| format!(" plugin = {}", quote(p))).unwrap_or_default();
+                nodes.push(format!(
+                    "{{ type = {kind} name = {}{plugin} label = {} control = {{ {control} }} }}",
+                    quote(&node),
+                    quote(label),
+                ));
+            }
+        }
+
+        let mut links = Vec::new();
+        for pair in self.stages.windows(2) {
+            for side in 0..2 {
+                links.push(format!(
+                    "{{ output = {} input = {} }}",
+                    quote(&pair[0].output(side)),
+                    quote(&pair[1].input(side)),
+                ));
+            }
+        }
+
+        let first = &self.stages[0];
+        let last = &self.stages[self.stages.len() - 1];
+        let inputs = (0..2).map(|side| quote(&first.input(side))).collect::<Vec<_>>().join(" ");
+        let outputs = (0..2).map(|side| quote(&last.output(side))).collect::<Vec<_>>().join(" ");
+
+        let description = format!("Undertone: {} Effects", channel.display_name);
+        format!(
+            "{{ node.description = {description} media.name = {description} \
+             filter.graph = {{ nodes = [ {nodes} ] links = [ {links} ] \
+             inputs = [ {inputs} ] outputs = [ {outputs} ] }} \
+             audio.channels = 2 audio.position = [ FL FR ] \
+             capture.props = {{ node.name = {input} node.autoconnect = false \
+             node.passive = true undertone.managed = true }} \
+             playback.props = {{ node.name = {output} node.autoconnect = false \
+             node.passive = true undertone.managed = true }} }}",
+            description = quote(&description),
+            nodes = nodes.join(" "),
+            links = links.join(" "),
+            input = quote(&channel.fx_input_node_name()),
+            output = quote(&channel.fx_output_node_name()),
+        )
+    }
+}
+
+/// Quote a string for SPA JSON.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
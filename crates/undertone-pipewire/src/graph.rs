@@ -264,6 +264,20 @@ impl GraphManager {
             .collect()
     }
 
+    /// Get all recording client nodes (apps capturing audio).
+    #[must_use]
+    pub fn get_capture_clients(&self) -> Vec<NodeInfo> {
+        self.nodes
+            .read()
+            .values()
+            .filter(|n| {
+                n.media_class.as_ref().is_some_and(|c| c == "Stream/Input/Audio")
+                    && !n.is_undertone_managed
+            })
+            .cloned()
+            .collect()
+    }
+
     /// Get all available audio output devices (sinks).
     ///
     /// Returns physical audio outputs like headphones, speakers, HDMI, etc.
@@ -295,6 +309,44 @@ impl GraphManager {
         self.created_links.write().insert(description, id);
     }
 
+    /// Forget a node we created (after it has been destroyed).
+    pub fn forget_created_node(&self, name: &str) -> Option<u32> {
+        self.created_nodes.write().remove(name)
+    }
+
+    /// Forget all created links that start or end at the given node.
+    ///
+    /// Link descriptions have the form `<output>-><input>:<channel>`.
+    pub fn forget_created_links_for(&self, node_name: &str) {
+        let as_output = format!("{node_name}->");
+        let as_input = format!("->{node_name}:");
+        self.created_links.write().retain(|description, _| {
+            !description.starts_with(&as_output) && !description.contains(&as_input)
+        });
+    }
+
+    /// Check whether a node is in the graph and has both input and output ports.
+    ///
+    /// Newly created sinks and filters are usable for linking once this returns true.
+    #[must_use]
+    pub fn has_ports_for_node(&self, name: &str) -> bool {
+        self.get_node_by_name(name).is_some_and(|node| {
+            !self.get_input_ports(node.id).is_empty() && !self.get_output_ports(node.id).is_empty()
+        })
+    }
+
+    /// Forget the whole registry view along with our created nodes and links.
+    ///
+    /// Used when the `PipeWire` connection is lost: our nodes and links died
+    /// with it, and the registry replays every global on reconnect.
+    pub fn clear(&self) {
+        self.nodes.write().clear();
+        self.ports.write().clear();
+        self.links.write().clear();
+        self.created_nodes.write().clear();
+        self.created_links.write().clear();
+    }
+
     /// Get the ID of a node we created by name.
     #[must_use]
     pub fn get_created_node_id(&self, name: &str) -> Option<u32> {
@@ -4,12 +4,16 @@
 //! - Connecting to the `PipeWire` daemon
 //! - Creating and managing virtual audio nodes
 //! - Managing links between nodes
+//! - Loading per-channel effects chains
 //! - Monitoring the audio graph for changes
+//! - Metering audio levels
 
 pub mod error;
 pub mod factory;
+pub mod filter_chain;
 pub mod graph;
 pub mod link;
+pub mod meter;
 pub mod monitor;
 pub mod node;
 pub mod reconcile;
@@ -17,6 +21,9 @@ pub mod runtime;
 
 pub use error::{PwError, PwResult};
 pub use factory::{FactoryRequest, FactoryResponse, NodeFactory};
+pub use filter_chain::FilterChain;
 pub use graph::GraphManager;
+pub use meter::{LevelMeter, MeterSource, MeterTarget};
 pub use monitor::{GraphEvent, GraphMonitor};
+pub use reconcile::{ReconcileAction, ReconcileReport, Reconciler};
 pub use runtime::PipeWireRuntime;
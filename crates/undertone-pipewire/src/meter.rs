@@ -0,0 +1,257 @@
+//! Level metering through passive capture streams.
+//!
+//! Each metered node gets a small capture stream: sinks are tapped on their
+//! monitor ports, the mic is captured directly. The stream's process callback
+//! feeds a shared `LevelMeter`, which the daemon drains at its publish rate.
+//! The callback runs on the realtime thread, so it never waits for the lock:
+//! each stream accumulates locally and hands its readings over whenever the
+//! lock is free.
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use libspa::param::ParamType;
+use libspa::param::audio::{AudioFormat, AudioInfoRaw};
+use libspa::param::format::{MediaSubtype, MediaType};
+use libspa::param::format_utils;
+use libspa::pod::serialize::PodSerializer;
+use libspa::pod::{Object, Value};
+use libspa::utils::{Direction, SpaTypes};
+use parking_lot::Mutex;
+use pipewire::properties::properties;
+use pipewire::spa::pod::Pod;
+use pipewire::stream::{StreamFlags, StreamListener, StreamRc};
+use tracing::{debug, info};
+
+use undertone_core::channel::ChannelConfig;
+use undertone_core::meter::{LevelAccumulator, MeterLevels};
+use undertone_core::mixer::MixConfig;
+
+use crate::error::{PwError, PwResult};
+use crate::graph::GraphManager;
+
+/// What a meter measures.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MeterSource {
+    /// A channel sink (pre-fader), by channel name
+    Channel(String),
+    /// A mix bus, by mix name (e.g. "stream", "monitor")
+    Mix(String),
+    /// The microphone
+    Mic,
+}
+
+impl MeterSource {
+    /// Short identifier used in stream node names, e.g. `ch-music`.
+    fn slug(&self) -> String {
+        match self {
+            Self::Channel(name) => format!("ch-{name}"),
+            Self::Mix(name) => format!("mix-{name}"),
+            Self::Mic => "mic".to_string(),
+        }
+    }
+}
+
+/// A node to meter.
+#[derive(Debug, Clone)]
+pub struct MeterTarget {
+    /// What the readings are reported as
+    pub source: MeterSource,
+    /// Name of the node to capture from
+    pub node_name: String,
+    /// Capture a sink's monitor ports rather than a source's output
+    pub capture_sink: bool,
+}
+
+impl MeterTarget {
+    /// Build the meter targets for every channel, every mix and the mic.
+    ///
+    /// The mic is only included if a Wave:3 source is currently in the graph.
+    #[must_use]
+    pub fn for_channels(
+        channels: &[ChannelConfig],
+        mixes: &[MixConfig],
+        graph: &GraphManager,
+    ) -> Vec<Self> {
+        let mut targets: Vec<Self> = channels
+            .iter()
+            .map(|channel| Self {
+                source: MeterSource::Channel(channel.name.clone()),
+                node_name: channel.node_name(),
+                capture_sink: true,
+            })
+            .collect();
+
+        for mix in mixes {
+            targets.push(Self {
+                source: MeterSource::Mix(mix.name.clone()),
+                node_name: mix.node_name(),
+                capture_sink: true,
+            });
+        }
+
+        if let Some(mic) = graph.find_wave3_source() {
+            targets.push(Self {
+                source: MeterSource::Mic,
+                node_name: mic.name,
+                capture_sink: false,
+            });
+        }
+
+        targets
+    }
+}
+
+/// Level accumulators shared between the `PipeWire` thread and the daemon.
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    accumulators: Mutex<HashMap<MeterSource, LevelAccumulator>>,
+}
+
+impl LevelMeter {
+    /// Take the levels measured since the last call, for every active meter.
+    #[must_use]
+    pub fn take_levels(&self) -> HashMap<MeterSource, MeterLevels> {
+        self.accumulators
+            .lock()
+            .iter_mut()
+            .map(|(source, acc)| (source.clone(), acc.take()))
+            .collect()
+    }
+
+    /// Replace the set of active meters, dropping readings for removed ones.
+    pub(crate) fn reset(&self, sources: impl IntoIterator<Item = MeterSource>) {
+        *self.accumulators.lock() =
+            sources.into_iter().map(|source| (source, LevelAccumulator::default())).collect();
+    }
+
+    /// Move a stream's readings into the shared accumulator unless the lock
+    /// is held, in which case they stay with the stream until its next block.
+    fn feed(&self, source: &MeterSource, readings: &mut LevelAccumulator) {
+        let Some(mut accumulators) = self.accumulators.try_lock() else { return };
+        match accumulators.get_mut(source) {
+            Some(acc) => acc.merge(readings),
+            None => *readings = LevelAccumulator::default(),
+        }
+    }
+}
+
+/// Per-stream state handed to the stream callbacks.
+struct MeterData {
+    source: MeterSource,
+    channels: usize,
+    meter: Arc<LevelMeter>,
+    /// Decoded samples, reused across process calls
+    samples: Vec<f32>,
+    /// Readings not yet handed to `meter`
+    readings: LevelAccumulator,
+}
+
+/// A running meter stream. Dropping it disconnects the stream.
+pub(crate) struct MeterStream {
+    _listener: StreamListener<MeterData>,
+    _stream: StreamRc,
+}
+
+/// Create and connect a passive capture stream for a meter target.
+pub(crate) fn create_meter_stream(
+    core: &pipewire::core::CoreRc,
+    target: &MeterTarget,
+    meter: &Arc<LevelMeter>,
+) -> PwResult<MeterStream> {
+    let stream_name = format!("ut-meter-{}", target.source.slug());
+    info!(name = %stream_name, target = %target.node_name, "Creating meter stream");
+
+    let props = properties! {
+        "media.type" => "Audio",
+        "media.category" => "Monitor",
+        "node.name" => stream_name.as_str(),
+        "target.object" => target.node_name.as_str(),
+        "stream.capture.sink" => if target.capture_sink { "true" } else { "false" },
+        "undertone.managed" => "true",
+        "undertone.meter" => "true",
+        // Don't keep the graph running or follow other nodes around
+        "node.passive" => "true",
+        "node.dont-reconnect" => "true",
+        "node.dont-fallback" => "true",
+    };
+
+    let stream = StreamRc::new(core.clone(), &stream_name, props)
+        .map_err(|e| PwError::NodeCreationFailed(format!("Failed to create meter stream: {e}")))?;
+
+    let data = MeterData {
+        source: target.source.clone(),
+        channels: 2,
+        meter: Arc::clone(meter),
+        samples: Vec::new(),
+        readings: LevelAccumulator::default(),
+    };
+
+    let listener = stream
+        .add_local_listener_with_user_data(data)
+        .param_changed(|_, data, id, param| {
+            let Some(param) = param else { return };
+            if id != ParamType::Format.as_raw() {
+                return;
+            }
+            let Ok((MediaType::Audio, MediaSubtype::Raw)) = format_utils::parse_format(param)
+            else {
+                return;
+            };
+
+            let mut info = AudioInfoRaw::new();
+            if info.parse(param).is_ok() {
+                data.channels = info.channels().max(1) as usize;
+                debug!(source = ?data.source, channels = data.channels, "Meter format negotiated");
+            }
+        })
+        .process(|stream, data| {
+            let Some(mut buffer) = stream.dequeue_buffer() else { return };
+            let Some(chunk) = buffer.datas_mut().first_mut() else { return };
+
+            let offset = chunk.chunk().offset() as usize;
+            let size = chunk.chunk().size() as usize;
+            if let Some(bytes) = chunk.data() {
+                let end = (offset + size).min(bytes.len());
+                data.samples.clear();
+                data.samples.extend(
+                    bytes[offset.min(end)..end]
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                );
+                data.readings.add_interleaved(&data.samples, data.channels);
+                data.meter.feed(&data.source, &mut data.readings);
+            }
+        })
+        .register()
+        .map_err(|e| {
+            PwError::NodeCreationFailed(format!("Failed to listen on meter stream: {e}"))
+        })?;
+
+    // Ask for F32 samples; leave rate and channels at the node's native values
+    let mut audio_info = AudioInfoRaw::new();
+    audio_info.set_format(AudioFormat::F32LE);
+    let format = Object {
+        type_: SpaTypes::ObjectParamFormat.as_raw(),
+        id: ParamType::EnumFormat.as_raw(),
+        properties: audio_info.into(),
+    };
+    let bytes: Vec<u8> = PodSerializer::serialize(Cursor::new(Vec::new()), &Value::Object(format))
+        .map_err(|e| PwError::NodeCreationFailed(format!("Failed to build meter format: {e:?}")))?
+        .0
+        .into_inner();
+    let pod = Pod::from_bytes(&bytes)
+        .ok_or_else(|| PwError::NodeCreationFailed("Invalid meter format pod".to_string()))?;
+
+    stream
+        .connect(
+            Direction::Input,
+            None,
+            StreamFlags::AUTOCONNECT | StreamFlags::MAP_BUFFERS,
+            &mut [pod],
+        )
+        .map_err(|e| PwError::NodeCreationFailed(format!("Failed to connect meter stream: {e}")))?;
+
+    Ok(MeterStream { _listener: listener, _stream: stream })
+}
@@ -18,7 +18,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::error::{PwError, PwResult};
 use crate::graph::GraphManager;
-use crate::node::{NodeInfo, PortDirection, PortInfo};
+use crate::node::{NodeInfo, PortDirection, PortInfo, process_id};
 
 /// Events emitted by the graph monitor.
 #[derive(Debug, Clone)]
@@ -47,6 +47,10 @@ pub enum GraphEvent {
     ClientAppeared { id: u32, name: String, pid: Option<u32> },
     /// An audio client disappeared
     ClientDisappeared { id: u32 },
+    /// A recording client (an app capturing audio) appeared
+    CaptureClientAppeared { id: u32, name: String, pid: Option<u32> },
+    /// The session manager (`WirePlumber`) came back after going away
+    SessionManagerRestarted,
 }
 
 /// Monitors the `PipeWire` graph for changes.
@@ -136,9 +140,7 @@ impl GraphMonitor {
                 let app_name = props.and_then(|p| p.get("application.name")).map(String::from);
                 let binary =
                     props.and_then(|p| p.get("application.process.binary")).map(String::from);
-                let pid = props
-                    .and_then(|p| p.get("application.process.id"))
-                    .and_then(|s| s.parse().ok());
+                let pid = props.and_then(process_id);
 
                 let is_undertone = name.starts_with("ut-");
 
@@ -188,6 +190,12 @@ impl GraphMonitor {
                         name: app_name.unwrap_or_else(|| name.clone()),
                         pid,
                     });
+                } else if media_class.as_deref() == Some("Stream/Input/Audio") && !is_undertone {
+                    let _ = event_tx.blocking_send(GraphEvent::CaptureClientAppeared {
+                        id: global.id,
+                        name: app_name.unwrap_or_else(|| name.clone()),
+                        pid,
+                    });
                 }
             }
 
@@ -1,8 +1,11 @@
 //! `PipeWire` node management.
 
+use pipewire::spa::utils::dict::DictRef;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use undertone_core::layout::ChannelLayout;
+
 /// Information about a `PipeWire` node.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeInfo {
@@ -48,21 +51,36 @@ impl NodeInfo {
     }
 
     /// Check if this is an Undertone channel node.
-    /// Excludes volume filter nodes which also start with "ut-ch-".
+    /// Excludes volume filter nodes (`ut-ch-<channel>-<mix>-vol`), which also
+    /// start with "ut-ch-".
     #[must_use]
     pub fn is_undertone_channel(&self) -> bool {
-        self.name.starts_with("ut-ch-")
-            && !self.name.ends_with("-stream-vol")
-            && !self.name.ends_with("-monitor-vol")
+        self.name.starts_with("ut-ch-") && !self.name.ends_with("-vol")
     }
 
-    /// Check if this is an Undertone mix node.
+    /// Check if this is an Undertone mix node (`ut-<mix>-mix`).
+    /// Mix names have no dashes, which keeps out channel nodes such as
+    /// `ut-ch-mix` or `ut-direct-mix`.
     #[must_use]
     pub fn is_undertone_mix(&self) -> bool {
-        self.name.starts_with("ut-stream-") || self.name.starts_with("ut-monitor-")
+        self.name
+            .strip_prefix("ut-")
+            .and_then(|name| name.strip_suffix("-mix"))
+            .is_some_and(|mix| !mix.is_empty() && !mix.contains('-'))
     }
 }
 
+/// Process ID of the client behind a node.
+///
+/// Prefers `pipewire.sec.pid`, which `PipeWire` takes from the socket and a
+/// client cannot fake, over the self-reported `application.process.id`.
+pub(crate) fn process_id(props: &DictRef) -> Option<u32> {
+    props
+        .get("pipewire.sec.pid")
+        .or_else(|| props.get("application.process.id"))
+        .and_then(|s| s.parse().ok())
+}
+
 /// Information about a `PipeWire` port.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortInfo {
@@ -78,6 +96,15 @@ pub struct PortInfo {
     pub channel: Option<String>,
 }
 
+impl PortInfo {
+    /// Speaker position of the port: its `audio.channel`, or failing that
+    /// the suffix of its name (`monitor_FL` → `FL`).
+    #[must_use]
+    pub fn position(&self) -> Option<&str> {
+        self.channel.as_deref().or_else(|| self.name.rsplit_once('_').map(|(_, position)| position))
+    }
+}
+
 /// Port direction.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PortDirection {
@@ -98,6 +125,9 @@ pub struct VirtualSinkProps {
     pub channels: u32,
     /// Audio channel positions (e.g., "FL,FR")
     pub positions: String,
+    /// Media class (`Audio/Sink`, or `Audio/Source/Virtual` for a node apps
+    /// record from)
+    pub media_class: String,
 }
 
 impl VirtualSinkProps {
@@ -109,9 +139,23 @@ impl VirtualSinkProps {
             description: description.to_string(),
             channels: 2,
             positions: "FL,FR".to_string(),
+            media_class: "Audio/Sink".to_string(),
         }
     }
 
+    /// Create properties for a stereo virtual source: audio linked into its
+    /// inputs is offered to apps as a microphone.
+    #[must_use]
+    pub fn virtual_source(name: &str, description: &str) -> Self {
+        Self { media_class: "Audio/Source/Virtual".to_string(), ..Self::stereo(name, description) }
+    }
+
+    /// Use a channel's speaker layout instead of stereo.
+    #[must_use]
+    pub fn with_layout(self, layout: ChannelLayout) -> Self {
+        Self { channels: layout.channels(), positions: layout.positions(), ..self }
+    }
+
     /// Create properties for a mono virtual sink.
     #[must_use]
     pub fn mono(name: &str, description: &str) -> Self {
@@ -120,6 +164,7 @@ impl VirtualSinkProps {
             description: description.to_string(),
             channels: 1,
             positions: "MONO".to_string(),
+            media_class: "Audio/Sink".to_string(),
         }
     }
 }
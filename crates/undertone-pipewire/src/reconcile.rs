@@ -1,18 +1,27 @@
 //! State reconciliation for self-healing.
 
+use std::collections::HashSet;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{debug, info, warn};
 
-use undertone_core::channel::ChannelConfig;
+use undertone_core::channel::{ChannelConfig, ChannelState};
+use undertone_core::layout::{ChannelLayout, position_links};
+use undertone_core::mixer::{MixConfig, MixType};
 
+use crate::filter_chain::FilterChain;
 use crate::graph::GraphManager;
-use crate::node::VirtualSinkProps;
+use crate::node::{NodeInfo, VirtualSinkProps};
+use crate::runtime::PipeWireRuntime;
 
 /// Actions that the reconciler determines need to be taken.
 #[derive(Debug, Clone)]
 pub enum ReconcileAction {
     /// Create a virtual sink node
     CreateSink(VirtualSinkProps),
+    /// Create a volume filter node
+    CreateVolumeFilter { name: String, description: String, layout: ChannelLayout },
+    /// Load a channel's effects chain
+    LoadEffects { channel: ChannelConfig, chain: FilterChain },
     /// Create a link between nodes
     CreateLink { output_node: String, output_port: String, input_node: String, input_port: String },
     /// Destroy a node
@@ -23,170 +32,401 @@ pub enum ReconcileAction {
     Warn(String),
 }
 
+impl ReconcileAction {
+    /// Whether this action creates or destroys a node (as opposed to a link).
+    #[must_use]
+    pub fn is_node_action(&self) -> bool {
+        matches!(
+            self,
+            Self::CreateSink(_)
+                | Self::CreateVolumeFilter { .. }
+                | Self::LoadEffects { .. }
+                | Self::DestroyNode(_)
+        )
+    }
+}
+
+/// Outcome of executing a reconciliation plan.
+#[derive(Debug, Default, Clone)]
+pub struct ReconcileReport {
+    /// Nodes created
+    pub nodes_created: usize,
+    /// Nodes destroyed
+    pub nodes_destroyed: usize,
+    /// Links created
+    pub links_created: usize,
+    /// Links destroyed
+    pub links_destroyed: usize,
+    /// Actions that failed, with the reason
+    pub failures: Vec<String>,
+}
+
+impl ReconcileReport {
+    /// Whether any node was created or destroyed.
+    #[must_use]
+    pub fn changed_nodes(&self) -> bool {
+        self.nodes_created > 0 || self.nodes_destroyed > 0
+    }
+
+    /// Fold another report into this one.
+    pub fn merge(&mut self, other: Self) {
+        self.nodes_created += other.nodes_created;
+        self.nodes_destroyed += other.nodes_destroyed;
+        self.links_created += other.links_created;
+        self.links_destroyed += other.links_destroyed;
+        self.failures.extend(other.failures);
+    }
+}
+
 /// Reconciler that compares desired state vs actual state.
 pub struct Reconciler {
     graph: Arc<GraphManager>,
+    /// Whether the stream mix should have its virtual source
+    stream_source: bool,
 }
 
 impl Reconciler {
     /// Create a new reconciler.
     #[must_use]
     pub fn new(graph: Arc<GraphManager>) -> Self {
-        Self { graph }
+        Self { graph, stream_source: false }
+    }
+
+    /// Expect the stream mix's virtual source (`ut-stream-source`) to exist
+    /// and be fed by the mix, or to be gone.
+    #[must_use]
+    pub fn with_stream_source(mut self, enabled: bool) -> Self {
+        self.stream_source = enabled;
+        self
     }
 
     /// Compute actions needed to reach desired state.
     ///
     /// This compares the current `PipeWire` graph state against what
     /// we expect to exist and returns a list of actions to take.
+    /// `monitor_output` is the node name the monitor mix should play to
+    /// (`wave3-sink` means the Wave:3 headphones, wherever they are).
     #[must_use]
-    pub fn reconcile(&self, channels: &[ChannelConfig]) -> Vec<ReconcileAction> {
+    pub fn reconcile(
+        &self,
+        channels: &[ChannelState],
+        mixes: &[MixConfig],
+        monitor_output: &str,
+    ) -> Vec<ReconcileAction> {
         let mut actions = Vec::new();
+        let mix_ids: Vec<MixType> = mixes.iter().map(MixConfig::id).collect();
+        // Effects chains are stereo; surround channels never get one
+        let chains: Vec<(&ChannelConfig, Option<FilterChain>)> = channels
+            .iter()
+            .map(|c| {
+                let chain = FilterChain::new(&c.effects).filter(|_| !c.config.layout.is_surround());
+                (&c.config, chain)
+            })
+            .collect();
+
+        // Remove channel, effects and mix nodes left behind by deleted
+        // channels, disabled effects and deleted mixes
+        let stream_source =
+            self.stream_source.then(|| mixes.iter().find(|m| m.id() == MixType::STREAM)).flatten();
+        let expected: HashSet<String> = chains
+            .iter()
+            .flat_map(|(c, chain)| {
+                let fx_nodes = chain
+                    .as_ref()
+                    .map(|_| [c.fx_input_node_name(), c.fx_output_node_name()])
+                    .into_iter()
+                    .flatten();
+                std::iter::once(c.node_name())
+                    .chain(fx_nodes)
+                    .chain(c.direct_out.then(|| c.direct_node_name()))
+                    .chain(mix_ids.iter().map(|mix| c.vol_node_name(mix)))
+            })
+            .chain(mix_ids.iter().flat_map(|mix| [mix.mix_node_name(), mix.mic_vol_node_name()]))
+            .chain(stream_source.map(|mix| mix.id().source_node_name()))
+            .collect();
+        for node in self.graph.get_all_nodes() {
+            let is_mix_node = node.name.starts_with("ut-") && node.name.ends_with("-mix");
+            let is_mic_node = node.name.starts_with("ut-mic-") && node.name.ends_with("-vol");
+            // Only the stream mix ever gets a virtual source
+            let is_source_node = node.name == MixType::STREAM.source_node_name();
+            let is_direct_node = node.name.starts_with("ut-direct-");
+            let is_fx_node = node.name.starts_with("ut-fx-")
+                && (node.name.ends_with("-in") || node.name.ends_with("-out"));
+            if node.is_undertone_managed
+                && (node.name.starts_with("ut-ch-")
+                    || is_mix_node
+                    || is_mic_node
+                    || is_fx_node
+                    || is_source_node
+                    || is_direct_node)
+                && !expected.contains(&node.name)
+            {
+                info!(name = %node.name, id = node.id, "Stale node, will destroy");
+                actions.push(ReconcileAction::DestroyNode(node.id));
+            }
+        }
 
         // Check that all channel sinks exist
-        for channel in channels {
+        for channel in channels.iter().map(|c| &c.config) {
             let node_name = channel.node_name();
             if self.graph.get_node_by_name(&node_name).is_none() {
                 info!(name = %node_name, "Channel node missing, will create");
-                actions.push(ReconcileAction::CreateSink(VirtualSinkProps::stereo(
-                    &node_name,
-                    &format!("Undertone: {} Channel", channel.display_name),
-                )));
+                actions.push(ReconcileAction::CreateSink(
+                    VirtualSinkProps::stereo(
+                        &node_name,
+                        &format!("Undertone: {} Channel", channel.display_name),
+                    )
+                    .with_layout(channel.layout),
+                ));
             }
         }
 
         // Check that mix nodes exist
-        let required_mixes = [
-            ("ut-stream-mix", "Undertone: Stream Mix"),
-            ("ut-stream-out", "Undertone: Stream Output"),
-            ("ut-monitor-mix", "Undertone: Monitor Mix"),
-        ];
-
-        for (name, description) in required_mixes {
-            if self.graph.get_node_by_name(name).is_none() {
-                info!(name, "Mix node missing, will create");
-                actions
-                    .push(ReconcileAction::CreateSink(VirtualSinkProps::stereo(name, description)));
+        for mix in mixes {
+            let name = mix.node_name();
+            if self.graph.get_node_by_name(&name).is_none() {
+                info!(name = %name, "Mix node missing, will create");
+                actions.push(ReconcileAction::CreateSink(VirtualSinkProps::stereo(
+                    &name,
+                    &format!("Undertone: {} Mix", mix.display_name),
+                )));
             }
         }
 
-        // Check that volume filter nodes exist for each channel
-        for channel in channels {
-            let stream_vol_name = channel.stream_vol_node_name();
-            let monitor_vol_name = channel.monitor_vol_node_name();
-
-            if self.graph.get_node_by_name(&stream_vol_name).is_none() {
-                info!(name = %stream_vol_name, "Stream volume node missing, will create");
-                actions.push(ReconcileAction::CreateSink(VirtualSinkProps::stereo(
-                    &stream_vol_name,
-                    &format!("Undertone: {} Stream Volume", channel.display_name),
+        // Check that the stream mix's virtual source exists
+        if let Some(mix) = stream_source {
+            let name = mix.id().source_node_name();
+            if self.graph.get_node_by_name(&name).is_none() {
+                info!(name = %name, "Mix source missing, will create");
+                actions.push(ReconcileAction::CreateSink(VirtualSinkProps::virtual_source(
+                    &name,
+                    &format!("Undertone {}", mix.display_name),
                 )));
             }
+        }
 
-            if self.graph.get_node_by_name(&monitor_vol_name).is_none() {
-                info!(name = %monitor_vol_name, "Monitor volume node missing, will create");
-                actions.push(ReconcileAction::CreateSink(VirtualSinkProps::stereo(
-                    &monitor_vol_name,
-                    &format!("Undertone: {} Monitor Volume", channel.display_name),
+        // Check that direct-out sources exist for the channels that want one
+        for channel in channels.iter().map(|c| &c.config).filter(|c| c.direct_out) {
+            let name = channel.direct_node_name();
+            if self.graph.get_node_by_name(&name).is_none() {
+                info!(name = %name, "Direct-out source missing, will create");
+                actions.push(ReconcileAction::CreateSink(VirtualSinkProps::virtual_source(
+                    &name,
+                    &format!("Undertone {} Direct", channel.display_name),
                 )));
             }
         }
 
-        // Check Wave:3 nodes exist
-        let wave3_sink = self.graph.find_wave3_sink();
-        if wave3_sink.is_none() {
-            actions.push(ReconcileAction::Warn(
-                "Wave:3 sink not found - device may be disconnected".to_string(),
-            ));
+        // Check that effects chains are loaded
+        for (channel, chain) in &chains {
+            let Some(chain) = chain else {
+                continue;
+            };
+            if self.graph.get_node_by_name(&channel.fx_input_node_name()).is_none() {
+                info!(channel = %channel.name, "Effects chain missing, will load");
+                actions.push(ReconcileAction::LoadEffects {
+                    channel: (*channel).clone(),
+                    chain: chain.clone(),
+                });
+            }
         }
 
-        // Check that links from volume nodes to mix nodes exist
-        let stream_mix = self.graph.get_node_by_name("ut-stream-mix");
-        let monitor_mix = self.graph.get_node_by_name("ut-monitor-mix");
+        // Check that volume filter nodes exist for each channel and mix
+        for channel in channels.iter().map(|c| &c.config) {
+            for mix in mixes {
+                let vol_name = channel.vol_node_name(&mix.id());
+                if self.graph.get_node_by_name(&vol_name).is_none() {
+                    info!(name = %vol_name, "Volume node missing, will create");
+                    actions.push(ReconcileAction::CreateVolumeFilter {
+                        name: vol_name,
+                        description: format!(
+                            "Undertone: {} {} Volume",
+                            channel.display_name, mix.display_name
+                        ),
+                        layout: channel.layout,
+                    });
+                }
+            }
+        }
 
-        for channel in channels {
-            let stream_vol_name = channel.stream_vol_node_name();
-            let monitor_vol_name = channel.monitor_vol_node_name();
+        // Check that the mic volume filters exist
+        for mix in mixes {
+            let name = mix.id().mic_vol_node_name();
+            if self.graph.get_node_by_name(&name).is_none() {
+                info!(name = %name, "Mic volume node missing, will create");
+                actions.push(ReconcileAction::CreateVolumeFilter {
+                    name,
+                    description: format!("Undertone: Mic {} Volume", mix.display_name),
+                    layout: ChannelLayout::Stereo,
+                });
+            }
+        }
+
+        // Check the monitor output exists
+        let monitor_sink = if monitor_output == "wave3-sink" {
+            self.graph.find_wave3_sink()
+        } else {
+            self.graph.get_node_by_name(monitor_output)
+        };
+        if monitor_sink.is_none() {
+            actions.push(ReconcileAction::Warn(format!(
+                "Monitor output {monitor_output} not found - device may be disconnected"
+            )));
+        }
+
+        // Check that links from channels through volume nodes to mix nodes exist
+        let mix_nodes: Vec<_> = mix_ids
+            .iter()
+            .map(|mix| (mix, self.graph.get_node_by_name(&mix.mix_node_name())))
+            .collect();
+
+        for (channel, chain) in &chains {
+            let channel_name = channel.node_name();
+            let channel_node = self.graph.get_node_by_name(&channel_name);
 
-            // Check stream volume → stream mix link
-            if let (Some(vol_node), Some(mix_node)) =
-                (self.graph.get_node_by_name(&stream_vol_name), stream_mix.as_ref())
-                && !self.graph.has_link(vol_node.id, mix_node.id)
+            // A channel with a loaded effects chain feeds its filters through
+            // it; until the chain is up, the channel feeds them directly
+            let fx_input = self.graph.get_node_by_name(&channel.fx_input_node_name());
+            let fx_output = self.graph.get_node_by_name(&channel.fx_output_node_name());
+            let fx = chain.as_ref().and(fx_input.zip(fx_output));
+
+            if let (Some(channel_node), Some((fx_input, _))) = (channel_node.as_ref(), fx.as_ref())
+                && !self.graph.has_link(channel_node.id, fx_input.id)
             {
-                info!(from = %stream_vol_name, to = "ut-stream-mix", "Link missing");
-                actions.push(ReconcileAction::CreateLink {
-                    output_node: stream_vol_name.clone(),
-                    output_port: "monitor_FL".to_string(),
-                    input_node: "ut-stream-mix".to_string(),
-                    input_port: "playback_FL".to_string(),
-                });
-                actions.push(ReconcileAction::CreateLink {
-                    output_node: stream_vol_name,
-                    output_port: "monitor_FR".to_string(),
-                    input_node: "ut-stream-mix".to_string(),
-                    input_port: "playback_FR".to_string(),
-                });
+                info!(from = %channel_name, to = %fx_input.name, "Link missing");
+                actions.extend(link_actions(&channel_name, "monitor", &fx_input.name, "input"));
+            }
+
+            for (mix, mix_node) in &mix_nodes {
+                let vol_name = channel.vol_node_name(mix);
+                let Some(vol_node) = self.graph.get_node_by_name(&vol_name) else {
+                    continue;
+                };
+
+                match (channel_node.as_ref(), fx.as_ref()) {
+                    // Check effects chain → volume filter link, and that the
+                    // channel no longer bypasses the chain
+                    (channel_node, Some((_, fx_output))) => {
+                        if !self.graph.has_link(fx_output.id, vol_node.id) {
+                            info!(from = %fx_output.name, to = %vol_name, "Link missing");
+                            actions.extend(link_actions(
+                                &fx_output.name,
+                                "output",
+                                &vol_name,
+                                "playback",
+                            ));
+                        }
+                        if let Some(channel_node) = channel_node {
+                            for link in self.graph.get_links_for_node(channel_node.id) {
+                                if link.output_node == channel_node.id
+                                    && link.input_node == vol_node.id
+                                {
+                                    info!(from = %channel_name, to = %vol_name, "Bypass link, will destroy");
+                                    actions.push(ReconcileAction::DestroyLink(link.id));
+                                }
+                            }
+                        }
+                    }
+                    // Check channel → volume filter link
+                    (Some(channel_node), None) => {
+                        if !self.graph.has_link(channel_node.id, vol_node.id) {
+                            info!(from = %channel_name, to = %vol_name, "Link missing");
+                            actions.extend(layout_link_actions(
+                                &channel_name,
+                                &vol_name,
+                                channel.layout,
+                            ));
+                        }
+                    }
+                    (None, None) => {}
+                }
+
+                // Check volume filter → mix link
+                if let Some(mix_node) = mix_node
+                    && !self.graph.has_link(vol_node.id, mix_node.id)
+                {
+                    info!(from = %vol_name, to = %mix_node.name, "Link missing");
+                    actions.extend(downmix_link_actions(
+                        &vol_name,
+                        &mix_node.name,
+                        "playback",
+                        channel.layout,
+                    ));
+                }
+            }
+
+            // Check the stream volume filter → direct-out source link
+            if channel.direct_out {
+                let vol_name = channel.vol_node_name(&MixType::STREAM);
+                let direct_name = channel.direct_node_name();
+                if let (Some(vol_node), Some(direct)) = (
+                    self.graph.get_node_by_name(&vol_name),
+                    self.graph.get_node_by_name(&direct_name),
+                ) && !self.graph.has_link(vol_node.id, direct.id)
+                {
+                    info!(from = %vol_name, to = %direct_name, "Link missing");
+                    actions.extend(downmix_link_actions(
+                        &vol_name,
+                        &direct_name,
+                        "input",
+                        channel.layout,
+                    ));
+                }
             }
+        }
+
+        // Mic → mic volume filters → mixes
+        let mic = self.graph.find_wave3_source();
+        for (mix, mix_node) in &mix_nodes {
+            let filter_name = mix.mic_vol_node_name();
+            let Some(filter) = self.graph.get_node_by_name(&filter_name) else {
+                continue;
+            };
 
-            // Check monitor volume → monitor mix link
-            if let (Some(vol_node), Some(mix_node)) =
-                (self.graph.get_node_by_name(&monitor_vol_name), monitor_mix.as_ref())
-                && !self.graph.has_link(vol_node.id, mix_node.id)
+            if let Some(mic) = mic.as_ref()
+                && !self.graph.has_link(mic.id, filter.id)
             {
-                info!(from = %monitor_vol_name, to = "ut-monitor-mix", "Link missing");
-                actions.push(ReconcileAction::CreateLink {
-                    output_node: monitor_vol_name.clone(),
-                    output_port: "monitor_FL".to_string(),
-                    input_node: "ut-monitor-mix".to_string(),
-                    input_port: "playback_FL".to_string(),
-                });
-                actions.push(ReconcileAction::CreateLink {
-                    output_node: monitor_vol_name,
-                    output_port: "monitor_FR".to_string(),
-                    input_node: "ut-monitor-mix".to_string(),
-                    input_port: "playback_FR".to_string(),
-                });
+                // A mono mic feeds both sides of the filter
+                let links = port_link_actions(&self.graph, mic, &filter);
+                if links.is_empty() {
+                    actions.push(ReconcileAction::Warn(format!(
+                        "Mic {} has no ports to link yet",
+                        mic.name
+                    )));
+                } else {
+                    info!(from = %mic.name, to = %filter_name, "Link missing");
+                    actions.extend(links);
+                }
+            }
+
+            if let Some(mix_node) = mix_node
+                && !self.graph.has_link(filter.id, mix_node.id)
+            {
+                info!(from = %filter_name, to = %mix_node.name, "Link missing");
+                actions.extend(stereo_link_actions(&filter_name, &mix_node.name));
             }
         }
 
-        // Check that links from mixes to outputs exist
-        // Stream mix → Stream output (for OBS capture)
-        if let (Some(stream_mix_node), Some(stream_out)) =
-            (stream_mix.as_ref(), self.graph.get_node_by_name("ut-stream-out"))
-            && !self.graph.has_link(stream_mix_node.id, stream_out.id)
-        {
-            info!(from = "ut-stream-mix", to = "ut-stream-out", "Link missing");
-            actions.push(ReconcileAction::CreateLink {
-                output_node: "ut-stream-mix".to_string(),
-                output_port: "monitor_FL".to_string(),
-                input_node: "ut-stream-out".to_string(),
-                input_port: "playback_FL".to_string(),
-            });
-            actions.push(ReconcileAction::CreateLink {
-                output_node: "ut-stream-mix".to_string(),
-                output_port: "monitor_FR".to_string(),
-                input_node: "ut-stream-out".to_string(),
-                input_port: "playback_FR".to_string(),
-            });
+        // Stream mix → its virtual source
+        if let Some(mix) = stream_source {
+            let source_name = mix.id().source_node_name();
+            let mix_node = self.graph.get_node_by_name(&mix.node_name());
+            let source = self.graph.get_node_by_name(&source_name);
+            if let (Some(mix_node), Some(source)) = (mix_node, source)
+                && !self.graph.has_link(mix_node.id, source.id)
+            {
+                info!(from = %mix_node.name, to = %source_name, "Link missing");
+                actions.extend(link_actions(&mix_node.name, "monitor", &source_name, "input"));
+            }
         }
 
-        // Monitor mix → Wave:3 sink (headphones)
-        if let (Some(monitor_mix_node), Some(wave3)) = (monitor_mix.as_ref(), wave3_sink)
-            && !self.graph.has_link(monitor_mix_node.id, wave3.id)
+        // Monitor mix → monitor output (headphones)
+        let monitor_mix = self.graph.get_node_by_name(&MixType::MONITOR.mix_node_name());
+        if let (Some(monitor_mix_node), Some(output)) = (monitor_mix.as_ref(), monitor_sink)
+            && !self.graph.has_link(monitor_mix_node.id, output.id)
         {
-            info!(from = "ut-monitor-mix", to = %wave3.name, "Link missing");
-            actions.push(ReconcileAction::CreateLink {
-                output_node: "ut-monitor-mix".to_string(),
-                output_port: "monitor_FL".to_string(),
-                input_node: wave3.name.clone(),
-                input_port: "playback_FL".to_string(),
-            });
-            actions.push(ReconcileAction::CreateLink {
-                output_node: "ut-monitor-mix".to_string(),
-                output_port: "monitor_FR".to_string(),
-                input_node: wave3.name,
-                input_port: "playback_FR".to_string(),
-            });
+            info!(from = "ut-monitor-mix", to = %output.name, "Link missing");
+            actions.extend(stereo_link_actions("ut-monitor-mix", &output.name));
         }
 
         actions
@@ -194,7 +434,231 @@ impl Reconciler {
 
     /// Check if the graph is in the expected state.
     #[must_use]
-    pub fn is_healthy(&self, channels: &[ChannelConfig]) -> bool {
-        self.reconcile(channels).is_empty()
+    pub fn is_healthy(
+        &self,
+        channels: &[ChannelState],
+        mixes: &[MixConfig],
+        monitor_output: &str,
+    ) -> bool {
+        self.reconcile(channels, mixes, monitor_output)
+            .iter()
+            .all(|action| matches!(action, ReconcileAction::Warn(_)))
     }
+
+    /// Execute a reconciliation plan through the runtime.
+    ///
+    /// Created nodes and links are recorded in the graph manager so volume
+    /// control and later teardown can find them. Links are resolved by node
+    /// name against the registry, so nodes created in the same plan must have
+    /// registered their ports before their links can succeed; callers should
+    /// run node actions, wait, then re-plan and run the link actions.
+    pub async fn execute(
+        &self,
+        runtime: &PipeWireRuntime,
+        actions: &[ReconcileAction],
+    ) -> ReconcileReport {
+        let mut report = ReconcileReport::default();
+
+        for action in actions {
+            match action {
+                ReconcileAction::CreateSink(props) => {
+                    match runtime.create_sink(props.clone()).await {
+                        Ok(node) => {
+                            self.graph.record_created_node(node.name, node.id);
+                            report.nodes_created += 1;
+                        }
+                        Err(e) => report.failures.push(format!("create sink {}: {e}", props.name)),
+                    }
+                }
+
+                ReconcileAction::CreateVolumeFilter { name, description, layout } => {
+                    match runtime.create_volume_filter(name, description, *layout).await {
+                        Ok(node) => {
+                            self.graph.record_created_node(node.name, node.id);
+                            report.nodes_created += 1;
+                        }
+                        Err(e) => report.failures.push(format!("create filter {name}: {e}")),
+                    }
+                }
+
+                ReconcileAction::LoadEffects { channel, chain } => {
+                    match runtime.load_effects(channel, chain).await {
+                        Ok(()) => report.nodes_created += 1,
+                        Err(e) => {
+                            report.failures.push(format!("load effects for {}: {e}", channel.name));
+                        }
+                    }
+                }
+
+                ReconcileAction::CreateLink {
+                    output_node,
+                    output_port,
+                    input_node,
+                    input_port,
+                } => {
+                    let ids = self
+                        .graph
+                        .get_node_by_name(output_node)
+                        .zip(self.graph.get_node_by_name(input_node))
+                        .map(|(output, input)| (output.id, input.id));
+
+                    let Some((output_id, input_id)) = ids else {
+                        report
+                            .failures
+                            .push(format!("link {output_node}->{input_node}: node not found"));
+                        continue;
+                    };
+
+                    match runtime.create_link(output_id, output_port, input_id, input_port).await {
+                        Ok(id) => {
+                            self.graph.record_created_link(
+                                link_description(output_node, input_node, input_port),
+                                id,
+                            );
+                            report.links_created += 1;
+                        }
+                        Err(e) => report.failures.push(format!(
+                            "link {output_node}:{output_port}->{input_node}:{input_port}: {e}"
+                        )),
+                    }
+                }
+
+                ReconcileAction::DestroyNode(id) => {
+                    // Destroy through our own proxy when we created the node
+                    let node_name = self.graph.get_node(*id).map(|n| n.name);
+                    let created =
+                        node_name.as_deref().and_then(|name| self.graph.get_created_node_id(name));
+                    let result = match created {
+                        Some(proxy_id) => runtime.destroy_node(proxy_id).await,
+                        None => runtime.destroy_external_node(*id).await,
+                    };
+
+                    match result {
+                        Ok(()) => {
+                            if let Some(name) = node_name {
+                                self.graph.forget_created_node(&name);
+                                self.graph.forget_created_links_for(&name);
+                            }
+                            report.nodes_destroyed += 1;
+                        }
+                        Err(e) => report.failures.push(format!("destroy node {id}: {e}")),
+                    }
+                }
+
+                ReconcileAction::DestroyLink(id) => match runtime.destroy_link(*id).await {
+                    Ok(()) => report.links_destroyed += 1,
+                    Err(e) => report.failures.push(format!("destroy link {id}: {e}")),
+                },
+
+                ReconcileAction::Warn(message) => warn!("{message}"),
+            }
+        }
+
+        debug!(?report, "Reconciliation plan executed");
+        report
+    }
+}
+
+/// Build the FL/FR link actions between two sink-style nodes.
+fn stereo_link_actions(output_node: &str, input_node: &str) -> [ReconcileAction; 2] {
+    link_actions(output_node, "monitor", input_node, "playback")
+}
+
+/// Build link actions feeding every position of a layout from a sink's
+/// monitor ports to the same positions on another sink.
+fn layout_link_actions(
+    output_node: &str,
+    input_node: &str,
+    layout: ChannelLayout,
+) -> Vec<ReconcileAction> {
+    layout
+        .downmix()
+        .iter()
+        .map(|term| ReconcileAction::CreateLink {
+            output_node: output_node.to_string(),
+            output_port: format!("monitor_{}", term.position),
+            input_node: input_node.to_string(),
+            input_port: format!("playback_{}", term.position),
+        })
+        .collect()
+}
+
+/// Build link actions folding a volume filter's positions into a stereo
+/// input following the layout's downmix matrix. For a stereo layout these
+/// are the usual FL/FR pair.
+fn downmix_link_actions(
+    output_node: &str,
+    input_node: &str,
+    input_prefix: &str,
+    layout: ChannelLayout,
+) -> Vec<ReconcileAction> {
+    let positions: Vec<&str> = layout.downmix().iter().map(|t| t.position).collect();
+    let sides = ["FL", "FR"];
+    position_links(&positions, &sides)
+        .into_iter()
+        .map(|(output, input)| ReconcileAction::CreateLink {
+            output_node: output_node.to_string(),
+            output_port: format!("monitor_{}", positions[output]),
+            input_node: input_node.to_string(),
+            input_port: format!("{input_prefix}_{}", sides[input]),
+        })
+        .collect()
+}
+
+/// Build link actions between two nodes from the ports they have
+/// registered, pairing them by speaker position. Empty if either node has
+/// no ports yet or none of them pair up.
+fn port_link_actions(
+    graph: &GraphManager,
+    output: &NodeInfo,
+    input: &NodeInfo,
+) -> Vec<ReconcileAction> {
+    let out_ports = graph.get_output_ports(output.id);
+    let in_ports = graph.get_input_ports(input.id);
+    let out_positions: Vec<&str> =
+        out_ports.iter().map(|p| p.position().unwrap_or_default()).collect();
+    let in_positions: Vec<&str> =
+        in_ports.iter().map(|p| p.position().unwrap_or_default()).collect();
+
+    position_links(&out_positions, &in_positions)
+        .into_iter()
+        .map(|(out, input_index)| ReconcileAction::CreateLink {
+            output_node: output.name.clone(),
+            output_port: out_ports[out].name.clone(),
+            input_node: input.name.clone(),
+            input_port: in_ports[input_index].name.clone(),
+        })
+        .collect()
+}
+
+/// Build the FL/FR link actions between two nodes whose ports are named
+/// `<prefix>_FL` and `<prefix>_FR`.
+///
+/// Sinks use `monitor`/`playback`; the effects chain's stream nodes use
+/// `output`/`input`.
+fn link_actions(
+    output_node: &str,
+    output_prefix: &str,
+    input_node: &str,
+    input_prefix: &str,
+) -> [ReconcileAction; 2] {
+    ["FL", "FR"].map(|channel| ReconcileAction::CreateLink {
+        output_node: output_node.to_string(),
+        output_port: format!("{output_prefix}_{channel}"),
+        input_node: input_node.to_string(),
+        input_port: format!("{input_prefix}_{channel}"),
+    })
+}
+
+/// Describe a link the same way the startup code does, e.g.
+/// `ut-ch-music-stream-vol->stream-mix:FL`.
+///
+/// The side is taken from the input port, so a mono source feeding both
+/// sides of a node gets two distinct descriptions.
+fn link_description(output_node: &str, input_node: &str, input_port: &str) -> String {
+    let short = |name: &str| -> String {
+        name.strip_prefix("ut-").filter(|n| n.ends_with("-mix")).unwrap_or(name).to_string()
+    };
+    let channel = input_port.rsplit('_').next().unwrap_or(input_port);
+    format!("{}->{}:{channel}", short(output_node), short(input_node))
 }
@@ -3,13 +3,14 @@
 //! This module provides a unified runtime that handles both graph monitoring
 //! and node creation in a single `PipeWire` thread.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::ffi::CString;
 use std::io::Cursor;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::sync::mpsc as std_mpsc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use libspa::param::ParamType;
 use libspa::pod::serialize::PodSerializer;
@@ -17,6 +18,7 @@ use libspa::pod::{Object, Property, Value};
 use libspa::utils::SpaTypes;
 use pipewire::context::ContextRc;
 use pipewire::main_loop::MainLoopRc;
+use pipewire::permissions::PermissionFlags;
 use pipewire::properties::properties;
 use pipewire::proxy::ProxyT;
 use pipewire::registry::GlobalObject;
@@ -26,20 +28,36 @@ use pipewire::types::ObjectType;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
+use undertone_core::channel::ChannelConfig;
+use undertone_core::layout::{ChannelLayout, position_links};
+use undertone_core::meter::MeterLevels;
+use undertone_core::mixer::{MixConfig, MixType};
+use undertone_core::routing::CAPTURE_MIC;
+use undertone_core::volume::{LevelWrite, NodeFades};
+
 use crate::error::{PwError, PwResult};
-use crate::factory::{CreatedNode, FactoryRequest, FactoryResponse, spa_props};
+use crate::factory::{
+    CreatedNode, FactoryRequest, FactoryResponse, PendingRequests, PendingResponse, TaggedRequest,
+    spa_props,
+};
+use crate::filter_chain::FilterChain;
 use crate::graph::GraphManager;
+use crate::meter::{LevelMeter, MeterSource, MeterStream, MeterTarget, create_meter_stream};
 use crate::monitor::GraphEvent;
-use crate::node::{NodeInfo, PortDirection, PortInfo, VirtualSinkProps};
+use crate::node::{NodeInfo, PortDirection, PortInfo, VirtualSinkProps, process_id};
 
 /// `PipeWire` runtime handle for the async world.
 pub struct PipeWireRuntime {
     /// Channel to send factory requests
-    factory_tx: pipewire::channel::Sender<FactoryRequest>,
-    /// Channel to receive factory responses
-    factory_rx: std_mpsc::Receiver<FactoryResponse>,
+    factory_tx: pipewire::channel::Sender<TaggedRequest>,
+    /// Requests waiting on a response from the `PipeWire` thread
+    pending: Arc<PendingRequests>,
     /// Graph manager (shared with monitor thread)
     graph: Arc<GraphManager>,
+    /// Whether the `PipeWire` thread currently has a live connection
+    connected: Arc<AtomicBool>,
+    /// Levels measured by the meter streams
+    levels: Arc<LevelMeter>,
 }
 
 impl PipeWireRuntime {
@@ -50,9 +68,14 @@ impl PipeWireRuntime {
     pub fn spawn(graph: Arc<GraphManager>) -> PwResult<(Self, mpsc::Receiver<GraphEvent>)> {
         let (event_tx, event_rx) = mpsc::channel(256);
         let (factory_tx, factory_internal_rx) = pipewire::channel::channel();
-        let (factory_internal_tx, factory_rx) = std_mpsc::channel();
+        let pending = Arc::new(PendingRequests::default());
+        let pending_clone = Arc::clone(&pending);
 
         let graph_clone = Arc::clone(&graph);
+        let connected = Arc::new(AtomicBool::new(false));
+        let connected_clone = Arc::clone(&connected);
+        let levels = Arc::new(LevelMeter::default());
+        let levels_clone = Arc::clone(&levels);
 
         std::thread::Builder::new()
             .name("pipewire-runtime".to_string())
@@ -61,21 +84,23 @@ impl PipeWireRuntime {
                     graph_clone,
                     event_tx,
                     factory_internal_rx,
-                    factory_internal_tx,
+                    pending_clone,
+                    connected_clone,
+                    levels_clone,
                 ) {
                     error!(error = %e, "PipeWire runtime failed");
                 }
             })
             .map_err(|e| PwError::MainLoopError(format!("Failed to spawn runtime thread: {e}")))?;
 
-        Ok((Self { factory_tx, factory_rx, graph }, event_rx))
+        Ok((Self { factory_tx, pending, graph, connected, levels }, event_rx))
     }
 
     /// Create a virtual sink node.
     ///
     /// If a node with the same name already exists and is undertone-managed,
     /// it will be reused instead of creating a duplicate.
-    pub fn create_sink(&self, props: VirtualSinkProps) -> PwResult<CreatedNode> {
+    pub async fn create_sink(&self, props: VirtualSinkProps) -> PwResult<CreatedNode> {
         // Check if a node with this name already exists
         if let Some(existing) = self.graph.get_node_by_name(&props.name)
             && existing.is_undertone_managed
@@ -88,36 +113,34 @@ impl PipeWireRuntime {
             return Ok(CreatedNode { id: existing.id, name: props.name });
         }
 
-        self.factory_tx
-            .send(FactoryRequest::CreateSink(props))
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
-
-        // Wait for response with timeout
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::NodeCreated(node)) => Ok(node),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::NodeCreationFailed(e)),
-            Ok(_) => Err(PwError::NodeCreationFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::NodeCreationFailed("Timeout waiting for response".to_string())),
+        match self.request(FactoryRequest::CreateSink(props)).await? {
+            FactoryResponse::NodeCreated(node) => Ok(node),
+            FactoryResponse::Error(e) => Err(PwError::NodeCreationFailed(e)),
+            _ => Err(PwError::NodeCreationFailed("Unexpected response".to_string())),
         }
     }
 
     /// Create all channel sinks.
-    pub fn create_channel_sinks(&self, channels: &[&str]) -> PwResult<Vec<CreatedNode>> {
+    pub async fn create_channel_sinks(
+        &self,
+        channels: &[ChannelConfig],
+    ) -> PwResult<Vec<CreatedNode>> {
         let mut nodes = Vec::new();
 
-        for name in channels {
+        for channel in channels {
             let props = VirtualSinkProps::stereo(
-                &format!("ut-ch-{name}"),
-                &format!("Undertone: {} Channel", capitalize(name)),
-            );
+                &channel.node_name(),
+                &format!("Undertone: {} Channel", channel.display_name),
+            )
+            .with_layout(channel.layout);
 
-            match self.create_sink(props) {
+            match self.create_sink(props).await {
                 Ok(node) => {
                     info!(name = %node.name, id = node.id, "Created channel sink");
                     nodes.push(node);
                 }
                 Err(e) => {
-                    error!(channel = %name, error = %e, "Failed to create channel sink");
+                    error!(channel = %channel.name, error = %e, "Failed to create channel sink");
                     return Err(e);
                 }
             }
@@ -126,18 +149,373 @@ impl PipeWireRuntime {
         Ok(nodes)
     }
 
-    /// Create mix nodes (stream-mix, monitor-mix).
-    pub fn create_mix_nodes(&self) -> PwResult<Vec<CreatedNode>> {
+    /// Create the nodes for a single channel: its sink and a volume filter
+    /// per mix.
+    ///
+    /// The nodes are not linked; call `link_channel_to_mixes` once their
+    /// ports have been registered.
+    ///
+    /// Returns the created nodes as (`node_name`, `node_id`) pairs.
+    pub async fn create_channel(
+        &self,
+        channel: &ChannelConfig,
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let mut nodes = Vec::new();
+
+        let sink = self.create_channel_sinks(std::slice::from_ref(channel)).await?;
+        nodes.extend(sink.into_iter().map(|node| (node.name, node.id)));
+        nodes.extend(
+            self.create_channel_volume_filters(std::slice::from_ref(channel), mixes).await?,
+        );
+
+        Ok(nodes)
+    }
+
+    /// Tear down a channel's sink, effects chain, volume filter and
+    /// direct-out nodes.
+    ///
+    /// Links between the channel, its effects chain, its filters, its
+    /// direct-out source and the mix nodes are destroyed first, then the
+    /// nodes themselves. Apps still routed
+    /// to the channel should be moved elsewhere before calling this.
+    pub async fn destroy_channel(
+        &self,
+        channel: &ChannelConfig,
+        mixes: &[MixConfig],
+    ) -> PwResult<()> {
+        let sink_name = channel.node_name();
+        let fx_input_name = channel.fx_input_node_name();
+        let fx_output_name = channel.fx_output_node_name();
+        let direct_name = channel.direct_node_name();
+        let filters: Vec<(String, String)> =
+            mixes.iter().map(|mix| (channel.vol_node_name(&mix.id()), mix.node_name())).collect();
+
+        // Destroy our links so their proxies don't outlive the nodes
+        let sink_id = self.graph.get_node_by_name(&sink_name).map(|n| n.id);
+        let fx_input_id = self.graph.get_node_by_name(&fx_input_name).map(|n| n.id);
+        let fx_output_id = self.graph.get_node_by_name(&fx_output_name).map(|n| n.id);
+        let direct_id = self.graph.get_node_by_name(&direct_name).map(|n| n.id);
+        if let (Some(sink_id), Some(fx_input_id)) = (sink_id, fx_input_id) {
+            self.destroy_links_between_nodes(sink_id, fx_input_id).await?;
+        }
+        for (filter_name, mix_name) in &filters {
+            let filter_id = self.graph.get_node_by_name(filter_name).map(|n| n.id);
+            let mix_id = self.graph.get_node_by_name(mix_name).map(|n| n.id);
+
+            for source_id in [sink_id, fx_output_id] {
+                if let (Some(source_id), Some(filter_id)) = (source_id, filter_id) {
+                    self.destroy_links_between_nodes(source_id, filter_id).await?;
+                }
+            }
+            if let (Some(filter_id), Some(mix_id)) = (filter_id, mix_id) {
+                self.destroy_links_between_nodes(filter_id, mix_id).await?;
+            }
+            if let (Some(filter_id), Some(direct_id)) = (filter_id, direct_id) {
+                self.destroy_links_between_nodes(filter_id, direct_id).await?;
+            }
+        }
+
+        let node_names = [
+            sink_name.as_str(),
+            fx_input_name.as_str(),
+            fx_output_name.as_str(),
+            direct_name.as_str(),
+        ]
+        .into_iter()
+        .chain(filters.iter().map(|(filter_name, _)| filter_name.as_str()));
+        for node_name in node_names {
+            self.destroy_managed_node(node_name).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the nodes for a single mix: its mix node, a volume filter for
+    /// each channel and the mic volume filter.
+    ///
+    /// The nodes are not linked; call `link_channel_to_mixes` and
+    /// `link_mic_to_mixes` with this mix once their ports have been
+    /// registered.
+    ///
+    /// Returns the created nodes as (`node_name`, `node_id`) pairs.
+    pub async fn create_mix(
+        &self,
+        mix: &MixConfig,
+        channels: &[ChannelConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let mixes = std::slice::from_ref(mix);
         let mut nodes = Vec::new();
 
-        let mix_configs = [
-            ("ut-stream-mix", "Undertone: Stream Mix"),
-            ("ut-monitor-mix", "Undertone: Monitor Mix"),
-        ];
+        let mix_node = self.create_mix_nodes(mixes).await?;
+        nodes.extend(mix_node.into_iter().map(|node| (node.name, node.id)));
+        nodes.extend(self.create_channel_volume_filters(channels, mixes).await?);
+        nodes.extend(self.create_mic_volume_filters(mixes).await?);
+
+        Ok(nodes)
+    }
+
+    /// Tear down a mix's node and every volume filter feeding it.
+    ///
+    /// Anything still linked to the mix node (a recording app, an output
+    /// device) loses its link along with the node.
+    pub async fn destroy_mix(&self, mix: &MixConfig, channels: &[ChannelConfig]) -> PwResult<()> {
+        let id = mix.id();
+        let mix_name = id.mix_node_name();
+        let mix_id = self.graph.get_node_by_name(&mix_name).map(|n| n.id);
+
+        let mut filters: Vec<(String, Option<u32>)> = channels
+            .iter()
+            .map(|channel| {
+                // Filters are fed by the effects chain when the channel has one
+                let source = self
+                    .graph
+                    .get_node_by_name(&channel.fx_output_node_name())
+                    .or_else(|| self.graph.get_node_by_name(&channel.node_name()))
+                    .map(|n| n.id);
+                (channel.vol_node_name(&id), source)
+            })
+            .collect();
+        let mic = self.graph.find_wave3_source().map(|n| n.id);
+        filters.push((id.mic_vol_node_name(), mic));
+
+        // Destroy our links so their proxies don't outlive the nodes
+        for (filter_name, source_id) in &filters {
+            let filter_id = self.graph.get_node_by_name(filter_name).map(|n| n.id);
+
+            if let (Some(source_id), Some(filter_id)) = (*source_id, filter_id) {
+                self.destroy_links_between_nodes(source_id, filter_id).await?;
+            }
+            if let (Some(filter_id), Some(mix_id)) = (filter_id, mix_id) {
+                self.destroy_links_between_nodes(filter_id, mix_id).await?;
+            }
+        }
+
+        for (filter_name, _) in &filters {
+            self.destroy_managed_node(filter_name).await?;
+        }
+        self.destroy_managed_node(&mix_name).await?;
+
+        Ok(())
+    }
+
+    /// Load a channel's effects chain.
+    ///
+    /// The chain's nodes register asynchronously; once their ports are in
+    /// the graph, call `insert_channel_effects` to route the channel through
+    /// them.
+    pub async fn load_effects(&self, channel: &ChannelConfig, chain: &FilterChain) -> PwResult<()> {
+        let request = FactoryRequest::LoadFilterChain {
+            name: channel.fx_input_node_name(),
+            args: chain.module_args(channel),
+        };
+
+        match self.request(request).await? {
+            FactoryResponse::FilterChainLoaded => Ok(()),
+            FactoryResponse::Error(e) => Err(PwError::NodeCreationFailed(e)),
+            _ => Err(PwError::NodeCreationFailed("Unexpected response".to_string())),
+        }
+    }
+
+    /// Update the control values of a channel's loaded effects chain.
+    ///
+    /// `chain` must have the same layout as the loaded one (see
+    /// `FilterChain::same_layout`); other changes need the chain reloaded.
+    pub async fn set_effect_params(
+        &self,
+        channel: &ChannelConfig,
+        chain: &FilterChain,
+    ) -> PwResult<()> {
+        let name = channel.fx_input_node_name();
+        let node_id =
+            self.graph.get_node_by_name(&name).map(|n| n.id).ok_or(PwError::NodeNotFound(name))?;
+
+        match self
+            .request(FactoryRequest::SetNodeParams { node_id, params: chain.params() })
+            .await?
+        {
+            FactoryResponse::ParamsSet { .. } => Ok(()),
+            FactoryResponse::Error(e) => Err(PwError::VolumeControlFailed(e)),
+            _ => Err(PwError::VolumeControlFailed("Unexpected response".to_string())),
+        }
+    }
+
+    /// Route a channel through its loaded effects chain.
+    ///
+    /// The channel → volume filter links are replaced by channel → chain
+    /// input and chain output → volume filter links.
+    ///
+    /// Returns a vector of (`link_description`, `link_id`) tuples for tracking.
+    pub async fn insert_channel_effects(
+        &self,
+        channel: &ChannelConfig,
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let node_id = |name: &str| {
+            self.graph
+                .get_node_by_name(name)
+                .map(|n| n.id)
+                .ok_or_else(|| PwError::NodeNotFound(name.to_string()))
+        };
+
+        let channel_name = channel.node_name();
+        let fx_input_name = channel.fx_input_node_name();
+        let fx_output_name = channel.fx_output_node_name();
+        let channel_id = node_id(&channel_name)?;
+        let fx_input_id = node_id(&fx_input_name)?;
+        let fx_output_id = node_id(&fx_output_name)?;
+
+        let mut created_links = Vec::new();
+
+        for mix in mixes {
+            let vol_name = channel.vol_node_name(&mix.id());
+            let vol_id = node_id(&vol_name)?;
+
+            self.destroy_links_between_nodes(channel_id, vol_id).await?;
+            let links = self.create_position_links(fx_output_id, vol_id).await?;
+            created_links.extend(
+                links
+                    .into_iter()
+                    .map(|(position, id)| (format!("{fx_output_name}->{vol_name}:{position}"), id)),
+            );
+        }
+
+        let links = self.create_position_links(channel_id, fx_input_id).await?;
+        created_links.extend(
+            links
+                .into_iter()
+                .map(|(position, id)| (format!("{channel_name}->{fx_input_name}:{position}"), id)),
+        );
+
+        info!(channel = %channel.name, "Routed channel through effects chain");
+        Ok(created_links)
+    }
+
+    /// Take a channel's effects chain out of its signal path and unload it.
+    ///
+    /// The channel is linked straight to its volume filters again.
+    ///
+    /// Returns a vector of (`link_description`, `link_id`) tuples for tracking.
+    pub async fn remove_channel_effects(
+        &self,
+        channel: &ChannelConfig,
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let fx_input_name = channel.fx_input_node_name();
+        let fx_output_name = channel.fx_output_node_name();
+        let channel_id = self.graph.get_node_by_name(&channel.node_name()).map(|n| n.id);
+        let fx_input_id = self.graph.get_node_by_name(&fx_input_name).map(|n| n.id);
+        let fx_output_id = self.graph.get_node_by_name(&fx_output_name).map(|n| n.id);
+
+        if let (Some(channel_id), Some(fx_input_id)) = (channel_id, fx_input_id) {
+            self.destroy_links_between_nodes(channel_id, fx_input_id).await?;
+        }
+        for mix in mixes {
+            let vol_id =
+                self.graph.get_node_by_name(&channel.vol_node_name(&mix.id())).map(|n| n.id);
+            if let (Some(fx_output_id), Some(vol_id)) = (fx_output_id, vol_id) {
+                self.destroy_links_between_nodes(fx_output_id, vol_id).await?;
+            }
+        }
+
+        // The module unloads itself once its nodes are gone
+        self.destroy_managed_node(&fx_input_name).await?;
+        self.destroy_managed_node(&fx_output_name).await?;
+
+        info!(channel = %channel.name, "Removed effects chain");
+        self.relink_channel_direct(channel, mixes).await
+    }
+
+    /// Link a channel sink straight to its volume filters.
+    async fn relink_channel_direct(
+        &self,
+        channel: &ChannelConfig,
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let channel_name = channel.node_name();
+        let channel_id = self
+            .graph
+            .get_node_by_name(&channel_name)
+            .ok_or_else(|| PwError::NodeNotFound(channel_name.clone()))?
+            .id;
+
+        let mut created_links = Vec::new();
+
+        for mix in mixes {
+            let vol_name = channel.vol_node_name(&mix.id());
+            let vol_id = self
+                .graph
+                .get_node_by_name(&vol_name)
+                .ok_or_else(|| PwError::NodeNotFound(vol_name.clone()))?
+                .id;
 
-        for (name, desc) in mix_configs {
-            let props = VirtualSinkProps::stereo(name, desc);
-            match self.create_sink(props) {
+            if !self.graph.has_link(channel_id, vol_id) {
+                let links = self.create_position_links(channel_id, vol_id).await?;
+                created_links.extend(
+                    links.into_iter().map(|(position, id)| {
+                        (format!("{channel_name}->{vol_name}:{position}"), id)
+                    }),
+                );
+            }
+        }
+
+        Ok(created_links)
+    }
+
+    /// Destroy an Undertone node by name and forget what we recorded for it.
+    async fn destroy_managed_node(&self, node_name: &str) -> PwResult<()> {
+        // Prefer the proxy ID we recorded; fall back to the registry ID for
+        // nodes left over from a previous daemon run
+        if let Some(id) = self.graph.get_created_node_id(node_name) {
+            self.destroy_node(id).await?;
+            info!(name = %node_name, id, "Destroyed node");
+        } else if let Some(node) = self.graph.get_node_by_name(node_name) {
+            self.destroy_external_node(node.id).await?;
+            info!(name = %node_name, id = node.id, "Destroyed leftover node");
+        }
+
+        self.graph.forget_created_node(node_name);
+        self.graph.forget_created_links_for(node_name);
+
+        Ok(())
+    }
+
+    /// Destroy a node we created, by proxy ID.
+    ///
+    /// Returns `NodeNotFound` if this connection holds no node with the ID.
+    pub async fn destroy_node(&self, id: u32) -> PwResult<()> {
+        match self.request(FactoryRequest::DestroyNode(id)).await? {
+            FactoryResponse::NodeDestroyed { id: _ } => Ok(()),
+            FactoryResponse::Error(e) => Err(PwError::NodeNotFound(e)),
+            _ => Err(PwError::MainLoopError("Unexpected response".to_string())),
+        }
+    }
+
+    /// Destroy a node we did not create, such as one left over from a
+    /// previous daemon run, by registry ID.
+    ///
+    /// Returns `NodeNotFound` if the registry has no such node, and
+    /// `MainLoopError` if `PipeWire` refuses to destroy it.
+    pub async fn destroy_external_node(&self, id: u32) -> PwResult<()> {
+        if self.graph.get_node(id).is_none() {
+            return Err(PwError::NodeNotFound(format!("Node {id} not found in registry")));
+        }
+        match self.request(FactoryRequest::DestroyExternalNode(id)).await? {
+            FactoryResponse::NodeDestroyed { id: _ } => Ok(()),
+            FactoryResponse::Error(e) => Err(PwError::MainLoopError(e)),
+            _ => Err(PwError::MainLoopError("Unexpected response".to_string())),
+        }
+    }
+
+    /// Create a mix node (`ut-<mix>-mix`) for each mix.
+    pub async fn create_mix_nodes(&self, mixes: &[MixConfig]) -> PwResult<Vec<CreatedNode>> {
+        let mut nodes = Vec::new();
+
+        for mix in mixes {
+            let name = mix.node_name();
+            let desc = format!("Undertone: {} Mix", mix.display_name);
+            let props = VirtualSinkProps::stereo(&name, &desc);
+            match self.create_sink(props).await {
                 Ok(node) => {
                     info!(name = %node.name, id = node.id, "Created mix node");
                     nodes.push(node);
@@ -152,6 +530,123 @@ impl PipeWireRuntime {
         Ok(nodes)
     }
 
+    /// Create the virtual source (`ut-<mix>-source`) for a mix.
+    ///
+    /// Capture apps see it as an ordinary microphone, rather than having to
+    /// record the mix node's monitor. Link it with `link_mix_source` once its
+    /// ports have been registered.
+    pub async fn create_mix_source(&self, mix: &MixConfig) -> PwResult<CreatedNode> {
+        let name = mix.id().source_node_name();
+        let props =
+            VirtualSinkProps::virtual_source(&name, &format!("Undertone {}", mix.display_name));
+        let node = self.create_sink(props).await?;
+        info!(name = %node.name, id = node.id, "Created mix source");
+        Ok(node)
+    }
+
+    /// Feed a mix into its virtual source.
+    ///
+    /// Returns a vector of (`link_description`, `link_id`) tuples for tracking;
+    /// empty if the links already exist.
+    pub async fn link_mix_source(&self, mix: &MixConfig) -> PwResult<Vec<(String, u32)>> {
+        let mix_name = mix.node_name();
+        let source_name = mix.id().source_node_name();
+        let mix_node = self
+            .graph
+            .get_node_by_name(&mix_name)
+            .ok_or_else(|| PwError::NodeNotFound(mix_name.clone()))?;
+        let source = self
+            .graph
+            .get_node_by_name(&source_name)
+            .ok_or_else(|| PwError::NodeNotFound(source_name.clone()))?;
+
+        if self.graph.has_link(mix_node.id, source.id) {
+            return Ok(Vec::new());
+        }
+
+        let links = self.create_position_links(mix_node.id, source.id).await?;
+        let short_mix = mix_name.trim_start_matches("ut-");
+        info!(mix = %mix_name, source = %source_name, "Linked mix to its source");
+        Ok(links
+            .into_iter()
+            .map(|(position, id)| (format!("{short_mix}->{source_name}:{position}"), id))
+            .collect())
+    }
+
+    /// Create a channel's direct-out recording source (`ut-direct-<name>`).
+    ///
+    /// Recording apps see it as an ordinary microphone carrying only that
+    /// channel, as heard in the stream mix. Link it with
+    /// `link_channel_direct_out` once its ports have been registered.
+    pub async fn create_channel_direct_out(
+        &self,
+        channel: &ChannelConfig,
+    ) -> PwResult<CreatedNode> {
+        let name = channel.direct_node_name();
+        let props = VirtualSinkProps::virtual_source(
+            &name,
+            &format!("Undertone {} Direct", channel.display_name),
+        );
+        let node = self.create_sink(props).await?;
+        info!(name = %node.name, id = node.id, "Created direct-out source");
+        Ok(node)
+    }
+
+    /// Feed a channel's stream volume filter into its direct-out source, so
+    /// the recording follows the channel's stream fader, mute and ducking.
+    ///
+    /// Returns a vector of (`link_description`, `link_id`) tuples for tracking;
+    /// empty if the links already exist.
+    pub async fn link_channel_direct_out(
+        &self,
+        channel: &ChannelConfig,
+    ) -> PwResult<Vec<(String, u32)>> {
+        let vol_name = channel.vol_node_name(&MixType::STREAM);
+        let direct_name = channel.direct_node_name();
+        let vol_node = self
+            .graph
+            .get_node_by_name(&vol_name)
+            .ok_or_else(|| PwError::NodeNotFound(vol_name.clone()))?;
+        let direct = self
+            .graph
+            .get_node_by_name(&direct_name)
+            .ok_or_else(|| PwError::NodeNotFound(direct_name.clone()))?;
+
+        if self.graph.has_link(vol_node.id, direct.id) {
+            return Ok(Vec::new());
+        }
+
+        let links = self.create_position_links(vol_node.id, direct.id).await?;
+        info!(channel = %channel.name, source = %direct_name, "Linked channel to its direct-out");
+        Ok(links
+            .into_iter()
+            .map(|(position, id)| (format!("{vol_name}->{direct_name}:{position}"), id))
+            .collect())
+    }
+
+    /// Tear down a channel's direct-out source and the links feeding it.
+    pub async fn destroy_channel_direct_out(&self, channel: &ChannelConfig) -> PwResult<()> {
+        let vol_id = self.graph.get_node_by_name(&channel.vol_node_name(&MixType::STREAM));
+        let direct_name = channel.direct_node_name();
+        let direct_id = self.graph.get_node_by_name(&direct_name).map(|n| n.id);
+        if let (Some(vol), Some(direct_id)) = (vol_id, direct_id) {
+            self.destroy_links_between_nodes(vol.id, direct_id).await?;
+        }
+        self.destroy_managed_node(&direct_name).await
+    }
+
+    /// Make a source the default input for apps, by node name.
+    ///
+    /// Needs the session manager's `default` metadata, so it fails while
+    /// `WirePlumber` is not running.
+    pub async fn set_default_source(&self, node_name: &str) -> PwResult<()> {
+        match self.request(FactoryRequest::SetDefaultSource(node_name.to_string())).await? {
+            FactoryResponse::DefaultSourceSet => Ok(()),
+            FactoryResponse::Error(e) => Err(PwError::MetadataFailed(e)),
+            _ => Err(PwError::MetadataFailed("Unexpected response".to_string())),
+        }
+    }
+
     /// Get the graph manager.
     #[must_use]
     pub fn graph(&self) -> &Arc<GraphManager> {
@@ -169,81 +664,90 @@ impl PipeWireRuntime {
     /// * `output_port` - The output port name (e.g., "`monitor_FL`")
     /// * `input_node` - The destination node ID
     /// * `input_port` - The input port name (e.g., "`input_FL`")
-    pub fn create_link(
+    pub async fn create_link(
         &self,
         output_node: u32,
         output_port: &str,
         input_node: u32,
         input_port: &str,
     ) -> PwResult<u32> {
-        self.factory_tx
-            .send(FactoryRequest::CreateLink {
-                output_node,
-                output_port: output_port.to_string(),
-                input_node,
-                input_port: input_port.to_string(),
-            })
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
-
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::LinkCreated { id }) => Ok(id),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::LinkCreationFailed(e)),
-            Ok(_) => Err(PwError::LinkCreationFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::LinkCreationFailed("Timeout waiting for response".to_string())),
+        let request = FactoryRequest::CreateLink {
+            output_node,
+            output_port: output_port.to_string(),
+            input_node,
+            input_port: input_port.to_string(),
+        };
+
+        match self.request(request).await? {
+            FactoryResponse::LinkCreated { id } => Ok(id),
+            FactoryResponse::Error(e) => Err(PwError::LinkCreationFailed(e)),
+            _ => Err(PwError::LinkCreationFailed("Unexpected response".to_string())),
         }
     }
 
-    /// Create stereo links between two nodes.
+    /// Link every output port of one node to another node's input ports by
+    /// speaker position, read from each port's `audio.channel`.
+    ///
+    /// Positions the input node also has are linked straight across, mono
+    /// sources feed both sides, and extra positions are folded into the
+    /// front pair (see `position_links`). Links carry no gain, so the
+    /// downmix coefficients come from the surround volume filters feeding
+    /// the mixes (see `ChannelLayout`).
     ///
-    /// This creates two links: one for the left channel (FL) and one for
-    /// the right channel (FR). This is the common case for stereo audio routing.
+    /// While either node's ports are not yet in the registry, the standard
+    /// stereo port names are assumed.
     ///
-    /// Uses port discovery to find the correct port names, falling back to
-    /// standard `PipeWire` naming conventions if discovery fails.
-    pub fn create_stereo_links(&self, output_node: u32, input_node: u32) -> PwResult<(u32, u32)> {
-        // Try to discover ports dynamically
+    /// Returns (`position`, `link_id`) pairs, e.g. `FL`, or `FC>FR` for a
+    /// folded position.
+    pub async fn create_position_links(
+        &self,
+        output_node: u32,
+        input_node: u32,
+    ) -> PwResult<Vec<(String, u32)>> {
         let out_ports = self.graph.get_output_ports(output_node);
         let in_ports = self.graph.get_input_ports(input_node);
+        if out_ports.is_empty() || in_ports.is_empty() {
+            debug!(output_node, input_node, "Ports not registered, assuming stereo port names");
+            let (left_id, right_id) = futures::future::join(
+                self.create_link(output_node, "monitor_FL", input_node, "playback_FL"),
+                self.create_link(output_node, "monitor_FR", input_node, "playback_FR"),
+            )
+            .await;
+            return Ok(vec![("FL".to_string(), left_id?), ("FR".to_string(), right_id?)]);
+        }
 
-        // Find FL/FR ports by channel, or fall back to name-based matching
-        let out_fl = out_ports
-            .iter()
-            .find(|p| p.channel.as_deref() == Some("FL"))
-            .map_or("monitor_FL", |p| p.name.as_str());
-        let out_fr = out_ports
-            .iter()
-            .find(|p| p.channel.as_deref() == Some("FR"))
-            .map_or("monitor_FR", |p| p.name.as_str());
-        let in_fl = in_ports
-            .iter()
-            .find(|p| p.channel.as_deref() == Some("FL"))
-            .map_or("playback_FL", |p| p.name.as_str());
-        let in_fr = in_ports
-            .iter()
-            .find(|p| p.channel.as_deref() == Some("FR"))
-            .map_or("playback_FR", |p| p.name.as_str());
-
-        debug!(
-            output_node,
-            input_node, out_fl, out_fr, in_fl, in_fr, "Creating stereo links with discovered ports"
-        );
+        let out_positions: Vec<&str> =
+            out_ports.iter().map(|p| p.position().unwrap_or_default()).collect();
+        let in_positions: Vec<&str> =
+            in_ports.iter().map(|p| p.position().unwrap_or_default()).collect();
+        let plan = position_links(&out_positions, &in_positions);
+        if plan.is_empty() {
+            return Err(PwError::PortNotFound(format!(
+                "No matching ports between nodes {output_node} and {input_node}"
+            )));
+        }
 
-        let left_id = self.create_link(output_node, out_fl, input_node, in_fl)?;
-        let right_id = self.create_link(output_node, out_fr, input_node, in_fr)?;
-        Ok((left_id, right_id))
+        debug!(output_node, input_node, links = plan.len(), "Creating position links");
+        let results = futures::future::join_all(plan.iter().map(|&(out, input)| {
+            self.create_link(output_node, &out_ports[out].name, input_node, &in_ports[input].name)
+        }))
+        .await;
+        plan.into_iter()
+            .zip(results)
+            .map(|((out, input), id)| {
+                let (from, to) = (out_positions[out], in_positions[input]);
+                let position = if from == to { from.to_string() } else { format!("{from}>{to}") };
+                id.map(|id| (position, id))
+            })
+            .collect()
     }
 
     /// Destroy a link by ID.
-    pub fn destroy_link(&self, id: u32) -> PwResult<()> {
-        self.factory_tx
-            .send(FactoryRequest::DestroyLink(id))
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
-
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::LinkDestroyed { id: _ }) => Ok(()),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::LinkCreationFailed(e)),
-            Ok(_) => Err(PwError::LinkCreationFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::LinkCreationFailed("Timeout waiting for response".to_string())),
+    pub async fn destroy_link(&self, id: u32) -> PwResult<()> {
+        match self.request(FactoryRequest::DestroyLink(id)).await? {
+            FactoryResponse::LinkDestroyed { id: _ } => Ok(()),
+            FactoryResponse::Error(e) => Err(PwError::LinkCreationFailed(e)),
+            _ => Err(PwError::LinkCreationFailed("Unexpected response".to_string())),
         }
     }
 
@@ -251,149 +755,126 @@ impl PipeWireRuntime {
     ///
     /// This is more reliable than `destroy_link` because it matches by node IDs
     /// rather than link proxy IDs (which can differ from registry IDs).
-    pub fn destroy_links_between_nodes(
+    pub async fn destroy_links_between_nodes(
         &self,
         output_node: u32,
         input_node: u32,
     ) -> PwResult<usize> {
-        self.factory_tx
-            .send(FactoryRequest::DestroyLinksBetweenNodes { output_node, input_node })
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
-
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::LinksDestroyed { count }) => Ok(count),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::LinkCreationFailed(e)),
-            Ok(_) => Err(PwError::LinkCreationFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::LinkCreationFailed("Timeout waiting for response".to_string())),
+        match self
+            .request(FactoryRequest::DestroyLinksBetweenNodes { output_node, input_node })
+            .await?
+        {
+            FactoryResponse::LinksDestroyed { count } => Ok(count),
+            FactoryResponse::Error(e) => Err(PwError::LinkCreationFailed(e)),
+            _ => Err(PwError::LinkCreationFailed("Unexpected response".to_string())),
         }
     }
 
-    /// Create links from all channel sinks to mix nodes through volume filter nodes.
+    /// Create links from the channel sinks to mix nodes through volume filter nodes.
     ///
-    /// This creates the full audio routing topology:
-    /// - channel → stream-vol-filter → stream-mix
-    /// - channel → monitor-vol-filter → monitor-mix
+    /// This creates the full audio routing topology, for every mix:
+    /// - channel → `ut-ch-{name}-{mix}-vol` → `ut-{mix}-mix`
     ///
-    /// This enables independent volume control for stream and monitor paths per channel.
+    /// This enables independent volume control per channel in each mix.
+    /// Channels whose sink is not in the graph are skipped.
     ///
     /// Returns a vector of (`link_description`, `link_id`) tuples for tracking.
-    pub fn create_channel_to_mix_links_with_filters(&self) -> PwResult<Vec<(String, u32)>> {
+    pub async fn create_channel_to_mix_links_with_filters(
+        &self,
+        channels: &[ChannelConfig],
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
         let mut created_links = Vec::new();
 
-        // Get mix node IDs from registry (NOT created_nodes, which has wrong IDs)
-        let stream_mix_id = self
-            .graph
-            .get_node_by_name("ut-stream-mix")
-            .ok_or_else(|| PwError::NodeNotFound("ut-stream-mix".to_string()))?
-            .id;
-
-        let monitor_mix_id = self
-            .graph
-            .get_node_by_name("ut-monitor-mix")
-            .ok_or_else(|| PwError::NodeNotFound("ut-monitor-mix".to_string()))?
-            .id;
-
-        // Get all channel nodes (ut-ch-{name})
-        let channel_nodes = self.graph.get_undertone_channels();
-
-        for channel in &channel_nodes {
-            let channel_id = channel.id;
-            let channel_name = &channel.name;
-
-            // Extract the base channel name (e.g., "music" from "ut-ch-music")
-            let base_name = channel_name.strip_prefix("ut-ch-").unwrap_or(channel_name);
-
-            // Get the stream volume filter node ID from registry
-            let stream_vol_name = format!("ut-ch-{base_name}-stream-vol");
-            let stream_vol_id = self
-                .graph
-                .get_node_by_name(&stream_vol_name)
-                .ok_or_else(|| PwError::NodeNotFound(stream_vol_name.clone()))?
-                .id;
-
-            // Get the monitor volume filter node ID from registry
-            let monitor_vol_name = format!("ut-ch-{base_name}-monitor-vol");
-            let monitor_vol_id = self
-                .graph
-                .get_node_by_name(&monitor_vol_name)
-                .ok_or_else(|| PwError::NodeNotFound(monitor_vol_name.clone()))?
-                .id;
+        for channel in
+            channels.iter().filter(|c| self.graph.get_node_by_name(&c.node_name()).is_some())
+        {
+            created_links.extend(self.link_channel_to_mixes(channel, mixes).await?);
+        }
 
-            // Link channel -> stream-vol-filter (stereo)
-            match self.create_stereo_links(channel_id, stream_vol_id) {
-                Ok((left_id, right_id)) => {
-                    info!(
-                        channel = %channel_name,
-                        filter = %stream_vol_name,
-                        "Linked channel to stream volume filter"
-                    );
-                    created_links.push((format!("{channel_name}->{stream_vol_name}:FL"), left_id));
-                    created_links.push((format!("{channel_name}->{stream_vol_name}:FR"), right_id));
-                }
-                Err(e) => {
-                    error!(
-                        channel = %channel_name,
-                        error = %e,
-                        "Failed to link channel to stream volume filter"
-                    );
-                }
-            }
+        Ok(created_links)
+    }
 
-            // Link stream-vol-filter -> stream-mix (stereo)
-            match self.create_stereo_links(stream_vol_id, stream_mix_id) {
-                Ok((left_id, right_id)) => {
-                    info!(
-                        filter = %stream_vol_name,
-                        "Linked stream volume filter to stream-mix"
-                    );
-                    created_links.push((format!("{stream_vol_name}->stream-mix:FL"), left_id));
-                    created_links.push((format!("{stream_vol_name}->stream-mix:FR"), right_id));
-                }
-                Err(e) => {
-                    error!(
-                        filter = %stream_vol_name,
-                        error = %e,
-                        "Failed to link stream volume filter to stream-mix"
-                    );
-                }
-            }
+    /// Link a single channel sink to the given mix nodes through its volume
+    /// filters.
+    ///
+    /// If the channel's effects chain is loaded, the filters are fed from the
+    /// chain's output instead; the channel → chain link is made by
+    /// `insert_channel_effects`.
+    ///
+    /// Failures on individual links are logged and skipped; an error is only
+    /// returned if one of the required nodes is missing from the graph.
+    ///
+    /// Returns a vector of (`link_description`, `link_id`) tuples for tracking.
+    pub async fn link_channel_to_mixes(
+        &self,
+        channel: &ChannelConfig,
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let mut created_links = Vec::new();
 
-            // Link channel -> monitor-vol-filter (stereo)
-            match self.create_stereo_links(channel_id, monitor_vol_id) {
-                Ok((left_id, right_id)) => {
+        // Get node IDs from registry (NOT created_nodes, which has wrong IDs)
+        let node_id = |name: &str| {
+            self.graph
+                .get_node_by_name(name)
+                .map(|n| n.id)
+                .ok_or_else(|| PwError::NodeNotFound(name.to_string()))
+        };
+
+        let channel_name = channel.node_name();
+        let channel_id = node_id(&channel_name)?;
+
+        let fx_output_name = channel.fx_output_node_name();
+        let (source_name, source_id) = match self.graph.get_node_by_name(&fx_output_name) {
+            Some(fx_output) => (fx_output_name, fx_output.id),
+            None => (channel_name, channel_id),
+        };
+
+        for mix in mixes {
+            let mix_name = mix.node_name();
+            let mix_id = node_id(&mix_name)?;
+            let short_mix = mix_name.trim_start_matches("ut-");
+            let vol_name = channel.vol_node_name(&mix.id());
+            let vol_id = node_id(&vol_name)?;
+
+            // Link channel (or its effects chain) -> vol-filter, every position
+            match self.create_position_links(source_id, vol_id).await {
+                Ok(links) => {
                     info!(
-                        channel = %channel_name,
-                        filter = %monitor_vol_name,
-                        "Linked channel to monitor volume filter"
+                        channel = %source_name,
+                        filter = %vol_name,
+                        "Linked channel to volume filter"
                     );
-                    created_links.push((format!("{channel_name}->{monitor_vol_name}:FL"), left_id));
-                    created_links
-                        .push((format!("{channel_name}->{monitor_vol_name}:FR"), right_id));
+                    created_links.extend(links.into_iter().map(|(position, id)| {
+                        (format!("{source_name}->{vol_name}:{position}"), id)
+                    }));
                 }
                 Err(e) => {
                     error!(
-                        channel = %channel_name,
+                        channel = %source_name,
+                        filter = %vol_name,
                         error = %e,
-                        "Failed to link channel to monitor volume filter"
+                        "Failed to link channel to volume filter"
                     );
                 }
             }
 
-            // Link monitor-vol-filter -> monitor-mix (stereo)
-            match self.create_stereo_links(monitor_vol_id, monitor_mix_id) {
-                Ok((left_id, right_id)) => {
-                    info!(
-                        filter = %monitor_vol_name,
-                        "Linked monitor volume filter to monitor-mix"
+            // Link vol-filter -> mix, downmixing surround to stereo
+            match self.create_position_links(vol_id, mix_id).await {
+                Ok(links) => {
+                    info!(filter = %vol_name, mix = %mix_name, "Linked volume filter to mix");
+                    created_links.extend(
+                        links.into_iter().map(|(position, id)| {
+                            (format!("{vol_name}->{short_mix}:{position}"), id)
+                        }),
                     );
-                    created_links.push((format!("{monitor_vol_name}->monitor-mix:FL"), left_id));
-                    created_links.push((format!("{monitor_vol_name}->monitor-mix:FR"), right_id));
                 }
                 Err(e) => {
                     error!(
-                        filter = %monitor_vol_name,
+                        filter = %vol_name,
+                        mix = %mix_name,
                         error = %e,
-                        "Failed to link monitor volume filter to monitor-mix"
+                        "Failed to link volume filter to mix"
                     );
                 }
             }
@@ -412,7 +893,7 @@ impl PipeWireRuntime {
     /// Use `create_channel_to_mix_links_with_filters` for volume control support.
     ///
     /// Returns a vector of (`link_description`, `link_id`) tuples for tracking.
-    pub fn create_channel_to_mix_links(&self) -> PwResult<Vec<(String, u32)>> {
+    pub async fn create_channel_to_mix_links(&self) -> PwResult<Vec<(String, u32)>> {
         let mut created_links = Vec::new();
 
         // Get mix node IDs from registry
@@ -435,16 +916,17 @@ impl PipeWireRuntime {
             let channel_id = channel.id;
             let channel_name = &channel.name;
 
-            // Link channel -> stream-mix (stereo)
-            match self.create_stereo_links(channel_id, stream_mix_id) {
-                Ok((left_id, right_id)) => {
+            // Link channel -> stream-mix
+            match self.create_position_links(channel_id, stream_mix_id).await {
+                Ok(links) => {
                     info!(
                         channel = %channel_name,
                         stream_mix_id,
                         "Linked channel to stream-mix"
                     );
-                    created_links.push((format!("{channel_name}->stream-mix:FL"), left_id));
-                    created_links.push((format!("{channel_name}->stream-mix:FR"), right_id));
+                    created_links.extend(links.into_iter().map(|(position, id)| {
+                        (format!("{channel_name}->stream-mix:{position}"), id)
+                    }));
                 }
                 Err(e) => {
                     error!(
@@ -455,16 +937,17 @@ impl PipeWireRuntime {
                 }
             }
 
-            // Link channel -> monitor-mix (stereo)
-            match self.create_stereo_links(channel_id, monitor_mix_id) {
-                Ok((left_id, right_id)) => {
+            // Link channel -> monitor-mix
+            match self.create_position_links(channel_id, monitor_mix_id).await {
+                Ok(links) => {
                     info!(
                         channel = %channel_name,
                         monitor_mix_id,
                         "Linked channel to monitor-mix"
                     );
-                    created_links.push((format!("{channel_name}->monitor-mix:FL"), left_id));
-                    created_links.push((format!("{channel_name}->monitor-mix:FR"), right_id));
+                    created_links.extend(links.into_iter().map(|(position, id)| {
+                        (format!("{channel_name}->monitor-mix:{position}"), id)
+                    }));
                 }
                 Err(e) => {
                     error!(
@@ -483,7 +966,9 @@ impl PipeWireRuntime {
     ///
     /// This enables local monitoring through the headphones.
     /// Uses flexible detection to find Wave:3 by name or device properties.
-    pub fn link_monitor_to_headphones(&self) -> PwResult<(u32, u32)> {
+    ///
+    /// Returns (`position`, `link_id`) pairs.
+    pub async fn link_monitor_to_headphones(&self) -> PwResult<Vec<(String, u32)>> {
         // Get monitor-mix from registry (not created_nodes)
         let monitor_mix = self
             .graph
@@ -502,13 +987,18 @@ impl PipeWireRuntime {
             "Linking monitor-mix to Wave:3 headphones"
         );
 
-        self.create_stereo_links(monitor_mix.id, wave3_sink.id)
+        self.create_position_links(monitor_mix.id, wave3_sink.id).await
     }
 
     /// Link the monitor-mix output to a specific output device by name.
     ///
     /// This allows switching monitor output to different devices (headphones, speakers, etc.)
-    pub fn link_monitor_to_output(&self, output_device_name: &str) -> PwResult<(u32, u32)> {
+    ///
+    /// Returns (`position`, `link_id`) pairs.
+    pub async fn link_monitor_to_output(
+        &self,
+        output_device_name: &str,
+    ) -> PwResult<Vec<(String, u32)>> {
         // Get monitor-mix
         let monitor_mix = self
             .graph
@@ -528,13 +1018,13 @@ impl PipeWireRuntime {
             "Linking monitor-mix to output device"
         );
 
-        self.create_stereo_links(monitor_mix.id, output_device.id)
+        self.create_position_links(monitor_mix.id, output_device.id).await
     }
 
     /// Destroy monitor-mix links to a specific output device.
     ///
     /// Call this before switching to a new output device.
-    pub fn unlink_monitor_from_output(&self, output_device_name: &str) -> PwResult<()> {
+    pub async fn unlink_monitor_from_output(&self, output_device_name: &str) -> PwResult<()> {
         // Get monitor-mix using registry ID (from graph manager)
         let monitor_mix = self
             .graph
@@ -555,7 +1045,7 @@ impl PipeWireRuntime {
                 output_name = %output_device_name,
                 "Destroying monitor-mix to output links"
             );
-            let count = self.destroy_links_between_nodes(monitor_mix.id, output.id)?;
+            let count = self.destroy_links_between_nodes(monitor_mix.id, output.id).await?;
             info!(count, "Destroyed links from monitor-mix to output");
         }
 
@@ -566,15 +1056,17 @@ impl PipeWireRuntime {
     ///
     /// This creates a null-audio-sink that can be used as a volume control point
     /// in the audio routing graph. The node supports volume and mute control
-    /// via `set_node_volume` and `set_node_mute`.
+    /// via `set_node_volume` and `set_node_mute`. A surround filter has a
+    /// port per position and applies the layout's downmix gains on top of
+    /// the volumes it is given.
     ///
     /// If a node with the same name already exists and is undertone-managed,
     /// it will be reused instead of creating a duplicate.
-    pub fn create_volume_filter(
+    pub async fn create_volume_filter(
         &self,
         name: &str,
         description: &str,
-        channels: u32,
+        layout: ChannelLayout,
     ) -> PwResult<CreatedNode> {
         // Check if a node with this name already exists
         if let Some(existing) = self.graph.get_node_by_name(name)
@@ -588,106 +1080,315 @@ impl PipeWireRuntime {
             return Ok(CreatedNode { id: existing.id, name: name.to_string() });
         }
 
-        self.factory_tx
-            .send(FactoryRequest::CreateVolumeFilter {
-                name: name.to_string(),
-                description: description.to_string(),
-                channels,
-            })
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
+        let request = FactoryRequest::CreateVolumeFilter {
+            name: name.to_string(),
+            description: description.to_string(),
+            layout,
+        };
 
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::NodeCreated(node)) => Ok(node),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::NodeCreationFailed(e)),
-            Ok(_) => Err(PwError::NodeCreationFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::NodeCreationFailed("Timeout waiting for response".to_string())),
+        match self.request(request).await? {
+            FactoryResponse::NodeCreated(node) => Ok(node),
+            FactoryResponse::Error(e) => Err(PwError::NodeCreationFailed(e)),
+            _ => Err(PwError::NodeCreationFailed("Unexpected response".to_string())),
         }
     }
 
     /// Create volume filter nodes for all channels.
     ///
-    /// For each channel, this creates two filter nodes:
-    /// - `ut-ch-{name}-stream-vol` for stream mix volume control
-    /// - `ut-ch-{name}-monitor-vol` for monitor mix volume control
+    /// For each channel, this creates one filter node per mix, named
+    /// `ut-ch-{name}-{mix}-vol` (e.g. `ut-ch-music-stream-vol`).
     ///
     /// Returns a vector of (`filter_name`, `node_id`) pairs.
-    pub fn create_channel_volume_filters(&self, channels: &[&str]) -> PwResult<Vec<(String, u32)>> {
+    pub async fn create_channel_volume_filters(
+        &self,
+        channels: &[ChannelConfig],
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
         let mut filters = Vec::new();
 
         for channel in channels {
-            // Create stream volume filter
-            let stream_name = format!("ut-ch-{channel}-stream-vol");
-            let stream_desc = format!("Undertone: {} Stream Volume", capitalize(channel));
-            match self.create_volume_filter(&stream_name, &stream_desc, 2) {
-                Ok(node) => {
-                    info!(name = %node.name, id = node.id, "Created stream volume filter");
-                    filters.push((node.name, node.id));
-                }
-                Err(e) => {
-                    error!(channel = %channel, error = %e, "Failed to create stream volume filter");
-                    return Err(e);
+            for mix in mixes {
+                let name = channel.vol_node_name(&mix.id());
+                let desc =
+                    format!("Undertone: {} {} Volume", channel.display_name, mix.display_name);
+                match self.create_volume_filter(&name, &desc, channel.layout).await {
+                    Ok(node) => {
+                        info!(name = %node.name, id = node.id, "Created volume filter");
+                        filters.push((node.name, node.id));
+                    }
+                    Err(e) => {
+                        error!(
+                            channel = %channel.name,
+                            mix = %mix.name,
+                            error = %e,
+                            "Failed to create volume filter"
+                        );
+                        return Err(e);
+                    }
                 }
             }
+        }
+
+        Ok(filters)
+    }
+
+    /// Create the mic volume filters, one per mix.
+    ///
+    /// `ut-mic-{mix}-vol` carries the mic into that mix; in the monitor mix
+    /// this is sidetone.
+    ///
+    /// Returns a vector of (`filter_name`, `node_id`) pairs.
+    pub async fn create_mic_volume_filters(
+        &self,
+        mixes: &[MixConfig],
+    ) -> PwResult<Vec<(String, u32)>> {
+        let mut filters = Vec::new();
+
+        for mix in mixes {
+            let name = mix.id().mic_vol_node_name();
+            let description = format!("Undertone: Mic {} Volume", mix.display_name);
+            let node =
+                self.create_volume_filter(&name, &description, ChannelLayout::Stereo).await?;
+            info!(name = %node.name, id = node.id, "Created mic volume filter");
+            filters.push((node.name, node.id));
+        }
+
+        Ok(filters)
+    }
+
+    /// Link the Wave:3 mic through its volume filters into the given mixes.
+    ///
+    /// Links that already exist are left alone, so this is safe to call
+    /// again when the mic reappears. Whether the mic is audible in a mix is
+    /// controlled by its filter's volume and mute, not by these links.
+    ///
+    /// Returns a vector of (`link_description`, `link_id`) tuples for tracking.
+    pub async fn link_mic_to_mixes(&self, mixes: &[MixConfig]) -> PwResult<Vec<(String, u32)>> {
+        let mic = self
+            .graph
+            .find_wave3_source()
+            .ok_or_else(|| PwError::NodeNotFound("Wave:3 source".to_string()))?;
+        if self.graph.get_output_ports(mic.id).is_empty() {
+            return Err(PwError::PortNotFound(format!("{} output ports", mic.name)));
+        }
+
+        let mut created_links = Vec::new();
+
+        for mix in mixes.iter().map(MixConfig::id) {
+            let filter_name = mix.mic_vol_node_name();
+            let mix_name = mix.mix_node_name();
+            let filter = self
+                .graph
+                .get_node_by_name(&filter_name)
+                .ok_or_else(|| PwError::NodeNotFound(filter_name.clone()))?;
+            let mix_node = self
+                .graph
+                .get_node_by_name(&mix_name)
+                .ok_or_else(|| PwError::NodeNotFound(mix_name.clone()))?;
+
+            // Mic -> mic volume filter (a mono mic feeds both sides)
+            if !self.graph.has_link(mic.id, filter.id) {
+                let links = self.create_position_links(mic.id, filter.id).await?;
+                created_links.extend(
+                    links
+                        .into_iter()
+                        .map(|(position, id)| (format!("mic->{filter_name}:{position}"), id)),
+                );
+                info!(mic = %mic.name, filter = %filter_name, "Linked mic to volume filter");
+            }
+
+            // Mic volume filter -> mix
+            if !self.graph.has_link(filter.id, mix_node.id) {
+                let links = self.create_position_links(filter.id, mix_node.id).await?;
+                let short_mix = mix_name.trim_start_matches("ut-");
+                created_links.extend(
+                    links.into_iter().map(|(position, id)| {
+                        (format!("{filter_name}->{short_mix}:{position}"), id)
+                    }),
+                );
+                info!(filter = %filter_name, mix = %mix_name, "Linked mic volume filter to mix");
+            }
+        }
+
+        Ok(created_links)
+    }
+
+    /// Set volume on a node.
+    ///
+    /// Returns as soon as the request is queued, so slider drags never wait
+    /// on `PipeWire`. Failures reported by the `PipeWire` thread are logged.
+    ///
+    /// # Arguments
+    /// * `node_id` - The `PipeWire` node ID
+    /// * `volume` - Volume level from 0.0 (silent) to 1.0 (full volume)
+    pub fn set_node_volume(&self, node_id: u32, volume: f32) -> PwResult<()> {
+        self.set_node_volumes(node_id, [volume, volume])
+    }
+
+    /// Set separate left and right volumes on a node, e.g. to apply a
+    /// channel's balance.
+    ///
+    /// Like `set_node_volume`, this returns as soon as the request is queued.
+    ///
+    /// # Arguments
+    /// * `node_id` - The `PipeWire` node ID
+    /// * `volumes` - Left and right volume levels from 0.0 to 1.0
+    pub fn set_node_volumes(&self, node_id: u32, volumes: [f32; 2]) -> PwResult<()> {
+        let volumes = volumes.map(|v| v.clamp(0.0, 1.0));
+        let pending = self.submit(FactoryRequest::SetNodeVolume { node_id, volumes })?;
+
+        tokio::spawn(async move {
+            match pending.wait(REQUEST_TIMEOUT).await {
+                Ok(FactoryResponse::VolumeSet { .. }) => {}
+                Ok(FactoryResponse::Error(e)) => warn!(node_id, error = %e, "Failed to set volume"),
+                Ok(_) => warn!(node_id, "Unexpected response to volume request"),
+                Err(e) => warn!(node_id, error = %e, "Volume request failed"),
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Set mute state on a node.
+    ///
+    /// Like `set_node_volume`, this returns as soon as the request is queued.
+    ///
+    /// # Arguments
+    /// * `node_id` - The `PipeWire` node ID
+    /// * `muted` - True to mute, false to unmute
+    pub fn set_node_mute(&self, node_id: u32, muted: bool) -> PwResult<()> {
+        let pending = self.submit(FactoryRequest::SetNodeMute { node_id, muted })?;
+
+        tokio::spawn(async move {
+            match pending.wait(REQUEST_TIMEOUT).await {
+                Ok(FactoryResponse::MuteSet { .. }) => {}
+                Ok(FactoryResponse::Error(e)) => warn!(node_id, error = %e, "Failed to set mute"),
+                Ok(_) => warn!(node_id, "Unexpected response to mute request"),
+                Err(e) => warn!(node_id, error = %e, "Mute request failed"),
+            }
+        });
+
+        Ok(())
+    }
 
-            // Create monitor volume filter
-            let monitor_name = format!("ut-ch-{channel}-monitor-vol");
-            let monitor_desc = format!("Undertone: {} Monitor Volume", capitalize(channel));
-            match self.create_volume_filter(&monitor_name, &monitor_desc, 2) {
-                Ok(node) => {
-                    info!(name = %node.name, id = node.id, "Created monitor volume filter");
-                    filters.push((node.name, node.id));
-                }
-                Err(e) => {
-                    error!(channel = %channel, error = %e, "Failed to create monitor volume filter");
-                    return Err(e);
+    /// Fade a node's left and right volumes to new levels over `duration`.
+    ///
+    /// The fade runs on the `PipeWire` thread, starting from wherever the
+    /// node is now; any fade already running on the node is cancelled, as it
+    /// is by `set_node_volumes` and `set_node_mute`. A fade out to mute is the
+    /// exception for `set_node_volumes`: it carries on and leaves the node at
+    /// the new volumes once muted. Like `set_node_volume`, this returns as
+    /// soon as the request is queued.
+    pub fn fade_node_volumes(
+        &self,
+        node_id: u32,
+        volumes: [f32; 2],
+        duration: Duration,
+    ) -> PwResult<()> {
+        let volumes = volumes.map(|v| v.clamp(0.0, 1.0));
+        let pending = self.submit(FactoryRequest::FadeNodeVolume { node_id, volumes, duration })?;
+
+        tokio::spawn(async move {
+            match pending.wait(REQUEST_TIMEOUT).await {
+                Ok(FactoryResponse::VolumeSet { .. }) => {}
+                Ok(FactoryResponse::Error(e)) => {
+                    warn!(node_id, error = %e, "Failed to fade volume")
                 }
+                Ok(_) => warn!(node_id, "Unexpected response to fade request"),
+                Err(e) => warn!(node_id, error = %e, "Fade request failed"),
             }
-        }
+        });
 
-        Ok(filters)
+        Ok(())
     }
 
-    /// Set volume on a node.
+    /// Fade a node out and then mute it, or unmute it and fade it in.
     ///
-    /// # Arguments
-    /// * `node_id` - The `PipeWire` node ID
-    /// * `volume` - Volume level from 0.0 (silent) to 1.0 (full volume)
-    pub fn set_node_volume(&self, node_id: u32, volume: f32) -> PwResult<()> {
-        let volume = volume.clamp(0.0, 1.0);
+    /// `volumes` are the node's levels while audible: an unmuted node fades
+    /// up to them, and a muted node is left at them so that a later unmute
+    /// is heard at the right level. Cancels fades like `fade_node_volumes`.
+    pub fn fade_node_mute(
+        &self,
+        node_id: u32,
+        muted: bool,
+        volumes: [f32; 2],
+        duration: Duration,
+    ) -> PwResult<()> {
+        let volumes = volumes.map(|v| v.clamp(0.0, 1.0));
+        let pending =
+            self.submit(FactoryRequest::FadeNodeMute { node_id, muted, volumes, duration })?;
+
+        tokio::spawn(async move {
+            match pending.wait(REQUEST_TIMEOUT).await {
+                Ok(FactoryResponse::MuteSet { .. }) => {}
+                Ok(FactoryResponse::Error(e)) => warn!(node_id, error = %e, "Failed to fade mute"),
+                Ok(_) => warn!(node_id, "Unexpected response to fade request"),
+                Err(e) => warn!(node_id, error = %e, "Fade request failed"),
+            }
+        });
 
-        self.factory_tx
-            .send(FactoryRequest::SetNodeVolume { node_id, volume })
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
+        Ok(())
+    }
 
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::VolumeSet { .. }) => Ok(()),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::VolumeControlFailed(e)),
-            Ok(_) => Err(PwError::VolumeControlFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::VolumeControlFailed("Timeout waiting for response".to_string())),
+    /// Start metering every channel, every mix and the mic.
+    ///
+    /// Replaces any meters started earlier, so call this again whenever
+    /// channels are added or removed or the graph has been rebuilt.
+    ///
+    /// Returns the number of meter streams running.
+    pub async fn start_metering(
+        &self,
+        channels: &[ChannelConfig],
+        mixes: &[MixConfig],
+    ) -> PwResult<usize> {
+        let targets = MeterTarget::for_channels(channels, mixes, &self.graph);
+        match self.request(FactoryRequest::SetMeters(targets)).await? {
+            FactoryResponse::MetersSet { count } => Ok(count),
+            FactoryResponse::Error(e) => Err(PwError::NodeCreationFailed(e)),
+            _ => Err(PwError::NodeCreationFailed("Unexpected response".to_string())),
         }
     }
 
-    /// Set mute state on a node.
+    /// Take the peak and RMS levels measured since the last call.
+    #[must_use]
+    pub fn take_levels(&self) -> HashMap<MeterSource, MeterLevels> {
+        self.levels.take_levels()
+    }
+
+    /// Whether the runtime is currently connected to `PipeWire`.
     ///
-    /// # Arguments
-    /// * `node_id` - The `PipeWire` node ID
-    /// * `muted` - True to mute, false to unmute
-    pub fn set_node_mute(&self, node_id: u32, muted: bool) -> PwResult<()> {
-        self.factory_tx
-            .send(FactoryRequest::SetNodeMute { node_id, muted })
-            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
+    /// While disconnected the runtime keeps trying to reconnect in the
+    /// background, and node/link requests fail immediately.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Acquire)
+    }
+
+    /// Send a request to the `PipeWire` thread and wait for its response.
+    async fn request(&self, request: FactoryRequest) -> PwResult<FactoryResponse> {
+        self.submit(request)?.wait(REQUEST_TIMEOUT).await
+    }
 
-        match self.factory_rx.recv_timeout(Duration::from_secs(5)) {
-            Ok(FactoryResponse::MuteSet { .. }) => Ok(()),
-            Ok(FactoryResponse::Error(e)) => Err(PwError::VolumeControlFailed(e)),
-            Ok(_) => Err(PwError::VolumeControlFailed("Unexpected response".to_string())),
-            Err(_) => Err(PwError::VolumeControlFailed("Timeout waiting for response".to_string())),
+    /// Send a request to the `PipeWire` thread, failing fast while disconnected.
+    ///
+    /// The request is tagged with a fresh ID; the returned handle receives
+    /// the response carrying that ID.
+    fn submit(&self, request: FactoryRequest) -> PwResult<PendingResponse> {
+        if !self.is_connected() {
+            return Err(PwError::Disconnected);
         }
+
+        let pending = self.pending.register();
+        self.factory_tx
+            .send(TaggedRequest { id: pending.id, request })
+            .map_err(|_| PwError::MainLoopError("Factory channel closed".to_string()))?;
+        Ok(pending)
     }
 
     /// Request shutdown of the `PipeWire` thread.
     pub fn shutdown(&self) {
-        let _ = self.factory_tx.send(FactoryRequest::Shutdown);
+        let id = self.pending.register().id;
+        let _ = self.factory_tx.send(TaggedRequest { id, request: FactoryRequest::Shutdown });
     }
 
     /// Route an app's audio output to a specific Undertone channel.
@@ -701,7 +1402,11 @@ impl PipeWireRuntime {
     ///
     /// # Returns
     /// A vector of created link IDs, or an error.
-    pub fn route_app_to_channel(&self, app_node_id: u32, channel_name: &str) -> PwResult<Vec<u32>> {
+    pub async fn route_app_to_channel(
+        &self,
+        app_node_id: u32,
+        channel_name: &str,
+    ) -> PwResult<Vec<u32>> {
         let channel_sink_name = format!("ut-ch-{channel_name}");
 
         // Get the channel sink node ID from registry
@@ -732,44 +1437,26 @@ impl PipeWireRuntime {
                 // Check if the destination is not already our target channel
                 if link.input_node != channel_id {
                     debug!(link_id = link.id, "Destroying existing app link");
-                    if let Err(e) = self.destroy_link(link.id) {
+                    if let Err(e) = self.destroy_link(link.id).await {
                         warn!(error = %e, link_id = link.id, "Failed to destroy existing link");
                     }
                 }
             }
         }
 
-        // Create new links from app to channel
-        // App streams use output_FL/output_FR for output ports
-        // Channel sinks use playback_FL/playback_FR for input ports
-        let mut created_links = Vec::new();
-
-        // Try FL/FR first (most common for stereo apps)
-        match self.create_link(app_node_id, "output_FL", channel_id, "playback_FL") {
-            Ok(id) => {
-                created_links.push(id);
-                debug!(link_id = id, "Created left channel link");
-            }
-            Err(e) => {
-                // Try MONO if stereo fails
-                debug!(error = %e, "Failed to create FL link, trying MONO");
-                if let Ok(id) =
-                    self.create_link(app_node_id, "output_MONO", channel_id, "playback_FL")
-                {
-                    created_links.push(id);
-                }
-            }
-        }
-
-        match self.create_link(app_node_id, "output_FR", channel_id, "playback_FR") {
-            Ok(id) => {
-                created_links.push(id);
-                debug!(link_id = id, "Created right channel link");
-            }
-            Err(e) => {
-                debug!(error = %e, "Failed to create FR link (mono source?)");
-            }
-        }
+        // Link every position the app plays to the channel, so mono apps
+        // reach both sides and surround apps keep their layout on a surround
+        // channel. Apps whose ports are not registered yet are linked by
+        // port name, front pair only.
+        let created_links = if self.graph.get_output_ports(app_node_id).is_empty() {
+            self.link_app_by_port_name(app_node_id, channel_id).await
+        } else {
+            self.create_position_links(app_node_id, channel_id)
+                .await?
+                .into_iter()
+                .map(|(_, id)| id)
+                .collect()
+        };
 
         if created_links.is_empty() {
             return Err(PwError::LinkCreationFailed(
@@ -787,6 +1474,101 @@ impl PipeWireRuntime {
         Ok(created_links)
     }
 
+    /// Feed a recording app from a mix, or from the mic.
+    ///
+    /// `source` is a mix name or [`CAPTURE_MIC`]. Whatever the session
+    /// manager linked into the app is replaced with links from the mix's
+    /// monitor ports (or the mic's capture ports), matched by position.
+    ///
+    /// Returns the created link IDs.
+    pub async fn route_capture_to_source(
+        &self,
+        app_node_id: u32,
+        source: &str,
+    ) -> PwResult<Vec<u32>> {
+        let source_node = if source == CAPTURE_MIC {
+            self.graph
+                .find_wave3_source()
+                .ok_or_else(|| PwError::NodeNotFound("Wave:3 source".to_string()))?
+        } else {
+            let mix_node_name = MixType::new(source).mix_node_name();
+            self.graph
+                .get_node_by_name(&mix_node_name)
+                .ok_or_else(|| PwError::NodeNotFound(mix_node_name.clone()))?
+        };
+
+        let app_node = self
+            .graph
+            .get_node(app_node_id)
+            .ok_or_else(|| PwError::NodeNotFound(format!("App node {app_node_id}")))?;
+        if self.graph.get_input_ports(app_node_id).is_empty() {
+            return Err(PwError::PortNotFound(format!(
+                "Recording app {} has no input ports yet",
+                app_node.name
+            )));
+        }
+
+        info!(
+            app_id = app_node_id,
+            app_name = %app_node.name,
+            source = %source,
+            "Routing recording app"
+        );
+
+        for link in self.graph.get_links_for_node(app_node_id) {
+            if link.input_node == app_node_id && link.output_node != source_node.id {
+                debug!(link_id = link.id, "Destroying existing capture link");
+                if let Err(e) = self.destroy_link(link.id).await {
+                    warn!(error = %e, link_id = link.id, "Failed to destroy existing link");
+                }
+            }
+        }
+
+        let created_links: Vec<u32> = self
+            .create_position_links(source_node.id, app_node_id)
+            .await?
+            .into_iter()
+            .map(|(_, id)| id)
+            .collect();
+
+        info!(
+            app_id = app_node_id,
+            source = %source,
+            links_created = created_links.len(),
+            "Recording app routed successfully"
+        );
+
+        Ok(created_links)
+    }
+
+    /// Link an app to a channel sink by port name, for apps whose ports
+    /// have not reached the registry yet.
+    ///
+    /// App streams use `output_FL`/`output_FR` (or `output_MONO`) for output
+    /// ports; channel sinks use `playback_FL`/`playback_FR` for input ports.
+    /// A mono app feeds both sides.
+    async fn link_app_by_port_name(&self, app_node_id: u32, channel_id: u32) -> Vec<u32> {
+        // Try FL/FR first (most common for stereo apps)
+        let (left, right) = futures::future::join(
+            self.create_link(app_node_id, "output_FL", channel_id, "playback_FL"),
+            self.create_link(app_node_id, "output_FR", channel_id, "playback_FR"),
+        )
+        .await;
+        let created_links: Vec<u32> = [left, right].into_iter().filter_map(Result::ok).collect();
+        if !created_links.is_empty() {
+            debug!(links = ?created_links, "Created stereo app links");
+            return created_links;
+        }
+
+        debug!("No stereo ports, trying MONO");
+        let (left, right) = futures::future::join(
+            self.create_link(app_node_id, "output_MONO", channel_id, "playback_FL"),
+            self.create_link(app_node_id, "output_MONO", channel_id, "playback_FR"),
+        )
+        .await;
+        [left, right].into_iter().filter_map(Result::ok).collect()
+    }
+
     /// Get all audio client apps currently in the graph.
     #[must_use]
     pub fn get_audio_clients(&self) -> Vec<crate::node::NodeInfo> {
@@ -794,20 +1576,56 @@ impl PipeWireRuntime {
     }
 }
 
-fn capitalize(s: &str) -> String {
-    let mut chars = s.chars();
-    match chars.next() {
-        None => String::new(),
-        Some(c) => c.to_uppercase().chain(chars).collect(),
-    }
+/// How long to wait for the `PipeWire` thread to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Delay before the first reconnection attempt; doubles on each failure.
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+/// Upper bound for the reconnection backoff.
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+/// How often the supervisor checks the connection.
+const SUPERVISOR_INTERVAL: Duration = Duration::from_millis(250);
+/// How often running fades are stepped.
+const FADE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Everything tied to a single `PipeWire` connection.
+///
+/// Dropping this disconnects from `PipeWire`; the nodes and links we created
+/// go away with it. Fields drop in declaration order, so the listeners and
+/// proxies are released before the core.
+struct Connection {
+    _core_listener: pipewire::core::Listener,
+    _registry_listener: pipewire::registry::Listener,
+    /// Running meter streams
+    meters: RefCell<Vec<MeterStream>>,
+    /// Created node proxies by proxy ID - must be kept alive to keep the nodes
+    node_proxies: Rc<RefCell<HashMap<u32, pipewire::node::Node>>>,
+    /// Created link proxies by proxy ID, with their (output, input) node IDs
+    link_proxies: Rc<RefCell<HashMap<u32, (pipewire::link::Link, u32, u32)>>>,
+    /// Volume and mute last written to each created node, and the fades
+    /// in progress on them
+    levels: RefCell<NodeFades>,
+    /// Layout of created surround volume filters, whose stereo volumes are
+    /// spread over every position with the downmix gains applied
+    surround_filters: RefCell<HashMap<u32, ChannelLayout>>,
+    /// Session manager client and default metadata, as seen in the registry
+    session_manager: Rc<RefCell<SessionManagerTracker>>,
+    registry: pipewire::registry::RegistryRc,
+    core: pipewire::core::CoreRc,
 }
 
 /// Run the `PipeWire` thread - this combines monitoring and node creation.
+///
+/// The main loop outlives individual connections. When the core reports the
+/// connection as broken, a supervisor timer tears it down, clears the graph
+/// and reconnects with exponential backoff. Factory requests received while
+/// disconnected are answered with an error straight away.
 fn run_pipewire_thread(
     graph: Arc<GraphManager>,
     event_tx: mpsc::Sender<GraphEvent>,
-    factory_rx: pipewire::channel::Receiver<FactoryRequest>,
-    factory_tx: std_mpsc::Sender<FactoryResponse>,
+    factory_rx: pipewire::channel::Receiver<TaggedRequest>,
+    pending: Arc<PendingRequests>,
+    connected: Arc<AtomicBool>,
+    levels: Arc<LevelMeter>,
 ) -> PwResult<()> {
     // Initialize PipeWire
     pipewire::init();
@@ -821,6 +1639,103 @@ fn run_pipewire_thread(
     let context = ContextRc::new(&main_loop, None)
         .map_err(|e| PwError::ConnectionFailed(format!("Failed to create context: {e}")))?;
 
+    // Set by the core error callback; acted on by the supervisor
+    let lost = Rc::new(Cell::new(false));
+
+    let connection = Rc::new(RefCell::new(Some(connect(&context, &graph, &event_tx, &lost)?)));
+    connected.store(true, Ordering::Release);
+
+    info!("Connected to PipeWire");
+    let _ = event_tx.blocking_send(GraphEvent::Connected);
+
+    // Attach factory request receiver to the loop
+    let main_loop_for_shutdown = main_loop.clone();
+    let connection_for_factory = Rc::clone(&connection);
+    let connection_for_fades = Rc::clone(&connection);
+    let pending_for_factory = Arc::clone(&pending);
+    let context_for_factory = context.clone();
+    let _factory_receiver =
+        factory_rx.attach(main_loop.loop_(), move |TaggedRequest { id, request }| {
+            if matches!(request, FactoryRequest::Shutdown) {
+                info!("Factory received shutdown request");
+                main_loop_for_shutdown.quit();
+                return;
+            }
+
+            let response = match connection_for_factory.borrow().as_ref() {
+                Some(conn) => handle_factory_request(conn, &context_for_factory, request, &levels),
+                None => {
+                    debug!(id, "Rejecting factory request while disconnected");
+                    FactoryResponse::Error("PipeWire is disconnected".to_string())
+                }
+            };
+            pending_for_factory.complete(id, response);
+        });
+
+    // Supervise the connection: tear down on loss, reconnect with backoff
+    let backoff = Cell::new(RECONNECT_INITIAL_DELAY);
+    let next_attempt = Cell::new(Instant::now());
+    let supervisor = main_loop.loop_().add_timer(move |_| {
+        if lost.replace(false) && connection.borrow_mut().take().is_some() {
+            warn!("Lost connection to PipeWire");
+            connected.store(false, Ordering::Release);
+            graph.clear();
+            let _ = event_tx.blocking_send(GraphEvent::Disconnected);
+
+            backoff.set(RECONNECT_INITIAL_DELAY);
+            next_attempt.set(Instant::now() + RECONNECT_INITIAL_DELAY);
+        }
+
+        if connection.borrow().is_some() || Instant::now() < next_attempt.get() {
+            return;
+        }
+
+        match connect(&context, &graph, &event_tx, &lost) {
+            Ok(conn) => {
+                *connection.borrow_mut() = Some(conn);
+                connected.store(true, Ordering::Release);
+                backoff.set(RECONNECT_INITIAL_DELAY);
+
+                info!("Reconnected to PipeWire");
+                let _ = event_tx.blocking_send(GraphEvent::Connected);
+            }
+            Err(e) => {
+                let delay = (backoff.get() * 2).min(RECONNECT_MAX_DELAY);
+                backoff.set(delay);
+                next_attempt.set(Instant::now() + delay);
+                warn!(error = %e, retry_in_ms = delay.as_millis(), "PipeWire reconnection failed");
+            }
+        }
+    });
+    let _ = supervisor.update_timer(Some(SUPERVISOR_INTERVAL), Some(SUPERVISOR_INTERVAL));
+
+    // Step volume fades; a no-op while none are running
+    let fader = main_loop.loop_().add_timer(move |_| {
+        if let Some(conn) = connection_for_fades.borrow().as_ref() {
+            step_fades(conn);
+        }
+    });
+    let _ = fader.update_timer(Some(FADE_INTERVAL), Some(FADE_INTERVAL));
+
+    info!("Starting PipeWire main loop");
+    main_loop.run();
+
+    // Nothing will answer from here on
+    pending.abandon_all();
+
+    info!("PipeWire runtime exiting");
+    Ok(())
+}
+
+/// Connect to `PipeWire` and start mirroring its registry into the graph.
+///
+/// `lost` is set when the core reports that the connection is broken.
+fn connect(
+    context: &ContextRc,
+    graph: &Arc<GraphManager>,
+    event_tx: &mpsc::Sender<GraphEvent>,
+    lost: &Rc<Cell<bool>>,
+) -> PwResult<Connection> {
     let core = context
         .connect_rc(None)
         .map_err(|e| PwError::ConnectionFailed(format!("Failed to connect: {e}")))?;
@@ -829,143 +1744,201 @@ fn run_pipewire_thread(
         .get_registry_rc()
         .map_err(|e| PwError::RegistryError(format!("Failed to get registry: {e}")))?;
 
-    info!("Connected to PipeWire");
-    let _ = event_tx.blocking_send(GraphEvent::Connected);
+    // EPIPE on the core object means the socket to PipeWire is gone
+    let lost_core = Rc::clone(lost);
+    let core_listener = core
+        .add_listener_local()
+        .error(move |id, _seq, res, message| {
+            if id == pipewire::core::PW_ID_CORE {
+                warn!(res, message, "PipeWire core error");
+                if res == -libc::EPIPE {
+                    lost_core.set(true);
+                }
+            }
+        })
+        .register();
 
     // Track nodes for removal events
     let nodes: Rc<RefCell<HashMap<u32, String>>> = Rc::new(RefCell::new(HashMap::new()));
     let nodes_remove = Rc::clone(&nodes);
 
+    // Track the session manager client to detect WirePlumber restarts
+    let session_manager: Rc<RefCell<SessionManagerTracker>> = Rc::default();
+    let session_manager_global = Rc::clone(&session_manager);
+    let session_manager_remove = Rc::clone(&session_manager);
+
     // Clone for closures
     let event_tx_global = event_tx.clone();
     let event_tx_remove = event_tx.clone();
-    let graph_global = Arc::clone(&graph);
-    let graph_remove = Arc::clone(&graph);
+    let graph_global = Arc::clone(graph);
+    let graph_remove = Arc::clone(graph);
 
     // Set up registry listener
-    let _listener = registry
+    let registry_listener = registry
         .add_listener_local()
         .global(move |global| {
-            handle_global(&event_tx_global, &graph_global, &nodes, global);
+            handle_global(&event_tx_global, &graph_global, &nodes, &session_manager_global, global);
         })
         .global_remove(move |id| {
-            handle_global_remove(&event_tx_remove, &graph_remove, &nodes_remove, id);
+            handle_global_remove(
+                &event_tx_remove,
+                &graph_remove,
+                &nodes_remove,
+                &session_manager_remove,
+                id,
+            );
         })
         .register();
 
-    // Clone main loop and core for factory callback
-    let main_loop_for_shutdown = main_loop.clone();
-    let core_for_factory = core.clone();
-
-    // Storage for created node proxies - must be kept alive to prevent node destruction
-    // Use HashMap to enable looking up nodes by ID for volume control and destruction
-    let node_proxies: Rc<RefCell<HashMap<u32, pipewire::node::Node>>> =
-        Rc::new(RefCell::new(HashMap::new()));
-    // Use HashMap for links, storing (proxy, output_node, input_node) to enable lookup by nodes
-    let link_proxies: Rc<RefCell<HashMap<u32, (pipewire::link::Link, u32, u32)>>> =
-        Rc::new(RefCell::new(HashMap::new()));
-    let node_proxies_clone = Rc::clone(&node_proxies);
-    let node_proxies_volume = Rc::clone(&node_proxies);
-    let node_proxies_mute = Rc::clone(&node_proxies);
-    let node_proxies_destroy = Rc::clone(&node_proxies);
-    let link_proxies_clone = Rc::clone(&link_proxies);
-    let link_proxies_destroy = Rc::clone(&link_proxies);
-    let link_proxies_destroy_by_nodes = Rc::clone(&link_proxies);
-    // Clone registry for destroying external links (not created by us)
-    let registry_for_destroy = registry.clone();
+    Ok(Connection {
+        _core_listener: core_listener,
+        _registry_listener: registry_listener,
+        meters: RefCell::new(Vec::new()),
+        node_proxies: Rc::new(RefCell::new(HashMap::new())),
+        link_proxies: Rc::new(RefCell::new(HashMap::new())),
+        levels: RefCell::new(NodeFades::new()),
+        surround_filters: RefCell::new(HashMap::new()),
+        session_manager,
+        registry,
+        core,
+    })
+}
 
-    // Attach factory request receiver to the loop
-    let _factory_receiver = factory_rx.attach(main_loop.loop_(), move |request| match request {
+/// Serve a factory request on the current connection.
+fn handle_factory_request(
+    conn: &Connection,
+    context: &ContextRc,
+    request: FactoryRequest,
+    levels: &Arc<LevelMeter>,
+) -> FactoryResponse {
+    match request {
         FactoryRequest::CreateSink(props) => {
-            match create_virtual_sink(&core_for_factory, &props, &node_proxies_clone) {
-                Ok(node) => {
-                    let _ = factory_tx.send(FactoryResponse::NodeCreated(node));
-                }
-                Err(e) => {
-                    let _ = factory_tx.send(FactoryResponse::Error(e.to_string()));
-                }
+            match create_virtual_sink(&conn.core, &props, &conn.node_proxies) {
+                Ok(node) => FactoryResponse::NodeCreated(node),
+                Err(e) => FactoryResponse::Error(e.to_string()),
             }
         }
-        FactoryRequest::CreateVolumeFilter { name, description, channels } => {
-            match create_volume_filter(
-                &core_for_factory,
-                &name,
-                &description,
-                channels,
-                &node_proxies_clone,
-            ) {
+        FactoryRequest::CreateVolumeFilter { name, description, layout } => {
+            match create_volume_filter(&conn.core, &name, &description, layout, &conn.node_proxies)
+            {
                 Ok(node) => {
-                    let _ = factory_tx.send(FactoryResponse::NodeCreated(node));
-                }
-                Err(e) => {
-                    let _ = factory_tx.send(FactoryResponse::Error(e.to_string()));
+                    if layout.is_surround() {
+                        conn.surround_filters.borrow_mut().insert(node.id, layout);
+                    }
+                    FactoryResponse::NodeCreated(node)
                 }
+                Err(e) => FactoryResponse::Error(e.to_string()),
+            }
+        }
+        FactoryRequest::LoadFilterChain { name, args } => {
+            match load_filter_chain(context, &name, &args) {
+                Ok(()) => FactoryResponse::FilterChainLoaded,
+                Err(e) => FactoryResponse::Error(e.to_string()),
             }
         }
         FactoryRequest::CreateLink { output_node, output_port, input_node, input_port } => {
             match create_link(
-                &core_for_factory,
+                &conn.core,
                 output_node,
                 &output_port,
                 input_node,
                 &input_port,
-                &link_proxies_clone,
+                &conn.link_proxies,
             ) {
-                Ok(id) => {
-                    let _ = factory_tx.send(FactoryResponse::LinkCreated { id });
-                }
-                Err(e) => {
-                    let _ = factory_tx.send(FactoryResponse::Error(e.to_string()));
-                }
+                Ok(id) => FactoryResponse::LinkCreated { id },
+                Err(e) => FactoryResponse::Error(e.to_string()),
             }
         }
-        FactoryRequest::SetNodeVolume { node_id, volume } => {
-            match set_node_volume(&node_proxies_volume, node_id, volume) {
-                Ok(()) => {
-                    let _ = factory_tx.send(FactoryResponse::VolumeSet { node_id });
-                }
-                Err(e) => {
-                    let _ = factory_tx.send(FactoryResponse::Error(e.to_string()));
-                }
+        FactoryRequest::SetNodeVolume { node_id, volumes } => {
+            let result = conn
+                .levels
+                .borrow_mut()
+                .set_volume(node_id, volumes, |id, change| write_level(conn, id, change));
+            match result {
+                Ok(()) => FactoryResponse::VolumeSet { node_id },
+                Err(e) => FactoryResponse::Error(e.to_string()),
             }
         }
         FactoryRequest::SetNodeMute { node_id, muted } => {
-            match set_node_mute(&node_proxies_mute, node_id, muted) {
-                Ok(()) => {
-                    let _ = factory_tx.send(FactoryResponse::MuteSet { node_id });
-                }
-                Err(e) => {
-                    let _ = factory_tx.send(FactoryResponse::Error(e.to_string()));
-                }
+            let result = conn
+                .levels
+                .borrow_mut()
+                .set_mute(node_id, muted, |id, change| write_level(conn, id, change));
+            match result {
+                Ok(()) => FactoryResponse::MuteSet { node_id },
+                Err(e) => FactoryResponse::Error(e.to_string()),
+            }
+        }
+        FactoryRequest::FadeNodeVolume { node_id, volumes, duration } => {
+            let result = conn.levels.borrow_mut().fade_volume(
+                node_id,
+                volumes,
+                duration,
+                Instant::now(),
+                |id, change| write_level(conn, id, change),
+            );
+            match result {
+                Ok(()) => FactoryResponse::VolumeSet { node_id },
+                Err(e) => FactoryResponse::Error(e.to_string()),
+            }
+        }
+        FactoryRequest::FadeNodeMute { node_id, muted, volumes, duration } => {
+            let result = conn.levels.borrow_mut().fade_mute(
+                node_id,
+                muted,
+                volumes,
+                duration,
+                Instant::now(),
+                |id, change| write_level(conn, id, change),
+            );
+            match result {
+                Ok(()) => FactoryResponse::MuteSet { node_id },
+                Err(e) => FactoryResponse::Error(e.to_string()),
+            }
+        }
+        FactoryRequest::SetNodeParams { node_id, params } => {
+            match set_node_params(&conn.registry, node_id, &params) {
+                Ok(()) => FactoryResponse::ParamsSet { node_id },
+                Err(e) => FactoryResponse::Error(e.to_string()),
             }
         }
         FactoryRequest::DestroyNode(id) => {
-            let removed = node_proxies_destroy.borrow_mut().remove(&id);
+            let removed = conn.node_proxies.borrow_mut().remove(&id);
             if removed.is_some() {
+                conn.levels.borrow_mut().forget(id);
+                conn.surround_filters.borrow_mut().remove(&id);
+                // Node we created - destroyed when the proxy is dropped
                 debug!(id, "Node destroyed");
-                let _ = factory_tx.send(FactoryResponse::NodeDestroyed { id });
+                FactoryResponse::NodeDestroyed { id }
             } else {
-                let _ = factory_tx.send(FactoryResponse::Error(format!("Node {id} not found")));
+                FactoryResponse::Error(format!("Node {id} not found in proxies"))
+            }
+        }
+        FactoryRequest::DestroyExternalNode(id) => {
+            // Node not owned by this connection - the id is a registry ID
+            debug!(id, "Destroying external node via registry");
+            match conn.registry.destroy_global(id).into_result() {
+                Ok(_) => FactoryResponse::NodeDestroyed { id },
+                Err(e) => FactoryResponse::Error(format!("Failed to destroy node {id}: {e}")),
             }
         }
         FactoryRequest::DestroyLink(id) => {
-            let removed = link_proxies_destroy.borrow_mut().remove(&id);
+            let removed = conn.link_proxies.borrow_mut().remove(&id);
             if removed.is_some() {
                 // Link we created - destroyed when the proxy is dropped (no object.linger)
                 debug!(id, "Internal link destroyed");
-                let _ = factory_tx.send(FactoryResponse::LinkDestroyed { id });
             } else {
                 // External link (not created by us) - use registry.destroy_global
                 // The id here is a registry/global ID, not a proxy ID
                 debug!(id, "Destroying external link via registry");
-                let result = registry_for_destroy.destroy_global(id);
+                let result = conn.registry.destroy_global(id);
                 debug!(id, ?result, "External link destroy result");
-                let _ = factory_tx.send(FactoryResponse::LinkDestroyed { id });
             }
+            FactoryResponse::LinkDestroyed { id }
         }
         FactoryRequest::DestroyLinksBetweenNodes { output_node, input_node } => {
             // Find and remove all links between the specified nodes
-            let mut proxies = link_proxies_destroy_by_nodes.borrow_mut();
+            let mut proxies = conn.link_proxies.borrow_mut();
             let ids_to_remove: Vec<u32> = proxies
                 .iter()
                 .filter(|(_, (_, out_node, in_node))| {
@@ -982,23 +1955,38 @@ fn run_pipewire_thread(
             }
 
             info!(count, output_node, input_node, "Destroyed links between nodes");
-            let _ = factory_tx.send(FactoryResponse::LinksDestroyed { count });
+            FactoryResponse::LinksDestroyed { count }
+        }
+        FactoryRequest::SetMeters(targets) => {
+            // Drop the old streams before starting new ones with the same names
+            let mut meters = conn.meters.borrow_mut();
+            meters.clear();
+            levels.reset(targets.iter().map(|t| t.source.clone()));
+
+            for target in &targets {
+                match create_meter_stream(&conn.core, target, levels) {
+                    Ok(stream) => meters.push(stream),
+                    Err(e) => {
+                        warn!(target = %target.node_name, error = %e, "Failed to start meter")
+                    }
+                }
+            }
+
+            info!(count = meters.len(), "Meter streams started");
+            FactoryResponse::MetersSet { count: meters.len() }
+        }
+        FactoryRequest::SetDefaultSource(node_name) => {
+            let metadata_id = conn.session_manager.borrow().default_metadata;
+            match set_default_source(&conn.registry, metadata_id, &node_name) {
+                Ok(()) => FactoryResponse::DefaultSourceSet,
+                Err(e) => FactoryResponse::Error(e.to_string()),
+            }
         }
         FactoryRequest::Shutdown => {
-            info!("Factory received shutdown request");
-            main_loop_for_shutdown.quit();
+            // Handled by the loop callback before dispatching here
+            FactoryResponse::Error("Unexpected shutdown request".to_string())
         }
-    });
-
-    // Keep proxies alive for the duration of the main loop
-    let _node_proxies = node_proxies;
-    let _link_proxies = link_proxies;
-
-    info!("Starting PipeWire main loop");
-    main_loop.run();
-
-    info!("PipeWire runtime exiting");
-    Ok(())
+    }
 }
 
 fn create_virtual_sink(
@@ -1006,13 +1994,13 @@ fn create_virtual_sink(
     props: &VirtualSinkProps,
     proxies: &Rc<RefCell<HashMap<u32, pipewire::node::Node>>>,
 ) -> PwResult<CreatedNode> {
-    info!(name = %props.name, "Creating virtual sink");
+    info!(name = %props.name, class = %props.media_class, "Creating virtual sink");
 
     let node_props = properties! {
         "factory.name" => "support.null-audio-sink",
         "node.name" => props.name.as_str(),
         "node.description" => props.description.as_str(),
-        "media.class" => "Audio/Sink",
+        "media.class" => props.media_class.as_str(),
         "audio.channels" => props.channels.to_string().as_str(),
         "audio.position" => props.positions.as_str(),
         "undertone.managed" => "true",
@@ -1039,20 +2027,18 @@ fn create_volume_filter(
     core: &pipewire::core::CoreRc,
     name: &str,
     description: &str,
-    channels: u32,
+    layout: ChannelLayout,
     proxies: &Rc<RefCell<HashMap<u32, pipewire::node::Node>>>,
 ) -> PwResult<CreatedNode> {
-    info!(name = %name, "Creating volume filter");
-
-    let positions = if channels == 1 { "MONO" } else { "FL,FR" };
+    info!(name = %name, layout = layout.as_str(), "Creating volume filter");
 
     let node_props = properties! {
         "factory.name" => "support.null-audio-sink",
         "node.name" => name,
         "node.description" => description,
         "media.class" => "Audio/Sink",
-        "audio.channels" => channels.to_string().as_str(),
-        "audio.position" => positions,
+        "audio.channels" => layout.channels().to_string().as_str(),
+        "audio.position" => layout.positions().as_str(),
         "undertone.managed" => "true",
         "undertone.volume-filter" => "true",
         "session.suspend-timeout-seconds" => "0",
@@ -1083,17 +2069,17 @@ fn create_volume_filter(
 fn set_node_volume(
     proxies: &Rc<RefCell<HashMap<u32, pipewire::node::Node>>>,
     node_id: u32,
-    volume: f32,
+    volumes: &[f32],
 ) -> PwResult<()> {
     let proxies = proxies.borrow();
     let node = proxies
         .get(&node_id)
         .ok_or_else(|| PwError::NodeNotFound(format!("Node {node_id} not found in proxies")))?;
 
-    debug!(node_id, volume, "Setting node volume via monitorVolumes");
+    debug!(node_id, ?volumes, "Setting node volume via monitorVolumes");
 
-    // Create stereo monitor volumes array [left, right]
-    let monitor_volumes = Value::ValueArray(libspa::pod::ValueArray::Float(vec![volume, volume]));
+    // One monitor volume per position: [left, right] for stereo nodes
+    let monitor_volumes = Value::ValueArray(libspa::pod::ValueArray::Float(volumes.to_vec()));
 
     // Create a Props object with monitorVolumes property
     let props_object = Value::Object(Object {
@@ -1118,7 +2104,69 @@ fn set_node_volume(
     // Set the param on the node
     node.set_param(ParamType::Props, 0, pod);
 
-    debug!(node_id, volume, "Volume set successfully via monitorVolumes");
+    debug!(node_id, ?volumes, "Volume set successfully via monitorVolumes");
+    Ok(())
+}
+
+/// Write a volume or mute change to a created node.
+fn write_level(conn: &Connection, node_id: u32, change: LevelWrite) -> PwResult<()> {
+    match change {
+        LevelWrite::Volume(volumes) => match conn.surround_filters.borrow().get(&node_id) {
+            Some(layout) => {
+                set_node_volume(&conn.node_proxies, node_id, &layout.position_volumes(volumes))
+            }
+            None => set_node_volume(&conn.node_proxies, node_id, &volumes),
+        },
+        LevelWrite::Mute(muted) => set_node_mute(&conn.node_proxies, node_id, muted),
+    }
+}
+
+/// Move every running fade on to the current time, finishing those that are
+/// over.
+fn step_fades(conn: &Connection) {
+    let mut levels = conn.levels.borrow_mut();
+    if levels.is_idle() {
+        return;
+    }
+    for (node_id, e) in levels.step(Instant::now(), |id, change| write_level(conn, id, change)) {
+        warn!(node_id, error = %e, "Failed to step volume fade");
+    }
+}
+
+/// Make a source the configured default input, the way `wpctl set-default`
+/// does.
+///
+/// The session manager remembers the choice, so it holds across restarts
+/// and takes effect again whenever the source appears.
+fn set_default_source(
+    registry: &pipewire::registry::RegistryRc,
+    metadata_id: Option<u32>,
+    node_name: &str,
+) -> PwResult<()> {
+    let id = metadata_id.ok_or_else(|| {
+        PwError::MetadataFailed("no default metadata - is WirePlumber running?".to_string())
+    })?;
+    let global = GlobalObject::<&DictRef> {
+        id,
+        permissions: PermissionFlags::empty(),
+        type_: ObjectType::Metadata,
+        version: 0,
+        props: None,
+    };
+    let metadata: pipewire::metadata::Metadata = registry
+        .bind(&global)
+        .map_err(|e| PwError::MetadataFailed(format!("Failed to bind metadata {id}: {e}")))?;
+
+    let value = format!("{{ \"name\": \"{node_name}\" }}");
+    // The request is sent immediately, so the proxy can go once it is made
+    metadata.set_property(
+        0,
+        "default.configured.audio.source",
+        Some("Spa:String:JSON"),
+        Some(&value),
+    );
+
+    info!(node = %node_name, "Default source set");
     Ok(())
 }
 
@@ -1163,6 +2211,94 @@ fn set_node_mute(
     Ok(())
 }
 
+/// Load `libpipewire-module-filter-chain` into our own context.
+///
+/// The module creates its nodes on our core connection. It unloads itself
+/// when those nodes are destroyed or the connection goes away, so there is
+/// no module handle to keep.
+#[allow(unsafe_code)]
+fn load_filter_chain(context: &ContextRc, name: &str, args: &str) -> PwResult<()> {
+    info!(name = %name, "Loading filter chain");
+
+    let module = c"libpipewire-module-filter-chain";
+    let args = CString::new(args)
+        .map_err(|e| PwError::NodeCreationFailed(format!("Invalid filter chain arguments: {e}")))?;
+
+    // SAFETY: the context is alive for the duration of the call, both
+    // strings are NUL-terminated and outlive it, and the properties argument
+    // may be null
+    let loaded = unsafe {
+        pipewire::sys::pw_context_load_module(
+            context.as_raw_ptr(),
+            module.as_ptr(),
+            args.as_ptr(),
+            std::ptr::null_mut(),
+        )
+    };
+
+    if loaded.is_null() {
+        return Err(PwError::NodeCreationFailed(format!(
+            "Failed to load filter chain {name}: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    debug!(name = %name, "Filter chain loaded");
+    Ok(())
+}
+
+/// Set filter-chain control values on a node using SPA Props.
+///
+/// The node belongs to a filter-chain module rather than one of our
+/// proxies, so it is bound through the registry for the call.
+fn set_node_params(
+    registry: &pipewire::registry::RegistryRc,
+    node_id: u32,
+    params: &[(String, f32)],
+) -> PwResult<()> {
+    let global = GlobalObject::<&DictRef> {
+        id: node_id,
+        permissions: PermissionFlags::empty(),
+        type_: ObjectType::Node,
+        version: 0,
+        props: None,
+    };
+    let node: pipewire::node::Node = registry
+        .bind(&global)
+        .map_err(|e| PwError::NodeNotFound(format!("Failed to bind node {node_id}: {e}")))?;
+
+    debug!(node_id, count = params.len(), "Setting node params");
+
+    // params is a struct of alternating control names and values
+    let fields = params
+        .iter()
+        .flat_map(|(name, value)| [Value::String(name.clone()), Value::Float(*value)])
+        .collect();
+
+    let props_object = Value::Object(Object {
+        type_: SpaTypes::ObjectParamProps.as_raw(),
+        id: ParamType::Props.as_raw(),
+        properties: vec![Property::new(spa_props::SPA_PROP_PARAMS, Value::Struct(fields))],
+    });
+
+    let pod_bytes: Vec<u8> = PodSerializer::serialize(Cursor::new(Vec::new()), &props_object)
+        .map_err(|e| {
+            PwError::VolumeControlFailed(format!("Failed to serialize params pod: {e:?}"))
+        })?
+        .0
+        .into_inner();
+
+    let pod = Pod::from_bytes(&pod_bytes).ok_or_else(|| {
+        PwError::VolumeControlFailed("Failed to create Pod from bytes".to_string())
+    })?;
+
+    // The request is sent immediately, so the proxy can go once it is made
+    node.set_param(ParamType::Props, 0, pod);
+
+    debug!(node_id, "Params set");
+    Ok(())
+}
+
 fn create_link(
     core: &pipewire::core::CoreRc,
     output_node: u32,
@@ -1194,10 +2330,24 @@ fn create_link(
     Ok(id)
 }
 
+/// Tracks the session manager (`WirePlumber`) client so restarts can be
+/// detected, and the `default` metadata it publishes.
+#[derive(Default)]
+struct SessionManagerTracker {
+    /// Registry ID of the current session manager client
+    client_id: Option<u32>,
+    /// Whether the session manager went away since it was last seen
+    lost: bool,
+    /// Registry ID of the `default` metadata object, which holds the default
+    /// sink and source
+    default_metadata: Option<u32>,
+}
+
 fn handle_global(
     event_tx: &mpsc::Sender<GraphEvent>,
     graph: &GraphManager,
     nodes: &Rc<RefCell<HashMap<u32, String>>>,
+    session_manager: &Rc<RefCell<SessionManagerTracker>>,
     global: &GlobalObject<&DictRef>,
 ) {
     let props = global.props.as_ref();
@@ -1210,8 +2360,7 @@ fn handle_global(
             let media_class = props.and_then(|p| p.get("media.class")).map(String::from);
             let app_name = props.and_then(|p| p.get("application.name")).map(String::from);
             let binary = props.and_then(|p| p.get("application.process.binary")).map(String::from);
-            let pid =
-                props.and_then(|p| p.get("application.process.id")).and_then(|s| s.parse().ok());
+            let pid = props.and_then(process_id);
 
             let is_undertone =
                 name.starts_with("ut-") || props.and_then(|p| p.get("undertone.managed")).is_some();
@@ -1257,6 +2406,12 @@ fn handle_global(
                     name: app_name.unwrap_or_else(|| name.clone()),
                     pid,
                 });
+            } else if media_class.as_deref() == Some("Stream/Input/Audio") && !is_undertone {
+                let _ = event_tx.blocking_send(GraphEvent::CaptureClientAppeared {
+                    id: global.id,
+                    name: app_name.unwrap_or_else(|| name.clone()),
+                    pid,
+                });
             }
         }
 
@@ -1321,6 +2476,28 @@ fn handle_global(
             });
         }
 
+        ObjectType::Client => {
+            let is_session_manager =
+                props.and_then(|p| p.get("application.name")) == Some("WirePlumber");
+
+            let mut tracker = session_manager.borrow_mut();
+            if is_session_manager && tracker.client_id.is_none() {
+                tracker.client_id = Some(global.id);
+                if tracker.lost {
+                    tracker.lost = false;
+                    info!(id = global.id, "Session manager restarted");
+                    let _ = event_tx.blocking_send(GraphEvent::SessionManagerRestarted);
+                }
+            }
+        }
+
+        ObjectType::Metadata => {
+            if props.and_then(|p| p.get("metadata.name")) == Some("default") {
+                debug!(id = global.id, "Default metadata found");
+                session_manager.borrow_mut().default_metadata = Some(global.id);
+            }
+        }
+
         _ => {}
     }
 }
@@ -1329,8 +2506,23 @@ fn handle_global_remove(
     event_tx: &mpsc::Sender<GraphEvent>,
     graph: &GraphManager,
     nodes: &Rc<RefCell<HashMap<u32, String>>>,
+    session_manager: &Rc<RefCell<SessionManagerTracker>>,
     id: u32,
 ) {
+    {
+        let mut tracker = session_manager.borrow_mut();
+        if tracker.client_id == Some(id) {
+            warn!(id, "Session manager went away");
+            tracker.client_id = None;
+            tracker.lost = true;
+            return;
+        }
+        if tracker.default_metadata == Some(id) {
+            tracker.default_metadata = None;
+            return;
+        }
+    }
+
     if let Some(name) = nodes.borrow_mut().remove(&id) {
         debug!(id, name = %name, "Node removed");
 
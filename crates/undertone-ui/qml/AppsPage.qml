@@ -18,8 +18,18 @@ Rectangle {
         "game": "#8b5cf6"
     })
 
+    function channelIndex(channel) {
+        for (let i = 0; i < controller.channel_count; i++) {
+            if (controller.channel_name(i) === channel)
+                return i
+        }
+        return -1
+    }
+
     function getChannelColor(channel) {
-        return channelColors[channel] || Kirigami.Theme.disabledTextColor
+        const idx = channelIndex(channel)
+        const custom = idx >= 0 ? controller.channel_color(idx) : ""
+        return custom || channelColors[channel] || Kirigami.Theme.disabledTextColor
     }
 
     function getChannelDisplayName(channel) {
@@ -30,7 +40,37 @@ Rectangle {
             "browser": "Browser",
             "game": "Game"
         }
-        return names[channel] || channel
+        const idx = channelIndex(channel)
+        const custom = idx >= 0 ? controller.channel_display_name(idx) : ""
+        return custom || names[channel] || channel
+    }
+
+    function getRuleTargetDisplayName(target) {
+        if (ruleDirection.currentValue !== "capture") {
+            return getChannelDisplayName(target)
+        }
+        if (target === "mic") {
+            return "Mic"
+        }
+        for (let i = 0; i < controller.mix_count; i++) {
+            if (controller.mix_name(i) === target) {
+                return controller.mix_display_name(i) + " mix"
+            }
+        }
+        return target
+    }
+
+    function getPatternTypeDisplayName(patternType) {
+        const names = {
+            "exact_ignore_case": "Exact (any case)",
+            "prefix_ignore_case": "Starts with (any case)",
+            "contains": "Contains",
+            "glob": "Glob",
+            "exact": "Exact",
+            "prefix": "Starts with",
+            "regex": "Regex"
+        }
+        return names[patternType] || patternType
     }
 
     ColumnLayout {
@@ -83,6 +123,8 @@ Rectangle {
                     property string appBinary: controller.app_binary(index)
                     property string appChannel: controller.app_channel(index)
                     property bool isPersistent: controller.app_persistent(index)
+                    property string streamName: controller.app_stream_name(index)
+                    property string streamOverride: controller.app_stream_override(index)
 
                     RowLayout {
                         anchors.fill: parent
@@ -120,7 +162,7 @@ Rectangle {
                             }
 
                             QQC2.Label {
-                                text: appBinary || "Unknown binary"
+                                text: (appBinary || "Unknown binary") + (streamName ? " \u2013 " + streamName : "")
                                 font.pixelSize: 11
                                 color: Kirigami.Theme.disabledTextColor
                                 elide: Text.ElideRight
@@ -154,6 +196,54 @@ Rectangle {
                             }
                         }
 
+                        // Stream routed apart from its app
+                        Rectangle {
+                            visible: streamOverride.length > 0
+                            Layout.preferredWidth: 20
+                            Layout.preferredHeight: 20
+                            radius: 10
+                            color: Kirigami.Theme.backgroundColor
+
+                            QQC2.Label {
+                                anchors.centerIn: parent
+                                text: "S"
+                                font.pixelSize: 10
+                                font.bold: true
+                                color: Kirigami.Theme.neutralTextColor
+                            }
+
+                            QQC2.ToolTip.visible: streamMouse.containsMouse
+                            QQC2.ToolTip.text: streamOverride === "moved"
+                                ? "This stream was moved by hand"
+                                : "Routed by a stream rule (" + streamOverride + ")"
+
+                            MouseArea {
+                                id: streamMouse
+                                anchors.fill: parent
+                                hoverEnabled: true
+                            }
+                        }
+
+                        QQC2.ToolButton {
+                            id: streamOnly
+                            icon.name: "split"
+                            checkable: true
+
+                            QQC2.ToolTip.visible: hovered
+                            QQC2.ToolTip.text: "Only this stream: move just this stream until it closes, leaving the app's other streams where they are"
+                        }
+
+                        QQC2.ToolButton {
+                            icon.name: "help-about"
+                            onClicked: {
+                                controller.explain_app_route(index)
+                                reportDialog.openWith("Why is " + appName + " here?")
+                            }
+
+                            QQC2.ToolTip.visible: hovered
+                            QQC2.ToolTip.text: "Show which rule routed this app"
+                        }
+
                         // Channel selector
                         QQC2.ComboBox {
                             id: channelCombo
@@ -165,7 +255,11 @@ Rectangle {
                             displayText: appsPage.getChannelDisplayName(currentText)
 
                             onActivated: (idx) => {
-                                controller.set_app_channel(appBinary || appName, model[idx])
+                                if (streamOnly.checked) {
+                                    controller.move_stream(index, model[idx])
+                                } else {
+                                    controller.set_app_channel(appBinary || appName, model[idx])
+                                }
                             }
 
                             delegate: QQC2.ItemDelegate {
@@ -195,7 +289,7 @@ Rectangle {
         // Route rules section
         Rectangle {
             Layout.fillWidth: true
-            Layout.preferredHeight: 150
+            Layout.preferredHeight: 190
             color: Kirigami.Theme.alternateBackgroundColor
             radius: 8
 
@@ -214,13 +308,41 @@ Rectangle {
                         color: Kirigami.Theme.disabledTextColor
                     }
 
+                    QQC2.Button {
+                        text: "Check Rules"
+                        icon.name: "tools-check-spelling"
+                        flat: true
+                        onClicked: {
+                            controller.check_routes()
+                            reportDialog.openWith("Routing Rule Check")
+                        }
+                    }
+
                     Item { Layout.fillWidth: true }
 
                     QQC2.Label {
-                        text: "Rules are applied automatically"
+                        text: "Unmatched apps go to"
                         font.pixelSize: 11
                         color: Kirigami.Theme.disabledTextColor
                     }
+
+                    QQC2.ComboBox {
+                        id: fallbackChannel
+                        Layout.preferredWidth: 120
+                        model: controller.available_channels().split(",")
+                        currentIndex: model.indexOf(controller.fallback_channel)
+                        displayText: appsPage.getChannelDisplayName(currentText)
+
+                        delegate: QQC2.ItemDelegate {
+                            width: fallbackChannel.width
+
+                            required property string modelData
+
+                            text: appsPage.getChannelDisplayName(modelData)
+                        }
+
+                        onActivated: (index) => controller.set_fallback_channel_name(model[index])
+                    }
                 }
 
                 // Quick reference of default routes
@@ -244,7 +366,140 @@ Rectangle {
                 }
 
                 Item { Layout.fillHeight: true }
+
+                // Add a rule
+                RowLayout {
+                    Layout.fillWidth: true
+                    spacing: 8
+
+                    QQC2.ComboBox {
+                        id: ruleDirection
+                        Layout.preferredWidth: 110
+                        textRole: "text"
+                        valueRole: "value"
+                        model: [
+                            { text: "Playback", value: "playback" },
+                            { text: "Recording", value: "capture" }
+                        ]
+
+                        QQC2.ToolTip.visible: hovered
+                        QQC2.ToolTip.text: "Playback rules send an app's audio to a channel. Recording rules choose the mix (or the mic) an app records from."
+                    }
+
+                    QQC2.TextField {
+                        id: ruleProperty
+                        Layout.preferredWidth: 150
+                        placeholderText: "App name"
+                        enabled: ruleStream.text.length === 0
+
+                        QQC2.ToolTip.visible: hovered
+                        QQC2.ToolTip.text: "Node property to match, e.g. media.role or application.id. Leave empty to match the app name."
+                    }
+
+                    QQC2.TextField {
+                        id: rulePattern
+                        Layout.fillWidth: true
+                        placeholderText: "Pattern"
+                    }
+
+                    QQC2.ComboBox {
+                        id: ruleType
+                        Layout.preferredWidth: 170
+                        model: controller.pattern_types().split(",")
+                        displayText: appsPage.getPatternTypeDisplayName(currentText)
+
+                        delegate: QQC2.ItemDelegate {
+                            width: ruleType.width
+
+                            required property string modelData
+
+                            text: appsPage.getPatternTypeDisplayName(modelData)
+                        }
+                    }
+
+                    QQC2.TextField {
+                        id: ruleStream
+                        Layout.preferredWidth: 130
+                        placeholderText: "All streams"
+                        enabled: ruleDirection.currentValue === "playback" && !ruleExclude.checked
+
+                        QQC2.ToolTip.visible: hovered
+                        QQC2.ToolTip.text: "Only route some of the app's streams: name:<text in the title>, role:<media.role> or index:<n>. Its other streams keep the app's route."
+                    }
+
+                    QQC2.CheckBox {
+                        id: ruleExclude
+                        text: "Never touch"
+
+                        QQC2.ToolTip.visible: hovered
+                        QQC2.ToolTip.text: "Leave matching apps where they are, e.g. level meters or speech-dispatcher."
+                    }
+
+                    QQC2.CheckBox {
+                        id: ruleAncestors
+                        text: "Match launcher"
+                        enabled: ruleProperty.text.length === 0 && ruleStream.text.length === 0
+
+                        QQC2.ToolTip.visible: hovered
+                        QQC2.ToolTip.text: "Also match apps started from a matching process, e.g. games launched by steam or lutris."
+                    }
+
+                    QQC2.ComboBox {
+                        id: ruleChannel
+                        Layout.preferredWidth: 120
+                        enabled: !ruleExclude.checked
+                        model: ruleDirection.currentValue === "capture"
+                            ? controller.capture_sources().split(",")
+                            : controller.available_channels().split(",")
+                        displayText: appsPage.getRuleTargetDisplayName(currentText)
+
+                        delegate: QQC2.ItemDelegate {
+                            width: ruleChannel.width
+
+                            required property string modelData
+
+                            text: appsPage.getRuleTargetDisplayName(modelData)
+                        }
+                    }
+
+                    QQC2.Button {
+                        text: "Add Rule"
+                        icon.name: "list-add"
+                        enabled: rulePattern.text.length > 0
+                        onClicked: {
+                            if (ruleStream.enabled && ruleStream.text.length > 0) {
+                                controller.add_stream_route(rulePattern.text, ruleType.currentText, ruleStream.text, ruleChannel.currentText)
+                            } else {
+                                controller.add_app_route(rulePattern.text, ruleType.currentText, ruleProperty.text, ruleDirection.currentValue, ruleChannel.currentText, ruleExclude.checked, ruleAncestors.checked && ruleProperty.text.length === 0)
+                            }
+                            rulePattern.text = ""
+                            ruleStream.text = ""
+                            ruleProperty.text = ""
+                            ruleExclude.checked = false
+                            ruleAncestors.checked = false
+                        }
+                    }
+                }
             }
         }
     }
+
+    // Route explanation / rule check results
+    QQC2.Dialog {
+        id: reportDialog
+        anchors.centerIn: parent
+        modal: true
+        standardButtons: QQC2.Dialog.Close
+
+        function openWith(newTitle) {
+            title = newTitle
+            open()
+        }
+
+        QQC2.Label {
+            text: controller.route_report || "Waiting for the daemon..."
+            font.family: "monospace"
+            color: Kirigami.Theme.textColor
+        }
+    }
 }
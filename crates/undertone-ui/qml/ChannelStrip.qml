@@ -14,12 +14,82 @@ Rectangle {
     property string displayName: ""
     property real volume: 1.0
     property bool muted: false
+    property real pan: 0.0
+    property bool soloed: false
     property real levelLeft: 0.0
     property real levelRight: 0.0
     property color channelColor: "#e94560"
+    property bool removable: false
+    property bool directOut: false
+    property string layout: "stereo"
 
     signal volumeAdjusted(real newVolume)
     signal muteToggled()
+    signal panAdjusted(real newPan)
+    signal soloToggled()
+    signal renameRequested()
+    signal colorRequested()
+    signal directOutToggled()
+    signal layoutRequested(string layout)
+    signal deleteRequested()
+
+    // Right-click for channel management
+    MouseArea {
+        anchors.fill: parent
+        acceptedButtons: Qt.RightButton
+        onClicked: channelMenu.popup()
+    }
+
+    QQC2.Menu {
+        id: channelMenu
+
+        QQC2.MenuItem {
+            text: "Rename..."
+            icon.name: "edit-rename"
+            onTriggered: channelStrip.renameRequested()
+        }
+
+        QQC2.MenuItem {
+            text: "Change Color..."
+            icon.name: "color-management"
+            onTriggered: channelStrip.colorRequested()
+        }
+
+        QQC2.MenuItem {
+            text: "Direct Out for Recording"
+            icon.name: "media-record"
+            checkable: true
+            checked: channelStrip.directOut
+            onTriggered: channelStrip.directOutToggled()
+        }
+
+        QQC2.Menu {
+            title: "Speaker Layout"
+
+            Repeater {
+                model: [
+                    { value: "stereo", label: "Stereo" },
+                    { value: "5.1", label: "5.1 Surround" },
+                    { value: "7.1", label: "7.1 Surround" }
+                ]
+
+                QQC2.MenuItem {
+                    required property var modelData
+                    text: modelData.label
+                    checkable: true
+                    checked: channelStrip.layout === modelData.value
+                    onTriggered: channelStrip.layoutRequested(modelData.value)
+                }
+            }
+        }
+
+        QQC2.MenuItem {
+            text: "Delete Channel"
+            icon.name: "edit-delete"
+            enabled: channelStrip.removable
+            onTriggered: channelStrip.deleteRequested()
+        }
+    }
 
     ColumnLayout {
         anchors.fill: parent
@@ -50,6 +120,44 @@ Rectangle {
             Layout.fillWidth: true
             Layout.fillHeight: true
 
+            // Peak level meters (left, right)
+            Row {
+                anchors.right: parent.right
+                anchors.top: parent.top
+                anchors.bottom: parent.bottom
+                spacing: 2
+
+                Rectangle {
+                    width: 4
+                    height: parent.height
+                    radius: 2
+                    color: Kirigami.Theme.backgroundColor
+
+                    Rectangle {
+                        anchors.bottom: parent.bottom
+                        width: parent.width
+                        height: parent.height * Math.min(1.0, channelStrip.levelLeft)
+                        radius: 2
+                        color: channelStrip.levelLeft > 0.9 ? Kirigami.Theme.negativeTextColor : channelStrip.channelColor
+                    }
+                }
+
+                Rectangle {
+                    width: 4
+                    height: parent.height
+                    radius: 2
+                    color: Kirigami.Theme.backgroundColor
+
+                    Rectangle {
+                        anchors.bottom: parent.bottom
+                        width: parent.width
+                        height: parent.height * Math.min(1.0, channelStrip.levelRight)
+                        radius: 2
+                        color: channelStrip.levelRight > 0.9 ? Kirigami.Theme.negativeTextColor : channelStrip.channelColor
+                    }
+                }
+            }
+
             QQC2.Slider {
                 id: volumeSlider
                 anchors.centerIn: parent
@@ -104,16 +212,86 @@ Rectangle {
             horizontalAlignment: Text.AlignHCenter
         }
 
-        // Mute button
-        QQC2.Button {
+        // Balance (double-click to center)
+        QQC2.Slider {
+            id: panSlider
+            Layout.fillWidth: true
+            Layout.preferredHeight: 20
+
+            from: -1
+            to: 1
+            value: channelStrip.pan
+            enabled: !channelStrip.muted
+
+            onMoved: {
+                channelStrip.panAdjusted(value)
+            }
+
+            QQC2.ToolTip.visible: hovered || pressed
+            QQC2.ToolTip.text: Math.abs(channelStrip.pan) < 0.005 ? "C"
+                : (channelStrip.pan < 0 ? "L " : "R ") + Math.round(Math.abs(channelStrip.pan) * 100)
+
+            TapHandler {
+                onDoubleTapped: channelStrip.panAdjusted(0.0)
+            }
+
+            background: Rectangle {
+                x: panSlider.leftPadding
+                y: panSlider.topPadding + panSlider.availableHeight / 2 - height / 2
+                width: panSlider.availableWidth
+                height: 4
+                radius: 2
+                color: Kirigami.Theme.backgroundColor
+
+                // Filled from the center towards the handle
+                Rectangle {
+                    x: Math.min(panSlider.visualPosition, 0.5) * parent.width
+                    width: Math.abs(panSlider.visualPosition - 0.5) * parent.width
+                    height: parent.height
+                    radius: 2
+                    color: panSlider.enabled ? channelStrip.channelColor : Kirigami.Theme.disabledTextColor
+                }
+            }
+
+            handle: Rectangle {
+                x: panSlider.leftPadding + panSlider.visualPosition * (panSlider.availableWidth - width)
+                y: panSlider.topPadding + panSlider.availableHeight / 2 - height / 2
+                width: 10
+                height: 10
+                radius: 5
+                color: panSlider.pressed ? Kirigami.Theme.textColor : (panSlider.enabled ? channelStrip.channelColor : Kirigami.Theme.disabledTextColor)
+            }
+        }
+
+        RowLayout {
             Layout.alignment: Qt.AlignHCenter
-            Layout.preferredWidth: 36
-            Layout.preferredHeight: 36
-            flat: true
-            icon.name: channelStrip.muted ? "audio-volume-muted" : "audio-volume-high"
-            icon.color: channelStrip.muted ? Kirigami.Theme.negativeTextColor : channelStrip.channelColor
+            spacing: 0
 
-            onClicked: channelStrip.muteToggled()
+            // Mute button
+            QQC2.Button {
+                Layout.preferredWidth: 36
+                Layout.preferredHeight: 36
+                flat: true
+                icon.name: channelStrip.muted ? "audio-volume-muted" : "audio-volume-high"
+                icon.color: channelStrip.muted ? Kirigami.Theme.negativeTextColor : channelStrip.channelColor
+
+                onClicked: channelStrip.muteToggled()
+            }
+
+            // Solo button (monitor mix only)
+            QQC2.Button {
+                Layout.preferredWidth: 36
+                Layout.preferredHeight: 36
+                flat: !channelStrip.soloed
+                highlighted: channelStrip.soloed
+                text: "S"
+                font.bold: true
+
+                QQC2.ToolTip.visible: hovered
+                QQC2.ToolTip.text: channelStrip.soloed ? "Release solo" : "Solo in monitor mix"
+
+                onClicked: channelStrip.soloToggled()
+            }
         }
     }
 }
@@ -210,6 +210,77 @@ Rectangle {
                         }
                     }
 
+                    // Input level row
+                    RowLayout {
+                        Layout.fillWidth: true
+                        spacing: 12
+
+                        QQC2.Label {
+                            text: "Level"
+                            font.pixelSize: 13
+                            color: Kirigami.Theme.disabledTextColor
+                            Layout.preferredWidth: 50
+                        }
+
+                        QQC2.ProgressBar {
+                            Layout.fillWidth: true
+                            from: 0.0
+                            to: 1.0
+                            value: controller.mic_level
+                        }
+                    }
+
+                    // Mic sends into each mix (sidetone in the monitor mix)
+                    Repeater {
+                        model: controller.mix_count
+
+                        delegate: RowLayout {
+                            required property int index
+
+                            // mic_sends_revision changes whenever a send does and re-evaluates these
+                            readonly property bool sendEnabled:
+                                (controller.mic_sends_revision, controller.mic_send_enabled(index))
+                            readonly property real sendVolume:
+                                (controller.mic_sends_revision, controller.mic_send_volume(index))
+
+                            Layout.fillWidth: true
+                            spacing: 12
+
+                            QQC2.Label {
+                                text: controller.mix_name(index) === "monitor"
+                                    ? "Sidetone" : controller.mix_display_name(index)
+                                font.pixelSize: 13
+                                color: Kirigami.Theme.disabledTextColor
+                                Layout.preferredWidth: 50
+                                elide: Text.ElideRight
+                            }
+
+                            QQC2.Switch {
+                                checked: sendEnabled
+                                onToggled: controller.set_mic_send_enabled(index, checked)
+                            }
+
+                            QQC2.Slider {
+                                id: sendSlider
+                                Layout.fillWidth: true
+                                from: 0
+                                to: 1
+                                value: sendVolume
+                                enabled: sendEnabled
+
+                                onMoved: controller.set_mic_send_volume(index, value)
+                            }
+
+                            QQC2.Label {
+                                text: Math.round(sendSlider.value * 100) + "%"
+                                font.pixelSize: 13
+                                color: Kirigami.Theme.textColor
+                                Layout.preferredWidth: 40
+                                horizontalAlignment: Text.AlignRight
+                            }
+                        }
+                    }
+
                     // Mute button row
                     RowLayout {
                         Layout.fillWidth: true
@@ -18,8 +18,8 @@ Rectangle {
         "game": "#8b5cf6"
     })
 
-    function getChannelColor(channelName) {
-        return channelColors[channelName] || Kirigami.Theme.highlightColor
+    function getChannelColor(channelName, index) {
+        return controller.channel_color(index) || channelColors[channelName] || Kirigami.Theme.highlightColor
     }
 
     RowLayout {
@@ -39,7 +39,15 @@ Rectangle {
                 displayName: controller.channel_display_name(index)
                 volume: controller.channel_volume(index)
                 muted: controller.channel_muted(index)
-                channelColor: mixerPage.getChannelColor(channelName)
+                pan: controller.channel_pan(index)
+                soloed: controller.channel_soloed(index)
+                // levels_revision changes on every meter update and re-evaluates these
+                levelLeft: (controller.levels_revision, controller.channel_level_left(index))
+                levelRight: (controller.levels_revision, controller.channel_level_right(index))
+                channelColor: mixerPage.getChannelColor(channelName, index)
+                removable: !controller.channel_is_system(index)
+                directOut: controller.channel_direct_out(index)
+                layout: controller.channel_layout(index)
 
                 onVolumeAdjusted: (newVolume) => {
                     controller.set_channel_volume(channelName, newVolume)
@@ -48,9 +56,37 @@ Rectangle {
                 onMuteToggled: {
                     controller.toggle_channel_mute(channelName)
                 }
+
+                onSoloToggled: {
+                    controller.toggle_channel_solo(channelName)
+                }
+
+                onPanAdjusted: (newPan) => {
+                    controller.set_channel_pan(channelName, newPan)
+                }
+
+                onRenameRequested: channelDialog.openFor("rename", channelName, displayName, controller.channel_color(index))
+                onColorRequested: channelDialog.openFor("color", channelName, displayName, controller.channel_color(index))
+                onDirectOutToggled: controller.set_channel_direct_out(channelName, !directOut)
+                onLayoutRequested: (newLayout) => controller.set_channel_layout(channelName, newLayout)
+                onDeleteRequested: {
+                    deleteDialog.channelName = channelName
+                    deleteDialog.open()
+                }
             }
         }
 
+        // Add channel button
+        QQC2.Button {
+            Layout.alignment: Qt.AlignVCenter
+            icon.name: "list-add"
+            visible: controller.connected
+            QQC2.ToolTip.text: "Add Channel"
+            QQC2.ToolTip.visible: hovered
+
+            onClicked: channelDialog.openFor("create", "", "", "")
+        }
+
         // Spacer
         Item { Layout.fillWidth: true }
 
@@ -90,6 +126,26 @@ Rectangle {
                     }
                 }
 
+                // Master peak level for the current mix
+                Column {
+                    Layout.alignment: Qt.AlignHCenter
+                    spacing: 2
+
+                    QQC2.ProgressBar {
+                        width: 56
+                        from: 0.0
+                        to: 1.0
+                        value: controller.master_level_left
+                    }
+
+                    QQC2.ProgressBar {
+                        width: 56
+                        from: 0.0
+                        to: 1.0
+                        value: controller.master_level_right
+                    }
+                }
+
                 // Master volume percentage
                 QQC2.Label {
                     Layout.alignment: Qt.AlignHCenter
@@ -115,6 +171,83 @@ Rectangle {
         }
     }
 
+    // Create / rename / recolor channel dialog
+    QQC2.Dialog {
+        id: channelDialog
+        anchors.centerIn: parent
+        modal: true
+        standardButtons: QQC2.Dialog.Ok | QQC2.Dialog.Cancel
+
+        property string mode: "create"
+        property string channelName: ""
+
+        title: mode === "create" ? "Add Channel" : (mode === "rename" ? "Rename Channel" : "Change Channel Color")
+
+        function openFor(newMode, name, displayName, color) {
+            mode = newMode
+            channelName = name
+            nameField.text = name
+            displayNameField.text = displayName
+            colorField.text = color
+            open()
+        }
+
+        ColumnLayout {
+            spacing: 8
+
+            QQC2.Label { text: "Name (lowercase letters, digits, dashes)"; visible: channelDialog.mode === "create" }
+            QQC2.TextField {
+                id: nameField
+                Layout.preferredWidth: 240
+                visible: channelDialog.mode === "create"
+                placeholderText: "e.g. alerts"
+            }
+
+            QQC2.Label { text: "Display name"; visible: channelDialog.mode !== "color" }
+            QQC2.TextField {
+                id: displayNameField
+                Layout.preferredWidth: 240
+                visible: channelDialog.mode !== "color"
+                placeholderText: "e.g. Alerts"
+            }
+
+            QQC2.Label { text: "Color"; visible: channelDialog.mode !== "rename" }
+            QQC2.TextField {
+                id: colorField
+                Layout.preferredWidth: 240
+                visible: channelDialog.mode !== "rename"
+                placeholderText: "#rrggbb (optional)"
+            }
+        }
+
+        onAccepted: {
+            if (mode === "create") {
+                controller.create_channel(nameField.text, displayNameField.text || nameField.text, colorField.text)
+            } else if (mode === "rename") {
+                controller.rename_channel(channelName, displayNameField.text)
+            } else {
+                controller.set_channel_color(channelName, colorField.text)
+            }
+        }
+    }
+
+    // Delete channel confirmation
+    QQC2.Dialog {
+        id: deleteDialog
+        anchors.centerIn: parent
+        modal: true
+        title: "Delete Channel"
+        standardButtons: QQC2.Dialog.Yes | QQC2.Dialog.No
+
+        property string channelName: ""
+
+        QQC2.Label {
+            text: "Delete \"" + deleteDialog.channelName + "\"? Apps on this channel will move to System."
+        }
+
+        onAccepted: controller.delete_channel(channelName)
+    }
+
     // Empty state when no channels
     Column {
         anchors.centerIn: parent
@@ -98,47 +98,33 @@ QQC2.ApplicationWindow {
                     anchors.margins: 2
                     spacing: 2
 
-                    Rectangle {
-                        Layout.fillHeight: true
-                        Layout.preferredWidth: streamLabel.implicitWidth + 24
-                        color: controller.mix_mode === 0 ? Kirigami.Theme.backgroundColor : "transparent"
-                        radius: 4
-
-                        QQC2.Label {
-                            id: streamLabel
-                            anchors.centerIn: parent
-                            text: "Stream"
-                            font.pixelSize: 12
-                            font.bold: controller.mix_mode === 0
-                            color: controller.mix_mode === 0 ? Kirigami.Theme.textColor : Kirigami.Theme.disabledTextColor
-                        }
-
-                        MouseArea {
-                            anchors.fill: parent
-                            cursorShape: Qt.PointingHandCursor
-                            onClicked: controller.change_mix_mode(0)
-                        }
-                    }
-
-                    Rectangle {
-                        Layout.fillHeight: true
-                        Layout.preferredWidth: monitorLabel.implicitWidth + 24
-                        color: controller.mix_mode === 1 ? Kirigami.Theme.backgroundColor : "transparent"
-                        radius: 4
-
-                        QQC2.Label {
-                            id: monitorLabel
-                            anchors.centerIn: parent
-                            text: "Monitor"
-                            font.pixelSize: 12
-                            font.bold: controller.mix_mode === 1
-                            color: controller.mix_mode === 1 ? Kirigami.Theme.textColor : Kirigami.Theme.disabledTextColor
-                        }
+                    Repeater {
+                        model: controller.mix_count
+
+                        delegate: Rectangle {
+                            required property int index
+
+                            readonly property bool selected: controller.mix_mode === index
+
+                            Layout.fillHeight: true
+                            Layout.preferredWidth: mixLabel.implicitWidth + 24
+                            color: selected ? Kirigami.Theme.backgroundColor : "transparent"
+                            radius: 4
+
+                            QQC2.Label {
+                                id: mixLabel
+                                anchors.centerIn: parent
+                                text: controller.mix_display_name(index)
+                                font.pixelSize: 12
+                                font.bold: selected
+                                color: selected ? Kirigami.Theme.textColor : Kirigami.Theme.disabledTextColor
+                            }
 
-                        MouseArea {
-                            anchors.fill: parent
-                            cursorShape: Qt.PointingHandCursor
-                            onClicked: controller.change_mix_mode(1)
+                            MouseArea {
+                                anchors.fill: parent
+                                cursorShape: Qt.PointingHandCursor
+                                onClicked: controller.change_mix_mode(index)
+                            }
                         }
                     }
                 }
@@ -2,6 +2,7 @@
 //!
 //! This module contains the cxx-qt bridge that exposes Rust types to QML.
 
+use std::collections::BTreeMap;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex, OnceLock};
 
@@ -9,7 +10,11 @@ use cxx_qt_lib::QString;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
-use undertone_core::mixer::MixType;
+use undertone_core::layout::ChannelLayout;
+use undertone_core::mixer::{MixSend, MixType};
+use undertone_core::routing::{
+    CAPTURE_MIC, FALLBACK_CHANNEL, PatternType, RouteDirection, RouteRule, StreamSelector,
+};
 
 use crate::ipc_handler::{IpcHandle, IpcUpdate};
 use crate::state::UiState;
@@ -24,6 +29,7 @@ static UI_DATA: OnceLock<Mutex<UiDataCache>> = OnceLock::new();
 #[derive(Default)]
 struct UiDataCache {
     channels: Vec<ChannelData>,
+    mixes: Vec<MixData>,
     apps: Vec<AppData>,
     profiles: Vec<ProfileData>,
     output_devices: Vec<OutputDeviceData>,
@@ -34,6 +40,7 @@ fn get_ui_data() -> &'static Mutex<UiDataCache> {
     UI_DATA.get_or_init(|| {
         Mutex::new(UiDataCache {
             channels: Vec::new(),
+            mixes: Vec::new(),
             apps: Vec::new(),
             profiles: vec![ProfileData { name: "Default".to_string(), is_default: true }],
             output_devices: Vec::new(),
@@ -42,6 +49,12 @@ fn get_ui_data() -> &'static Mutex<UiDataCache> {
     })
 }
 
+/// Get a copy of the mix at `index` in the cache.
+fn mix_at(index: i32) -> Option<MixData> {
+    let cache = get_ui_data().lock().ok()?;
+    usize::try_from(index).ok().and_then(|i| cache.mixes.get(i).cloned())
+}
+
 /// Send a command to the daemon via the global IPC handle.
 fn send_command(cmd: UiCommand) {
     if let Some(handle) = IPC_HANDLE.get()
@@ -66,14 +79,50 @@ pub fn init_ipc(state: Arc<UiState>) -> mpsc::Sender<UiCommand> {
 pub struct ChannelData {
     pub name: String,
     pub display_name: String,
-    pub stream_volume: f32,
-    pub stream_muted: bool,
-    pub monitor_volume: f32,
-    pub monitor_muted: bool,
+    /// Channel color (`#rrggbb`), empty if the channel has none
+    pub color: String,
+    pub is_system: bool,
+    /// Whether the channel has its own recording source
+    pub direct_out: bool,
+    /// Speaker layout (`stereo`, `5.1` or `7.1`)
+    pub layout: String,
+    /// Whether the channel is soloed in the monitor mix
+    pub soloed: bool,
+    /// Level and mute in each mix
+    pub sends: BTreeMap<MixType, MixSend>,
     pub level_left: f32,
     pub level_right: f32,
 }
 
+impl ChannelData {
+    /// Level and mute in a mix.
+    #[must_use]
+    pub fn send(&self, mix: &MixType) -> MixSend {
+        self.sends.get(mix).copied().unwrap_or_default()
+    }
+}
+
+/// Mix data for QML model.
+#[derive(Clone, Default, Debug)]
+pub struct MixData {
+    pub name: String,
+    pub display_name: String,
+    pub is_system: bool,
+    pub master_volume: f32,
+    pub master_muted: bool,
+    /// Whether the mic is sent to this mix
+    pub mic_enabled: bool,
+    pub mic_volume: f32,
+}
+
+impl MixData {
+    /// The identifier used to address this mix.
+    #[must_use]
+    pub fn id(&self) -> MixType {
+        MixType::new(self.name.as_str())
+    }
+}
+
 /// App data for QML model.
 #[derive(Clone, Default, Debug)]
 pub struct AppData {
@@ -82,6 +131,11 @@ pub struct AppData {
     pub binary_name: String,
     pub channel: String,
     pub is_persistent: bool,
+    /// The stream's title (`media.name`), if it has one
+    pub media_name: String,
+    /// Why the stream is routed apart from its app: `moved`, or the
+    /// selector of the stream rule that picked it; empty if it is not
+    pub stream_override: String,
 }
 
 /// Profile data for QML model.
@@ -112,7 +166,8 @@ mod ffi {
         #[qproperty(bool, device_connected)] // Wave:3 device connected
         #[qproperty(QString, device_serial)]
         #[qproperty(QString, active_profile)]
-        #[qproperty(i32, mix_mode)]
+        #[qproperty(i32, mix_mode)] // Index of the mix being shown
+        #[qproperty(i32, mix_count)]
         #[qproperty(i32, channel_count)]
         #[qproperty(i32, app_count)]
         #[qproperty(bool, mic_muted)]
@@ -122,12 +177,39 @@ mod ffi {
         #[qproperty(bool, master_muted)] // Master mute for current mix mode
         #[qproperty(i32, output_device_count)] // Number of available output devices
         #[qproperty(QString, monitor_output)] // Current monitor output device name
+        #[qproperty(QString, fallback_channel)] // Channel unmatched apps are sent to
+        #[qproperty(QString, route_report)] // Last route explanation or rule check
+        #[qproperty(i32, levels_revision)] // Bumped on every level meter update
+        #[qproperty(f32, master_level_left)] // Peak level of the current mix
+        #[qproperty(f32, master_level_right)]
+        #[qproperty(f32, mic_level)] // Peak mic level
+        #[qproperty(i32, mic_sends_revision)] // Bumped when any mic send changes
         type UndertoneController = super::UndertoneControllerRust;
 
-        /// Change the mix mode (0 = Stream, 1 = Monitor).
+        /// Change the mix being shown, by index.
         #[qinvokable]
         fn change_mix_mode(self: Pin<&mut UndertoneController>, mode: i32);
 
+        /// Get mix name by index.
+        #[qinvokable]
+        fn mix_name(self: &UndertoneController, index: i32) -> QString;
+
+        /// Get mix display name by index.
+        #[qinvokable]
+        fn mix_display_name(self: &UndertoneController, index: i32) -> QString;
+
+        /// Check if a mix is a built-in system mix by index.
+        #[qinvokable]
+        fn mix_is_system(self: &UndertoneController, index: i32) -> bool;
+
+        /// Create a user-defined mix.
+        #[qinvokable]
+        fn create_mix(self: Pin<&mut UndertoneController>, name: QString, display_name: QString);
+
+        /// Delete a user-defined mix.
+        #[qinvokable]
+        fn delete_mix(self: Pin<&mut UndertoneController>, name: QString);
+
         /// Set volume for a channel.
         #[qinvokable]
         fn set_channel_volume(self: Pin<&mut UndertoneController>, channel: QString, volume: f32);
@@ -136,6 +218,14 @@ mod ffi {
         #[qinvokable]
         fn toggle_channel_mute(self: Pin<&mut UndertoneController>, channel: QString);
 
+        /// Toggle solo for a channel in the monitor mix.
+        #[qinvokable]
+        fn toggle_channel_solo(self: Pin<&mut UndertoneController>, channel: QString);
+
+        /// Set balance for a channel (-1.0 left to 1.0 right).
+        #[qinvokable]
+        fn set_channel_pan(self: Pin<&mut UndertoneController>, channel: QString, pan: f32);
+
         /// Get channel name by index.
         #[qinvokable]
         fn channel_name(self: &UndertoneController, index: i32) -> QString;
@@ -144,6 +234,22 @@ mod ffi {
         #[qinvokable]
         fn channel_display_name(self: &UndertoneController, index: i32) -> QString;
 
+        /// Get channel color by index (empty if unset).
+        #[qinvokable]
+        fn channel_color(self: &UndertoneController, index: i32) -> QString;
+
+        /// Check if a channel is a built-in system channel by index.
+        #[qinvokable]
+        fn channel_is_system(self: &UndertoneController, index: i32) -> bool;
+
+        /// Check if a channel has a direct-out recording source by index.
+        #[qinvokable]
+        fn channel_direct_out(self: &UndertoneController, index: i32) -> bool;
+
+        /// Get channel speaker layout by index (`stereo`, `5.1` or `7.1`).
+        #[qinvokable]
+        fn channel_layout(self: &UndertoneController, index: i32) -> QString;
+
         /// Get channel volume by index (uses current mix mode).
         #[qinvokable]
         fn channel_volume(self: &UndertoneController, index: i32) -> f32;
@@ -152,10 +258,65 @@ mod ffi {
         #[qinvokable]
         fn channel_muted(self: &UndertoneController, index: i32) -> bool;
 
+        /// Check if a channel is soloed in the monitor mix by index.
+        #[qinvokable]
+        fn channel_soloed(self: &UndertoneController, index: i32) -> bool;
+
+        /// Get channel balance by index (uses current mix mode).
+        #[qinvokable]
+        fn channel_pan(self: &UndertoneController, index: i32) -> f32;
+
+        /// Get channel peak level (left) by index.
+        #[qinvokable]
+        fn channel_level_left(self: &UndertoneController, index: i32) -> f32;
+
+        /// Get channel peak level (right) by index.
+        #[qinvokable]
+        fn channel_level_right(self: &UndertoneController, index: i32) -> f32;
+
         /// Request state refresh from daemon.
         #[qinvokable]
         fn refresh(self: Pin<&mut UndertoneController>);
 
+        // Channel management methods
+
+        /// Create a user-defined channel (empty color for none).
+        #[qinvokable]
+        fn create_channel(
+            self: Pin<&mut UndertoneController>,
+            name: QString,
+            display_name: QString,
+            color: QString,
+        );
+
+        /// Change a channel's display name.
+        #[qinvokable]
+        fn rename_channel(
+            self: Pin<&mut UndertoneController>,
+            name: QString,
+            display_name: QString,
+        );
+
+        /// Change a channel's color (empty to clear).
+        #[qinvokable]
+        fn set_channel_color(self: Pin<&mut UndertoneController>, name: QString, color: QString);
+
+        /// Turn a channel's direct-out recording source on or off.
+        #[qinvokable]
+        fn set_channel_direct_out(
+            self: Pin<&mut UndertoneController>,
+            name: QString,
+            enabled: bool,
+        );
+
+        /// Change a channel's speaker layout (`stereo`, `5.1` or `7.1`).
+        #[qinvokable]
+        fn set_channel_layout(self: Pin<&mut UndertoneController>, name: QString, layout: QString);
+
+        /// Delete a user-defined channel.
+        #[qinvokable]
+        fn delete_channel(self: Pin<&mut UndertoneController>, name: QString);
+
         // App routing methods
 
         /// Get app name by index.
@@ -174,6 +335,20 @@ mod ffi {
         #[qinvokable]
         fn app_persistent(self: &UndertoneController, index: i32) -> bool;
 
+        /// Get the title of an app's stream by index.
+        #[qinvokable]
+        fn app_stream_name(self: &UndertoneController, index: i32) -> QString;
+
+        /// Get why an app's stream is routed apart from the app by index
+        /// (empty if it is not).
+        #[qinvokable]
+        fn app_stream_override(self: &UndertoneController, index: i32) -> QString;
+
+        /// Move just this stream of an app to a channel, by index, until it
+        /// closes.
+        #[qinvokable]
+        fn move_stream(self: Pin<&mut UndertoneController>, index: i32, channel: QString);
+
         /// Set app channel routing.
         #[qinvokable]
         fn set_app_channel(
@@ -182,10 +357,66 @@ mod ffi {
             channel: QString,
         );
 
+        /// Add a routing rule matching apps by `pattern`, using one of the
+        /// names from `pattern_types`. A non-empty `property` matches that
+        /// node property (e.g. `media.role`) instead of the app name.
+        /// `direction` is `playback` (`target` is a channel) or `capture`
+        /// (`target` is one of `capture_sources`). With `exclude` set, the
+        /// rule leaves matching apps alone and `target` is ignored. With
+        /// `match_ancestors` set, apps started from a matching process
+        /// (e.g. `steam`) match too.
+        #[qinvokable]
+        fn add_app_route(
+            self: Pin<&mut UndertoneController>,
+            pattern: QString,
+            pattern_type: QString,
+            property: QString,
+            direction: QString,
+            target: QString,
+            exclude: bool,
+            match_ancestors: bool,
+        );
+
+        /// Add a playback rule for some streams of apps matching `pattern`.
+        /// `stream` selects them as `name:<text in the title>`,
+        /// `role:<media.role>` or `index:<n>`; the apps' other streams keep
+        /// their route.
+        #[qinvokable]
+        fn add_stream_route(
+            self: Pin<&mut UndertoneController>,
+            pattern: QString,
+            pattern_type: QString,
+            stream: QString,
+            target: QString,
+        );
+
         /// Get list of available channel names (for dropdown).
         #[qinvokable]
         fn available_channels(self: &UndertoneController) -> QString;
 
+        /// Get list of routing pattern type names (for dropdown).
+        #[qinvokable]
+        fn pattern_types(self: &UndertoneController) -> QString;
+
+        /// Choose the channel that apps no rule matches are sent to.
+        #[qinvokable]
+        fn set_fallback_channel_name(self: Pin<&mut UndertoneController>, channel: QString);
+
+        /// Get list of sources a recording app can be fed from: the mixes,
+        /// then the mic (for dropdown).
+        #[qinvokable]
+        fn capture_sources(self: &UndertoneController) -> QString;
+
+        /// Ask the daemon why an app is routed where it is, by index. The
+        /// answer arrives in `route_report`.
+        #[qinvokable]
+        fn explain_app_route(self: Pin<&mut UndertoneController>, index: i32);
+
+        /// Ask the daemon to check the routing rules. The answer arrives in
+        /// `route_report`.
+        #[qinvokable]
+        fn check_routes(self: Pin<&mut UndertoneController>);
+
         // Device control methods
 
         /// Set microphone gain (0.0 - 1.0).
@@ -196,6 +427,22 @@ mod ffi {
         #[qinvokable]
         fn toggle_mic_mute(self: Pin<&mut UndertoneController>);
 
+        /// Check if the mic is sent to a mix, by mix index.
+        #[qinvokable]
+        fn mic_send_enabled(self: &UndertoneController, mix: i32) -> bool;
+
+        /// Get the mic level in a mix, by mix index.
+        #[qinvokable]
+        fn mic_send_volume(self: &UndertoneController, mix: i32) -> f32;
+
+        /// Include or exclude the mic from a mix, by mix index.
+        #[qinvokable]
+        fn set_mic_send_enabled(self: Pin<&mut UndertoneController>, mix: i32, enabled: bool);
+
+        /// Set the mic level in a mix, by mix index.
+        #[qinvokable]
+        fn set_mic_send_volume(self: Pin<&mut UndertoneController>, mix: i32, volume: f32);
+
         // Master volume methods
 
         /// Set master volume (0.0 - 1.0).
@@ -260,6 +507,7 @@ pub struct UndertoneControllerRust {
     device_serial: QString,
     active_profile: QString,
     mix_mode: i32,
+    mix_count: i32,
     channel_count: i32,
     app_count: i32,
     mic_muted: bool,
@@ -269,6 +517,13 @@ pub struct UndertoneControllerRust {
     master_muted: bool,
     output_device_count: i32,
     monitor_output: QString,
+    fallback_channel: QString,
+    route_report: QString,
+    levels_revision: i32,
+    master_level_left: f32,
+    master_level_right: f32,
+    mic_level: f32,
+    mic_sends_revision: i32,
 }
 
 impl Default for UndertoneControllerRust {
@@ -279,6 +534,7 @@ impl Default for UndertoneControllerRust {
             device_serial: QString::from(""),
             active_profile: QString::from("Default"),
             mix_mode: 0,
+            mix_count: 0,
             channel_count: 0,
             app_count: 0,
             mic_muted: false,
@@ -288,6 +544,13 @@ impl Default for UndertoneControllerRust {
             master_muted: false,
             output_device_count: 0,
             monitor_output: QString::from("wave3-sink"),
+            fallback_channel: QString::from(FALLBACK_CHANNEL),
+            route_report: QString::default(),
+            levels_revision: 0,
+            master_level_left: 0.0,
+            master_level_right: 0.0,
+            mic_level: 0.0,
+            mic_sends_revision: 0,
         }
     }
 }
@@ -297,11 +560,28 @@ pub enum UiCommand {
     SetMixMode(MixType),
     SetVolume { channel: String, mix: MixType, volume: f32 },
     SetMute { channel: String, mix: MixType, muted: bool },
+    SetPan { channel: String, mix: MixType, pan: f32 },
+    SetSolo { channel: String, soloed: bool },
     SetMasterVolume { mix: MixType, volume: f32 },
     SetMasterMute { mix: MixType, muted: bool },
     SetAppChannel { app_pattern: String, channel: String },
+    MoveStream { app_id: u32, channel: String },
+    AddAppRoute(RouteRule),
+    SetFallbackChannel { channel: String },
+    ExplainRoute { app_id: u32 },
+    LintRoutes,
+    CreateChannel { name: String, display_name: String, color: Option<String> },
+    RenameChannel { name: String, display_name: String },
+    SetChannelColor { name: String, color: Option<String> },
+    SetChannelDirectOut { name: String, enabled: bool },
+    SetChannelLayout { name: String, layout: ChannelLayout },
+    DeleteChannel { name: String },
+    CreateMix { name: String, display_name: String },
+    DeleteMix { name: String },
     SetMicGain { gain: f32 },
     SetMicMute { muted: bool },
+    SetMicMixEnabled { mix: MixType, enabled: bool },
+    SetMicMixVolume { mix: MixType, volume: f32 },
     SetMonitorOutput { device_name: String },
     SaveProfile { name: String },
     LoadProfile { name: String },
@@ -312,17 +592,51 @@ pub enum UiCommand {
 impl ffi::UndertoneController {
     /// Change the mix mode.
     fn change_mix_mode(mut self: Pin<&mut Self>, mode: i32) {
-        let mix = if mode == 0 { MixType::Stream } else { MixType::Monitor };
-        debug!(?mix, "Changing mix mode");
+        let Some(mix) = mix_at(mode) else {
+            warn!(mode, "No mix at index");
+            return;
+        };
+        debug!(mix = %mix.name, "Changing mix mode");
 
         self.as_mut().set_mix_mode(mode);
-        send_command(UiCommand::SetMixMode(mix));
+        self.as_mut().set_master_volume(mix.master_volume);
+        self.as_mut().set_master_muted(mix.master_muted);
+        send_command(UiCommand::SetMixMode(mix.id()));
+    }
+
+    /// Get mix name by index.
+    fn mix_name(&self, index: i32) -> QString {
+        mix_at(index).map(|m| QString::from(&m.name)).unwrap_or_default()
+    }
+
+    /// Get mix display name by index.
+    fn mix_display_name(&self, index: i32) -> QString {
+        mix_at(index).map(|m| QString::from(&m.display_name)).unwrap_or_default()
+    }
+
+    /// Check if a mix is a system mix by index.
+    fn mix_is_system(&self, index: i32) -> bool {
+        mix_at(index).is_some_and(|m| m.is_system)
+    }
+
+    /// Create a user-defined mix.
+    fn create_mix(self: Pin<&mut Self>, name: QString, display_name: QString) {
+        let name = name.to_string();
+        debug!(mix = %name, "Creating mix");
+        send_command(UiCommand::CreateMix { name, display_name: display_name.to_string() });
+    }
+
+    /// Delete a user-defined mix.
+    fn delete_mix(self: Pin<&mut Self>, name: QString) {
+        let name = name.to_string();
+        debug!(mix = %name, "Deleting mix");
+        send_command(UiCommand::DeleteMix { name });
     }
 
     /// Set volume for a channel.
     fn set_channel_volume(self: Pin<&mut Self>, channel: QString, volume: f32) {
         let channel_name = channel.to_string();
-        let mix = if self.mix_mode == 0 { MixType::Stream } else { MixType::Monitor };
+        let mix = self.current_mix();
         debug!(channel = %channel_name, ?mix, volume, "Setting channel volume");
 
         send_command(UiCommand::SetVolume { channel: channel_name, mix, volume });
@@ -331,17 +645,18 @@ impl ffi::UndertoneController {
     /// Toggle mute for a channel.
     fn toggle_channel_mute(self: Pin<&mut Self>, channel: QString) {
         let channel_name = channel.to_string();
-        let mix = if self.mix_mode == 0 { MixType::Stream } else { MixType::Monitor };
+        let mix = self.current_mix();
 
         // Get current muted state to toggle
-        let current_muted =
-            if let Ok(cache) = get_ui_data().lock() {
-                cache.channels.iter().find(|c| c.name == channel_name).is_some_and(|c| {
-                    if self.mix_mode == 0 { c.stream_muted } else { c.monitor_muted }
-                })
-            } else {
-                false
-            };
+        let current_muted = if let Ok(cache) = get_ui_data().lock() {
+            cache
+                .channels
+                .iter()
+                .find(|c| c.name == channel_name)
+                .is_some_and(|c| c.send(&mix).muted)
+        } else {
+            false
+        };
 
         let new_muted = !current_muted;
         debug!(channel = %channel_name, ?mix, muted = new_muted, "Setting channel mute");
@@ -349,6 +664,31 @@ impl ffi::UndertoneController {
         send_command(UiCommand::SetMute { channel: channel_name, mix, muted: new_muted });
     }
 
+    /// Toggle solo for a channel.
+    fn toggle_channel_solo(self: Pin<&mut Self>, channel: QString) {
+        let channel_name = channel.to_string();
+
+        let current_soloed = if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.iter().find(|c| c.name == channel_name).is_some_and(|c| c.soloed)
+        } else {
+            false
+        };
+
+        let soloed = !current_soloed;
+        debug!(channel = %channel_name, soloed, "Setting channel solo");
+
+        send_command(UiCommand::SetSolo { channel: channel_name, soloed });
+    }
+
+    /// Set balance for a channel.
+    fn set_channel_pan(self: Pin<&mut Self>, channel: QString, pan: f32) {
+        let channel_name = channel.to_string();
+        let mix = self.current_mix();
+        debug!(channel = %channel_name, ?mix, pan, "Setting channel pan");
+
+        send_command(UiCommand::SetPan { channel: channel_name, mix, pan });
+    }
+
     /// Get channel name by index.
     fn channel_name(&self, index: i32) -> QString {
         if let Ok(cache) = get_ui_data().lock() {
@@ -371,12 +711,47 @@ impl ffi::UndertoneController {
         }
     }
 
+    /// Get channel color by index.
+    fn channel_color(&self, index: i32) -> QString {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).map(|c| QString::from(&c.color)).unwrap_or_default()
+        } else {
+            QString::default()
+        }
+    }
+
+    /// Check if a channel has a direct-out recording source by index.
+    fn channel_direct_out(&self, index: i32) -> bool {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).is_some_and(|c| c.direct_out)
+        } else {
+            false
+        }
+    }
+
+    /// Get channel speaker layout by index.
+    fn channel_layout(&self, index: i32) -> QString {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).map(|c| QString::from(&c.layout)).unwrap_or_default()
+        } else {
+            QString::default()
+        }
+    }
+
+    /// Check if a channel is a system channel by index.
+    fn channel_is_system(&self, index: i32) -> bool {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).is_some_and(|c| c.is_system)
+        } else {
+            false
+        }
+    }
+
     /// Get channel volume by index.
     fn channel_volume(&self, index: i32) -> f32 {
+        let mix = self.current_mix();
         if let Ok(cache) = get_ui_data().lock() {
-            cache.channels.get(index as usize).map_or(1.0, |c| {
-                if self.mix_mode == 0 { c.stream_volume } else { c.monitor_volume }
-            })
+            cache.channels.get(index as usize).map_or(1.0, |c| c.send(&mix).volume)
         } else {
             1.0
         }
@@ -384,22 +759,112 @@ impl ffi::UndertoneController {
 
     /// Get channel muted by index.
     fn channel_muted(&self, index: i32) -> bool {
+        let mix = self.current_mix();
         if let Ok(cache) = get_ui_data().lock() {
-            cache
-                .channels
-                .get(index as usize)
-                .is_some_and(|c| if self.mix_mode == 0 { c.stream_muted } else { c.monitor_muted })
+            cache.channels.get(index as usize).is_some_and(|c| c.send(&mix).muted)
+        } else {
+            false
+        }
+    }
+
+    /// Check if a channel is soloed by index.
+    fn channel_soloed(&self, index: i32) -> bool {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).is_some_and(|c| c.soloed)
         } else {
             false
         }
     }
 
+    /// Get channel balance by index.
+    fn channel_pan(&self, index: i32) -> f32 {
+        let mix = self.current_mix();
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).map_or(0.0, |c| c.send(&mix).pan)
+        } else {
+            0.0
+        }
+    }
+
+    /// Get channel peak level (left) by index.
+    fn channel_level_left(&self, index: i32) -> f32 {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).map_or(0.0, |c| c.level_left)
+        } else {
+            0.0
+        }
+    }
+
+    /// Get channel peak level (right) by index.
+    fn channel_level_right(&self, index: i32) -> f32 {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.channels.get(index as usize).map_or(0.0, |c| c.level_right)
+        } else {
+            0.0
+        }
+    }
+
     /// Request state refresh.
     fn refresh(self: Pin<&mut Self>) {
         debug!("Requesting state refresh");
         send_command(UiCommand::Refresh);
     }
 
+    /// Create a user-defined channel.
+    fn create_channel(self: Pin<&mut Self>, name: QString, display_name: QString, color: QString) {
+        let name = name.to_string();
+        let color = color.to_string();
+        debug!(channel = %name, "Creating channel");
+        send_command(UiCommand::CreateChannel {
+            name,
+            display_name: display_name.to_string(),
+            color: (!color.is_empty()).then_some(color),
+        });
+    }
+
+    /// Change a channel's display name.
+    fn rename_channel(self: Pin<&mut Self>, name: QString, display_name: QString) {
+        let name = name.to_string();
+        debug!(channel = %name, "Renaming channel");
+        send_command(UiCommand::RenameChannel { name, display_name: display_name.to_string() });
+    }
+
+    /// Change a channel's color.
+    fn set_channel_color(self: Pin<&mut Self>, name: QString, color: QString) {
+        let name = name.to_string();
+        let color = color.to_string();
+        debug!(channel = %name, color = %color, "Setting channel color");
+        send_command(UiCommand::SetChannelColor {
+            name,
+            color: (!color.is_empty()).then_some(color),
+        });
+    }
+
+    /// Turn a channel's direct-out recording source on or off.
+    fn set_channel_direct_out(self: Pin<&mut Self>, name: QString, enabled: bool) {
+        let name = name.to_string();
+        debug!(channel = %name, enabled, "Setting channel direct-out");
+        send_command(UiCommand::SetChannelDirectOut { name, enabled });
+    }
+
+    /// Change a channel's speaker layout.
+    fn set_channel_layout(self: Pin<&mut Self>, name: QString, layout: QString) {
+        let name = name.to_string();
+        let Ok(layout) = serde_json::from_value::<ChannelLayout>(layout.to_string().into()) else {
+            warn!(channel = %name, layout = %layout, "Unknown channel layout");
+            return;
+        };
+        debug!(channel = %name, layout = layout.as_str(), "Setting channel layout");
+        send_command(UiCommand::SetChannelLayout { name, layout });
+    }
+
+    /// Delete a user-defined channel.
+    fn delete_channel(self: Pin<&mut Self>, name: QString) {
+        let name = name.to_string();
+        debug!(channel = %name, "Deleting channel");
+        send_command(UiCommand::DeleteChannel { name });
+    }
+
     /// Get app name by index.
     fn app_name(&self, index: i32) -> QString {
         if let Ok(cache) = get_ui_data().lock() {
@@ -440,6 +905,44 @@ impl ffi::UndertoneController {
         }
     }
 
+    /// Get the title of an app's stream by index.
+    fn app_stream_name(&self, index: i32) -> QString {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache.apps.get(index as usize).map(|a| QString::from(&a.media_name)).unwrap_or_default()
+        } else {
+            QString::default()
+        }
+    }
+
+    /// Get why an app's stream is routed apart from the app by index.
+    fn app_stream_override(&self, index: i32) -> QString {
+        if let Ok(cache) = get_ui_data().lock() {
+            cache
+                .apps
+                .get(index as usize)
+                .map(|a| QString::from(&a.stream_override))
+                .unwrap_or_default()
+        } else {
+            QString::default()
+        }
+    }
+
+    /// Move one stream of an app to a channel.
+    fn move_stream(self: Pin<&mut Self>, index: i32, channel: QString) {
+        let app_id = get_ui_data()
+            .lock()
+            .ok()
+            .and_then(|cache| cache.apps.get(index as usize).map(|a| a.app_id));
+        let Some(app_id) = app_id else {
+            warn!(index, "No app at index");
+            return;
+        };
+        let channel = channel.to_string();
+        debug!(app_id, channel = %channel, "Moving stream");
+
+        send_command(UiCommand::MoveStream { app_id, channel });
+    }
+
     /// Set app channel routing.
     fn set_app_channel(self: Pin<&mut Self>, app_pattern: QString, channel: QString) {
         let pattern = app_pattern.to_string();
@@ -449,6 +952,79 @@ impl ffi::UndertoneController {
         send_command(UiCommand::SetAppChannel { app_pattern: pattern, channel: channel_name });
     }
 
+    /// Add a routing rule.
+    fn add_app_route(
+        self: Pin<&mut Self>,
+        pattern: QString,
+        pattern_type: QString,
+        property: QString,
+        direction: QString,
+        target: QString,
+        exclude: bool,
+        match_ancestors: bool,
+    ) {
+        let Some(pattern_type) = PatternType::from_name(&pattern_type.to_string()) else {
+            warn!(pattern_type = %pattern_type, "Unknown pattern type");
+            return;
+        };
+        let Some(direction) = RouteDirection::from_name(&direction.to_string()) else {
+            warn!(direction = %direction, "Unknown route direction");
+            return;
+        };
+        let mut rule = if exclude {
+            RouteRule::exclusion(pattern.to_string(), pattern_type, 100)
+        } else {
+            RouteRule::new(pattern.to_string(), pattern_type, target.to_string(), 100)
+        }
+        .with_direction(direction);
+        let property = property.to_string();
+        if !property.trim().is_empty() {
+            rule = rule.with_property(property.trim());
+        }
+        if match_ancestors {
+            rule = rule.with_ancestors();
+        }
+        debug!(
+            app = %rule.pattern,
+            ?pattern_type,
+            property = ?rule.property,
+            ?direction,
+            exclude,
+            match_ancestors = rule.match_ancestors,
+            target = %rule.channel,
+            "Adding app route"
+        );
+        send_command(UiCommand::AddAppRoute(rule));
+    }
+
+    /// Add a rule for some streams of an app.
+    fn add_stream_route(
+        self: Pin<&mut Self>,
+        pattern: QString,
+        pattern_type: QString,
+        stream: QString,
+        target: QString,
+    ) {
+        let Some(pattern_type) = PatternType::from_name(&pattern_type.to_string()) else {
+            warn!(pattern_type = %pattern_type, "Unknown pattern type");
+            return;
+        };
+        let Some(stream) = StreamSelector::from_key(stream.to_string().trim()) else {
+            warn!(stream = %stream, "Unknown stream selector");
+            return;
+        };
+        let rule = RouteRule::new(pattern.to_string(), pattern_type, target.to_string(), 100)
+            .with_stream(stream);
+        debug!(
+            app = %rule.pattern,
+            ?pattern_type,
+            stream = ?rule.stream,
+            target = %rule.channel,
+            "Adding stream route"
+        );
+        send_command(UiCommand::AddAppRoute(rule));
+    }
+
     /// Get list of available channel names (comma-separated for QML).
     fn available_channels(&self) -> QString {
         if let Ok(cache) = get_ui_data().lock() {
@@ -459,6 +1035,53 @@ impl ffi::UndertoneController {
         }
     }
 
+    /// Get list of routing pattern type names (comma-separated for QML).
+    fn pattern_types(&self) -> QString {
+        let names: Vec<&str> = PatternType::ALL.iter().map(|t| t.as_str()).collect();
+        QString::from(names.join(",").as_str())
+    }
+
+    /// Choose the fallback channel.
+    fn set_fallback_channel_name(mut self: Pin<&mut Self>, channel: QString) {
+        let name = channel.to_string();
+        debug!(channel = %name, "Setting fallback channel");
+        self.as_mut().set_fallback_channel(channel);
+        send_command(UiCommand::SetFallbackChannel { channel: name });
+    }
+
+    /// Get list of capture sources (comma-separated for QML).
+    fn capture_sources(&self) -> QString {
+        if let Ok(cache) = get_ui_data().lock() {
+            let names: Vec<&str> =
+                cache.mixes.iter().map(|m| m.name.as_str()).chain([CAPTURE_MIC]).collect();
+            QString::from(names.join(",").as_str())
+        } else {
+            QString::from(CAPTURE_MIC)
+        }
+    }
+
+    /// Ask why an app is routed where it is.
+    fn explain_app_route(mut self: Pin<&mut Self>, index: i32) {
+        let app_id = get_ui_data()
+            .lock()
+            .ok()
+            .and_then(|cache| cache.apps.get(index as usize).map(|a| a.app_id));
+        let Some(app_id) = app_id else {
+            warn!(index, "No app at index");
+            return;
+        };
+        debug!(app_id, "Explaining app route");
+        self.as_mut().set_route_report(QString::default());
+        send_command(UiCommand::ExplainRoute { app_id });
+    }
+
+    /// Check the routing rules for problems.
+    fn check_routes(mut self: Pin<&mut Self>) {
+        debug!("Checking routing rules");
+        self.as_mut().set_route_report(QString::default());
+        send_command(UiCommand::LintRoutes);
+    }
+
     /// Set microphone gain.
     fn set_mic_gain_value(mut self: Pin<&mut Self>, gain: f32) {
         debug!(gain, "Setting mic gain");
@@ -474,10 +1097,41 @@ impl ffi::UndertoneController {
         send_command(UiCommand::SetMicMute { muted: new_muted });
     }
 
+    /// Check if the mic is sent to a mix.
+    fn mic_send_enabled(&self, mix: i32) -> bool {
+        mix_at(mix).is_some_and(|m| m.mic_enabled)
+    }
+
+    /// Get the mic level in a mix.
+    fn mic_send_volume(&self, mix: i32) -> f32 {
+        mix_at(mix).map_or(0.0, |m| m.mic_volume)
+    }
+
+    /// Include or exclude the mic from a mix.
+    fn set_mic_send_enabled(mut self: Pin<&mut Self>, mix: i32, enabled: bool) {
+        let Some(mix) = mix_at(mix).map(|m| m.id()) else {
+            return;
+        };
+        debug!(?mix, enabled, "Setting mic send");
+        self.as_mut().apply_mic_send(&mix, Some(enabled), None);
+        send_command(UiCommand::SetMicMixEnabled { mix, enabled });
+    }
+
+    /// Set the mic level in a mix.
+    fn set_mic_send_volume(mut self: Pin<&mut Self>, mix: i32, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        let Some(mix) = mix_at(mix).map(|m| m.id()) else {
+            return;
+        };
+        debug!(?mix, volume, "Setting mic send volume");
+        self.as_mut().apply_mic_send(&mix, None, Some(volume));
+        send_command(UiCommand::SetMicMixVolume { mix, volume });
+    }
+
     /// Set master volume.
     fn set_master_volume_value(mut self: Pin<&mut Self>, volume: f32) {
         let volume = volume.clamp(0.0, 1.0);
-        let mix = if self.mix_mode == 0 { MixType::Stream } else { MixType::Monitor };
+        let mix = self.current_mix();
         debug!(?mix, volume, "Setting master volume");
         self.as_mut().set_master_volume(volume);
         send_command(UiCommand::SetMasterVolume { mix, volume });
@@ -486,7 +1140,7 @@ impl ffi::UndertoneController {
     /// Toggle master mute.
     fn toggle_master_mute(mut self: Pin<&mut Self>) {
         let new_muted = !self.master_muted;
-        let mix = if self.mix_mode == 0 { MixType::Stream } else { MixType::Monitor };
+        let mix = self.current_mix();
         debug!(?mix, muted = new_muted, "Toggling master mute");
         self.as_mut().set_master_muted(new_muted);
         send_command(UiCommand::SetMasterMute { mix, muted: new_muted });
@@ -579,6 +1233,34 @@ impl ffi::UndertoneController {
 }
 
 impl ffi::UndertoneController {
+    /// The mix currently being shown.
+    fn current_mix(&self) -> MixType {
+        mix_at(self.mix_mode).map_or(MixType::STREAM, |m| m.id())
+    }
+
+    /// Update the cached mic send for a mix.
+    fn apply_mic_send(
+        mut self: Pin<&mut Self>,
+        mix: &MixType,
+        enabled: Option<bool>,
+        volume: Option<f32>,
+    ) {
+        {
+            let mut cache = get_ui_data().lock().expect("UI_DATA mutex poisoned");
+            if let Some(m) = cache.mixes.iter_mut().find(|m| m.name == mix.as_str()) {
+                if let Some(enabled) = enabled {
+                    m.mic_enabled = enabled;
+                }
+                if let Some(volume) = volume {
+                    m.mic_volume = volume;
+                }
+            }
+        } // Lock released before touching Qt properties
+
+        let revision = self.mic_sends_revision.wrapping_add(1);
+        self.as_mut().set_mic_sends_revision(revision);
+    }
+
     /// Apply an IPC update to the controller state.
     fn apply_update(mut self: Pin<&mut Self>, update: IpcUpdate) {
         match update {
@@ -597,12 +1279,10 @@ impl ffi::UndertoneController {
                 device_connected,
                 device_serial,
                 active_profile,
-                stream_master_volume,
-                stream_master_muted,
-                monitor_master_volume,
-                monitor_master_muted,
+                mixes,
                 output_devices,
                 monitor_output,
+                fallback_channel,
             } => {
                 debug!(
                     channels = channels.len(),
@@ -625,30 +1305,31 @@ impl ffi::UndertoneController {
                 ));
                 self.as_mut().set_active_profile(QString::from(active_profile.as_str()));
                 self.as_mut().set_monitor_output(QString::from(monitor_output.as_str()));
-
-                // Set master volume based on current mix mode
-                let mix_mode = self.mix_mode;
-                if mix_mode == 0 {
-                    self.as_mut().set_master_volume(stream_master_volume);
-                    self.as_mut().set_master_muted(stream_master_muted);
-                } else {
-                    self.as_mut().set_master_volume(monitor_master_volume);
-                    self.as_mut().set_master_muted(monitor_master_muted);
+                self.as_mut().set_fallback_channel(QString::from(fallback_channel.as_str()));
+
+                // Stay on the same mix if it still exists, otherwise go back to the first
+                let current = self.current_mix();
+                let mix_mode = mixes.iter().position(|m| m.name == current.as_str()).unwrap_or(0);
+                if let Some(mix) = mixes.get(mix_mode) {
+                    self.as_mut().set_master_volume(mix.master_volume);
+                    self.as_mut().set_master_muted(mix.master_muted);
                 }
 
                 // Update global cache for vector data
                 // IMPORTANT: Release the lock BEFORE calling Qt setters to avoid deadlock!
                 // Qt property changes can trigger QML binding re-evaluation, which may call
                 // methods like channel_name() that also need this lock.
-                let (channel_count, app_count, profile_count, output_device_count) = {
+                let (channel_count, mix_count, app_count, profile_count, output_device_count) = {
                     let mut cache = get_ui_data().lock().expect("UI_DATA mutex poisoned");
                     cache.channels = channels;
+                    cache.mixes = mixes;
                     cache.apps = apps;
                     cache.profiles = profiles;
                     cache.output_devices = output_devices;
                     cache.monitor_output = monitor_output;
                     (
                         cache.channels.len() as i32,
+                        cache.mixes.len() as i32,
                         cache.apps.len() as i32,
                         cache.profiles.len() as i32,
                         cache.output_devices.len() as i32,
@@ -656,8 +1337,12 @@ impl ffi::UndertoneController {
                 }; // Lock released here
 
                 // Now safe to update Qt properties - QML bindings can access UI_DATA
+                self.as_mut().set_mix_mode(mix_mode as i32);
+                self.as_mut().set_mix_count(mix_count);
                 self.as_mut().set_channel_count(channel_count);
                 self.as_mut().set_app_count(app_count);
+                let revision = self.mic_sends_revision.wrapping_add(1);
+                self.as_mut().set_mic_sends_revision(revision);
                 self.as_mut().set_output_device_count(output_device_count);
                 self.as_mut().set_profile_count(profile_count);
             }
@@ -667,10 +1352,7 @@ impl ffi::UndertoneController {
                 let count = {
                     let mut cache = get_ui_data().lock().expect("UI_DATA mutex poisoned");
                     if let Some(ch) = cache.channels.iter_mut().find(|c| c.name == channel) {
-                        match mix {
-                            MixType::Stream => ch.stream_volume = volume,
-                            MixType::Monitor => ch.monitor_volume = volume,
-                        }
+                        ch.sends.entry(mix).or_default().volume = volume;
                     }
                     cache.channels.len() as i32
                 };
@@ -683,15 +1365,28 @@ impl ffi::UndertoneController {
                 let count = {
                     let mut cache = get_ui_data().lock().expect("UI_DATA mutex poisoned");
                     if let Some(ch) = cache.channels.iter_mut().find(|c| c.name == channel) {
-                        match mix {
-                            MixType::Stream => ch.stream_muted = muted,
-                            MixType::Monitor => ch.monitor_muted = muted,
-                        }
+                        ch.sends.entry(mix).or_default().muted = muted;
+                    }
+                    cache.channels.len() as i32
+                };
+                self.as_mut().set_channel_count(count);
+            }
+            IpcUpdate::ChannelPanChanged { channel, mix, pan } => {
+                debug!(channel = %channel, ?mix, pan, "Channel pan changed");
+                // Release lock before triggering Qt property change
+                let count = {
+                    let mut cache = get_ui_data().lock().expect("UI_DATA mutex poisoned");
+                    if let Some(ch) = cache.channels.iter_mut().find(|c| c.name == channel) {
+                        ch.sends.entry(mix).or_default().pan = pan;
                     }
                     cache.channels.len() as i32
                 };
                 self.as_mut().set_channel_count(count);
             }
+            IpcUpdate::MicMixChanged { mix, enabled, volume } => {
+                debug!(?mix, enabled, volume, "Mic send changed");
+                self.as_mut().apply_mic_send(&mix, Some(enabled), Some(volume));
+            }
             IpcUpdate::DeviceConnected { serial } => {
                 info!(?serial, "Device connected");
                 self.as_mut().set_device_connected(true);
@@ -703,6 +1398,29 @@ impl ffi::UndertoneController {
                 self.as_mut().set_device_connected(false);
                 self.as_mut().set_device_serial(QString::from(""));
             }
+            IpcUpdate::LevelsUpdated(levels) => {
+                {
+                    let mut cache = get_ui_data().lock().expect("UI_DATA mutex poisoned");
+                    for ch in &mut cache.channels {
+                        let reading = levels.channels.get(&ch.name).copied().unwrap_or_default();
+                        ch.level_left = reading.peak_left;
+                        ch.level_right = reading.peak_right;
+                    }
+                } // Lock released before touching Qt properties
+
+                let mix = self.current_mix();
+                let master = levels.mixes.get(mix.as_str()).copied().unwrap_or_default();
+                self.as_mut().set_master_level_left(master.peak_left);
+                self.as_mut().set_master_level_right(master.peak_right);
+                self.as_mut()
+                    .set_mic_level(levels.mic.map_or(0.0, |m| m.peak_left.max(m.peak_right)));
+
+                let revision = self.levels_revision.wrapping_add(1);
+                self.as_mut().set_levels_revision(revision);
+            }
+            IpcUpdate::RouteReport(report) => {
+                self.as_mut().set_route_report(QString::from(report.as_str()));
+            }
             IpcUpdate::Error(msg) => {
                 warn!(error = %msg, "IPC error");
             }
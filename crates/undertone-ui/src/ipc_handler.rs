@@ -4,6 +4,7 @@
 //! all communication with the daemon, bridging between the async IPC client
 //! and the synchronous Qt event loop.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::thread;
 
@@ -13,13 +14,18 @@ use tracing::{debug, error, info, warn};
 
 use undertone_ipc::client::IpcClient;
 use undertone_ipc::events::{
-    ChannelMuteChangedData, ChannelVolumeChangedData, DeviceConnectedData, Event, EventType,
+    ChannelMuteChangedData, ChannelPanChangedData, ChannelVolumeChangedData, DeviceConnectedData,
+    Event, EventType, LevelsData, MicMixChangedData,
 };
 use undertone_ipc::messages::Method;
 
-use crate::bridge::{AppData, ChannelData, OutputDeviceData, ProfileData, UiCommand};
+use crate::bridge::{AppData, ChannelData, MixData, OutputDeviceData, ProfileData, UiCommand};
 use crate::state::UiState;
-use undertone_core::mixer::MixType;
+use undertone_core::explain::RouteTrace;
+use undertone_core::layout::ChannelLayout;
+use undertone_core::lint::RuleIssue;
+use undertone_core::mixer::{MixConfig, MixType, MixerState, default_mixes};
+use undertone_core::routing::{FALLBACK_CHANNEL, PatternType, RouteDirection, StreamRoute};
 
 /// Messages sent from the IPC handler back to the UI.
 #[derive(Debug)]
@@ -33,14 +39,12 @@ pub enum IpcUpdate {
         device_connected: bool,
         device_serial: Option<String>,
         active_profile: String,
-        // Mixer master state
-        stream_master_volume: f32,
-        stream_master_muted: bool,
-        monitor_master_volume: f32,
-        monitor_master_muted: bool,
+        // Mixes with their master levels and mic sends
+        mixes: Vec<MixData>,
         // Output devices
         output_devices: Vec<OutputDeviceData>,
         monitor_output: String,
+        fallback_channel: String,
     },
     ChannelVolumeChanged {
         channel: String,
@@ -52,10 +56,23 @@ pub enum IpcUpdate {
         mix: MixType,
         muted: bool,
     },
+    ChannelPanChanged {
+        channel: String,
+        mix: MixType,
+        pan: f32,
+    },
+    MicMixChanged {
+        mix: MixType,
+        enabled: bool,
+        volume: f32,
+    },
     DeviceConnected {
         serial: Option<String>,
     },
     DeviceDisconnected,
+    LevelsUpdated(LevelsData),
+    /// Answer to a route explanation or rule check, ready to show
+    RouteReport(String),
     Error(String),
 }
 
@@ -123,11 +140,20 @@ async fn run_ipc_loop(
                         events: vec![
                             "channel_volume_changed".to_string(),
                             "channel_mute_changed".to_string(),
+                            "channel_created".to_string(),
+                            "channel_updated".to_string(),
+                            "channel_deleted".to_string(),
+                            "app_route_changed".to_string(),
                             "device_connected".to_string(),
                             "device_disconnected".to_string(),
                             "app_discovered".to_string(),
                             "app_removed".to_string(),
                             "profile_changed".to_string(),
+                            "levels_updated".to_string(),
+                            "mic_mix_changed".to_string(),
+                            "mix_created".to_string(),
+                            "mix_deleted".to_string(),
+                            "fallback_channel_changed".to_string(),
                         ],
                     })
                     .await
@@ -148,17 +174,37 @@ async fn run_ipc_loop(
                     tokio::select! {
                         // Handle commands from UI
                         Some(cmd) = command_rx.recv() => {
-                            if let Some(method) = command_to_method(cmd)
-                                && let Err(e) = client.request(method).await {
-                                    error!(error = %e, "Request failed");
+                            let report = report_formatter(&cmd);
+                            if let Some(method) = command_to_method(cmd) {
+                                match client.request(method).await {
+                                    Ok(response) => {
+                                        if let Some(format) = report {
+                                            let text = match response.result {
+                                                Ok(value) => format(value),
+                                                Err(e) => e.message,
+                                            };
+                                            let _ = update_tx.send(IpcUpdate::RouteReport(text)).await;
+                                        }
+                                    }
+                                    Err(e) => error!(error = %e, "Request failed"),
                                 }
+                            }
                         }
 
                         // Handle events from daemon
                         Some(event) = client.events().recv() => {
-                            // For profile/app changes, request full state refresh
+                            // For profile/channel/mix/app changes, request full state refresh
                             if matches!(event.event,
                                 undertone_ipc::events::EventType::ProfileChanged |
+                                undertone_ipc::events::EventType::ChannelCreated |
+                                undertone_ipc::events::EventType::ChannelUpdated |
+                                undertone_ipc::events::EventType::ChannelEffectsChanged |
+                                undertone_ipc::events::EventType::SoloChanged |
+                                undertone_ipc::events::EventType::ChannelDeleted |
+                                undertone_ipc::events::EventType::MixCreated |
+                                undertone_ipc::events::EventType::MixDeleted |
+                                undertone_ipc::events::EventType::AppRouteChanged |
+                                undertone_ipc::events::EventType::FallbackChannelChanged |
                                 undertone_ipc::events::EventType::AppDiscovered |
                                 undertone_ipc::events::EventType::AppRemoved
                             ) {
@@ -200,28 +246,135 @@ fn command_to_method(cmd: UiCommand) -> Option<Method> {
             None
         }
         UiCommand::SetVolume { channel, mix, volume } => {
-            Some(Method::SetChannelVolume { channel, mix, volume })
+            Some(Method::SetChannelVolume { channel, mix, volume: volume.into() })
         }
         UiCommand::SetMute { channel, mix, muted } => {
-            Some(Method::SetChannelMute { channel, mix, muted })
+            Some(Method::SetChannelMute { channel, mix, muted, fade_ms: None })
+        }
+        UiCommand::SetPan { channel, mix, pan } => {
+            Some(Method::SetChannelPan { channel, mix, pan })
+        }
+        UiCommand::SetSolo { channel, soloed } => Some(Method::SetChannelSolo { channel, soloed }),
+        UiCommand::SetMasterVolume { mix, volume } => {
+            Some(Method::SetMasterVolume { mix, volume: volume.into(), fade_ms: None })
         }
-        UiCommand::SetMasterVolume { mix, volume } => Some(Method::SetMasterVolume { mix, volume }),
         UiCommand::SetMasterMute { mix, muted } => Some(Method::SetMasterMute { mix, muted }),
-        UiCommand::SetAppChannel { app_pattern, channel } => {
-            Some(Method::SetAppRoute { app_pattern, channel })
+        UiCommand::SetAppChannel { app_pattern, channel } => Some(Method::SetAppRoute {
+            app_pattern,
+            channel,
+            pattern_type: PatternType::Exact,
+            property: None,
+            direction: RouteDirection::Playback,
+            exclude: false,
+            match_ancestors: false,
+            stream: None,
+        }),
+        UiCommand::MoveStream { app_id, channel } => Some(Method::MoveStream { app_id, channel }),
+        UiCommand::AddAppRoute(rule) => Some(Method::SetAppRoute {
+            app_pattern: rule.pattern,
+            channel: rule.channel,
+            pattern_type: rule.pattern_type,
+            property: rule.property,
+            direction: rule.direction,
+            exclude: rule.exclude,
+            match_ancestors: rule.match_ancestors,
+            stream: rule.stream,
+        }),
+        UiCommand::SetFallbackChannel { channel } => Some(Method::SetFallbackChannel { channel }),
+        UiCommand::ExplainRoute { app_id } => Some(Method::ExplainRoute {
+            app_id: Some(app_id),
+            app_name: None,
+            binary_name: None,
+            properties: HashMap::new(),
+            stream_index: 0,
+            direction: RouteDirection::Playback,
+        }),
+        UiCommand::LintRoutes => Some(Method::LintRoutes),
+        UiCommand::CreateChannel { name, display_name, color } => Some(Method::CreateChannel {
+            name,
+            display_name,
+            color,
+            icon: None,
+            layout: ChannelLayout::Stereo,
+        }),
+        UiCommand::RenameChannel { name, display_name } => {
+            Some(Method::RenameChannel { name, display_name })
+        }
+        UiCommand::SetChannelColor { name, color } => Some(Method::SetChannelColor { name, color }),
+        UiCommand::SetChannelDirectOut { name, enabled } => {
+            Some(Method::SetChannelDirectOut { name, enabled })
+        }
+        UiCommand::SetChannelLayout { name, layout } => {
+            Some(Method::SetChannelLayout { name, layout })
         }
+        UiCommand::DeleteChannel { name } => Some(Method::DeleteChannel { name }),
+        UiCommand::CreateMix { name, display_name } => {
+            Some(Method::CreateMix { name, display_name })
+        }
+        UiCommand::DeleteMix { name } => Some(Method::DeleteMix { name }),
         UiCommand::SetMicGain { gain } => Some(Method::SetMicGain { gain }),
         UiCommand::SetMicMute { muted } => Some(Method::SetMicMute { muted }),
+        UiCommand::SetMicMixEnabled { mix, enabled } => {
+            Some(Method::SetMicMixEnabled { mix, enabled })
+        }
+        UiCommand::SetMicMixVolume { mix, volume } => {
+            Some(Method::SetMicMixVolume { mix, volume: volume.into() })
+        }
         UiCommand::SetMonitorOutput { device_name } => {
             Some(Method::SetMonitorOutput { device_name })
         }
         UiCommand::SaveProfile { name } => Some(Method::SaveProfile { name }),
-        UiCommand::LoadProfile { name } => Some(Method::LoadProfile { name }),
+        UiCommand::LoadProfile { name } => Some(Method::LoadProfile { name, fade_ms: None }),
         UiCommand::DeleteProfile { name } => Some(Method::DeleteProfile { name }),
         UiCommand::Refresh => Some(Method::GetState),
     }
 }
 
+/// For commands whose answer is shown to the user, how to put it into words.
+fn report_formatter(cmd: &UiCommand) -> Option<fn(serde_json::Value) -> String> {
+    match cmd {
+        UiCommand::ExplainRoute { .. } => Some(format_route_trace),
+        UiCommand::LintRoutes => Some(format_rule_issues),
+        _ => None,
+    }
+}
+
+/// One line for the outcome, then one per rule in the order they were
+/// tried, the deciding rule marked with `>`.
+fn format_route_trace(value: serde_json::Value) -> String {
+    let Ok(trace) = serde_json::from_value::<RouteTrace>(value) else {
+        return "Could not read the route explanation".to_string();
+    };
+
+    let mut lines = vec![format!("{}: {}", trace.direction.as_str(), trace.decision)];
+    for (index, evaluation) in trace.rules.iter().enumerate() {
+        let rule = &evaluation.rule;
+        let marker = if trace.winner == Some(index) { '>' } else { ' ' };
+        let target = if rule.exclude { "never touch" } else { rule.channel.as_str() };
+        let on = rule.property.as_ref().map(|key| format!(" on {key}")).unwrap_or_default();
+        let outcome = match (&evaluation.matched, &evaluation.error) {
+            (Some(matched), _) => format!("matched {matched}"),
+            (None, Some(error)) => format!("invalid pattern: {error}"),
+            (None, None) => "no match".to_string(),
+        };
+        lines.push(format!(
+            "{marker} {} ({}{on}, priority {}) -> {target}: {outcome}",
+            rule.pattern,
+            rule.pattern_type.as_str(),
+            rule.priority,
+        ));
+    }
+    lines.join("\n")
+}
+
+fn format_rule_issues(value: serde_json::Value) -> String {
+    match serde_json::from_value::<Vec<RuleIssue>>(value) {
+        Ok(issues) if issues.is_empty() => "No problems found".to_string(),
+        Ok(issues) => issues.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"),
+        Err(_) => "Could not read the rule check".to_string(),
+    }
+}
+
 fn event_to_update(event: Event) -> Option<IpcUpdate> {
     match event.event {
         EventType::ChannelVolumeChanged => {
@@ -249,6 +402,30 @@ fn event_to_update(event: Event) -> Option<IpcUpdate> {
                 None
             }
         }
+        EventType::ChannelPanChanged => {
+            if let Ok(data) = serde_json::from_value::<ChannelPanChangedData>(event.data) {
+                Some(IpcUpdate::ChannelPanChanged {
+                    channel: data.channel,
+                    mix: data.mix,
+                    pan: data.pan,
+                })
+            } else {
+                warn!("Failed to parse ChannelPanChanged event");
+                None
+            }
+        }
+        EventType::MicMixChanged => {
+            if let Ok(data) = serde_json::from_value::<MicMixChangedData>(event.data) {
+                Some(IpcUpdate::MicMixChanged {
+                    mix: data.mix,
+                    enabled: data.enabled,
+                    volume: data.volume,
+                })
+            } else {
+                warn!("Failed to parse MicMixChanged event");
+                None
+            }
+        }
         EventType::DeviceConnected => {
             if let Ok(data) = serde_json::from_value::<DeviceConnectedData>(event.data) {
                 Some(IpcUpdate::DeviceConnected { serial: Some(data.serial) })
@@ -257,6 +434,9 @@ fn event_to_update(event: Event) -> Option<IpcUpdate> {
             }
         }
         EventType::DeviceDisconnected => Some(IpcUpdate::DeviceDisconnected),
+        EventType::LevelsUpdated => {
+            serde_json::from_value::<LevelsData>(event.data).ok().map(IpcUpdate::LevelsUpdated)
+        }
         _ => None,
     }
 }
@@ -264,6 +444,13 @@ fn event_to_update(event: Event) -> Option<IpcUpdate> {
 fn parse_state_response(value: &serde_json::Value) -> Option<IpcUpdate> {
     use serde_json::Value;
 
+    // Channels soloed in the monitor mix
+    let soloed: Vec<&str> = value
+        .pointer("/solo/channels")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
     // Parse channels - daemon format has config.name, config.display_name
     let channels = value
         .get("channels")
@@ -273,29 +460,38 @@ fn parse_state_response(value: &serde_json::Value) -> Option<IpcUpdate> {
                 .filter_map(|ch| {
                     // Channel config is nested
                     let config = ch.get("config")?;
+                    let name = config.get("name")?.as_str()?;
                     Some(ChannelData {
-                        name: config.get("name")?.as_str()?.to_string(),
+                        name: name.to_string(),
                         display_name: config
                             .get("display_name")
                             .and_then(|v| v.as_str())
                             .unwrap_or_default()
                             .to_string(),
-                        stream_volume: ch
-                            .get("stream_volume")
-                            .and_then(serde_json::Value::as_f64)
-                            .unwrap_or(1.0) as f32,
-                        stream_muted: ch
-                            .get("stream_muted")
+                        color: config
+                            .get("color")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        is_system: config
+                            .get("is_system")
                             .and_then(serde_json::Value::as_bool)
                             .unwrap_or(false),
-                        monitor_volume: ch
-                            .get("monitor_volume")
-                            .and_then(serde_json::Value::as_f64)
-                            .unwrap_or(1.0) as f32,
-                        monitor_muted: ch
-                            .get("monitor_muted")
+                        direct_out: config
+                            .get("direct_out")
                             .and_then(serde_json::Value::as_bool)
                             .unwrap_or(false),
+                        layout: config
+                            .get("layout")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("stereo")
+                            .to_string(),
+                        soloed: soloed.contains(&name),
+                        sends: ch
+                            .get("sends")
+                            .cloned()
+                            .and_then(|v| serde_json::from_value(v).ok())
+                            .unwrap_or_default(),
                         level_left: ch
                             .get("level_left")
                             .and_then(serde_json::Value::as_f64)
@@ -339,6 +535,19 @@ fn parse_state_response(value: &serde_json::Value) -> Option<IpcUpdate> {
                             .get("is_persistent")
                             .and_then(serde_json::Value::as_bool)
                             .unwrap_or(false),
+                        media_name: app
+                            .get("media_name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        stream_override: app
+                            .get("stream_route")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok())
+                            .map(|route| match route {
+                                StreamRoute::Manual => "moved".to_string(),
+                                StreamRoute::Rule(selector) => selector.to_key(),
+                            })
+                            .unwrap_or_default(),
                     })
                 })
                 .collect()
@@ -380,24 +589,32 @@ fn parse_state_response(value: &serde_json::Value) -> Option<IpcUpdate> {
     let device_serial =
         value.get("device_serial").and_then(|v: &Value| v.as_str()).map(String::from);
 
-    // Parse mixer state
-    let mixer = value.get("mixer");
-    let stream_master_volume = mixer
-        .and_then(|m| m.get("stream_master_volume"))
-        .and_then(serde_json::Value::as_f64)
-        .unwrap_or(1.0) as f32;
-    let stream_master_muted = mixer
-        .and_then(|m| m.get("stream_master_muted"))
-        .and_then(serde_json::Value::as_bool)
-        .unwrap_or(false);
-    let monitor_master_volume = mixer
-        .and_then(|m| m.get("monitor_master_volume"))
-        .and_then(serde_json::Value::as_f64)
-        .unwrap_or(1.0) as f32;
-    let monitor_master_muted = mixer
-        .and_then(|m| m.get("monitor_master_muted"))
-        .and_then(serde_json::Value::as_bool)
-        .unwrap_or(false);
+    // Parse mixes and the mixer state (master levels and mic sends per mix)
+    let mixer: MixerState = value
+        .get("mixer")
+        .cloned()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    let mixes = value
+        .get("mixes")
+        .cloned()
+        .and_then(|v| serde_json::from_value::<Vec<MixConfig>>(v).ok())
+        .unwrap_or_else(default_mixes)
+        .into_iter()
+        .map(|config| {
+            let mix = config.id();
+            let master = mixer.master(&mix);
+            MixData {
+                name: config.name,
+                display_name: config.display_name,
+                is_system: config.is_system,
+                master_volume: master.volume,
+                master_muted: master.muted,
+                mic_enabled: mixer.mic_enabled(&mix),
+                mic_volume: mixer.mic_volume(&mix),
+            }
+        })
+        .collect();
 
     // Parse output devices
     let output_devices = value
@@ -422,6 +639,12 @@ fn parse_state_response(value: &serde_json::Value) -> Option<IpcUpdate> {
     let monitor_output =
         value.get("monitor_output").and_then(|v| v.as_str()).unwrap_or("wave3-sink").to_string();
 
+    let fallback_channel = value
+        .get("fallback_channel")
+        .and_then(|v| v.as_str())
+        .unwrap_or(FALLBACK_CHANNEL)
+        .to_string();
+
     Some(IpcUpdate::StateUpdated {
         channels,
         apps,
@@ -429,11 +652,9 @@ fn parse_state_response(value: &serde_json::Value) -> Option<IpcUpdate> {
         device_connected,
         device_serial,
         active_profile,
-        stream_master_volume,
-        stream_master_muted,
-        monitor_master_volume,
-        monitor_master_muted,
+        mixes,
         output_devices,
         monitor_output,
+        fallback_channel,
     })
 }
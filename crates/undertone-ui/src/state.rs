@@ -21,7 +21,7 @@ struct UiStateInner {
     active_profile: String,
     /// Channel states
     channels: Vec<ChannelState>,
-    /// Current mix view (Stream or Monitor)
+    /// Mix currently being shown
     mix_mode: MixType,
 }
 
@@ -38,7 +38,7 @@ impl UiState {
         Self {
             inner: Arc::new(ArcSwap::from_pointee(UiStateInner {
                 active_profile: "Default".to_string(),
-                mix_mode: MixType::Stream,
+                mix_mode: MixType::STREAM,
                 ..Default::default()
             })),
         }
@@ -111,31 +111,25 @@ impl UiState {
     /// Get the mix mode.
     #[must_use]
     pub fn mix_mode(&self) -> MixType {
-        self.inner.load().mix_mode
+        self.inner.load().mix_mode.clone()
     }
 
     /// Update a channel's volume.
-    pub fn update_channel_volume(&self, channel: &str, mix: MixType, volume: f32) {
+    pub fn update_channel_volume(&self, channel: &str, mix: &MixType, volume: f32) {
         let guard = self.inner.load();
         let mut state: UiStateInner = (**guard).clone();
         if let Some(ch) = state.channels.iter_mut().find(|c| c.config.name == channel) {
-            match mix {
-                MixType::Stream => ch.stream_volume = volume,
-                MixType::Monitor => ch.monitor_volume = volume,
-            }
+            ch.set_volume(mix, volume);
         }
         self.inner.store(Arc::new(state));
     }
 
     /// Update a channel's mute state.
-    pub fn update_channel_mute(&self, channel: &str, mix: MixType, muted: bool) {
+    pub fn update_channel_mute(&self, channel: &str, mix: &MixType, muted: bool) {
         let guard = self.inner.load();
         let mut state: UiStateInner = (**guard).clone();
         if let Some(ch) = state.channels.iter_mut().find(|c| c.config.name == channel) {
-            match mix {
-                MixType::Stream => ch.stream_muted = muted,
-                MixType::Monitor => ch.monitor_muted = muted,
-            }
+            ch.set_muted(mix, muted);
         }
         self.inner.store(Arc::new(state));
     }